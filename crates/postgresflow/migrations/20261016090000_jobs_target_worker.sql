@@ -0,0 +1,4 @@
+-- Optional worker pinning: when set, only the named worker may lease the job
+-- until the pin times out (see JobsRepo::with_pin_timeout_secs).
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS target_worker_id text NULL;
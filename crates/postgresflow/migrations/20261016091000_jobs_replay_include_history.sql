@@ -0,0 +1,4 @@
+-- Replays created with include_history surface the source job's attempts
+-- (read-only) in their own timeline.
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS replay_include_history boolean NOT NULL DEFAULT false;
@@ -0,0 +1,7 @@
+-- Repeated identical decisions for a job (e.g. THROTTLED every lease attempt)
+-- are coalesced into one row: `count` occurrences, last one at `last_seen_at`.
+ALTER TABLE policy_decisions
+  ADD COLUMN IF NOT EXISTS count int NOT NULL DEFAULT 1;
+
+ALTER TABLE policy_decisions
+  ADD COLUMN IF NOT EXISTS last_seen_at timestamptz NOT NULL DEFAULT now();
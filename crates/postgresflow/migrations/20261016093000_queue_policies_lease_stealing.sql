@@ -0,0 +1,8 @@
+-- Opt-in lease stealing: a running job whose lease is still valid may be
+-- re-leased by another worker once it has run longer than
+-- steal_latency_multiple x the typical latency of its job_type.
+ALTER TABLE queue_policies
+  ADD COLUMN IF NOT EXISTS steal_enabled boolean NOT NULL DEFAULT false;
+
+ALTER TABLE queue_policies
+  ADD COLUMN IF NOT EXISTS steal_latency_multiple double precision NOT NULL DEFAULT 10;
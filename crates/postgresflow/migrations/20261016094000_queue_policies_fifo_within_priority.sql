@@ -0,0 +1,5 @@
+-- Per-queue dispatch order: when set, runnable jobs of equal priority are
+-- leased strictly by created_at, so a retried job (whose run_at moved
+-- forward) keeps its place ahead of newer jobs.
+ALTER TABLE queue_policies
+  ADD COLUMN IF NOT EXISTS fifo_within_priority boolean NOT NULL DEFAULT false;
@@ -0,0 +1,16 @@
+-- Worker heartbeats: lets maintenance reclaim a dead worker's jobs as soon as
+-- its heartbeat goes stale instead of waiting for each lease to expire.
+CREATE TABLE IF NOT EXISTS workers (
+  worker_id TEXT PRIMARY KEY,
+  queue TEXT NOT NULL,
+  started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  last_heartbeat_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS workers_last_heartbeat_idx
+ON workers(last_heartbeat_at);
+
+-- fast reap: jobs WHERE status='running' AND locked_by=?
+CREATE INDEX IF NOT EXISTS jobs_running_locked_by_idx
+ON jobs(locked_by)
+WHERE status = 'running';
@@ -0,0 +1,10 @@
+-- Per-job_type SLA targets, e.g. "99% of email_send within 5s, 99.5% success".
+CREATE TABLE IF NOT EXISTS job_type_slas (
+  job_type TEXT PRIMARY KEY,
+  target_latency_ms INT NOT NULL CHECK (target_latency_ms > 0),
+  latency_percentile DOUBLE PRECISION NOT NULL DEFAULT 0.99
+    CHECK (latency_percentile > 0 AND latency_percentile <= 1),
+  target_success_rate DOUBLE PRECISION NOT NULL
+    CHECK (target_success_rate >= 0 AND target_success_rate <= 1),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
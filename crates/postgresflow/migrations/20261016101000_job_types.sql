@@ -0,0 +1,6 @@
+-- Registry of known job types. Workers register their handlers here at startup;
+-- with PGFLOW_REJECT_UNKNOWN_JOB_TYPES, enqueue rejects types not listed.
+CREATE TABLE IF NOT EXISTS job_types (
+  job_type TEXT PRIMARY KEY,
+  registered_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
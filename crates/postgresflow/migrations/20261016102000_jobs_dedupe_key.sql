@@ -0,0 +1,6 @@
+-- Optional producer-supplied key for schedule-once enqueues.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS dedupe_key TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_jobs_queue_dedupe_key
+  ON jobs (queue, dedupe_key, run_at)
+  WHERE dedupe_key IS NOT NULL;
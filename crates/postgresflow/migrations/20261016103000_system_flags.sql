@@ -0,0 +1,7 @@
+-- Global on/off switches flipped at runtime by operators. A missing row means
+-- the flag is at its default (enabled).
+CREATE TABLE IF NOT EXISTS system_flags (
+  name TEXT PRIMARY KEY,
+  enabled BOOLEAN NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,9 @@
+-- The error code a job was DLQ'd with, next to the DLQ reason: the reason says why
+-- it stopped (e.g. MAX_ATTEMPTS_EXCEEDED), this says what kept failing (e.g. TIMEOUT).
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS dlq_error_code TEXT;
+
+UPDATE jobs
+SET dlq_error_code = last_error_code
+WHERE status = 'dlq'
+  AND dlq_error_code IS NULL
+  AND last_error_code IS NOT NULL;
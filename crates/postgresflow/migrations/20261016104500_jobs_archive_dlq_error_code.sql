@@ -0,0 +1,2 @@
+-- Keep jobs_archive in step with jobs: archived DLQ jobs carry their last error code too.
+ALTER TABLE jobs_archive ADD COLUMN IF NOT EXISTS dlq_error_code TEXT;
@@ -0,0 +1,4 @@
+-- Optional value a handler returns on success (e.g. a generated report URL),
+-- written together with status = 'succeeded'. Carried into the archive.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS result_json JSONB;
+ALTER TABLE jobs_archive ADD COLUMN IF NOT EXISTS result_json JSONB;
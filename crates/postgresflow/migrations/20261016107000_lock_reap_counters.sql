@@ -0,0 +1,7 @@
+-- Running total of expired leases requeued by `reap_expired_locks`, per queue.
+-- Shared by every worker so `/metrics/prom` can export one counter.
+CREATE TABLE IF NOT EXISTS lock_reap_counters (
+  queue TEXT PRIMARY KEY,
+  reaped_total BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,3 @@
+-- Set by JobsRepo::set_non_retryable: the job's next failure goes straight to
+-- the DLQ whatever its error code or remaining attempts.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS force_dlq_on_failure BOOLEAN NOT NULL DEFAULT false;
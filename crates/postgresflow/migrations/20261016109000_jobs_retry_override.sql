@@ -0,0 +1,4 @@
+-- Per-job backoff set at enqueue (NewJob::retry). NULL falls back to the
+-- worker's RetryConfig; max_attempts is already per job.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS retry_base_seconds BIGINT;
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS retry_max_seconds BIGINT;
@@ -0,0 +1,7 @@
+-- Running total of enqueues skipped because their dedupe_key was already
+-- scheduled, per queue. Exported as pgflow_enqueue_deduped_total.
+CREATE TABLE IF NOT EXISTS enqueue_dedupe_counters (
+  queue TEXT PRIMARY KEY,
+  deduped_total BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,37 @@
+-- Append-only audit log of every job status change, including the initial
+-- enqueue (from_status NULL). Written by a trigger so every transition path
+-- (single and batch updates, reaper, admin actions) records it in the same
+-- transaction as the change itself. Rows outlive archiving and history pruning.
+CREATE TABLE IF NOT EXISTS job_state_transitions (
+  id BIGSERIAL PRIMARY KEY,
+  job_id UUID NOT NULL,
+  dataset_id TEXT NOT NULL,
+  from_status TEXT,
+  to_status TEXT NOT NULL,
+  -- the lease holder: the worker that leased or finished the job
+  worker_id TEXT,
+  at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS job_state_transitions_job_idx
+  ON job_state_transitions (job_id, id);
+
+CREATE OR REPLACE FUNCTION record_job_state_transition()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF TG_OP = 'INSERT' THEN
+    INSERT INTO job_state_transitions (job_id, dataset_id, from_status, to_status, worker_id)
+    VALUES (NEW.id, NEW.dataset_id, NULL, NEW.status, NEW.locked_by);
+  ELSIF NEW.status IS DISTINCT FROM OLD.status THEN
+    INSERT INTO job_state_transitions (job_id, dataset_id, from_status, to_status, worker_id)
+    VALUES (NEW.id, NEW.dataset_id, OLD.status, NEW.status, COALESCE(NEW.locked_by, OLD.locked_by));
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_jobs_state_transitions ON jobs;
+CREATE TRIGGER trg_jobs_state_transitions
+AFTER INSERT OR UPDATE OF status ON jobs
+FOR EACH ROW
+EXECUTE FUNCTION record_job_state_transition();
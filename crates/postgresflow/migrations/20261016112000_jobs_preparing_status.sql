@@ -0,0 +1,40 @@
+-- Two-phase enqueue: a prepared job sits in status 'preparing' (never leased)
+-- until committed to 'queued', aborted, or past prepared_until.
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS prepared_until TIMESTAMPTZ NULL;
+
+-- The original status CHECK was created inline, so its name depends on the
+-- table's history (jobs_status_check, jobs_status_check1, ...): replace
+-- whichever one doesn't know 'preparing' yet.
+DO $$
+DECLARE
+  c record;
+BEGIN
+  FOR c IN
+    SELECT conname
+    FROM pg_constraint
+    WHERE conrelid = 'jobs'::regclass
+      AND contype = 'c'
+      AND pg_get_constraintdef(oid) LIKE '%status%canceled%'
+      AND pg_get_constraintdef(oid) NOT LIKE '%preparing%'
+  LOOP
+    EXECUTE format('ALTER TABLE jobs DROP CONSTRAINT %I', c.conname);
+  END LOOP;
+
+  IF NOT EXISTS (
+    SELECT 1 FROM pg_constraint
+    WHERE conrelid = 'jobs'::regclass AND conname = 'jobs_status_valid'
+  ) THEN
+    -- NOT VALID skips the full-table scan under the ACCESS EXCLUSIVE lock;
+    -- the VALIDATE below scans without blocking reads and writes
+    ALTER TABLE jobs ADD CONSTRAINT jobs_status_valid
+      CHECK (status IN ('preparing','queued','running','succeeded','failed','dlq','canceled'))
+      NOT VALID;
+  END IF;
+END $$;
+
+ALTER TABLE jobs VALIDATE CONSTRAINT jobs_status_valid;
+
+CREATE INDEX IF NOT EXISTS jobs_prepared_until_idx
+  ON jobs (prepared_until)
+  WHERE status = 'preparing';
@@ -0,0 +1,4 @@
+-- Structured context a handler attached to its failure (HTTP status, upstream
+-- request id, ...), alongside error_code/error_message.
+ALTER TABLE job_attempts
+  ADD COLUMN IF NOT EXISTS error_details_json JSONB NULL;
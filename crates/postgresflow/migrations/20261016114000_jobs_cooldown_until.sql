@@ -0,0 +1,8 @@
+-- Set by a handler on success: enqueue_scheduled_once won't schedule another
+-- run with the same dedupe_key before this time.
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS cooldown_until TIMESTAMPTZ NULL;
+
+CREATE INDEX IF NOT EXISTS jobs_cooldown_until_idx
+  ON jobs (queue, dedupe_key, cooldown_until)
+  WHERE cooldown_until IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Per-queue retry backoff. NULL columns keep the worker's RetryConfig value,
+-- so existing policy rows (storm control only) don't change retry behavior.
+ALTER TABLE queue_policies
+  ADD COLUMN IF NOT EXISTS retry_base_seconds INT NULL,
+  ADD COLUMN IF NOT EXISTS retry_max_seconds INT NULL,
+  ADD COLUMN IF NOT EXISTS retry_jitter_pct DOUBLE PRECISION NULL;
@@ -0,0 +1,19 @@
+-- Producer-supplied idempotency keys. `jobs` is partitioned by dataset_id, so
+-- a unique index on it can't span queues' datasets; the key lives here
+-- instead, one row per (queue, key) pointing at the job that last claimed it.
+-- Rows go away with their job (archive, retention prune).
+CREATE TABLE IF NOT EXISTS job_idempotency_keys (
+  queue TEXT NOT NULL,
+  idempotency_key TEXT NOT NULL,
+  dataset_id TEXT NOT NULL,
+  job_id UUID NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CONSTRAINT job_idempotency_keys_job_fkey
+    FOREIGN KEY (dataset_id, job_id) REFERENCES jobs(dataset_id, id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS job_idempotency_keys_queue_key_idx
+  ON job_idempotency_keys (queue, idempotency_key);
+
+CREATE INDEX IF NOT EXISTS job_idempotency_keys_job_idx
+  ON job_idempotency_keys (job_id);
@@ -0,0 +1,18 @@
+-- Per-tenant enqueue rate limits count in the same minute buckets, keyed by
+-- (queue, tenant); queue-wide buckets use tenant ''.
+ALTER TABLE enqueue_rate_counters
+  ADD COLUMN IF NOT EXISTS tenant TEXT NOT NULL DEFAULT '';
+
+DO $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM pg_constraint
+    WHERE conrelid = 'enqueue_rate_counters'::regclass
+      AND conname = 'enqueue_rate_counters_queue_tenant_window_pkey'
+  ) THEN
+    ALTER TABLE enqueue_rate_counters DROP CONSTRAINT IF EXISTS enqueue_rate_counters_pkey;
+    ALTER TABLE enqueue_rate_counters
+      ADD CONSTRAINT enqueue_rate_counters_queue_tenant_window_pkey
+      PRIMARY KEY (queue, tenant, window_start);
+  END IF;
+END $$;
@@ -0,0 +1,7 @@
+-- Drain: set drain_requested on a worker's row to have it stop leasing, finish
+-- its in-flight batch and exit; it reports status 'draining' meanwhile.
+ALTER TABLE workers
+  ADD COLUMN IF NOT EXISTS drain_requested BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE workers
+  ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'running';
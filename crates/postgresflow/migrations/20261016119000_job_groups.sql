@@ -0,0 +1,9 @@
+-- Job groups: jobs enqueued with the same group_id make up one unit of work
+-- whose status GET /groups/:id reports; optionally a member going to the DLQ
+-- cancels the group's still-queued members (PGFLOW_CANCEL_GROUP_ON_DLQ).
+ALTER TABLE jobs
+  ADD COLUMN IF NOT EXISTS group_id UUID;
+
+CREATE INDEX IF NOT EXISTS jobs_group_id_idx
+ON jobs(group_id)
+WHERE group_id IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Per-queue priority normalization: when set, priorities written for the
+-- queue's jobs are clamped into [priority_min, priority_max] so the lease
+-- ORDER BY sees a small, bounded set of values. NULL = full i32 range.
+ALTER TABLE queue_policies
+  ADD COLUMN IF NOT EXISTS priority_min INT NULL,
+  ADD COLUMN IF NOT EXISTS priority_max INT NULL;
+
+-- p_priority clamped into p_queue's bounds (unchanged without a policy row or bounds).
+CREATE OR REPLACE FUNCTION public.normalize_job_priority(p_queue text, p_priority int)
+RETURNS int
+LANGUAGE sql
+STABLE
+AS $$
+  SELECT COALESCE(
+    (
+      SELECT LEAST(GREATEST(p_priority, qp.priority_min), qp.priority_max)
+      FROM queue_policies qp
+      WHERE qp.queue = p_queue
+    ),
+    p_priority
+  );
+$$;
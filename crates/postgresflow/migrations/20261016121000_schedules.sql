@@ -0,0 +1,19 @@
+-- Recurring jobs: each schedule materializes a queued job whenever its cron
+-- expression's next fire time (next_run_at) passes, then advances it. A NULL
+-- next_run_at means the expression has no further fire times.
+CREATE TABLE IF NOT EXISTS schedules (
+  name TEXT PRIMARY KEY,
+  queue TEXT NOT NULL,
+  job_type TEXT NOT NULL,
+  payload_json JSONB NOT NULL DEFAULT '{}'::jsonb,
+  cron_expr TEXT NOT NULL,
+  next_run_at TIMESTAMPTZ,
+  last_run_at TIMESTAMPTZ,
+  last_job_id UUID,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS schedules_next_run_at_idx
+ON schedules(next_run_at)
+WHERE next_run_at IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Lines handlers log during an attempt (JobContext::log), read back through
+-- GET /jobs/:id/logs. Capped per attempt by PGFLOW_ATTEMPT_LOG_MAX_LINES.
+CREATE TABLE IF NOT EXISTS attempt_logs (
+  id BIGSERIAL PRIMARY KEY,
+  job_id UUID NOT NULL,
+  attempt_no INT NOT NULL,
+  level TEXT NOT NULL CHECK (level IN ('debug', 'info', 'warn', 'error')),
+  message TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS attempt_logs_job_attempt_idx
+ON attempt_logs(job_id, attempt_no, id);
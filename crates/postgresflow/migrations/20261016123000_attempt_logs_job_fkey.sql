@@ -0,0 +1,28 @@
+-- Tie attempt_logs to their job like job_idempotency_keys: `jobs` is
+-- partitioned by dataset_id, so the reference needs it too. Lines go away
+-- with their job (archive, retention prune); lines of jobs already gone are
+-- dropped.
+ALTER TABLE attempt_logs
+  ADD COLUMN IF NOT EXISTS dataset_id TEXT;
+
+UPDATE attempt_logs l
+SET dataset_id = j.dataset_id
+FROM jobs j
+WHERE j.id = l.job_id
+  AND l.dataset_id IS NULL;
+
+DELETE FROM attempt_logs WHERE dataset_id IS NULL;
+
+ALTER TABLE attempt_logs
+  ALTER COLUMN dataset_id SET NOT NULL;
+
+DO $$
+BEGIN
+  IF NOT EXISTS (
+    SELECT 1 FROM pg_constraint
+    WHERE conrelid = 'attempt_logs'::regclass AND conname = 'attempt_logs_job_fkey'
+  ) THEN
+    ALTER TABLE attempt_logs ADD CONSTRAINT attempt_logs_job_fkey
+      FOREIGN KEY (dataset_id, job_id) REFERENCES jobs(dataset_id, id) ON DELETE CASCADE;
+  END IF;
+END $$;
@@ -0,0 +1,27 @@
+-- Opt-out for the transitions trigger: with the `state_transitions_enabled`
+-- system flag off, status changes are no longer recorded (one indexed
+-- lookup per changed row remains).
+CREATE OR REPLACE FUNCTION record_job_state_transition()
+RETURNS TRIGGER AS $$
+BEGIN
+  IF NOT COALESCE(
+    (SELECT enabled FROM system_flags WHERE name = 'state_transitions_enabled'),
+    true
+  ) THEN
+    RETURN NULL;
+  END IF;
+
+  IF TG_OP = 'INSERT' THEN
+    INSERT INTO job_state_transitions (job_id, dataset_id, from_status, to_status, worker_id)
+    VALUES (NEW.id, NEW.dataset_id, NULL, NEW.status, NEW.locked_by);
+  ELSIF NEW.status IS DISTINCT FROM OLD.status THEN
+    INSERT INTO job_state_transitions (job_id, dataset_id, from_status, to_status, worker_id)
+    VALUES (NEW.id, NEW.dataset_id, OLD.status, NEW.status, COALESCE(NEW.locked_by, OLD.locked_by));
+  END IF;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Retention (MaintenanceRepo::prune_state_transitions) deletes by age.
+CREATE INDEX IF NOT EXISTS job_state_transitions_at_idx
+  ON job_state_transitions (at);
@@ -0,0 +1,4 @@
+-- Priority and max_attempts given to the jobs a schedule enqueues.
+ALTER TABLE schedules
+  ADD COLUMN IF NOT EXISTS priority INT NOT NULL DEFAULT 0,
+  ADD COLUMN IF NOT EXISTS max_attempts INT NOT NULL DEFAULT 25 CHECK (max_attempts > 0);
@@ -0,0 +1,8 @@
+-- Storm-control limits are unset (no limit) on policy rows created only for
+-- other settings (retry backoff, priority bounds, FIFO, lease stealing);
+-- set_policy still writes both.
+ALTER TABLE queue_policies
+  ALTER COLUMN max_attempts_per_minute DROP NOT NULL,
+  ALTER COLUMN max_attempts_per_minute DROP DEFAULT,
+  ALTER COLUMN max_in_flight DROP NOT NULL,
+  ALTER COLUMN max_in_flight DROP DEFAULT;
@@ -0,0 +1,15 @@
+-- Running total of enqueues accepted over the soft payload-size threshold,
+-- per queue. Exported as pgflow_enqueue_payload_warnings_total; seeded from
+-- the WARNED ingest decisions recorded so far.
+CREATE TABLE IF NOT EXISTS payload_warning_counters (
+  queue TEXT PRIMARY KEY,
+  warned_total BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+INSERT INTO payload_warning_counters (queue, warned_total)
+SELECT queue, COUNT(*)
+FROM ingest_decisions
+WHERE decision = 'WARNED' AND reason_code = 'PAYLOAD_LARGE_WARN'
+GROUP BY queue
+ON CONFLICT (queue) DO NOTHING;
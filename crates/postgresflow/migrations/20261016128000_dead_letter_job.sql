@@ -0,0 +1,57 @@
+-- The one way a worker moves its leased job to the DLQ (runner failures and
+-- the attempt overflow guard alike), so every path gets the same columns and,
+-- with p_cancel_group, cancels the job's queued group members in the same
+-- transaction (GROUP_CANCEL decisions). NULL p_error_code / p_error_message
+-- keep the job's last error. Returns false when p_worker_id no longer holds
+-- the job.
+CREATE OR REPLACE FUNCTION dead_letter_job(
+  p_dataset_id TEXT,
+  p_job_id UUID,
+  p_worker_id TEXT,
+  p_reason_code TEXT,
+  p_error_code TEXT,
+  p_error_message TEXT,
+  p_cancel_group BOOLEAN
+)
+RETURNS BOOLEAN AS $$
+DECLARE
+  v_group_id UUID;
+BEGIN
+  UPDATE jobs
+  SET status = 'dlq',
+      dlq_reason_code = p_reason_code,
+      dlq_error_code = COALESCE(p_error_code, last_error_code),
+      dlq_at = now(),
+      locked_at = NULL,
+      locked_by = NULL,
+      lock_expires_at = NULL,
+      updated_at = now(),
+      last_error_code = COALESCE(p_error_code, last_error_code),
+      last_error_message = COALESCE(p_error_message, last_error_message)
+  WHERE (p_dataset_id IS NULL OR dataset_id = p_dataset_id)
+    AND id = p_job_id
+    AND locked_by = p_worker_id
+  RETURNING group_id INTO v_group_id;
+
+  IF NOT FOUND THEN
+    RETURN false;
+  END IF;
+
+  IF p_cancel_group AND v_group_id IS NOT NULL THEN
+    WITH canceled AS (
+      UPDATE jobs
+      SET status = 'canceled',
+          updated_at = now()
+      WHERE group_id = v_group_id
+        AND status = 'queued'
+      RETURNING dataset_id, id
+    )
+    INSERT INTO policy_decisions (id, dataset_id, job_id, decision, reason_code, details_json)
+    SELECT gen_random_uuid(), dataset_id, id, 'GROUP_CANCEL', 'GROUP_MEMBER_DLQ',
+           jsonb_build_object('group_id', v_group_id, 'dlq_job_id', p_job_id)
+    FROM canceled;
+  END IF;
+
+  RETURN true;
+END;
+$$ LANGUAGE plpgsql;
@@ -3,6 +3,8 @@ use serde::Serialize;
 use sqlx::{FromRow, PgPool};
 use std::collections::HashMap;
 
+use crate::jobs::{JobStatus, JobsRepo};
+
 #[derive(Clone)]
 pub struct AdminState {
     pub pool: PgPool,
@@ -34,22 +36,12 @@ pub struct QueueMetrics {
     pub attempts_last_min: i64,
 }
 
-#[derive(FromRow)]
-struct TotalsRow {
-    queued: i64,
-    running: i64,
-    succeeded: i64,
-    failed: i64,
-    dlq: i64,
-}
-
 #[derive(FromRow)]
 struct QueueRow {
     queue: String,
     runnable: i64,
     scheduled: i64,
     in_flight: i64,
-    dlq: i64,
 }
 
 #[derive(FromRow)]
@@ -59,21 +51,25 @@ struct AttemptsRow {
 }
 
 pub async fn metrics(State(st): State<AdminState>) -> Result<Json<Metrics>, (StatusCode, String)> {
-    // Totals by status
-    let totals_row = sqlx::query_as::<_, TotalsRow>(
-        r#"
-        SELECT
-          COUNT(*) FILTER (WHERE status = 'queued')     AS queued,
-          COUNT(*) FILTER (WHERE status = 'running')    AS running,
-          COUNT(*) FILTER (WHERE status = 'succeeded')  AS succeeded,
-          COUNT(*) FILTER (WHERE status = 'failed')     AS failed,
-          COUNT(*) FILTER (WHERE status = 'dlq')        AS dlq
-        FROM jobs
-        "#,
-    )
-    .fetch_one(&st.pool)
-    .await
-    .map_err(db_err)?;
+    // Status counts per queue (shared with /metrics/prom)
+    let status_counts = JobsRepo::new(st.pool.clone())
+        .status_counts_by_queue()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("db error: {e}")))?;
+
+    let count = |counts: &HashMap<String, i64>, status: &JobStatus| {
+        counts.get(status.as_str()).copied().unwrap_or(0)
+    };
+    let total = |status: JobStatus| -> i64 {
+        status_counts
+            .iter()
+            .map(|(_, counts)| count(counts, &status))
+            .sum()
+    };
+    let dlq_map: HashMap<&str, i64> = status_counts
+        .iter()
+        .map(|(queue, counts)| (queue.as_str(), count(counts, &JobStatus::Dlq)))
+        .collect();
 
     // Per-queue job stats
     let q_rows = sqlx::query_as::<_, QueueRow>(
@@ -82,8 +78,7 @@ pub async fn metrics(State(st): State<AdminState>) -> Result<Json<Metrics>, (Sta
           queue,
           COUNT(*) FILTER (WHERE status='queued' AND run_at <= now()) AS runnable,
           COUNT(*) FILTER (WHERE status='queued' AND run_at >  now()) AS scheduled,
-          COUNT(*) FILTER (WHERE status='running' AND lock_expires_at > now()) AS in_flight,
-          COUNT(*) FILTER (WHERE status='dlq') AS dlq
+          COUNT(*) FILTER (WHERE status='running' AND lock_expires_at > now()) AS in_flight
         FROM jobs
         GROUP BY queue
         ORDER BY queue
@@ -119,13 +114,14 @@ pub async fn metrics(State(st): State<AdminState>) -> Result<Json<Metrics>, (Sta
         .map(|r| {
             // IMPORTANT: look up before moving r.queue
             let attempts_last_min = attempts_map.get(&r.queue).copied().unwrap_or(0);
+            let dlq = dlq_map.get(r.queue.as_str()).copied().unwrap_or(0);
 
             QueueMetrics {
                 queue: r.queue,
                 runnable: r.runnable,
                 scheduled: r.scheduled,
                 in_flight: r.in_flight,
-                dlq: r.dlq,
+                dlq,
                 attempts_last_min,
             }
         })
@@ -134,11 +130,11 @@ pub async fn metrics(State(st): State<AdminState>) -> Result<Json<Metrics>, (Sta
     let out = Metrics {
         now_utc: chrono::Utc::now().to_rfc3339(),
         totals: Totals {
-            queued: totals_row.queued,
-            running: totals_row.running,
-            succeeded: totals_row.succeeded,
-            failed: totals_row.failed,
-            dlq: totals_row.dlq,
+            queued: total(JobStatus::Queued),
+            running: total(JobStatus::Running),
+            succeeded: total(JobStatus::Succeeded),
+            failed: total(JobStatus::Failed),
+            dlq: total(JobStatus::Dlq),
         },
         per_queue,
     };
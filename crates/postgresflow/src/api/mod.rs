@@ -2,7 +2,7 @@ use axum::response::Html;
 use axum::{
     body::Body,
     extract::{Path, Query, State},
-    http::{header, Request, StatusCode},
+    http::{header, Method, Request, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::{get, post},
@@ -11,28 +11,82 @@ use axum::{
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::compression::CompressionLayer;
 use uuid::Uuid;
 
-use crate::api::models::JobListItem;
+use crate::api::models::{JobDetail, JobListItem};
+use crate::jobs::attempts::{AttemptLogLine, AttemptOrder, JobAttempt};
 use crate::jobs::enqueue_guard::EnqueueGuard;
 use crate::jobs::ingest_decisions::IngestDecisionsRepo;
-use crate::jobs::metrics::MetricsRepo;
-use crate::jobs::model::NewJob;
-use crate::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
+use crate::jobs::metrics::{MetricsRepo, DEFAULT_METRICS_WINDOW_SECS};
+use crate::jobs::model::{JobRecovery, JobStateTransition, NewJob, PayloadEdit};
+use crate::jobs::payload_template;
+use crate::jobs::retry::RetryOverride;
+use crate::jobs::sla::{JobTypeSla, SlaStatus};
+use crate::jobs::system_flags::ENQUEUE_ENABLED;
+use crate::jobs::timeline::TimelineOptions;
+use crate::jobs::{
+    AttemptsRepo, GroupStatus, HandlerPermits, JobsRepo, PolicyDecisionsRepo, SlaRepo,
+    SystemFlagsRepo, WakeupCoalescer,
+};
+use crate::shutdown::ShutdownSignal;
 
 pub mod models;
+pub mod versioning;
 
 #[derive(Clone)]
 pub struct ApiState {
+    /// Primary: enqueue, replay, edits and anything else that writes.
     pub jobs: JobsRepo,
+    /// Job reads for lists, lookups, timelines and metrics; on the replica
+    /// pool when `PGFLOW_READ_DATABASE_URL` is set, else the same as `jobs`.
+    pub read_jobs: JobsRepo,
+    // only read through the API, so these can sit on the replica pool too
     pub attempts: AttemptsRepo,
     pub policy_decisions: PolicyDecisionsRepo,
     pub ingest_decisions: IngestDecisionsRepo,
     pub metrics: MetricsRepo,
+    pub sla: SlaRepo,
+    pub system_flags: SystemFlagsRepo,
     pub enqueue_guard: EnqueueGuard,
+    /// Fill `likely_throttled` / `utilization` on `POST /jobs`
+    /// (`PGFLOW_ENQUEUE_PRESSURE_HINT`); costs one query per enqueue.
+    pub enqueue_pressure_hint: bool,
     pub api_token: Option<String>,
+    pub wakeups: WakeupCoalescer,
+    pub handler_permits: HandlerPermits,
+    /// Runbook link per error code (`PGFLOW_RUNBOOK_URLS`), shown by `/jobs/:id/explain`.
+    pub runbook_urls: Arc<HashMap<String, String>>,
+    /// Serve reads only: every other request gets a JSON `503` (set when the
+    /// worker started on a schema it could not migrate).
+    pub read_only: bool,
+}
+
+/// With `read_only`, reject anything but `GET`/`HEAD` (and the `POST
+/// /jobs/get` lookup) with a JSON `503`.
+async fn reject_writes(State(read_only): State<bool>, req: Request<Body>, next: Next) -> Response {
+    let is_read = matches!(*req.method(), Method::GET | Method::HEAD)
+        || (req.method() == Method::POST && req.uri().path() == "/jobs/get");
+    if read_only && !is_read {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "READ_ONLY",
+                "message": "schema mismatch: this instance serves reads only"
+            })),
+        )
+            .into_response();
+    }
+
+    next.run(req).await
 }
 
+/// With `PGFLOW_API_TOKEN` set, reject requests lacking `x-api-key: <token>`
+/// or `Authorization: Bearer <token>` with a JSON `401`; a no-op otherwise.
 async fn require_api_key(
     State(expected): State<Option<String>>,
     req: Request<Body>,
@@ -51,7 +105,15 @@ async fn require_api_key(
             });
 
         if provided != Some(expected.as_str()) {
-            return (StatusCode::UNAUTHORIZED, "unauthorized").into_response();
+            let error = match provided {
+                None => "missing api token",
+                Some(_) => "invalid api token",
+            };
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response();
         }
     }
 
@@ -62,14 +124,43 @@ pub fn router(state: ApiState) -> Router {
     let protected = Router::new()
         // Admin / inspect
         .route("/jobs", get(list_jobs).post(enqueue_job))
+        .route("/jobs/get", post(get_jobs))
+        .route("/jobs/search", get(search_jobs))
+        .route("/jobs/:id", get(get_job))
         .route("/jobs/:id/timeline", get(get_timeline))
+        .route("/jobs/:id/attempts", get(list_job_attempts))
+        .route("/jobs/:id/logs", get(list_job_logs))
+        .route("/jobs/:id/transitions", get(list_job_transitions))
         .route("/jobs/:id/explain", get(explain_job))
         .route("/jobs/:id/replay", post(replay_job))
+        .route("/jobs/:id/payload", axum::routing::put(put_job_payload))
+        .route("/jobs/:id/recover", post(recover_job))
+        .route("/jobs/:id/dlq", post(dead_letter_failed_job))
+        .route("/jobs/:id/cancel", post(cancel_job))
+        .route("/jobs/:id/priority", axum::routing::patch(set_job_priority))
+        .route("/groups/:id", get(get_group))
         .route("/dlq", get(list_dlq))
+        .route("/dlq/requeue", post(requeue_dlq))
+        .route("/dlq/replay", post(replay_dlq))
+        .route("/failed", get(list_failed))
+        .route("/queues/move", post(move_queue_jobs))
         .route("/ingest/decisions", get(list_ingest_decisions))
+        .route("/ingest/summary", get(ingest_summary))
         // Metrics
         .route("/metrics", get(metrics))
         .route("/metrics/prom", get(metrics_prom))
+        .route("/metrics/full", get(metrics_full))
+        .route("/job-types", get(job_types))
+        .route("/sla", get(sla_report))
+        .route("/sla/:job_type", axum::routing::put(put_sla))
+        // Incident controls
+        .route("/system/enqueue", axum::routing::put(put_enqueue_enabled))
+        // Deploy checks
+        .route("/version", get(version))
+        .layer(middleware::from_fn_with_state(
+            state.read_only,
+            reject_writes,
+        ))
         .layer(middleware::from_fn_with_state(
             state.api_token.clone(),
             require_api_key,
@@ -80,9 +171,45 @@ pub fn router(state: ApiState) -> Router {
         // Keep health unauthenticated for readiness/liveness checks.
         .route("/health", get(health))
         .merge(protected)
+        // Accept-Version / X-Pgflow-API-Version; inside compression so it sees plain JSON
+        .layer(middleware::from_fn(versioning::negotiate_version))
+        // gzip only when the client sends `Accept-Encoding: gzip`
+        .layer(CompressionLayer::new())
         .with_state(state)
 }
 
+/// Serve the admin API until `shutdown` fires, then stop accepting connections
+/// and give in-flight requests up to `grace` to finish before dropping them.
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    shutdown: ShutdownSignal,
+    grace: Duration,
+) -> anyhow::Result<()> {
+    let mut graceful = shutdown.clone();
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(async move { graceful.wait().await })
+        .into_future();
+    tokio::pin!(server);
+
+    let mut shutdown = shutdown;
+    tokio::select! {
+        res = &mut server => return Ok(res?),
+        _ = shutdown.wait() => {}
+    }
+
+    match tokio::time::timeout(grace, server).await {
+        Ok(res) => Ok(res?),
+        Err(_) => {
+            eprintln!(
+                "[api] shutdown grace period ({}ms) elapsed; dropping in-flight requests",
+                grace.as_millis()
+            );
+            Ok(())
+        }
+    }
+}
+
 const ADMIN_HTML: &str = r#"<!doctype html>
 <html lang="en">
 <head>
@@ -159,7 +286,7 @@ const ADMIN_HTML: &str = r#"<!doctype html>
 <body>
   <header>
     <h1>PostgresFlow Admin</h1>
-    <div class="muted">Endpoints: GET /jobs, POST /jobs, /jobs/:id/timeline, /jobs/:id/explain, /jobs/:id/replay, /dlq, /metrics, /metrics/prom</div>
+    <div class="muted">Endpoints: GET /jobs, POST /jobs, /jobs/:id/timeline, /jobs/:id/explain, /jobs/:id/replay, /jobs/:id/recover, /jobs/:id/dlq, /dlq, /failed, /metrics, /metrics/prom</div>
   </header>
   <main>
     <section>
@@ -371,6 +498,12 @@ fn internal_err(e: anyhow::Error) -> (StatusCode, String) {
 pub struct ReplayRequest {
     pub queue: Option<String>,
     pub run_at: Option<DateTime<Utc>>,
+    pub priority: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    pub include_history: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -383,15 +516,41 @@ pub struct ReplayResponse {
 pub struct EnqueueRequest {
     pub queue: Option<String>,
     pub job_type: String,
-    pub payload_json: Value,
+    /// `None` when the body has no `payload_json` key; an explicit `null`
+    /// is `Some(Value::Null)` and is stored as JSON null.
+    #[serde(default, deserialize_with = "present")]
+    pub payload_json: Option<Value>,
+    pub payload_template: Option<Value>,
     pub run_at: Option<DateTime<Utc>>,
     pub priority: Option<i32>,
     pub max_attempts: Option<i32>,
+    pub target_worker_id: Option<String>,
+    /// Per-job backoff; unset uses the worker's retry config.
+    pub retry_base_seconds: Option<i64>,
+    pub retry_max_seconds: Option<i64>,
+    /// Retries with the same key return the live job it created instead of a duplicate.
+    pub idempotency_key: Option<String>,
+    /// Counted against this tenant's enqueue rate limit on the queue, if one is configured.
+    /// Self-declared by the caller, so the tenant limit is advisory.
+    pub tenant: Option<String>,
+    /// Job group to add the job to; see `GET /groups/:id`.
+    pub group_id: Option<Uuid>,
+}
+
+// any value in the body, `null` included, is `Some`; only a missing key is `None`
+fn present<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<Value>, D::Error> {
+    Value::deserialize(d).map(Some)
 }
 
 #[derive(Debug, Serialize)]
 pub struct EnqueueResponse {
     pub job_id: Uuid,
+    // the idempotency_key matched an existing job; nothing was enqueued
+    pub deduplicated: bool,
+    // backpressure hint: the queue is at a storm-control limit right now
+    pub likely_throttled: bool,
+    // in_flight / max_in_flight; None when the queue has no policy
+    pub utilization: Option<f64>,
 }
 
 fn enqueue_err(e: anyhow::Error) -> (StatusCode, String) {
@@ -400,6 +559,10 @@ fn enqueue_err(e: anyhow::Error) -> (StatusCode, String) {
         (StatusCode::PAYLOAD_TOO_LARGE, msg)
     } else if msg.contains("ENQUEUE_RATE_EXCEEDED") {
         (StatusCode::TOO_MANY_REQUESTS, msg)
+    } else if msg.contains("UNKNOWN_JOB_TYPE") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else if msg.contains("ENQUEUE_DISABLED") {
+        (StatusCode::SERVICE_UNAVAILABLE, msg)
     } else {
         internal_err(e)
     }
@@ -413,20 +576,49 @@ pub async fn enqueue_job(
         queue,
         job_type,
         payload_json,
+        payload_template,
         run_at,
         priority,
         max_attempts,
+        target_worker_id,
+        retry_base_seconds,
+        retry_max_seconds,
+        idempotency_key,
+        tenant,
+        group_id,
     } = body;
 
     if job_type.trim().is_empty() {
         return Err((StatusCode::BAD_REQUEST, "job_type is required".into()));
     }
 
+    let payload_json = match (payload_json, payload_template) {
+        (Some(_), Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "provide either payload_json or payload_template, not both".into(),
+            ));
+        }
+        (None, Some(template)) => payload_template::render(&template, Utc::now()),
+        (Some(payload_json), None) => payload_json,
+        (None, None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "payload_json or payload_template is required".into(),
+            ));
+        }
+    };
+
     let queue = queue.unwrap_or_else(|| "default".to_string());
     let payload_bytes = serde_json::to_vec(&payload_json)
         .map_err(|e| internal_err(e.into()))?
         .len();
 
+    state
+        .enqueue_guard
+        .check_enabled(&queue)
+        .await
+        .map_err(enqueue_err)?;
     state
         .enqueue_guard
         .check_payload(&queue, payload_bytes)
@@ -434,7 +626,12 @@ pub async fn enqueue_job(
         .map_err(enqueue_err)?;
     state
         .enqueue_guard
-        .check_rate(&queue)
+        .check_job_type(&queue, &job_type)
+        .await
+        .map_err(enqueue_err)?;
+    state
+        .enqueue_guard
+        .check_rate(&queue, tenant.as_deref())
         .await
         .map_err(enqueue_err)?;
 
@@ -442,33 +639,75 @@ pub async fn enqueue_job(
     if max_attempts <= 0 {
         return Err((StatusCode::BAD_REQUEST, "max_attempts must be > 0".into()));
     }
+    if retry_base_seconds.is_some_and(|s| s <= 0) || retry_max_seconds.is_some_and(|s| s <= 0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "retry_base_seconds and retry_max_seconds must be > 0".into(),
+        ));
+    }
+    let retry =
+        (retry_base_seconds.is_some() || retry_max_seconds.is_some()).then_some(RetryOverride {
+            base_seconds: retry_base_seconds,
+            max_seconds: retry_max_seconds,
+        });
+
+    let pressure = if state.enqueue_pressure_hint {
+        state
+            .jobs
+            .queue_pressure(&queue)
+            .await
+            .map_err(internal_err)?
+    } else {
+        None
+    };
 
-    let job_id = state
+    let enqueued = state
         .jobs
-        .enqueue(NewJob {
+        .enqueue_idempotent(NewJob {
             queue,
             job_type,
             payload_json,
             run_at: run_at.unwrap_or_else(Utc::now),
             priority: priority.unwrap_or(0),
             max_attempts,
+            target_worker_id,
+            retry,
+            dedupe_key: None,
+            idempotency_key,
+            group_id,
         })
         .await
         .map_err(internal_err)?;
 
-    Ok(Json(EnqueueResponse { job_id }))
+    if !enqueued.deduplicated {
+        state.wakeups.wake();
+    }
+
+    Ok(Json(EnqueueResponse {
+        job_id: enqueued.job_id,
+        deduplicated: enqueued.deduplicated,
+        likely_throttled: pressure.as_ref().is_some_and(|p| p.likely_throttled()),
+        utilization: pressure.as_ref().and_then(|p| p.utilization()),
+    }))
 }
 
 pub async fn replay_job(
     State(state): State<ApiState>,
     Path(id): Path<Uuid>,
+    Query(q): Query<ReplayQuery>,
     Json(body): Json<ReplayRequest>,
 ) -> Result<Json<ReplayResponse>, (StatusCode, String)> {
     let new_id = state
         .jobs
-        .replay_job(id, body.queue.as_deref(), body.run_at)
+        .replay_job(
+            id,
+            body.queue.as_deref(),
+            body.run_at,
+            body.priority,
+            q.include_history.unwrap_or(false),
+        )
         .await
-        .map_err(internal_err)?;
+        .map_err(enqueue_err)?;
 
     Ok(Json(ReplayResponse {
         new_job_id: new_id,
@@ -476,15 +715,421 @@ pub async fn replay_job(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PutPayloadRequest {
+    pub payload_json: Value,
+}
+
+/// Manually replace the payload of a queued or dead-lettered job.
+///
+/// There is no per-job_type schema registry yet, so validation is limited to
+/// the enqueue payload size cap and requiring a JSON object.
+pub async fn put_job_payload(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<PutPayloadRequest>,
+) -> Result<Json<JobDetail>, (StatusCode, String)> {
+    if !req.payload_json.is_object() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "payload_json must be a JSON object".into(),
+        ));
+    }
+    let payload_bytes = serde_json::to_vec(&req.payload_json)
+        .map_err(|e| internal_err(e.into()))?
+        .len();
+    let max = state.enqueue_guard.max_payload_bytes();
+    if payload_bytes > max {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("PAYLOAD_TOO_LARGE: {payload_bytes} bytes > {max}"),
+        ));
+    }
+
+    match state
+        .jobs
+        .update_payload(id, req.payload_json)
+        .await
+        .map_err(internal_err)?
+    {
+        PayloadEdit::Updated(job) => Ok(Json(JobDetail::from(*job))),
+        PayloadEdit::NotFound => Err((StatusCode::NOT_FOUND, "job not found".into())),
+        PayloadEdit::NotEditable(status) => Err((
+            StatusCode::CONFLICT,
+            format!("job is {status}; only queued or dlq jobs can be edited"),
+        )),
+    }
+}
+
+/// Move a `failed` job back to `queued`.
+pub async fn recover_job(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobDetail>, (StatusCode, String)> {
+    match state
+        .jobs
+        .recover_failed_job(id)
+        .await
+        .map_err(internal_err)?
+    {
+        JobRecovery::Recovered(job) => Ok(Json(JobDetail::from(*job))),
+        JobRecovery::NotFound => Err((StatusCode::NOT_FOUND, "job not found".into())),
+        JobRecovery::NotFailed(status) => Err((
+            StatusCode::CONFLICT,
+            format!("job is {status}; only failed jobs can be recovered"),
+        )),
+    }
+}
+
+/// Move a `failed` job on to the DLQ.
+pub async fn dead_letter_failed_job(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobDetail>, (StatusCode, String)> {
+    match state
+        .jobs
+        .dead_letter_failed_job(id)
+        .await
+        .map_err(internal_err)?
+    {
+        JobRecovery::Recovered(job) => Ok(Json(JobDetail::from(*job))),
+        JobRecovery::NotFound => Err((StatusCode::NOT_FOUND, "job not found".into())),
+        JobRecovery::NotFailed(status) => Err((
+            StatusCode::CONFLICT,
+            format!("job is {status}; only failed jobs can be moved to the DLQ"),
+        )),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CancelJobResponse {
+    pub canceled: bool,
+}
+
+/// Cancel a `queued` job; `canceled: false` when it is missing, already
+/// running, or finished.
+pub async fn cancel_job(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CancelJobResponse>, (StatusCode, String)> {
+    let canceled = state.jobs.cancel_job(id).await.map_err(internal_err)?;
+    Ok(Json(CancelJobResponse { canceled }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPriorityRequest {
+    pub priority: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetPriorityResponse {
+    pub updated: bool,
+}
+
+/// Reprioritize a `queued` job; `updated: false` when it is missing, already
+/// running, or finished.
+pub async fn set_job_priority(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetPriorityRequest>,
+) -> Result<Json<SetPriorityResponse>, (StatusCode, String)> {
+    let updated = state
+        .jobs
+        .set_priority(id, req.priority)
+        .await
+        .map_err(internal_err)?;
+    Ok(Json(SetPriorityResponse { updated }))
+}
+
+/// Default and upper bound on jobs requeued by one `POST /dlq/requeue`.
+const DEFAULT_REQUEUE_LIMIT: i64 = 1000;
+const MAX_REQUEUE_LIMIT: i64 = 10_000;
+/// Attempts a requeued job gets unless the request says otherwise.
+const DEFAULT_REQUEUE_EXTRA_ATTEMPTS: i32 = 1;
+
+#[derive(Debug, Deserialize)]
+pub struct RequeueDlqRequest {
+    pub queue: Option<String>,
+    pub priority: Option<i32>,
+    pub extra_attempts: Option<i32>,
+    pub spread_seconds: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RequeueDlqResponse {
+    pub requeued: u64,
+}
+
+/// Put DLQ jobs back to `queued`, optionally staggered over a window.
+pub async fn requeue_dlq(
+    State(state): State<ApiState>,
+    Json(req): Json<RequeueDlqRequest>,
+) -> Result<Json<RequeueDlqResponse>, (StatusCode, String)> {
+    let limit = req
+        .limit
+        .unwrap_or(DEFAULT_REQUEUE_LIMIT)
+        .clamp(1, MAX_REQUEUE_LIMIT);
+    let spread = Duration::from_secs(req.spread_seconds.unwrap_or(0));
+
+    let requeued = state
+        .jobs
+        .requeue_dlq(
+            req.queue.as_deref(),
+            req.priority,
+            req.extra_attempts.unwrap_or(DEFAULT_REQUEUE_EXTRA_ATTEMPTS),
+            spread,
+            limit,
+        )
+        .await
+        .map_err(internal_err)?;
+    if requeued > 0 {
+        state.wakeups.wake();
+    }
+
+    Ok(Json(RequeueDlqResponse { requeued }))
+}
+
+/// Default and upper bound on jobs replayed by one `POST /dlq/replay`.
+const DEFAULT_REPLAY_LIMIT: i64 = 1000;
+const MAX_REPLAY_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayDlqRequest {
+    pub queue: Option<String>,
+    pub limit: Option<i64>,
+    pub run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayDlqResponse {
+    pub new_job_ids: Vec<Uuid>,
+}
+
+/// Replay DLQ jobs as fresh queued jobs, leaving the dead letters in place.
+pub async fn replay_dlq(
+    State(state): State<ApiState>,
+    Json(req): Json<ReplayDlqRequest>,
+) -> Result<Json<ReplayDlqResponse>, (StatusCode, String)> {
+    let limit = req
+        .limit
+        .unwrap_or(DEFAULT_REPLAY_LIMIT)
+        .clamp(1, MAX_REPLAY_LIMIT);
+
+    let new_job_ids = state
+        .jobs
+        .replay_dlq_batch(req.queue.as_deref(), limit, req.run_at)
+        .await
+        .map_err(enqueue_err)?;
+    if !new_job_ids.is_empty() {
+        state.wakeups.wake();
+    }
+
+    Ok(Json(ReplayDlqResponse { new_job_ids }))
+}
+
+/// Default and upper bound on jobs moved by one `POST /queues/move`.
+const DEFAULT_MOVE_LIMIT: i64 = 1000;
+const MAX_MOVE_LIMIT: i64 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct MoveJobsRequest {
+    pub from_queue: String,
+    pub to_queue: String,
+    pub job_type: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MoveJobsResponse {
+    pub moved: u64,
+}
+
+/// Move queued jobs from one queue to another (running jobs stay put).
+pub async fn move_queue_jobs(
+    State(state): State<ApiState>,
+    Json(req): Json<MoveJobsRequest>,
+) -> Result<Json<MoveJobsResponse>, (StatusCode, String)> {
+    if req.from_queue.trim().is_empty() || req.to_queue.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "from_queue and to_queue are required".into(),
+        ));
+    }
+    if req.from_queue == req.to_queue {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "from_queue and to_queue must differ".into(),
+        ));
+    }
+    let limit = req
+        .limit
+        .unwrap_or(DEFAULT_MOVE_LIMIT)
+        .clamp(1, MAX_MOVE_LIMIT);
+
+    let moved = state
+        .jobs
+        .move_jobs(
+            &req.from_queue,
+            &req.to_queue,
+            req.job_type.as_deref(),
+            limit,
+        )
+        .await
+        .map_err(internal_err)?;
+    if moved > 0 {
+        state.wakeups.wake();
+    }
+
+    Ok(Json(MoveJobsResponse { moved }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AttemptsQuery {
+    pub order: Option<AttemptOrder>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttemptsResponse {
+    pub job_id: Uuid,
+    pub attempts: Vec<JobAttempt>,
+}
+
+/// A job's attempts, oldest first unless `?order=desc`.
+pub async fn list_job_attempts(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<AttemptsQuery>,
+) -> Result<Json<AttemptsResponse>, (StatusCode, String)> {
+    if state
+        .read_jobs
+        .get_job(id)
+        .await
+        .map_err(internal_err)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "job not found".into()));
+    }
+
+    let attempts = state
+        .attempts
+        .list_attempts_for_job(id, q.order.unwrap_or_default())
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(AttemptsResponse {
+        job_id: id,
+        attempts,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LogsQuery {
+    /// Only this attempt's lines.
+    pub attempt: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogsResponse {
+    pub job_id: Uuid,
+    pub lines: Vec<AttemptLogLine>,
+}
+
+/// Lines the job's handler logged through `JobContext::log`, by attempt.
+pub async fn list_job_logs(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+    Query(q): Query<LogsQuery>,
+) -> Result<Json<LogsResponse>, (StatusCode, String)> {
+    if state
+        .read_jobs
+        .get_job(id)
+        .await
+        .map_err(internal_err)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "job not found".into()));
+    }
+
+    let lines = state
+        .attempts
+        .list_logs(id, q.attempt)
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(LogsResponse { job_id: id, lines }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransitionsResponse {
+    pub job_id: Uuid,
+    pub transitions: Vec<JobStateTransition>,
+}
+
+/// A job's status audit log, oldest first. Still answers for archived jobs.
+pub async fn list_job_transitions(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TransitionsResponse>, (StatusCode, String)> {
+    let transitions = state
+        .read_jobs
+        .state_transitions(id)
+        .await
+        .map_err(internal_err)?;
+
+    if transitions.is_empty()
+        && state
+            .read_jobs
+            .get_job_header(id)
+            .await
+            .map_err(internal_err)?
+            .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "job not found".into()));
+    }
+
+    Ok(Json(TransitionsResponse {
+        job_id: id,
+        transitions,
+    }))
+}
+
+/// Story events `GET /jobs/:id/timeline` returns unless `limit` or `full` is given.
+pub const DEFAULT_TIMELINE_STORY_LIMIT: usize = 500;
+const MAX_TIMELINE_STORY_LIMIT: usize = 10_000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineQuery {
+    pub limit: Option<usize>,
+    pub since: Option<DateTime<Utc>>,
+    /// whole story, ignoring `limit` and `since` (audits)
+    pub full: Option<bool>,
+}
+
 pub async fn get_timeline(
     Path(id): Path<Uuid>,
     State(state): State<ApiState>,
+    Query(q): Query<TimelineQuery>,
 ) -> impl IntoResponse {
+    let opts = if q.full.unwrap_or(false) {
+        TimelineOptions::full()
+    } else {
+        TimelineOptions {
+            limit: Some(
+                q.limit
+                    .unwrap_or(DEFAULT_TIMELINE_STORY_LIMIT)
+                    .clamp(1, MAX_TIMELINE_STORY_LIMIT),
+            ),
+            since: q.since,
+        }
+    };
+
     match crate::jobs::timeline::build_timeline(
-        &state.jobs,
+        &state.read_jobs,
         &state.attempts,
         &state.policy_decisions,
         id,
+        opts,
     )
     .await
     {
@@ -527,7 +1172,7 @@ pub async fn list_jobs(
     Query(q): Query<ListJobsQuery>,
 ) -> Result<Json<ListJobsResponse>, (StatusCode, String)> {
     let items = state
-        .jobs
+        .read_jobs
         .list_jobs(
             q.queue.as_deref(),
             q.status.as_deref(),
@@ -550,6 +1195,123 @@ pub async fn list_jobs(
     }))
 }
 
+/// One job with its payload and, once it succeeded, its `result_json`.
+pub async fn get_job(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<JobDetail>, (StatusCode, String)> {
+    let job = match state.read_jobs.get_job(id).await.map_err(internal_err)? {
+        Some(job) => Some(job),
+        // a replica may not have replayed a job enqueued a moment ago yet
+        None => state.jobs.get_job(id).await.map_err(internal_err)?,
+    };
+    match job {
+        Some(job) => Ok(Json(JobDetail::from(job))),
+        None => Err((StatusCode::NOT_FOUND, "job not found".into())),
+    }
+}
+
+/// Status of a job group, computed from its members.
+pub async fn get_group(
+    State(state): State<ApiState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<GroupStatus>, (StatusCode, String)> {
+    match state
+        .read_jobs
+        .group_status(id)
+        .await
+        .map_err(internal_err)?
+    {
+        Some(group) => Ok(Json(group)),
+        None => Err((StatusCode::NOT_FOUND, "group not found".into())),
+    }
+}
+
+/// Upper bound on ids accepted by `POST /jobs/get`.
+const MAX_GET_JOBS_IDS: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct GetJobsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetJobsResponse {
+    pub jobs: Vec<JobDetail>,
+    // requested ids with no matching job
+    pub missing: Vec<Uuid>,
+}
+
+pub async fn get_jobs(
+    State(state): State<ApiState>,
+    Json(req): Json<GetJobsRequest>,
+) -> Result<Json<GetJobsResponse>, (StatusCode, String)> {
+    if req.ids.len() > MAX_GET_JOBS_IDS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("at most {MAX_GET_JOBS_IDS} ids per request"),
+        ));
+    }
+
+    let jobs = state
+        .read_jobs
+        .get_jobs(&req.ids)
+        .await
+        .map_err(internal_err)?;
+    let missing = req
+        .ids
+        .iter()
+        .filter(|id| !jobs.iter().any(|j| j.id == **id))
+        .copied()
+        .collect();
+
+    Ok(Json(GetJobsResponse {
+        jobs: jobs.into_iter().map(JobDetail::from).collect(),
+        missing,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchJobsQuery {
+    pub field: String,
+    pub value: String,
+    pub queue: Option<String>,
+    pub job_type: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchJobsResponse {
+    pub jobs: Vec<JobDetail>,
+}
+
+/// Jobs whose top-level payload field `field` equals `value` as text
+/// (`payload_json->>field`), newest first.
+pub async fn search_jobs(
+    State(state): State<ApiState>,
+    Query(q): Query<SearchJobsQuery>,
+) -> Result<Json<SearchJobsResponse>, (StatusCode, String)> {
+    if q.field.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "field is required".into()));
+    }
+
+    let jobs = state
+        .read_jobs
+        .search_jobs_by_payload_field(
+            &q.field,
+            &q.value,
+            q.queue.as_deref(),
+            q.job_type.as_deref(),
+            q.limit.unwrap_or(50),
+        )
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(SearchJobsResponse {
+        jobs: jobs.into_iter().map(JobDetail::from).collect(),
+    }))
+}
+
 pub async fn list_dlq(
     State(state): State<ApiState>,
     Query(mut q): Query<ListJobsQuery>,
@@ -559,6 +1321,58 @@ pub async fn list_dlq(
     list_jobs(State(state), Query(q)).await
 }
 
+pub async fn list_failed(
+    State(state): State<ApiState>,
+    Query(mut q): Query<ListJobsQuery>,
+) -> Result<Json<ListJobsResponse>, (StatusCode, String)> {
+    // force status=failed
+    q.status = Some("failed".to_string());
+    list_jobs(State(state), Query(q)).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestSummaryQuery {
+    pub queue: Option<String>,
+    pub window_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngestSummaryResponse {
+    pub window_secs: i64,
+    pub total: i64,
+    pub by_reason_code: BTreeMap<String, i64>,
+    pub by_queue: BTreeMap<String, i64>,
+    pub by_queue_and_reason: Vec<crate::jobs::ingest_decisions::DenialCount>,
+}
+
+/// Enqueue denials over the window (default 1h), grouped by reason and by queue.
+pub async fn ingest_summary(
+    State(state): State<ApiState>,
+    Query(q): Query<IngestSummaryQuery>,
+) -> Result<Json<IngestSummaryResponse>, (StatusCode, String)> {
+    let window_secs = q.window_secs.unwrap_or(3600).clamp(60, 7 * 24 * 3600);
+    let by_queue_and_reason = state
+        .ingest_decisions
+        .denial_counts_since(window_secs, q.queue.as_deref())
+        .await
+        .map_err(internal_err)?;
+
+    let mut by_reason_code = BTreeMap::new();
+    let mut by_queue = BTreeMap::new();
+    for row in &by_queue_and_reason {
+        *by_reason_code.entry(row.reason_code.clone()).or_insert(0) += row.count;
+        *by_queue.entry(row.queue.clone()).or_insert(0) += row.count;
+    }
+
+    Ok(Json(IngestSummaryResponse {
+        window_secs,
+        total: by_queue.values().sum(),
+        by_reason_code,
+        by_queue,
+        by_queue_and_reason,
+    }))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListIngestDecisionsQuery {
     pub queue: Option<String>,
@@ -605,6 +1419,8 @@ pub async fn list_ingest_decisions(
 #[derive(Debug, Deserialize)]
 pub struct MetricsQuery {
     pub queue: Option<String>,
+    /// Window of the rate/latency fields (default 60, clamped to 60s..7d).
+    pub window_secs: Option<i64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -617,14 +1433,22 @@ pub async fn metrics(
     State(state): State<ApiState>,
     Query(q): Query<MetricsQuery>,
 ) -> Result<Json<MetricsResponse>, (StatusCode, String)> {
+    let window_secs = q
+        .window_secs
+        .unwrap_or(DEFAULT_METRICS_WINDOW_SECS)
+        .clamp(60, 7 * 24 * 3600);
     let queues = if let Some(queue) = q.queue {
         vec![state
             .metrics
-            .snapshot_for_queue(&queue)
+            .snapshot_for_queue(&queue, window_secs)
             .await
             .map_err(internal_err)?]
     } else {
-        state.metrics.snapshot_all().await.map_err(internal_err)?
+        state
+            .metrics
+            .snapshot_all(window_secs)
+            .await
+            .map_err(internal_err)?
     };
 
     Ok(Json(MetricsResponse {
@@ -633,36 +1457,397 @@ pub async fn metrics(
     }))
 }
 
+#[derive(Debug, Serialize)]
+pub struct FullMetricsResponse {
+    pub now_utc: DateTime<Utc>,
+    pub queues: Vec<crate::jobs::metrics::Metrics>,
+    pub totals_by_status: BTreeMap<String, i64>,
+    pub dlq: Vec<crate::jobs::metrics::DlqReasonCount>,
+    pub enqueue_denials: Vec<crate::jobs::ingest_decisions::DenialCount>,
+}
+
+/// `/metrics`, status totals, DLQ breakdown and enqueue denials in one call.
+pub async fn metrics_full(
+    State(state): State<ApiState>,
+) -> Result<Json<FullMetricsResponse>, (StatusCode, String)> {
+    let queues = state
+        .metrics
+        .snapshot_all(DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .map_err(internal_err)?;
+
+    let mut totals_by_status = BTreeMap::new();
+    for (_, counts) in state
+        .read_jobs
+        .status_counts_by_queue()
+        .await
+        .map_err(internal_err)?
+    {
+        for (status, count) in counts {
+            *totals_by_status.entry(status).or_insert(0) += count;
+        }
+    }
+
+    let dlq = state.metrics.dlq_report().await.map_err(internal_err)?;
+    let enqueue_denials = state
+        .ingest_decisions
+        .denial_counts()
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(FullMetricsResponse {
+        now_utc: Utc::now(),
+        queues,
+        totals_by_status,
+        dlq,
+        enqueue_denials,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobTypeCounts {
+    pub job_type: String,
+    pub queued: i64,
+    pub running: i64,
+    pub dlq: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobTypesResponse {
+    pub job_types: Vec<JobTypeCounts>,
+}
+
+/// Distinct job_types with their queued/running/dlq counts.
+pub async fn job_types(
+    State(state): State<ApiState>,
+) -> Result<Json<JobTypesResponse>, (StatusCode, String)> {
+    let rows = state
+        .read_jobs
+        .job_type_summary()
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(JobTypesResponse {
+        job_types: rows
+            .into_iter()
+            .map(|(job_type, queued, running, dlq)| JobTypeCounts {
+                job_type,
+                queued,
+                running,
+                dlq,
+            })
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SlaQuery {
+    pub window_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SlaResponse {
+    pub now_utc: DateTime<Utc>,
+    pub window_secs: i64,
+    pub job_types: Vec<SlaStatus>,
+}
+
+/// Pass/fail per configured job_type SLA over the window (default 1h).
+pub async fn sla_report(
+    State(state): State<ApiState>,
+    Query(q): Query<SlaQuery>,
+) -> Result<Json<SlaResponse>, (StatusCode, String)> {
+    let window_secs = q.window_secs.unwrap_or(3600).clamp(60, 7 * 24 * 3600);
+    let job_types = state.sla.report(window_secs).await.map_err(internal_err)?;
+
+    Ok(Json(SlaResponse {
+        now_utc: Utc::now(),
+        window_secs,
+        job_types,
+    }))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EnqueueSwitch {
+    pub enabled: bool,
+}
+
+/// Global enqueue kill-switch: with `enabled: false` every `POST /jobs` gets a
+/// 503 (`ENQUEUE_DISABLED`) while workers keep draining what is already queued.
+pub async fn put_enqueue_enabled(
+    State(state): State<ApiState>,
+    Json(req): Json<EnqueueSwitch>,
+) -> Result<Json<EnqueueSwitch>, (StatusCode, String)> {
+    state
+        .system_flags
+        .set(ENQUEUE_ENABLED, req.enabled)
+        .await
+        .map_err(internal_err)?;
+
+    Ok(Json(req))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PutSlaRequest {
+    pub target_latency_ms: i32,
+    pub target_success_rate: f64,
+    pub latency_percentile: Option<f64>,
+}
+
+pub async fn put_sla(
+    State(state): State<ApiState>,
+    Path(job_type): Path<String>,
+    Json(req): Json<PutSlaRequest>,
+) -> Result<Json<JobTypeSla>, (StatusCode, String)> {
+    let latency_percentile = req.latency_percentile.unwrap_or(0.99);
+    if req.target_latency_ms <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "target_latency_ms must be > 0".into(),
+        ));
+    }
+    if !(0.0..=1.0).contains(&req.target_success_rate) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "target_success_rate must be in 0..=1".into(),
+        ));
+    }
+    if !(latency_percentile > 0.0 && latency_percentile <= 1.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "latency_percentile must be in (0, 1]".into(),
+        ));
+    }
+
+    let sla = JobTypeSla {
+        job_type,
+        target_latency_ms: req.target_latency_ms,
+        latency_percentile,
+        target_success_rate: req.target_success_rate,
+    };
+    state.sla.upsert(&sla).await.map_err(internal_err)?;
+
+    Ok(Json(sla))
+}
+
 pub async fn metrics_prom(State(state): State<ApiState>) -> Response {
     // Minimal Prometheus text format (no extra crate needed).
-    match state.jobs.metrics_snapshot().await {
-        Ok((queued, running, succeeded_last_60s, failed_last_60s)) => {
-            let body = format!(
-                concat!(
-                    "# HELP pgflow_queue_depth Number of queued jobs\n",
-                    "# TYPE pgflow_queue_depth gauge\n",
-                    "pgflow_queue_depth {}\n",
-                    "# HELP pgflow_running_jobs Number of running jobs\n",
-                    "# TYPE pgflow_running_jobs gauge\n",
-                    "pgflow_running_jobs {}\n",
-                    "# HELP pgflow_jobs_succeeded_last_60s Jobs succeeded in last 60s\n",
-                    "# TYPE pgflow_jobs_succeeded_last_60s gauge\n",
-                    "pgflow_jobs_succeeded_last_60s {}\n",
-                    "# HELP pgflow_jobs_failed_last_60s Jobs failed/dlq in last 60s\n",
-                    "# TYPE pgflow_jobs_failed_last_60s gauge\n",
-                    "pgflow_jobs_failed_last_60s {}\n"
-                ),
-                queued, running, succeeded_last_60s, failed_last_60s
-            );
+    let (queued, running, succeeded_last_60s, failed_last_60s) =
+        match state.read_jobs.metrics_snapshot().await {
+            Ok(v) => v,
+            Err(e) => return prom_err(e),
+        };
+    let queues = match state
+        .metrics
+        .snapshot_all(DEFAULT_METRICS_WINDOW_SECS)
+        .await
+    {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+    let attempts_to_success = match state.metrics.attempts_to_success(60).await {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+    let archive_backlog = match state.metrics.archive_backlog().await {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+    let locks_reaped = match state.metrics.locks_reaped_totals().await {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+    let deduped = match state.metrics.enqueue_deduped_totals().await {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+    let payload_warnings = match state.metrics.payload_warning_totals().await {
+        Ok(v) => v,
+        Err(e) => return prom_err(e),
+    };
+
+    let mut body = format!(
+        concat!(
+            "# HELP pgflow_queue_depth Number of queued jobs\n",
+            "# TYPE pgflow_queue_depth gauge\n",
+            "pgflow_queue_depth {}\n",
+            "# HELP pgflow_running_jobs Number of running jobs\n",
+            "# TYPE pgflow_running_jobs gauge\n",
+            "pgflow_running_jobs {}\n",
+            "# HELP pgflow_jobs_succeeded_last_60s Jobs succeeded in last 60s\n",
+            "# TYPE pgflow_jobs_succeeded_last_60s gauge\n",
+            "pgflow_jobs_succeeded_last_60s {}\n",
+            "# HELP pgflow_jobs_failed_last_60s Jobs failed/dlq in last 60s\n",
+            "# TYPE pgflow_jobs_failed_last_60s gauge\n",
+            "pgflow_jobs_failed_last_60s {}\n"
+        ),
+        queued, running, succeeded_last_60s, failed_last_60s
+    );
+
+    body.push_str("# HELP pgflow_queue_in_flight Running jobs by queue\n");
+    body.push_str("# TYPE pgflow_queue_in_flight gauge\n");
+    for m in &queues {
+        body.push_str(&format!(
+            "pgflow_queue_in_flight{{queue=\"{}\"}} {}\n",
+            prom_label(&m.queue),
+            m.in_flight
+        ));
+    }
 
-            (StatusCode::OK, body).into_response()
+    body.push_str("# HELP pgflow_queue_max_in_flight Policy max_in_flight by queue\n");
+    body.push_str("# TYPE pgflow_queue_max_in_flight gauge\n");
+    for m in &queues {
+        if let Some(max) = m.max_in_flight {
+            body.push_str(&format!(
+                "pgflow_queue_max_in_flight{{queue=\"{}\"}} {}\n",
+                prom_label(&m.queue),
+                max
+            ));
         }
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("metrics error: {e}"),
-        )
-            .into_response(),
     }
+
+    body.push_str(
+        "# HELP pgflow_attempts_started_per_sec Attempts started per second (incl. retries) over the last 60s by queue\n",
+    );
+    body.push_str("# TYPE pgflow_attempts_started_per_sec gauge\n");
+    for m in &queues {
+        body.push_str(&format!(
+            "pgflow_attempts_started_per_sec{{queue=\"{}\"}} {}\n",
+            prom_label(&m.queue),
+            m.attempts_started_per_sec
+        ));
+    }
+
+    body.push_str(
+        "# HELP pgflow_queue_mean_wait_ms Mean enqueue-to-first-attempt wait in last 60s by queue\n",
+    );
+    body.push_str("# TYPE pgflow_queue_mean_wait_ms gauge\n");
+    for m in &queues {
+        body.push_str(&format!(
+            "pgflow_queue_mean_wait_ms{{queue=\"{}\"}} {}\n",
+            prom_label(&m.queue),
+            m.mean_wait_ms
+        ));
+    }
+
+    body.push_str(
+        "# HELP pgflow_attempt_failures_total Failed attempts in last 60s by queue and error_code\n",
+    );
+    body.push_str("# TYPE pgflow_attempt_failures_total gauge\n");
+    for m in &queues {
+        for f in &m.failures_by_error_code {
+            body.push_str(&format!(
+                "pgflow_attempt_failures_total{{queue=\"{}\",error_code=\"{}\"}} {}\n",
+                prom_label(&m.queue),
+                prom_label(&f.error_code),
+                f.count
+            ));
+        }
+    }
+
+    body.push_str(
+        "# HELP pgflow_job_attempts_to_success Attempts needed by jobs that succeeded in last 60s\n",
+    );
+    body.push_str("# TYPE pgflow_job_attempts_to_success histogram\n");
+    for b in &attempts_to_success.buckets {
+        body.push_str(&format!(
+            "pgflow_job_attempts_to_success_bucket{{le=\"{}\"}} {}\n",
+            b.le, b.count
+        ));
+    }
+    body.push_str(&format!(
+        concat!(
+            "pgflow_job_attempts_to_success_bucket{{le=\"+Inf\"}} {}\n",
+            "pgflow_job_attempts_to_success_sum {}\n",
+            "pgflow_job_attempts_to_success_count {}\n"
+        ),
+        attempts_to_success.count, attempts_to_success.sum, attempts_to_success.count
+    ));
+
+    body.push_str(&format!(
+        concat!(
+            "# HELP pgflow_archive_backlog Succeeded jobs past the archive cutoff not yet archived\n",
+            "# TYPE pgflow_archive_backlog gauge\n",
+            "pgflow_archive_backlog {}\n"
+        ),
+        archive_backlog
+    ));
+
+    body.push_str("# HELP pgflow_locks_reaped_total Running jobs requeued after their lease expired, by queue\n");
+    body.push_str("# TYPE pgflow_locks_reaped_total counter\n");
+    for c in &locks_reaped {
+        body.push_str(&format!(
+            "pgflow_locks_reaped_total{{queue=\"{}\"}} {}\n",
+            prom_label(&c.queue),
+            c.total
+        ));
+    }
+
+    body.push_str("# HELP pgflow_enqueue_deduped_total Enqueues skipped because their dedupe_key was already scheduled, by queue\n");
+    body.push_str("# TYPE pgflow_enqueue_deduped_total counter\n");
+    for c in &deduped {
+        body.push_str(&format!(
+            "pgflow_enqueue_deduped_total{{queue=\"{}\"}} {}\n",
+            prom_label(&c.queue),
+            c.total
+        ));
+    }
+
+    body.push_str("# HELP pgflow_enqueue_payload_warnings_total Enqueues accepted with a payload over the soft warning threshold, by queue\n");
+    body.push_str("# TYPE pgflow_enqueue_payload_warnings_total counter\n");
+    for c in &payload_warnings {
+        body.push_str(&format!(
+            "pgflow_enqueue_payload_warnings_total{{queue=\"{}\"}} {}\n",
+            prom_label(&c.queue),
+            c.total
+        ));
+    }
+
+    body.push_str(&format!(
+        concat!(
+            "# HELP pgflow_wakeups_coalesced_total Enqueue wakeups folded into an earlier wakeup's lease cycle\n",
+            "# TYPE pgflow_wakeups_coalesced_total counter\n",
+            "pgflow_wakeups_coalesced_total {}\n"
+        ),
+        state.wakeups.coalesced_total()
+    ));
+
+    let permits = state.handler_permits.snapshot();
+    body.push_str("# HELP pgflow_handler_permits_available Free concurrency permits by job_type (0 = jobs wait for a permit)\n");
+    body.push_str("# TYPE pgflow_handler_permits_available gauge\n");
+    for p in &permits {
+        body.push_str(&format!(
+            "pgflow_handler_permits_available{{job_type=\"{}\"}} {}\n",
+            prom_label(&p.job_type),
+            p.available
+        ));
+    }
+    body.push_str("# HELP pgflow_handler_permits_total Handler max_concurrency by job_type\n");
+    body.push_str("# TYPE pgflow_handler_permits_total gauge\n");
+    for p in &permits {
+        body.push_str(&format!(
+            "pgflow_handler_permits_total{{job_type=\"{}\"}} {}\n",
+            prom_label(&p.job_type),
+            p.total
+        ));
+    }
+
+    (StatusCode::OK, body).into_response()
+}
+
+fn prom_err(e: anyhow::Error) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("metrics error: {e}"),
+    )
+        .into_response()
+}
+
+/// Escape a Prometheus label value (backslash, double quote, newline).
+fn prom_label(v: &str) -> String {
+    v.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 #[derive(Debug, Serialize)]
@@ -677,11 +1862,15 @@ pub struct ExplainResponse {
     pub next_run_at: Option<DateTime<Utc>>,
     pub last_error: Option<crate::jobs::timeline::LastError>,
     pub dlq_reason_code: Option<String>,
+    pub dlq_error_code: Option<String>,
     pub suggested_action: Option<String>,
+    /// `suggested_action` as ordered remediation steps; empty without an error.
+    pub suggested_steps: Vec<String>,
+    pub runbook_url: Option<String>,
 }
 
 pub async fn explain_job(Path(id): Path<Uuid>, State(state): State<ApiState>) -> impl IntoResponse {
-    let job = match state.jobs.get_job(id).await {
+    let job = match state.read_jobs.get_job_header(id).await {
         Ok(Some(job)) => job,
         Ok(None) => {
             return (
@@ -704,10 +1893,11 @@ pub async fn explain_job(Path(id): Path<Uuid>, State(state): State<ApiState>) ->
     };
 
     let timeline = match crate::jobs::timeline::build_timeline(
-        &state.jobs,
+        &state.read_jobs,
         &state.attempts,
         &state.policy_decisions,
         id,
+        TimelineOptions::full(),
     )
     .await
     {
@@ -739,21 +1929,36 @@ pub async fn explain_job(Path(id): Path<Uuid>, State(state): State<ApiState>) ->
         .filter(|a| a.status == "failed")
         .count() as i32;
 
-    let suggested_action = timeline
+    let last_error_code = timeline
         .last_error
         .as_ref()
-        .and_then(|e| e.error_code.as_deref())
-        .map(|code| crate::jobs::error_codes::suggested_action(code).to_string());
+        .and_then(|e| e.error_code.as_deref());
+    let suggested_action =
+        last_error_code.map(|code| crate::jobs::error_codes::suggested_action(code).to_string());
+    let suggested_steps = last_error_code
+        .map(|code| {
+            crate::jobs::error_codes::suggested_steps(code)
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let runbook_url =
+        last_error_code.and_then(|code| state.runbook_urls.get(&code.to_uppercase()).cloned());
 
     let summary = match timeline.status.as_str() {
         "succeeded" => format!("Succeeded after {} attempt(s).", attempts.max(1)),
         "running" => "Currently running.".to_string(),
         "dlq" => format!(
-            "Moved to DLQ after {} attempt(s). Reason: {}.",
+            "Moved to DLQ after {} attempt(s). Reason: {}{}.",
             attempts.max(1),
             job.dlq_reason_code
                 .clone()
-                .unwrap_or_else(|| "UNKNOWN".to_string())
+                .unwrap_or_else(|| "UNKNOWN".to_string()),
+            job.dlq_error_code
+                .as_deref()
+                .map(|code| format!(" (last error {code})"))
+                .unwrap_or_default()
         ),
         "queued" => {
             if timeline.last_error.is_some() {
@@ -782,7 +1987,10 @@ pub async fn explain_job(Path(id): Path<Uuid>, State(state): State<ApiState>) ->
             next_run_at: timeline.next_run_at,
             last_error: timeline.last_error,
             dlq_reason_code: job.dlq_reason_code,
+            dlq_error_code: job.dlq_error_code,
             suggested_action,
+            suggested_steps,
+            runbook_url,
         }),
     )
         .into_response()
@@ -791,3 +1999,33 @@ pub async fn explain_job(Path(id): Path<Uuid>, State(state): State<ApiState>) ->
 pub async fn health() -> impl IntoResponse {
     (StatusCode::OK, "ok")
 }
+
+#[derive(Debug, Serialize)]
+pub struct MigrationVersion {
+    pub version: i64,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionResponse {
+    pub version: String,
+    // latest applied sqlx migration; None on an unmigrated database
+    pub migration: Option<MigrationVersion>,
+}
+
+pub async fn version(
+    State(state): State<ApiState>,
+) -> Result<Json<VersionResponse>, (StatusCode, String)> {
+    let migration = crate::db::latest_applied_migration(state.jobs.pool())
+        .await
+        .map_err(internal_err)?
+        .map(|(version, description)| MigrationVersion {
+            version,
+            description,
+        });
+
+    Ok(Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        migration,
+    }))
+}
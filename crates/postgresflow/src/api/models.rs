@@ -1,9 +1,12 @@
 // crates/postgresflow/src/api/models.rs
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::jobs::Job;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct JobListItem {
     pub id: Uuid,
@@ -19,7 +22,58 @@ pub struct JobListItem {
     pub last_error_message: Option<String>,
 
     pub dlq_reason_code: Option<String>,
+    pub dlq_error_code: Option<String>,
 
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+/// Full job row (including payload) as returned by `POST /jobs/get`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobDetail {
+    pub id: Uuid,
+    pub queue: String,
+    pub job_type: String,
+    pub status: String,
+    pub payload_json: Value,
+
+    pub run_at: DateTime<Utc>,
+    pub priority: i32,
+    pub max_attempts: i32,
+
+    pub locked_by: Option<String>,
+    pub lock_expires_at: Option<DateTime<Utc>>,
+
+    pub dlq_reason_code: Option<String>,
+    pub dlq_error_code: Option<String>,
+    pub replay_of_job_id: Option<Uuid>,
+    pub group_id: Option<Uuid>,
+    pub result_json: Option<Value>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Job> for JobDetail {
+    fn from(job: Job) -> Self {
+        Self {
+            id: job.id,
+            queue: job.queue,
+            job_type: job.job_type,
+            status: job.status,
+            payload_json: job.payload_json,
+            run_at: job.run_at,
+            priority: job.priority,
+            max_attempts: job.max_attempts,
+            locked_by: job.locked_by,
+            lock_expires_at: job.lock_expires_at,
+            dlq_reason_code: job.dlq_reason_code,
+            dlq_error_code: job.dlq_error_code,
+            replay_of_job_id: job.replay_of_job_id,
+            group_id: job.group_id,
+            result_json: job.result_json,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+
+/// Response header carrying the shape version a response was rendered in.
+pub const API_VERSION_HEADER: &str = "x-pgflow-api-version";
+/// Request header a client pins its expected shape with (`1`, `v1`, ...).
+pub const ACCEPT_VERSION_HEADER: &str = "accept-version";
+pub const LATEST_API_VERSION: u32 = 3;
+
+/// Fields added in each version after 1, and the route they were added to
+/// (`None`: wherever they appear). Older clients get responses without them,
+/// removed from the top-level object and from objects in its arrays (e.g.
+/// `items`); nested values such as `payload_json` are never touched. A field
+/// a response gains goes here, under `LATEST_API_VERSION` (bumped once per
+/// release that adds fields).
+const ADDED_FIELDS: &[(u32, Option<&str>, &[&str])] = &[
+    (2, None, &["dlq_error_code", "result_json"]),
+    (
+        3,
+        None,
+        &[
+            "group_id",
+            "deduplicated",
+            "suggested_steps",
+            "runbook_url",
+            "attempts_started_per_sec",
+        ],
+    ),
+    // `/sla` and `/ingest/summary` already had one
+    (3, Some("/metrics"), &["window_secs"]),
+];
+
+pub fn parse_version(v: &str) -> Option<u32> {
+    let v = v.trim();
+    let n: u32 = v
+        .strip_prefix('v')
+        .or_else(|| v.strip_prefix('V'))
+        .unwrap_or(v)
+        .parse()
+        .ok()?;
+    (1..=LATEST_API_VERSION).contains(&n).then_some(n)
+}
+
+/// Remove every field newer than `version` from the JSON response body of
+/// the route at `path`.
+pub fn strip_newer_fields(body: &mut Value, version: u32, path: &str) {
+    let newer: Vec<&str> = ADDED_FIELDS
+        .iter()
+        .filter(|(added_in, route, _)| *added_in > version && route.is_none_or(|r| r == path))
+        .flat_map(|(_, _, fields)| fields.iter().copied())
+        .collect();
+    if newer.is_empty() {
+        return;
+    }
+
+    let strip = |v: &mut Value| {
+        if let Value::Object(map) = v {
+            for f in &newer {
+                map.remove(*f);
+            }
+        }
+    };
+
+    match body {
+        Value::Array(items) => items.iter_mut().for_each(strip),
+        Value::Object(map) => {
+            for f in &newer {
+                map.remove(*f);
+            }
+            for v in map.values_mut() {
+                if let Value::Array(items) = v {
+                    items.iter_mut().for_each(strip);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Negotiate the response shape from `Accept-Version` (default: latest) and
+/// stamp `X-Pgflow-API-Version` on every response.
+pub async fn negotiate_version(req: Request<Body>, next: Next) -> Response {
+    let requested = req
+        .headers()
+        .get(ACCEPT_VERSION_HEADER)
+        .map(|v| v.to_str().ok().and_then(parse_version));
+    let version = match requested {
+        None => LATEST_API_VERSION,
+        Some(Some(v)) => v,
+        Some(None) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unsupported Accept-Version (supported: 1..={LATEST_API_VERSION})"),
+            )
+                .into_response();
+        }
+    };
+
+    let path = req.uri().path().to_string();
+    let mut resp = next.run(req).await;
+    if version < LATEST_API_VERSION && is_json(&resp) {
+        resp = downgrade(resp, version, &path).await;
+    }
+    resp.headers_mut()
+        .insert(API_VERSION_HEADER, HeaderValue::from(version));
+    resp
+}
+
+fn is_json(resp: &Response) -> bool {
+    resp.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"))
+}
+
+async fn downgrade(resp: Response, version: u32, path: &str) -> Response {
+    let (mut parts, body) = resp.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(b) => b,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("internal error: {e}"),
+            )
+                .into_response()
+        }
+    };
+
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    strip_newer_fields(&mut value, version, path);
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = serde_json::to_vec(&value).unwrap_or_else(|_| bytes.to_vec());
+    Response::from_parts(parts, Body::from(body))
+}
@@ -1,4 +1,9 @@
 use chrono::{DateTime, Utc};
+use postgresflow::jobs::maintenance::MaintenanceRepo;
+use postgresflow::jobs::system_flags::{
+    queue_enqueue_flag, SystemFlagsRepo, STATE_TRANSITIONS_ENABLED,
+};
+use postgresflow::jobs::WorkersRepo;
 use serde_json::Value;
 use sqlx::{postgres::PgPoolOptions, FromRow, PgPool};
 use std::env;
@@ -17,6 +22,10 @@ async fn main() -> anyhow::Result<()> {
              - demo\n\
              - timeline <job_id>\n\
              - demo-timeline\n\
+             - doctor\n\
+             - retire-queue <queue> [--include-dlq] [--timeout-secs <n>]\n\
+             - drain-worker <worker_id>\n\
+             - state-transitions <on|off>\n\
              \n\
              Uses DATABASE_URL or TEST_DATABASE_URL.\n"
         );
@@ -27,6 +36,11 @@ async fn main() -> anyhow::Result<()> {
         .or_else(|_| env::var("TEST_DATABASE_URL"))
         .expect("DATABASE_URL or TEST_DATABASE_URL must be set");
 
+    if args[1] == "doctor" {
+        let healthy = doctor(&url).await;
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+
     let pool = PgPoolOptions::new()
         .max_connections(5)
         .connect(&url)
@@ -48,6 +62,40 @@ async fn main() -> anyhow::Result<()> {
             let job_id: Uuid = id.parse()?;
             print_timeline(&pool, job_id).await?;
         }
+        "retire-queue" => {
+            let queue = args.get(2).expect(
+                "usage: pgflowctl retire-queue <queue> [--include-dlq] [--timeout-secs <n>]",
+            );
+            let include_dlq = args.iter().any(|a| a == "--include-dlq");
+            let timeout_secs: u64 = match args.iter().position(|a| a == "--timeout-secs") {
+                Some(i) => args
+                    .get(i + 1)
+                    .and_then(|s| s.parse().ok())
+                    .expect("usage: --timeout-secs <n>"),
+                None => RETIRE_QUEUE_TIMEOUT_SECS,
+            };
+            retire_queue(&pool, queue, include_dlq, timeout_secs).await?;
+        }
+        "drain-worker" => {
+            let worker_id = args
+                .get(2)
+                .expect("usage: pgflowctl drain-worker <worker_id>");
+            drain_worker(&pool, worker_id).await?;
+        }
+        "state-transitions" => {
+            let enabled = match args.get(2).map(String::as_str) {
+                Some("on") => true,
+                Some("off") => false,
+                _ => panic!("usage: pgflowctl state-transitions <on|off>"),
+            };
+            SystemFlagsRepo::new(pool.clone())
+                .set(STATE_TRANSITIONS_ENABLED, enabled)
+                .await?;
+            println!(
+                "job_state_transitions recording {}",
+                if enabled { "on" } else { "off" }
+            );
+        }
         "demo-timeline" => {
             reset(&pool).await?;
             let job_id = seed_one_with_failed_attempt(&pool, "default", "fail_me").await?;
@@ -63,6 +111,102 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Prints a checklist and returns whether everything passed.
+async fn doctor(url: &str) -> bool {
+    let pool = match PgPoolOptions::new().max_connections(1).connect(url).await {
+        Ok(p) => p,
+        Err(e) => {
+            println!("FAIL connect: {e}");
+            return false;
+        }
+    };
+    println!("ok   connect");
+
+    let report = match postgresflow::db::doctor(&pool).await {
+        Ok(r) => r,
+        Err(e) => {
+            println!("FAIL query: {e}");
+            return false;
+        }
+    };
+    println!("ok   SELECT 1");
+
+    for table in postgresflow::db::EXPECTED_TABLES {
+        if report.missing_tables.iter().any(|t| t == table) {
+            println!("FAIL table {table} missing");
+        } else {
+            println!("ok   table {table}");
+        }
+    }
+
+    println!("ok   {} migrations applied", report.applied_migrations);
+    for m in &report.failed_migrations {
+        println!("FAIL migration failed: {m}");
+    }
+    for m in &report.pending_migrations {
+        println!("FAIL migration pending: {m}");
+    }
+
+    let healthy = report.is_healthy();
+    println!("{}", if healthy { "healthy" } else { "unhealthy" });
+    healthy
+}
+
+/// Stops enqueues to `queue`, waits until nothing is queued or running, then
+/// archives its terminal jobs.
+/// How long `retire-queue` waits for the queue to drain by default.
+const RETIRE_QUEUE_TIMEOUT_SECS: u64 = 3600;
+
+async fn retire_queue(
+    pool: &PgPool,
+    queue: &str,
+    include_dlq: bool,
+    timeout_secs: u64,
+) -> anyhow::Result<()> {
+    SystemFlagsRepo::new(pool.clone())
+        .set(&queue_enqueue_flag(queue), false)
+        .await?;
+    println!("paused enqueue for {queue}");
+
+    let maintenance = MaintenanceRepo::new(pool.clone());
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    loop {
+        let pending = maintenance.queue_pending(queue).await?;
+        if pending == 0 {
+            break;
+        }
+        // e.g. no worker serves the queue; enqueue stays paused, nothing is archived
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!(
+                "{queue} still has {pending} queued/running jobs after {timeout_secs}s; \
+                 enqueue stays paused, nothing archived"
+            );
+        }
+        println!("waiting for {queue} to drain: {pending} queued/running");
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+
+    let archived = maintenance.archive_queue(queue, include_dlq).await?;
+    println!("archived {archived} jobs from {queue}");
+    Ok(())
+}
+
+async fn drain_worker(pool: &PgPool, worker_id: &str) -> anyhow::Result<()> {
+    let workers = WorkersRepo::new(pool.clone());
+    if !workers.request_drain(worker_id).await? {
+        anyhow::bail!("no registered worker {worker_id}");
+    }
+    println!("drain requested for {worker_id}");
+
+    // the worker deregisters once its in-flight batch is done
+    while workers.is_registered(worker_id).await? {
+        println!("waiting for {worker_id} to finish in-flight jobs");
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+    println!("{worker_id} stopped");
+    Ok(())
+}
+
 async fn reset(pool: &PgPool) -> anyhow::Result<()> {
     sqlx::query(
         r#"
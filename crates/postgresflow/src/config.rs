@@ -1,3 +1,17 @@
+use crate::db::{
+    MigrationMismatchMode, TxIsolation, DEFAULT_BATCH_CHUNK_SIZE, DEFAULT_SERIALIZATION_RETRIES,
+};
+use crate::jobs::attempts::{DEFAULT_ATTEMPT_LOG_MAX_LINES, DEFAULT_ATTEMPT_OVERFLOW_MARGIN};
+use crate::jobs::enqueue_guard::RateWindow;
+use crate::jobs::maintenance::{
+    MaintenanceWindow, DEFAULT_ARCHIVE_AFTER_DAYS, DEFAULT_MAINTENANCE_INTERVAL_SECS,
+    DEFAULT_PRUNE_HISTORY_AFTER_DAYS,
+};
+use crate::jobs::policies::StormControlLock;
+use crate::jobs::repo::DEFAULT_IDEMPOTENCY_WINDOW_SECS;
+use chrono::FixedOffset;
+use std::collections::HashMap;
+
 // Clone: lets you safely duplicate the config
 
 #[derive(Clone, Debug)]
@@ -9,17 +23,68 @@
 // It gives you a typed, validated struct instead of raw strings everywhere
 pub struct Config {
     pub database_url: String,
+    /// Replica for the admin API's reads; `None` reads from the primary.
+    pub read_database_url: Option<String>,
     pub worker_id: String,
     pub queue: String,
     pub lease_seconds: i64,
     pub dequeue_batch_size: i64,
     pub reap_interval_ms: u64,
+    /// Delay before a reaped job is leasable again; `0` = immediately.
+    pub reap_requeue_delay_ms: u64,
     pub verbose_job_logs: bool,
     pub admin_addr: Option<String>,
     pub api_token: Option<String>,
+    /// Error code (uppercase) -> runbook link for `/jobs/:id/explain`.
+    pub runbook_urls: HashMap<String, String>,
     pub migrate_on_startup: bool,
+    pub migration_mismatch: MigrationMismatchMode,
     pub max_payload_bytes: usize,
+    /// How long an enqueue's idempotency key dedupes against its live job.
+    pub idempotency_window_secs: i64,
+    /// Payloads larger than this are accepted but recorded as `PAYLOAD_LARGE_WARN`.
+    pub warn_payload_bytes: Option<usize>,
     pub max_enqueues_per_minute_per_queue: i64,
+    /// Per-minute enqueue limit of each tenant on a queue; `None` = no tenant limit.
+    /// Advisory: the tenant is whatever the enqueue request names.
+    pub max_enqueues_per_minute_per_tenant: Option<i64>,
+    /// Tenant -> its own per-minute limit, overriding the default above.
+    pub tenant_enqueue_limits: HashMap<String, i64>,
+    pub enqueue_rate_window: RateWindow,
+    pub pin_timeout_secs: i64,
+    pub wakeup_coalesce_ms: u64,
+    pub success_overrides_cancel: bool,
+    pub cancel_group_on_dlq: bool,
+    pub enqueue_pressure_hint: bool,
+    pub notify_on_enqueue: bool,
+    pub lease_isolation: TxIsolation,
+    pub storm_control_lock: StormControlLock,
+    pub serialization_retries: u32,
+    pub strict_handlers: bool,
+    pub decision_coalesce_secs: i64,
+    pub shutdown_grace_ms: u64,
+    pub heartbeat_interval_ms: u64,
+    pub worker_stale_secs: i64,
+    pub scheduler_interval_ms: u64,
+    pub application_name: String,
+    pub dataset_round_robin: bool,
+    pub reject_unknown_job_types: bool,
+    pub retry_min_delay_seconds: i64,
+    pub idle_poll_ms: u64,
+    pub attempt_overflow_margin: i32,
+    pub attempt_log_max_lines: i64,
+    pub maintenance_window: Option<MaintenanceWindow>,
+    pub archive_after_days: i64,
+    pub prune_history_after_days: i64,
+    pub maintenance_interval_secs: u64,
+    pub batch_chunk_size: usize,
+    /// Client-side cap on handler starts per second for this worker; `None` = unlimited.
+    pub max_jobs_per_sec: Option<f64>,
+    /// Timeout for handlers registered without one; `None` = unbounded.
+    pub default_job_timeout_ms: Option<u64>,
+    /// Most datasets one worker runs jobs of at once; above 1, a leased batch
+    /// may span up to this many datasets.
+    pub max_concurrent_datasets: usize,
 }
 
 impl Config {
@@ -27,10 +92,23 @@ impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
         dotenvy::dotenv().ok();
 
-        let database_url = std::env::var("DATABASE_URL")
-            .map_err(|_| anyhow::anyhow!("DATABASE_URL is missing"))?;
-        //.map_err(...) converts that error into an anyhow::Error
-        //std::env::var returns Result<String, VarError>
+        let mut problems = EnvProblems::new(env_bool("PGFLOW_STRICT_CONFIG").unwrap_or(false));
+
+        let database_url = match std::env::var("DATABASE_URL")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .or_else(database_url_from_pg_env)
+        {
+            Some(url) => url,
+            None => {
+                problems.fatal(
+                    "DATABASE_URL is missing (or set PGHOST and friends instead)".to_string(),
+                )?;
+                String::new()
+            }
+        };
+
+        let read_database_url = env_or_fallback("PGFLOW_READ_DATABASE_URL", "READ_DATABASE_URL");
 
         let worker_id = env_or_fallback("PGFLOW_WORKER_ID", "WORKER_ID")
             .or_else(|| std::env::var("HOSTNAME").ok())
@@ -39,51 +117,411 @@ impl Config {
         let queue =
             env_or_fallback("PGFLOW_QUEUE", "QUEUE").unwrap_or_else(|| "default".to_string());
 
-        let lease_seconds = env_or_fallback("PGFLOW_LEASE_SECONDS", "LEASE_SECONDS")
-            .and_then(|s| s.parse().ok())
+        let lease_seconds = problems
+            .parse("PGFLOW_LEASE_SECONDS", "LEASE_SECONDS")
             .unwrap_or(10);
+        if lease_seconds <= 0 {
+            problems.note(format!(
+                "PGFLOW_LEASE_SECONDS={lease_seconds}: must be positive"
+            ));
+        }
 
-        let dequeue_batch_size = env_or_fallback("PGFLOW_DEQUEUE_BATCH_SIZE", "DEQUEUE_BATCH_SIZE")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(256)
-            .clamp(1, 4096);
+        let dequeue_batch_size = problems
+            .parse("PGFLOW_DEQUEUE_BATCH_SIZE", "DEQUEUE_BATCH_SIZE")
+            .unwrap_or(256);
+        let dequeue_batch_size =
+            problems.clamp("PGFLOW_DEQUEUE_BATCH_SIZE", dequeue_batch_size, 1, 4096);
 
-        let reap_interval_ms = env_or_fallback("PGFLOW_REAP_INTERVAL_MS", "REAP_INTERVAL_MS")
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(5_000)
-            .clamp(250, 60_000);
+        let reap_interval_ms = problems
+            .parse("PGFLOW_REAP_INTERVAL_MS", "REAP_INTERVAL_MS")
+            .unwrap_or(5_000);
+        let reap_interval_ms =
+            problems.clamp("PGFLOW_REAP_INTERVAL_MS", reap_interval_ms, 250, 60_000);
 
-        let verbose_job_logs = env_bool("PGFLOW_VERBOSE_JOB_LOGS").unwrap_or(false);
+        let reap_requeue_delay_ms = problems
+            .parse("PGFLOW_REAP_REQUEUE_DELAY_MS", "REAP_REQUEUE_DELAY_MS")
+            .unwrap_or(0);
+        let reap_requeue_delay_ms = problems.clamp(
+            "PGFLOW_REAP_REQUEUE_DELAY_MS",
+            reap_requeue_delay_ms,
+            0,
+            600_000,
+        );
+
+        let verbose_job_logs = problems.flag("PGFLOW_VERBOSE_JOB_LOGS").unwrap_or(false);
 
         let admin_addr = env_or_fallback("PGFLOW_ADMIN_ADDR", "ADMIN_ADDR")
             .and_then(|s| normalize_optional_addr(&s));
 
         let api_token = env_or_fallback("PGFLOW_API_TOKEN", "API_TOKEN");
 
-        let migrate_on_startup = env_bool("PGFLOW_MIGRATE_ON_STARTUP").unwrap_or(false);
+        // "TIMEOUT=https://wiki/timeouts,RATE_LIMIT=https://wiki/rate-limits"
+        let mut runbook_urls = HashMap::new();
+        for entry in env_or_fallback("PGFLOW_RUNBOOK_URLS", "RUNBOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            match entry.split_once('=') {
+                Some((code, url)) if !code.trim().is_empty() && !url.trim().is_empty() => {
+                    runbook_urls.insert(code.trim().to_uppercase(), url.trim().to_string());
+                }
+                _ => problems.note(format!(
+                    "PGFLOW_RUNBOOK_URLS entry {entry:?}: expected CODE=url"
+                )),
+            }
+        }
+
+        let migrate_on_startup = problems.flag("PGFLOW_MIGRATE_ON_STARTUP").unwrap_or(false);
 
-        let max_payload_bytes = env_or_fallback("PGFLOW_MAX_PAYLOAD_BYTES", "MAX_PAYLOAD_BYTES")
-            .and_then(|s| s.parse().ok())
+        let migration_mismatch = problems
+            .one_of(
+                "PGFLOW_MIGRATION_MISMATCH",
+                "MIGRATION_MISMATCH",
+                &["fail", "skip", "continue"],
+            )
+            .map(|s| MigrationMismatchMode::parse(&s))
+            .unwrap_or(MigrationMismatchMode::Fail);
+
+        let max_payload_bytes = problems
+            .parse("PGFLOW_MAX_PAYLOAD_BYTES", "MAX_PAYLOAD_BYTES")
             .unwrap_or(256 * 1024);
 
-        let max_enqueues_per_minute_per_queue =
-            env_or_fallback("PGFLOW_MAX_ENQUEUE_PER_MINUTE", "MAX_ENQUEUE_PER_MINUTE")
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(10_000);
+        let idempotency_window_secs = problems
+            .parse("PGFLOW_IDEMPOTENCY_WINDOW_SECS", "IDEMPOTENCY_WINDOW_SECS")
+            .unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW_SECS);
+        let idempotency_window_secs = problems.clamp(
+            "PGFLOW_IDEMPOTENCY_WINDOW_SECS",
+            idempotency_window_secs,
+            0,
+            30 * 24 * 3600,
+        );
+
+        let warn_payload_bytes = problems
+            .parse::<usize>("PGFLOW_WARN_PAYLOAD_BYTES", "WARN_PAYLOAD_BYTES")
+            .filter(|v| {
+                let ok = *v < max_payload_bytes;
+                if !ok {
+                    problems.note(format!(
+                        "PGFLOW_WARN_PAYLOAD_BYTES={v}: must be below the payload limit ({max_payload_bytes})"
+                    ));
+                }
+                ok
+            });
+
+        let max_enqueues_per_minute_per_queue = problems
+            .parse("PGFLOW_MAX_ENQUEUE_PER_MINUTE", "MAX_ENQUEUE_PER_MINUTE")
+            .unwrap_or(10_000);
+
+        let max_enqueues_per_minute_per_tenant = problems
+            .parse::<i64>(
+                "PGFLOW_MAX_ENQUEUE_PER_MINUTE_PER_TENANT",
+                "MAX_ENQUEUE_PER_MINUTE_PER_TENANT",
+            )
+            .map(|v| problems.at_least("PGFLOW_MAX_ENQUEUE_PER_MINUTE_PER_TENANT", v, 1));
+
+        // "acme=100,globex=20"
+        let mut tenant_enqueue_limits = HashMap::new();
+        for entry in env_or_fallback("PGFLOW_TENANT_ENQUEUE_LIMITS", "TENANT_ENQUEUE_LIMITS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+        {
+            match entry
+                .split_once('=')
+                .map(|(tenant, limit)| (tenant.trim(), limit.trim().parse::<i64>()))
+            {
+                Some((tenant, Ok(limit))) if !tenant.is_empty() && limit > 0 => {
+                    tenant_enqueue_limits.insert(tenant.to_string(), limit);
+                }
+                _ => problems.note(format!(
+                    "PGFLOW_TENANT_ENQUEUE_LIMITS entry {entry:?}: expected tenant=limit with a positive limit"
+                )),
+            }
+        }
+
+        let enqueue_rate_window = problems
+            .one_of(
+                "PGFLOW_ENQUEUE_RATE_WINDOW",
+                "ENQUEUE_RATE_WINDOW",
+                &["fixed", "sliding"],
+            )
+            .map(|s| RateWindow::parse(&s))
+            .unwrap_or_default();
+
+        let pin_timeout_secs = problems
+            .parse("PGFLOW_PIN_TIMEOUT_SECS", "PIN_TIMEOUT_SECS")
+            .unwrap_or(300);
+        let pin_timeout_secs = problems.at_least("PGFLOW_PIN_TIMEOUT_SECS", pin_timeout_secs, 0);
+
+        let wakeup_coalesce_ms = problems
+            .parse("PGFLOW_WAKEUP_COALESCE_MS", "WAKEUP_COALESCE_MS")
+            .unwrap_or(20);
+        let wakeup_coalesce_ms =
+            problems.clamp("PGFLOW_WAKEUP_COALESCE_MS", wakeup_coalesce_ms, 0, 5_000);
+
+        let success_overrides_cancel = problems
+            .flag("PGFLOW_SUCCESS_OVERRIDES_CANCEL")
+            .unwrap_or(false);
+
+        let cancel_group_on_dlq = problems.flag("PGFLOW_CANCEL_GROUP_ON_DLQ").unwrap_or(false);
+
+        let notify_on_enqueue = problems.flag("PGFLOW_ENQUEUE_NOTIFY").unwrap_or(true);
+
+        let enqueue_pressure_hint = problems
+            .flag("PGFLOW_ENQUEUE_PRESSURE_HINT")
+            .unwrap_or(false);
+
+        let lease_isolation = problems
+            .one_of(
+                "PGFLOW_LEASE_ISOLATION",
+                "LEASE_ISOLATION",
+                &["default", "serializable"],
+            )
+            .map(|s| TxIsolation::parse(&s))
+            .unwrap_or(TxIsolation::Default);
+
+        let storm_control_lock = problems
+            .one_of(
+                "PGFLOW_STORM_CONTROL_LOCK",
+                "STORM_CONTROL_LOCK",
+                &["off", "shared", "queue"],
+            )
+            .map(|s| StormControlLock::parse(&s))
+            .unwrap_or(StormControlLock::Off);
+
+        let serialization_retries = problems
+            .parse("PGFLOW_SERIALIZATION_RETRIES", "SERIALIZATION_RETRIES")
+            .unwrap_or(DEFAULT_SERIALIZATION_RETRIES);
+        let serialization_retries =
+            problems.clamp("PGFLOW_SERIALIZATION_RETRIES", serialization_retries, 0, 20);
+
+        let strict_handlers = problems.flag("PGFLOW_STRICT_HANDLERS").unwrap_or(false);
+
+        let decision_coalesce_secs = problems
+            .parse("PGFLOW_DECISION_COALESCE_SECS", "DECISION_COALESCE_SECS")
+            .unwrap_or(60);
+        let decision_coalesce_secs =
+            problems.at_least("PGFLOW_DECISION_COALESCE_SECS", decision_coalesce_secs, 0);
+
+        let shutdown_grace_ms = problems
+            .parse("PGFLOW_SHUTDOWN_GRACE_MS", "SHUTDOWN_GRACE_MS")
+            .unwrap_or(10_000);
+        let shutdown_grace_ms =
+            problems.clamp("PGFLOW_SHUTDOWN_GRACE_MS", shutdown_grace_ms, 0, 300_000);
+
+        let heartbeat_interval_ms = problems
+            .parse("PGFLOW_HEARTBEAT_INTERVAL_MS", "HEARTBEAT_INTERVAL_MS")
+            .unwrap_or(5_000);
+        let heartbeat_interval_ms = problems.clamp(
+            "PGFLOW_HEARTBEAT_INTERVAL_MS",
+            heartbeat_interval_ms,
+            100,
+            60_000,
+        );
+
+        let worker_stale_secs = problems
+            .parse("PGFLOW_WORKER_STALE_SECS", "WORKER_STALE_SECS")
+            .unwrap_or(0);
+        let worker_stale_secs = problems.at_least("PGFLOW_WORKER_STALE_SECS", worker_stale_secs, 0);
+
+        let scheduler_interval_ms = problems
+            .parse("PGFLOW_SCHEDULER_INTERVAL_MS", "SCHEDULER_INTERVAL_MS")
+            .unwrap_or(1_000);
+        let scheduler_interval_ms = problems.clamp(
+            "PGFLOW_SCHEDULER_INTERVAL_MS",
+            scheduler_interval_ms,
+            0,
+            60_000,
+        );
+
+        let application_name = env_or_fallback("PGFLOW_APPLICATION_NAME", "APPLICATION_NAME")
+            .unwrap_or_else(|| format!("pgflow-worker-{worker_id}"));
+
+        let dataset_round_robin = problems.flag("PGFLOW_DATASET_ROUND_ROBIN").unwrap_or(false);
+
+        let reject_unknown_job_types = problems
+            .flag("PGFLOW_REJECT_UNKNOWN_JOB_TYPES")
+            .unwrap_or(false);
+
+        let retry_min_delay_seconds = problems
+            .parse("PGFLOW_RETRY_MIN_DELAY_SECONDS", "RETRY_MIN_DELAY_SECONDS")
+            .unwrap_or(0);
+        let retry_min_delay_seconds =
+            problems.at_least("PGFLOW_RETRY_MIN_DELAY_SECONDS", retry_min_delay_seconds, 0);
+
+        let idle_poll_ms = problems
+            .parse("PGFLOW_IDLE_POLL_MS", "IDLE_POLL_MS")
+            .unwrap_or(250);
+        let idle_poll_ms = problems.clamp("PGFLOW_IDLE_POLL_MS", idle_poll_ms, 10, 60_000);
+
+        let max_jobs_per_sec = problems
+            .parse::<f64>("PGFLOW_MAX_JOBS_PER_SEC", "MAX_JOBS_PER_SEC")
+            .filter(|v| {
+                let ok = v.is_finite() && *v > 0.0;
+                if !ok {
+                    problems.note(format!("PGFLOW_MAX_JOBS_PER_SEC={v}: must be positive"));
+                }
+                ok
+            });
+
+        let default_job_timeout_ms = problems
+            .parse::<u64>("PGFLOW_DEFAULT_JOB_TIMEOUT_MS", "DEFAULT_JOB_TIMEOUT_MS")
+            .filter(|v| {
+                if *v == 0 {
+                    problems.note("PGFLOW_DEFAULT_JOB_TIMEOUT_MS=0: must be positive".to_string());
+                }
+                *v > 0
+            });
+
+        let max_concurrent_datasets = problems
+            .parse("PGFLOW_MAX_CONCURRENT_DATASETS", "MAX_CONCURRENT_DATASETS")
+            .unwrap_or(1);
+        let max_concurrent_datasets =
+            problems.at_least("PGFLOW_MAX_CONCURRENT_DATASETS", max_concurrent_datasets, 1);
+
+        let attempt_overflow_margin = problems
+            .parse("PGFLOW_ATTEMPT_OVERFLOW_MARGIN", "ATTEMPT_OVERFLOW_MARGIN")
+            .unwrap_or(DEFAULT_ATTEMPT_OVERFLOW_MARGIN);
+        let attempt_overflow_margin =
+            problems.at_least("PGFLOW_ATTEMPT_OVERFLOW_MARGIN", attempt_overflow_margin, 0);
+
+        let attempt_log_max_lines = problems
+            .parse("PGFLOW_ATTEMPT_LOG_MAX_LINES", "ATTEMPT_LOG_MAX_LINES")
+            .unwrap_or(DEFAULT_ATTEMPT_LOG_MAX_LINES);
+        let attempt_log_max_lines = problems.clamp(
+            "PGFLOW_ATTEMPT_LOG_MAX_LINES",
+            attempt_log_max_lines,
+            0,
+            100_000,
+        );
+
+        let utc = FixedOffset::east_opt(0).expect("zero offset is valid");
+        let maintenance_utc_offset =
+            match env_or_fallback("PGFLOW_MAINTENANCE_UTC_OFFSET", "MAINTENANCE_UTC_OFFSET") {
+                Some(s) => match s.trim().parse::<FixedOffset>() {
+                    Ok(offset) => offset,
+                    Err(e) => {
+                        problems
+                            .fatal(format!("invalid PGFLOW_MAINTENANCE_UTC_OFFSET {s:?}: {e}"))?;
+                        utc
+                    }
+                },
+                None => utc,
+            };
+        let maintenance_window =
+            match env_or_fallback("PGFLOW_MAINTENANCE_WINDOW", "MAINTENANCE_WINDOW")
+                .map(|s| MaintenanceWindow::parse(&s, maintenance_utc_offset))
+                .transpose()
+            {
+                Ok(window) => window,
+                Err(e) => {
+                    problems.fatal(e.to_string())?;
+                    None
+                }
+            };
+
+        let archive_after_days = problems
+            .parse(
+                "PGFLOW_ARCHIVE_SUCCEEDED_AFTER_DAYS",
+                "ARCHIVE_SUCCEEDED_AFTER_DAYS",
+            )
+            .unwrap_or(DEFAULT_ARCHIVE_AFTER_DAYS);
+        let archive_after_days = problems.clamp(
+            "PGFLOW_ARCHIVE_SUCCEEDED_AFTER_DAYS",
+            archive_after_days,
+            0,
+            3_650,
+        );
+
+        let prune_history_after_days = problems
+            .parse(
+                "PGFLOW_PRUNE_HISTORY_AFTER_DAYS",
+                "PRUNE_HISTORY_AFTER_DAYS",
+            )
+            .unwrap_or(DEFAULT_PRUNE_HISTORY_AFTER_DAYS);
+        let prune_history_after_days = problems.clamp(
+            "PGFLOW_PRUNE_HISTORY_AFTER_DAYS",
+            prune_history_after_days,
+            0,
+            3_650,
+        );
+
+        let maintenance_interval_secs = problems
+            .parse(
+                "PGFLOW_MAINTENANCE_INTERVAL_SECS",
+                "MAINTENANCE_INTERVAL_SECS",
+            )
+            .unwrap_or(DEFAULT_MAINTENANCE_INTERVAL_SECS);
+        let maintenance_interval_secs = problems.clamp(
+            "PGFLOW_MAINTENANCE_INTERVAL_SECS",
+            maintenance_interval_secs,
+            1,
+            86_400,
+        );
+
+        let batch_chunk_size = problems
+            .parse("PGFLOW_BATCH_CHUNK_SIZE", "BATCH_CHUNK_SIZE")
+            .unwrap_or(DEFAULT_BATCH_CHUNK_SIZE);
+        let batch_chunk_size =
+            problems.clamp("PGFLOW_BATCH_CHUNK_SIZE", batch_chunk_size, 1, 100_000);
+
+        problems.finish()?;
 
         Ok(Self {
             database_url,
+            read_database_url,
             worker_id,
             queue,
             lease_seconds,
             dequeue_batch_size,
             reap_interval_ms,
+            reap_requeue_delay_ms,
             verbose_job_logs,
             admin_addr,
             api_token,
+            runbook_urls,
             migrate_on_startup,
+            migration_mismatch,
             max_payload_bytes,
+            idempotency_window_secs,
+            warn_payload_bytes,
             max_enqueues_per_minute_per_queue,
+            max_enqueues_per_minute_per_tenant,
+            tenant_enqueue_limits,
+            enqueue_rate_window,
+            pin_timeout_secs,
+            wakeup_coalesce_ms,
+            success_overrides_cancel,
+            cancel_group_on_dlq,
+            enqueue_pressure_hint,
+            notify_on_enqueue,
+            lease_isolation,
+            storm_control_lock,
+            serialization_retries,
+            strict_handlers,
+            decision_coalesce_secs,
+            shutdown_grace_ms,
+            heartbeat_interval_ms,
+            worker_stale_secs,
+            scheduler_interval_ms,
+            application_name,
+            dataset_round_robin,
+            reject_unknown_job_types,
+            retry_min_delay_seconds,
+            idle_poll_ms,
+            attempt_overflow_margin,
+            attempt_log_max_lines,
+            maintenance_window,
+            archive_after_days,
+            prune_history_after_days,
+            maintenance_interval_secs,
+            batch_chunk_size,
+            max_jobs_per_sec,
+            default_job_timeout_ms,
+            max_concurrent_datasets,
         })
     }
 
@@ -94,6 +532,51 @@ impl Config {
     // Return it to the caller
 }
 
+/// DSN assembled from libpq-style `PGHOST`, `PGPORT`, `PGUSER`, `PGPASSWORD`,
+/// `PGDATABASE` and `PGSSLMODE`, for deployments that don't hand out a full
+/// `DATABASE_URL` (e.g. a password injected separately). `None` without `PGHOST`.
+fn database_url_from_pg_env() -> Option<String> {
+    let var = |key: &str| std::env::var(key).ok().filter(|s| !s.trim().is_empty());
+
+    let host = var("PGHOST")?;
+    let host = if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]") // IPv6 literal
+    } else {
+        host
+    };
+    let port = var("PGPORT").unwrap_or_else(|| "5432".to_string());
+
+    let userinfo = match (var("PGUSER"), var("PGPASSWORD")) {
+        (Some(user), Some(password)) => {
+            format!("{}:{}@", url_encode(&user), url_encode(&password))
+        }
+        (Some(user), None) => format!("{}@", url_encode(&user)),
+        (None, _) => String::new(),
+    };
+    let database = var("PGDATABASE")
+        .map(|db| url_encode(&db))
+        .unwrap_or_default();
+    let query = var("PGSSLMODE")
+        .map(|mode| format!("?sslmode={}", url_encode(&mode)))
+        .unwrap_or_default();
+
+    Some(format!(
+        "postgres://{userinfo}{host}:{port}/{database}{query}"
+    ))
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn url_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
 fn env_or_fallback(primary: &str, fallback: &str) -> Option<String> {
     std::env::var(primary)
         .ok()
@@ -121,3 +604,116 @@ fn normalize_optional_addr(value: &str) -> Option<String> {
     }
     Some(v.to_string())
 }
+
+/// Problems found while reading the environment. By default a bad value falls
+/// back to its default (or is clamped into range) and only fatal problems
+/// fail; with `PGFLOW_STRICT_CONFIG` every problem is collected and
+/// `finish` reports them all at once.
+struct EnvProblems {
+    strict: bool,
+    found: Vec<String>,
+}
+
+impl EnvProblems {
+    fn new(strict: bool) -> Self {
+        Self {
+            strict,
+            found: Vec::new(),
+        }
+    }
+
+    fn note(&mut self, problem: String) {
+        self.found.push(problem);
+    }
+
+    /// A problem that fails startup in any mode; strict mode defers it so it
+    /// is reported along with the rest.
+    fn fatal(&mut self, problem: String) -> anyhow::Result<()> {
+        if !self.strict {
+            anyhow::bail!(problem);
+        }
+        self.note(problem);
+        Ok(())
+    }
+
+    /// `primary` (or `fallback`) parsed as `T`; `None` when unset or unparsable.
+    fn parse<T>(&mut self, primary: &str, fallback: &str) -> Option<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let raw = env_or_fallback(primary, fallback)?;
+        match raw.trim().parse() {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.note(format!("{primary}={raw:?}: {e}"));
+                None
+            }
+        }
+    }
+
+    fn clamp<T: PartialOrd + std::fmt::Display + Copy>(
+        &mut self,
+        name: &str,
+        value: T,
+        min: T,
+        max: T,
+    ) -> T {
+        if value < min || value > max {
+            self.note(format!("{name}={value}: must be between {min} and {max}"));
+        }
+        if value < min {
+            min
+        } else if value > max {
+            max
+        } else {
+            value
+        }
+    }
+
+    fn at_least<T: PartialOrd + std::fmt::Display + Copy>(
+        &mut self,
+        name: &str,
+        value: T,
+        min: T,
+    ) -> T {
+        if value < min {
+            self.note(format!("{name}={value}: must be at least {min}"));
+            return min;
+        }
+        value
+    }
+
+    fn flag(&mut self, key: &str) -> Option<bool> {
+        let raw = std::env::var(key).ok()?;
+        let known = matches!(
+            raw.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on" | "0" | "false" | "no" | "off" | ""
+        );
+        if !known {
+            self.note(format!("{key}={raw:?}: expected true/false"));
+        }
+        env_bool(key)
+    }
+
+    fn one_of(&mut self, primary: &str, fallback: &str, allowed: &[&str]) -> Option<String> {
+        let raw = env_or_fallback(primary, fallback)?;
+        if !allowed.contains(&raw.trim().to_lowercase().as_str()) {
+            self.note(format!(
+                "{primary}={raw:?}: expected one of {}",
+                allowed.join(", ")
+            ));
+        }
+        Some(raw)
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        if !self.strict || self.found.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "invalid configuration (PGFLOW_STRICT_CONFIG):\n  - {}",
+            self.found.join("\n  - ")
+        )
+    }
+}
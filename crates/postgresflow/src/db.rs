@@ -1,6 +1,65 @@
-use sqlx::{postgres::PgPoolOptions, PgPool};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::future::Future;
+use std::str::FromStr;
 use std::time::Duration;
 
+/// Default retry budget for transactions aborted with a serialization failure.
+pub const DEFAULT_SERIALIZATION_RETRIES: u32 = 3;
+
+/// Default max rows per statement in the batch attempt/success paths; larger
+/// batches are split into several statements inside one transaction.
+pub const DEFAULT_BATCH_CHUNK_SIZE: usize = 1000;
+
+/// Isolation level for transactions that opt in (currently the lease path).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxIsolation {
+    /// Whatever the server/session default is (normally READ COMMITTED).
+    Default,
+    Serializable,
+}
+
+impl TxIsolation {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "serializable" => TxIsolation::Serializable,
+            _ => TxIsolation::Default,
+        }
+    }
+}
+
+/// True for SQLSTATE 40001 (serialization_failure).
+pub fn is_serialization_failure(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .and_then(|d| d.code())
+        .is_some_and(|code| code == "40001")
+}
+
+/// Run `op` (which should open and commit its own transaction), re-running it
+/// up to `max_retries` times when Postgres aborts it with a serialization
+/// failure. Any other error is returned immediately.
+pub async fn run_serializable_with_retry<T, F, Fut>(
+    max_retries: u32,
+    mut op: F,
+) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut retries = 0;
+    loop {
+        match op().await {
+            Err(e) if retries < max_retries && is_serialization_failure(&e) => {
+                retries += 1;
+                // short backoff so the conflicting transaction can finish
+                tokio::time::sleep(Duration::from_millis(5 * u64::from(retries))).await;
+            }
+            res => return res,
+        }
+    }
+}
+
 fn env_bool(key: &str, default: bool) -> bool {
     std::env::var(key)
         .ok()
@@ -8,7 +67,9 @@ fn env_bool(key: &str, default: bool) -> bool {
         .unwrap_or(default)
 }
 
-pub async fn make_pool(database_url: &str) -> anyhow::Result<PgPool> {
+/// `application_name` is reported on every pool connection (visible in
+/// `pg_stat_activity`) so DBAs can attribute load to a worker.
+pub async fn make_pool(database_url: &str, application_name: &str) -> anyhow::Result<PgPool> {
     let max_connections = std::env::var("PGFLOW_DB_MAX_CONNECTIONS")
         .ok()
         .and_then(|s| s.parse::<u32>().ok())
@@ -42,12 +103,214 @@ pub async fn make_pool(database_url: &str) -> anyhow::Result<PgPool> {
         })
     });
 
-    let pool = opts.connect(database_url).await?;
+    let connect_opts = PgConnectOptions::from_str(database_url)?.application_name(application_name);
+    let pool = opts.connect_with(connect_opts).await?;
 
     Ok(pool)
 }
 
+/// What the worker does when `PGFLOW_MIGRATE_ON_STARTUP` finds an applied
+/// migration whose file was edited afterwards (checksum mismatch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationMismatchMode {
+    /// Log the mismatch and refuse to start.
+    Fail,
+    /// Log the mismatch, apply nothing and start on the existing schema.
+    Skip,
+}
+
+impl MigrationMismatchMode {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "skip" | "continue" => MigrationMismatchMode::Skip,
+            _ => MigrationMismatchMode::Fail,
+        }
+    }
+}
+
+/// An applied migration whose file no longer matches what was run.
+#[derive(Debug, Clone)]
+pub struct ChecksumMismatch {
+    pub version: i64,
+    pub description: String,
+    // hex sha384: recorded in _sqlx_migrations vs computed from the file now
+    pub applied_checksum: String,
+    pub file_checksum: String,
+}
+
+/// Returned (inside anyhow) by `run_migrations` instead of sqlx's bare
+/// "migration N was previously applied but has been modified".
+#[derive(Debug)]
+pub struct MigrationChecksumError {
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+impl std::fmt::Display for MigrationChecksumError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} applied migration(s) were edited after being run; restore the original file(s) \
+             and put the change in a new migration:",
+            self.mismatches.len()
+        )?;
+        for m in &self.mismatches {
+            write!(
+                f,
+                "\n  {} {}: applied checksum {}, file checksum {}",
+                m.version, m.description, m.applied_checksum, m.file_checksum
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MigrationChecksumError {}
+
+/// Compare every applied migration's recorded checksum with the embedded file.
+pub async fn verify_migration_checksums(pool: &PgPool) -> anyhow::Result<Vec<ChecksumMismatch>> {
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if !has_migrations_table {
+        return Ok(Vec::new());
+    }
+
+    let applied: Vec<(i64, Vec<u8>)> =
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await?;
+
+    let mut mismatches = Vec::new();
+    for m in sqlx::migrate!("./migrations").iter() {
+        if m.migration_type.is_down_migration() {
+            continue;
+        }
+        if let Some((_, checksum)) = applied.iter().find(|(v, _)| *v == m.version) {
+            if checksum.as_slice() != &*m.checksum {
+                mismatches.push(ChecksumMismatch {
+                    version: m.version,
+                    description: m.description.to_string(),
+                    applied_checksum: hex(checksum),
+                    file_checksum: hex(&m.checksum),
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Apply pending migrations. Edited migrations fail up front with a
+/// `MigrationChecksumError` naming each one, before anything is applied.
 pub async fn run_migrations(pool: &PgPool) -> anyhow::Result<()> {
+    let mismatches = verify_migration_checksums(pool).await?;
+    if !mismatches.is_empty() {
+        return Err(MigrationChecksumError { mismatches }.into());
+    }
+
     sqlx::migrate!("./migrations").run(pool).await?;
     Ok(())
 }
+
+/// Most recently applied sqlx migration as `(version, description)`, or
+/// `None` if no migrations have been applied. Ordered by `installed_on`
+/// rather than version, since a mis-dated file (`22260205110000`) would
+/// otherwise always win.
+pub async fn latest_applied_migration(pool: &PgPool) -> anyhow::Result<Option<(i64, String)>> {
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    if !has_migrations_table {
+        return Ok(None);
+    }
+
+    let row = sqlx::query_as::<_, (i64, String)>(
+        r#"
+        SELECT version, description
+        FROM _sqlx_migrations
+        WHERE success
+        ORDER BY installed_on DESC, version DESC
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Tables the worker and admin API cannot run without.
+pub const EXPECTED_TABLES: [&str; 4] =
+    ["jobs", "job_attempts", "policy_decisions", "queue_policies"];
+
+#[derive(Debug)]
+pub struct DoctorReport {
+    pub missing_tables: Vec<String>,
+    pub applied_migrations: usize,
+    // "<version> <description>" for migrations not yet applied / recorded as failed
+    pub pending_migrations: Vec<String>,
+    pub failed_migrations: Vec<String>,
+}
+
+impl DoctorReport {
+    pub fn is_healthy(&self) -> bool {
+        self.missing_tables.is_empty()
+            && self.pending_migrations.is_empty()
+            && self.failed_migrations.is_empty()
+    }
+}
+
+/// Connectivity + schema check used by `pgflowctl doctor`.
+pub async fn doctor(pool: &PgPool) -> anyhow::Result<DoctorReport> {
+    sqlx::query("SELECT 1").execute(pool).await?;
+
+    let mut missing_tables = Vec::new();
+    for table in EXPECTED_TABLES {
+        let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(table)
+            .fetch_one(pool)
+            .await?;
+        if !exists {
+            missing_tables.push(table.to_string());
+        }
+    }
+
+    let has_migrations_table: bool =
+        sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+    let applied: Vec<(i64, bool)> = if has_migrations_table {
+        sqlx::query_as("SELECT version, success FROM _sqlx_migrations")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    let mut pending_migrations = Vec::new();
+    let mut failed_migrations = Vec::new();
+    let mut applied_migrations = 0;
+    for m in sqlx::migrate!("./migrations").iter() {
+        if m.migration_type.is_down_migration() {
+            continue;
+        }
+        let label = format!("{} {}", m.version, m.description);
+        match applied.iter().find(|(v, _)| *v == m.version) {
+            Some((_, true)) => applied_migrations += 1,
+            Some((_, false)) => failed_migrations.push(label),
+            None => pending_migrations.push(label),
+        }
+    }
+
+    Ok(DoctorReport {
+        missing_tables,
+        applied_migrations,
+        pending_migrations,
+        failed_migrations,
+    })
+}
@@ -1,8 +1,11 @@
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+use crate::db::DEFAULT_BATCH_CHUNK_SIZE;
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
 pub struct JobAttempt {
     pub id: Uuid,
     pub job_id: Uuid,
@@ -15,11 +18,23 @@ pub struct JobAttempt {
 
     pub error_code: Option<String>,
     pub error_message: Option<String>,
+    /// Structured failure context from the handler (`JobError` details).
+    pub error_details_json: Option<serde_json::Value>,
 
     pub latency_ms: Option<i32>,
     pub worker_id: String,
 }
 
+/// Ordering of `list_attempts_for_job` by attempt_no.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttemptOrder {
+    #[default]
+    Asc,
+    /// Newest first, e.g. for UIs that show the latest failure on top.
+    Desc,
+}
+
 pub enum AttemptStatus {
     Running,
     Succeeded,
@@ -36,14 +51,91 @@ impl AttemptStatus {
     }
 }
 
+/// One line a handler logged during an attempt (`attempt_logs`).
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct AttemptLogLine {
+    pub attempt_no: i32,
+    pub level: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+/// Default cap on log lines a handler stores per attempt (`JobContext::log`);
+/// later lines are dropped.
+pub const DEFAULT_ATTEMPT_LOG_MAX_LINES: i64 = 1000;
+
+/// Log messages longer than this are cut to it (on a char boundary).
+pub const ATTEMPT_LOG_MAX_MESSAGE_BYTES: usize = 4096;
+
+/// Outcome of `AttemptsRepo::start_attempts_batch`.
+#[derive(Debug, Clone, Default)]
+pub struct StartedAttempts {
+    /// (job_id, attempt_id, attempt_no) of every attempt started.
+    pub started: Vec<(Uuid, Uuid, i32)>,
+    /// Jobs refused by the attempt overflow guard; moved to the DLQ unless
+    /// another worker holds their lease by now.
+    pub overflowed: Vec<Uuid>,
+}
+
+/// How far past `max_attempts` a job's attempt_no may go (e.g. through
+/// repeated lease expiry) before it is forced to the DLQ.
+pub const DEFAULT_ATTEMPT_OVERFLOW_MARGIN: i32 = 100;
+
 #[derive(Clone)]
 pub struct AttemptsRepo {
     pool: PgPool,
+    attempt_overflow_margin: i32,
+    cancel_group_on_dlq: bool,
+    batch_chunk_size: usize,
 }
 
 impl AttemptsRepo {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            attempt_overflow_margin: DEFAULT_ATTEMPT_OVERFLOW_MARGIN,
+            cancel_group_on_dlq: false,
+            batch_chunk_size: DEFAULT_BATCH_CHUNK_SIZE,
+        }
+    }
+
+    /// Refuse to start attempts beyond `max_attempts + margin`; such jobs are
+    /// moved to the DLQ with `ATTEMPT_OVERFLOW` instead.
+    pub fn with_attempt_overflow_margin(mut self, margin: i32) -> Self {
+        self.attempt_overflow_margin = margin.max(0);
+        self
+    }
+
+    /// Cancel the queued members of an overflowing job's group along with its
+    /// DLQ move, like `JobsRepo::with_cancel_group_on_dlq`.
+    pub fn with_cancel_group_on_dlq(mut self, enabled: bool) -> Self {
+        self.cancel_group_on_dlq = enabled;
+        self
+    }
+
+    /// Max rows per statement in `start_attempts_batch` / `finish_succeeded_batch`.
+    pub fn with_batch_chunk_size(mut self, size: usize) -> Self {
+        self.batch_chunk_size = size.max(1);
+        self
     }
 
     /// Insert attempt row as "running", auto-increment attempt_no per job.
@@ -65,6 +157,11 @@ impl AttemptsRepo {
     }
 
     /// Insert attempt row as "running" when caller already knows dataset_id.
+    ///
+    /// Errors with `ATTEMPT_OVERFLOW` when the new attempt_no would exceed
+    /// `max_attempts + margin`, after moving the job to the DLQ through
+    /// `dead_letter_job` (with an `ATTEMPT_GUARD` policy decision) if
+    /// `worker_id` holds its lease.
     pub async fn start_attempt_for_dataset(
         &self,
         dataset_id: &str,
@@ -73,6 +170,58 @@ impl AttemptsRepo {
     ) -> anyhow::Result<JobAttempt> {
         let status = AttemptStatus::Running.as_str();
 
+        let (next_attempt_no, max_attempts) = sqlx::query_as::<_, (i32, i32)>(
+            r#"
+            SELECT
+              COALESCE(
+                (SELECT MAX(attempt_no) FROM job_attempts WHERE job_id = $2 AND dataset_id = $1),
+                0
+              ) + 1,
+              max_attempts
+            FROM jobs
+            WHERE dataset_id = $1 AND id = $2
+            "#,
+        )
+        .bind(dataset_id)
+        .bind(job_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        if next_attempt_no > max_attempts.saturating_add(self.attempt_overflow_margin) {
+            sqlx::query(
+                r#"
+                WITH overflowed AS (
+                  SELECT dead_letter_job($1, $2, $3, 'ATTEMPT_OVERFLOW', NULL, NULL, $4) AS dead
+                )
+                INSERT INTO policy_decisions (
+                  id, dataset_id, job_id, decision, reason_code, details_json
+                )
+                SELECT
+                  gen_random_uuid(), $1, $2, 'ATTEMPT_GUARD', 'ATTEMPT_OVERFLOW',
+                  jsonb_build_object(
+                    'attempt_no', $5::int,
+                    'max_attempts', $6::int,
+                    'overflow_margin', $7::int
+                  )
+                FROM overflowed
+                WHERE dead
+                "#,
+            )
+            .bind(dataset_id)
+            .bind(job_id)
+            .bind(worker_id)
+            .bind(self.cancel_group_on_dlq)
+            .bind(next_attempt_no)
+            .bind(max_attempts)
+            .bind(self.attempt_overflow_margin)
+            .execute(&self.pool)
+            .await?;
+
+            anyhow::bail!(
+                "ATTEMPT_OVERFLOW: job {job_id} would start attempt {next_attempt_no} (max_attempts={max_attempts})"
+            );
+        }
+
         let attempt = sqlx::query_as::<_, JobAttempt>(
             r#"
             INSERT INTO job_attempts (dataset_id, job_id, attempt_no, status, worker_id)
@@ -100,15 +249,21 @@ impl AttemptsRepo {
     }
 
     /// Insert many "running" attempts in one round-trip.
-    /// Returns tuples of (job_id, attempt_id, attempt_no).
+    ///
+    /// Jobs whose next attempt_no would exceed `max_attempts + margin` get no
+    /// attempt; they are moved to the DLQ with `ATTEMPT_OVERFLOW` (through
+    /// `dead_letter_job`, if `worker_id` holds their lease, with an
+    /// `ATTEMPT_GUARD` policy decision) and reported in `overflowed`. The
+    /// input `jobs` rows are locked (in id order) before any history is
+    /// written.
     pub async fn start_attempts_batch(
         &self,
         dataset_ids: &[String],
         job_ids: &[Uuid],
         worker_id: &str,
-    ) -> anyhow::Result<Vec<(Uuid, Uuid, i32)>> {
+    ) -> anyhow::Result<StartedAttempts> {
         if dataset_ids.is_empty() || job_ids.is_empty() {
-            return Ok(Vec::new());
+            return Ok(StartedAttempts::default());
         }
         if dataset_ids.len() != job_ids.len() {
             anyhow::bail!("dataset_ids and job_ids length mismatch");
@@ -116,43 +271,113 @@ impl AttemptsRepo {
 
         let status = AttemptStatus::Running.as_str();
 
-        let rows = sqlx::query_as::<_, (Uuid, Uuid, i32)>(
-            r#"
-            WITH input AS (
-              SELECT *
-              FROM unnest($1::text[], $2::uuid[]) AS t(dataset_id, job_id)
-            ),
-            inserted AS (
-              INSERT INTO job_attempts (dataset_id, job_id, attempt_no, status, worker_id)
-              SELECT
-                i.dataset_id,
-                i.job_id,
-                COALESCE(
-                  (
-                    SELECT MAX(a.attempt_no)
-                    FROM job_attempts a
-                    WHERE a.dataset_id = i.dataset_id
-                      AND a.job_id = i.job_id
-                  ),
-                  0
-                ) + 1,
-                $3,
-                $4
-              FROM input i
-              RETURNING job_id, id, attempt_no
+        let mut tx = self.pool.begin().await?;
+        let mut out = StartedAttempts {
+            started: Vec::with_capacity(job_ids.len()),
+            overflowed: Vec::new(),
+        };
+        for (datasets, jobs) in dataset_ids
+            .chunks(self.batch_chunk_size)
+            .zip(job_ids.chunks(self.batch_chunk_size))
+        {
+            // jobs before history (see ARCHITECTURE.md): the overflow path
+            // below dead-letters jobs and writes decisions in one statement
+            sqlx::query(
+                r#"
+                SELECT j.id
+                FROM jobs j
+                JOIN unnest($1::text[], $2::uuid[]) AS t(dataset_id, job_id)
+                  ON j.dataset_id = t.dataset_id AND j.id = t.job_id
+                ORDER BY j.id
+                FOR UPDATE OF j
+                "#,
             )
-            SELECT job_id, id, attempt_no
-            FROM inserted
-            "#,
-        )
-        .bind(dataset_ids)
-        .bind(job_ids)
-        .bind(status)
-        .bind(worker_id)
-        .fetch_all(&self.pool)
-        .await?;
+            .bind(datasets)
+            .bind(jobs)
+            .execute(&mut *tx)
+            .await?;
 
-        Ok(rows)
+            let chunk = sqlx::query_as::<_, (Uuid, Option<Uuid>, Option<i32>)>(
+                r#"
+                WITH input AS (
+                  SELECT *
+                  FROM unnest($1::text[], $2::uuid[]) AS t(dataset_id, job_id)
+                ),
+                next AS (
+                  SELECT
+                    i.dataset_id,
+                    i.job_id,
+                    COALESCE(
+                      (
+                        SELECT MAX(a.attempt_no)
+                        FROM job_attempts a
+                        WHERE a.dataset_id = i.dataset_id
+                          AND a.job_id = i.job_id
+                      ),
+                      0
+                    ) + 1 AS attempt_no,
+                    j.max_attempts
+                  FROM input i
+                  JOIN jobs j ON j.dataset_id = i.dataset_id AND j.id = i.job_id
+                ),
+                overflowed AS (
+                  SELECT
+                    n.*,
+                    dead_letter_job(
+                      n.dataset_id, n.job_id, $4, 'ATTEMPT_OVERFLOW', NULL, NULL, $6
+                    ) AS dead
+                  FROM next n
+                  WHERE n.attempt_no > n.max_attempts + $5
+                ),
+                audited AS (
+                  INSERT INTO policy_decisions (
+                    id, dataset_id, job_id, decision, reason_code, details_json
+                  )
+                  SELECT
+                    gen_random_uuid(), o.dataset_id, o.job_id, 'ATTEMPT_GUARD', 'ATTEMPT_OVERFLOW',
+                    jsonb_build_object(
+                      'attempt_no', o.attempt_no,
+                      'max_attempts', o.max_attempts,
+                      'overflow_margin', $5::int
+                    )
+                  FROM overflowed o
+                  WHERE o.dead
+                ),
+                inserted AS (
+                  INSERT INTO job_attempts (dataset_id, job_id, attempt_no, status, worker_id)
+                  SELECT n.dataset_id, n.job_id, n.attempt_no, $3, $4
+                  FROM next n
+                  WHERE n.attempt_no <= n.max_attempts + $5
+                  RETURNING job_id, id, attempt_no
+                )
+                SELECT job_id, id, attempt_no
+                FROM inserted
+                UNION ALL
+                -- a SELECT CTE only runs when read
+                SELECT job_id, NULL, NULL
+                FROM overflowed
+                "#,
+            )
+            .bind(datasets)
+            .bind(jobs)
+            .bind(status)
+            .bind(worker_id)
+            .bind(self.attempt_overflow_margin)
+            .bind(self.cancel_group_on_dlq)
+            .fetch_all(&mut *tx)
+            .await?;
+            for row in chunk {
+                match row {
+                    (job_id, Some(attempt_id), Some(attempt_no)) => {
+                        out.started.push((job_id, attempt_id, attempt_no))
+                    }
+                    (job_id, _, _) => out.overflowed.push(job_id),
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(out)
     }
 
     pub async fn finish_succeeded(&self, attempt_id: Uuid, latency_ms: i32) -> anyhow::Result<()> {
@@ -176,36 +401,41 @@ impl AttemptsRepo {
         Ok(())
     }
 
-    /// Fast-path for successful batch execution: updates many attempts in one statement.
+    /// Fast-path for successful batch execution: updates many attempts per statement.
     pub async fn finish_succeeded_batch(&self, updates: &[(Uuid, i32)]) -> anyhow::Result<()> {
         if updates.is_empty() {
             return Ok(());
         }
 
-        let attempt_ids: Vec<Uuid> = updates.iter().map(|(attempt_id, _)| *attempt_id).collect();
-        let latencies_ms: Vec<i32> = updates.iter().map(|(_, latency_ms)| *latency_ms).collect();
         let status = AttemptStatus::Succeeded.as_str();
 
-        sqlx::query(
-            r#"
-            WITH data AS (
-              SELECT
-                unnest($1::uuid[]) AS attempt_id,
-                unnest($2::int4[]) AS latency_ms
+        let mut tx = self.pool.begin().await?;
+        for chunk in updates.chunks(self.batch_chunk_size) {
+            let attempt_ids: Vec<Uuid> = chunk.iter().map(|(attempt_id, _)| *attempt_id).collect();
+            let latencies_ms: Vec<i32> = chunk.iter().map(|(_, latency_ms)| *latency_ms).collect();
+
+            sqlx::query(
+                r#"
+                WITH data AS (
+                  SELECT
+                    unnest($1::uuid[]) AS attempt_id,
+                    unnest($2::int4[]) AS latency_ms
+                )
+                UPDATE job_attempts a
+                SET status = $3,
+                    finished_at = now(),
+                    latency_ms = d.latency_ms
+                FROM data d
+                WHERE a.id = d.attempt_id
+                "#,
             )
-            UPDATE job_attempts a
-            SET status = $3,
-                finished_at = now(),
-                latency_ms = d.latency_ms
-            FROM data d
-            WHERE a.id = d.attempt_id
-            "#,
-        )
-        .bind(&attempt_ids)
-        .bind(&latencies_ms)
-        .bind(status)
-        .execute(&self.pool)
-        .await?;
+            .bind(&attempt_ids)
+            .bind(&latencies_ms)
+            .bind(status)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
 
         Ok(())
     }
@@ -216,6 +446,19 @@ impl AttemptsRepo {
         latency_ms: i32,
         error_code: &str,
         error_message: &str,
+    ) -> anyhow::Result<()> {
+        self.finish_failed_with_details(attempt_id, latency_ms, error_code, error_message, None)
+            .await
+    }
+
+    /// `finish_failed`, also storing structured context in `error_details_json`.
+    pub async fn finish_failed_with_details(
+        &self,
+        attempt_id: Uuid,
+        latency_ms: i32,
+        error_code: &str,
+        error_message: &str,
+        error_details: Option<&serde_json::Value>,
     ) -> anyhow::Result<()> {
         let status = AttemptStatus::Failed.as_str();
 
@@ -226,7 +469,8 @@ impl AttemptsRepo {
                 finished_at = now(),
                 latency_ms = $3,
                 error_code = $4,
-                error_message = $5
+                error_message = $5,
+                error_details_json = $6
             WHERE id = $1
             "#,
         )
@@ -235,22 +479,84 @@ impl AttemptsRepo {
         .bind(latency_ms)
         .bind(error_code)
         .bind(error_message)
+        .bind(error_details)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    pub async fn list_attempts_for_job(&self, job_id: Uuid) -> anyhow::Result<Vec<JobAttempt>> {
+    pub async fn list_attempts_for_job(
+        &self,
+        job_id: Uuid,
+        order: AttemptOrder,
+    ) -> anyhow::Result<Vec<JobAttempt>> {
         let rows = sqlx::query_as::<_, JobAttempt>(
             r#"
             SELECT *
             FROM job_attempts
             WHERE job_id = $1
-            ORDER BY attempt_no ASC
+            ORDER BY CASE WHEN $2 THEN attempt_no END DESC, attempt_no ASC
+            "#,
+        )
+        .bind(job_id)
+        .bind(order == AttemptOrder::Desc)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Append a line to the log of `job_id`'s attempt `attempt_no`. The
+    /// per-attempt line cap is the caller's (`JobContext` counts its lines).
+    pub async fn append_log(
+        &self,
+        dataset_id: &str,
+        job_id: Uuid,
+        attempt_no: i32,
+        level: LogLevel,
+        message: &str,
+    ) -> anyhow::Result<()> {
+        let mut end = message.len().min(ATTEMPT_LOG_MAX_MESSAGE_BYTES);
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO attempt_logs (dataset_id, job_id, attempt_no, level, message)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(dataset_id)
+        .bind(job_id)
+        .bind(attempt_no)
+        .bind(level.as_str())
+        .bind(&message[..end])
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A job's log lines, by attempt then in the order they were written;
+    /// only attempt `attempt_no` when given.
+    pub async fn list_logs(
+        &self,
+        job_id: Uuid,
+        attempt_no: Option<i32>,
+    ) -> anyhow::Result<Vec<AttemptLogLine>> {
+        let rows = sqlx::query_as::<_, AttemptLogLine>(
+            r#"
+            SELECT attempt_no, level, message, created_at
+            FROM attempt_logs
+            WHERE job_id = $1
+              AND ($2::int IS NULL OR attempt_no = $2)
+            ORDER BY attempt_no ASC, id ASC
             "#,
         )
         .bind(job_id)
+        .bind(attempt_no)
         .fetch_all(&self.pool)
         .await?;
 
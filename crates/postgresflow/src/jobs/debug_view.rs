@@ -35,7 +35,14 @@ pub async fn build_debug_view(
         return Ok(None);
     };
 
-    let tl = crate::jobs::timeline::build_timeline(jobs, attempts, decisions, job_id).await?;
+    let tl = crate::jobs::timeline::build_timeline(
+        jobs,
+        attempts,
+        decisions,
+        job_id,
+        crate::jobs::timeline::TimelineOptions::full(),
+    )
+    .await?;
     let attempts_json = serde_json::to_value(&tl.as_ref().map(|t| &t.attempts)).unwrap_or_default();
     let decisions_json =
         serde_json::to_value(&tl.as_ref().map(|t| &t.decisions)).unwrap_or_default();
@@ -8,31 +8,119 @@
 use chrono::{DateTime, Timelike, Utc};
 use serde_json::json;
 use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
 
+use crate::jobs::clock::{Clock, SystemClock};
 use crate::jobs::ingest_decisions::IngestDecisionsRepo;
+use crate::jobs::job_types::JobTypesRepo;
+use crate::jobs::model::NewJob;
+use crate::jobs::system_flags::{queue_enqueue_flag, ENQUEUE_ENABLED};
+
+/// Whether `e` is one of the guard's denials (rather than e.g. a DB error).
+pub fn is_denial(e: &anyhow::Error) -> bool {
+    let msg = e.to_string();
+    [
+        "ENQUEUE_DISABLED",
+        "PAYLOAD_TOO_LARGE",
+        "UNKNOWN_JOB_TYPE",
+        "ENQUEUE_RATE_EXCEEDED",
+    ]
+    .iter()
+    .any(|code| msg == *code)
+}
+
+/// The kill switch on its own: deny `queue` (recording `ENQUEUE_DISABLED`)
+/// while the global or the queue's `enqueue_enabled` flag is off. `JobsRepo`
+/// runs this on every insert path even without a full guard.
+pub async fn check_enabled(
+    pool: &PgPool,
+    decisions: &IngestDecisionsRepo,
+    queue: &str,
+) -> anyhow::Result<()> {
+    // both flags in one round trip; a missing row means enabled
+    let disabled: Vec<String> =
+        sqlx::query_scalar("SELECT name FROM system_flags WHERE name IN ($1, $2) AND NOT enabled")
+            .bind(ENQUEUE_ENABLED)
+            .bind(queue_enqueue_flag(queue))
+            .fetch_all(pool)
+            .await?;
+    let details = if disabled.iter().any(|name| name == ENQUEUE_ENABLED) {
+        json!({})
+    } else if !disabled.is_empty() {
+        json!({ "scope": "queue" })
+    } else {
+        return Ok(());
+    };
+
+    let _ = decisions
+        .record(queue, "DENIED", "ENQUEUE_DISABLED", details)
+        .await?;
+    anyhow::bail!("ENQUEUE_DISABLED");
+}
+
+/// How `check_rate` counts enqueues against the per-minute limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateWindow {
+    /// Per calendar minute. A burst straddling a minute boundary can get up
+    /// to twice the limit through.
+    #[default]
+    Fixed,
+    /// Current minute plus the previous minute weighted by the part of it
+    /// still inside the last 60s; smooths out boundary bursts.
+    Sliding,
+}
+
+impl RateWindow {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "sliding" => RateWindow::Sliding,
+            _ => RateWindow::Fixed,
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct EnqueueGuardConfig {
     pub max_payload_bytes: usize,
+    // payloads above this (but within max_payload_bytes) are let through with
+    // a PAYLOAD_LARGE_WARN decision
+    pub warn_payload_bytes: Option<usize>,
     pub max_enqueues_per_minute_per_queue: i64,
+    // per (queue, tenant) limit for enqueues naming a tenant; None = only the
+    // queue-wide limit applies
+    pub max_enqueues_per_minute_per_tenant: Option<i64>,
+    // tenant -> its own per-minute limit, overriding the one above
+    pub tenant_limits: HashMap<String, i64>,
+    // deny job types missing from the `job_types` registry
+    pub reject_unknown_job_types: bool,
+    pub rate_window: RateWindow,
 }
 
 impl Default for EnqueueGuardConfig {
     fn default() -> Self {
         Self {
-            max_payload_bytes: 256 * 1024,             // 256KB default
+            max_payload_bytes: 256 * 1024, // 256KB default
+            warn_payload_bytes: None,
             max_enqueues_per_minute_per_queue: 10_000, // very high default (safe)
+            max_enqueues_per_minute_per_tenant: None,
+            tenant_limits: HashMap::new(),
+            reject_unknown_job_types: false,
+            rate_window: RateWindow::Fixed,
         }
     }
 }
 
-/// Enqueue-time protection: payload-size + enqueue rate limiting.
-/// Writes ingest_decisions rows for denials so Law 4 is provable without logs.
+/// Enqueue-time protection: the global enqueue kill-switch, payload-size +
+/// enqueue rate limiting, and optionally rejecting unregistered job types.
+/// Writes ingest_decisions rows for denials so Law 4 is provable without logs,
+/// and `WARNED` rows for payloads over the soft size threshold.
 #[derive(Clone)]
 pub struct EnqueueGuard {
     pool: PgPool,
     decisions: IngestDecisionsRepo,
     cfg: EnqueueGuardConfig,
+    clock: Arc<dyn Clock>,
 }
 
 impl EnqueueGuard {
@@ -41,13 +129,25 @@ impl EnqueueGuard {
             pool,
             decisions,
             cfg,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Clock the rate-limit minute buckets are taken from; tests pass a `MockClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub fn max_payload_bytes(&self) -> usize {
         self.cfg.max_payload_bytes
     }
 
+    /// Deny everything while the `enqueue_enabled` system flag is off.
+    pub async fn check_enabled(&self, queue: &str) -> anyhow::Result<()> {
+        check_enabled(&self.pool, &self.decisions, queue).await
+    }
+
     pub async fn check_payload(&self, queue: &str, payload_bytes: usize) -> anyhow::Result<()> {
         if payload_bytes > self.cfg.max_payload_bytes {
             let _ = self
@@ -64,46 +164,166 @@ impl EnqueueGuard {
                 .await?;
             anyhow::bail!("PAYLOAD_TOO_LARGE");
         }
+
+        if let Some(warn_bytes) = self.cfg.warn_payload_bytes {
+            if payload_bytes > warn_bytes {
+                eprintln!(
+                    "warning: queue={queue} payload of {payload_bytes} bytes is over the {warn_bytes} byte warning threshold (limit {})",
+                    self.cfg.max_payload_bytes
+                );
+                self.decisions
+                    .record(
+                        queue,
+                        "WARNED",
+                        "PAYLOAD_LARGE_WARN",
+                        json!({
+                            "warn_payload_bytes": warn_bytes,
+                            "max_payload_bytes": self.cfg.max_payload_bytes,
+                            "payload_bytes": payload_bytes
+                        }),
+                    )
+                    .await?;
+                sqlx::query(
+                    r#"
+                    INSERT INTO payload_warning_counters (queue, warned_total)
+                    VALUES ($1, 1)
+                    ON CONFLICT (queue)
+                    DO UPDATE SET warned_total = payload_warning_counters.warned_total + 1,
+                                  updated_at = now()
+                    "#,
+                )
+                .bind(queue)
+                .execute(&self.pool)
+                .await?;
+            }
+        }
         Ok(())
     }
 
-    pub async fn check_rate(&self, queue: &str) -> anyhow::Result<()> {
-        let now = Utc::now();
+    pub async fn check_job_type(&self, queue: &str, job_type: &str) -> anyhow::Result<()> {
+        if !self.cfg.reject_unknown_job_types {
+            return Ok(());
+        }
+        if JobTypesRepo::new(self.pool.clone())
+            .is_registered(job_type)
+            .await?
+        {
+            return Ok(());
+        }
+
+        let _ = self
+            .decisions
+            .record(
+                queue,
+                "DENIED",
+                "UNKNOWN_JOB_TYPE",
+                json!({ "job_type": job_type }),
+            )
+            .await?;
+        anyhow::bail!("UNKNOWN_JOB_TYPE");
+    }
+
+    /// Every check above for one job, in the order `POST /jobs` runs them.
+    pub async fn check_new_job(&self, job: &NewJob, tenant: Option<&str>) -> anyhow::Result<()> {
+        let payload_bytes = serde_json::to_vec(&job.payload_json)?.len();
+        self.check_enabled(&job.queue).await?;
+        self.check_payload(&job.queue, payload_bytes).await?;
+        self.check_job_type(&job.queue, &job.job_type).await?;
+        self.check_rate(&job.queue, tenant).await
+    }
+
+    /// Count this enqueue against `queue`'s per-minute limit and, when a
+    /// `tenant` with a limit is given, against that tenant's own bucket on the
+    /// queue first, so a tenant over its limit doesn't use up the queue's.
+    pub async fn check_rate(&self, queue: &str, tenant: Option<&str>) -> anyhow::Result<()> {
+        if let Some(tenant) = tenant {
+            if let Some(limit) = self.tenant_limit(tenant) {
+                self.check_bucket(queue, tenant, limit).await?;
+            }
+        }
+        self.check_bucket(queue, "", self.cfg.max_enqueues_per_minute_per_queue)
+            .await
+    }
+
+    fn tenant_limit(&self, tenant: &str) -> Option<i64> {
+        self.cfg
+            .tenant_limits
+            .get(tenant)
+            .copied()
+            .or(self.cfg.max_enqueues_per_minute_per_tenant)
+    }
+
+    // tenant "" is the queue-wide bucket
+    async fn check_bucket(&self, queue: &str, tenant: &str, limit: i64) -> anyhow::Result<()> {
+        let now = self.clock.now();
         let window_start =
             DateTime::<Utc>::from_timestamp(now.timestamp() - (now.second() as i64), 0)
-                .unwrap_or_else(Utc::now);
+                .unwrap_or(now);
 
         let mut tx = self.pool.begin().await?;
 
         let count: i64 = sqlx::query_scalar(
             r#"
-            INSERT INTO enqueue_rate_counters(queue, window_start, count)
-            VALUES ($1, $2, 1)
-            ON CONFLICT (queue, window_start)
+            INSERT INTO enqueue_rate_counters(queue, tenant, window_start, count)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (queue, tenant, window_start)
             DO UPDATE SET count = enqueue_rate_counters.count + 1
             RETURNING count
             "#,
         )
         .bind(queue)
+        .bind(tenant)
         .bind(window_start)
         .fetch_one(&mut *tx)
         //SQLx treats the transaction as an executor (like a “connection handle” you can run queries on).
         // Running a query through a transaction requires mutable access to that transaction object, because the transaction’s internal state is being used/advanced
         .await?;
 
-        if count > self.cfg.max_enqueues_per_minute_per_queue as i64 {
-            // record deny
-            let _ = self
-                .decisions
-                .record(
-                    queue,
-                    "DENIED",
-                    "ENQUEUE_RATE_EXCEEDED",
+        let (effective, mut details) = match self.cfg.rate_window {
+            RateWindow::Fixed => (
+                count as f64,
+                json!({
+                    "max_per_minute": limit,
+                    "count_this_minute": count
+                }),
+            ),
+            RateWindow::Sliding => {
+                let previous: i64 = sqlx::query_scalar(
+                    r#"
+                    SELECT count
+                    FROM enqueue_rate_counters
+                    WHERE queue = $1 AND tenant = $2 AND window_start = $3
+                    "#,
+                )
+                .bind(queue)
+                .bind(tenant)
+                .bind(window_start - chrono::Duration::minutes(1))
+                .fetch_optional(&mut *tx)
+                .await?
+                .unwrap_or(0);
+
+                let elapsed = (now - window_start).num_milliseconds() as f64 / 60_000.0;
+                let weighted = previous as f64 * (1.0 - elapsed).max(0.0) + count as f64;
+                (
+                    weighted,
                     json!({
-                        "max_per_minute": self.cfg.max_enqueues_per_minute_per_queue,
-                        "count_this_minute": count
+                        "max_per_minute": limit,
+                        "count_this_minute": count,
+                        "count_previous_minute": previous,
+                        "sliding_count": weighted
                     }),
                 )
+            }
+        };
+
+        if effective > limit as f64 {
+            if !tenant.is_empty() {
+                details["tenant"] = json!(tenant);
+            }
+            // record deny
+            let _ = self
+                .decisions
+                .record(queue, "DENIED", "ENQUEUE_RATE_EXCEEDED", details)
                 .await?;
 
             tx.commit().await?;
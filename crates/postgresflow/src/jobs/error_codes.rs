@@ -12,6 +12,7 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.trim().to_uppercase().as_str() {
             "TIMEOUT" => Self::Timeout,
@@ -39,6 +40,18 @@ impl ErrorCode {
     }
 }
 
+/// Map an HTTP status from an upstream call to an error code, so handlers
+/// classify external failures consistently (and get the right retry behavior).
+pub fn classify_http_status(status: u16) -> ErrorCode {
+    match status {
+        408 | 504 => ErrorCode::Timeout,
+        429 => ErrorCode::RateLimit,
+        500..=599 => ErrorCode::DependencyDown,
+        400..=499 => ErrorCode::BadPayload,
+        _ => ErrorCode::Unknown,
+    }
+}
+
 pub fn suggested_action(code: &str) -> &'static str {
     match ErrorCode::from_str(code) {
         ErrorCode::Timeout => {
@@ -67,3 +80,12 @@ pub fn suggested_action(code: &str) -> &'static str {
         }
     }
 }
+
+/// `suggested_action` broken into ordered steps (one per sentence) an
+/// operator (or a runbook tool) can work through.
+pub fn suggested_steps(code: &str) -> Vec<&'static str> {
+    suggested_action(code)
+        .split_inclusive(". ")
+        .map(str::trim_end)
+        .collect()
+}
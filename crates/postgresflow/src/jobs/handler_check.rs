@@ -0,0 +1,32 @@
+use crate::jobs::JobsRepo;
+
+/// Startup check: job types queued on `queue` that no registered handler covers.
+///
+/// Each one is logged as a warning (it would otherwise only surface as
+/// `UNKNOWN_JOB_TYPE` failures at runtime). With `fatal`, any gap is an error.
+pub async fn validate_handlers(
+    jobs: &JobsRepo,
+    queue: &str,
+    registered: &[String],
+    fatal: bool,
+) -> anyhow::Result<Vec<String>> {
+    let missing: Vec<String> = jobs
+        .queued_job_types(queue)
+        .await?
+        .into_iter()
+        .filter(|t| !registered.contains(t))
+        .collect();
+
+    for job_type in &missing {
+        eprintln!("warning: queue={queue} has queued jobs of type {job_type} but no handler is registered");
+    }
+
+    if fatal && !missing.is_empty() {
+        anyhow::bail!(
+            "no handler registered for job types on queue {queue}: {}",
+            missing.join(", ")
+        );
+    }
+
+    Ok(missing)
+}
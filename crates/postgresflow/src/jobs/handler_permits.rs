@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+/// A handler's semaphore and the permits it was created with.
+type Tracked = (Arc<Semaphore>, usize);
+
+/// Concurrency permits of the handlers registered in this process, keyed by
+/// job_type. The worker's handler registry tracks each limited handler's
+/// semaphore here so `/metrics/prom` can show how saturated it is.
+#[derive(Clone, Debug, Default)]
+pub struct HandlerPermits {
+    semaphores: Arc<Mutex<BTreeMap<String, Tracked>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandlerPermitsSnapshot {
+    pub job_type: String,
+    pub available: usize,
+    pub total: usize,
+}
+
+impl HandlerPermits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start reporting `semaphore` (created with `total` permits) for `job_type`.
+    pub fn track(&self, job_type: &str, semaphore: Arc<Semaphore>, total: usize) {
+        self.semaphores
+            .lock()
+            .unwrap()
+            .insert(job_type.to_string(), (semaphore, total));
+    }
+
+    /// Available and total permits per job_type, ordered by job_type.
+    pub fn snapshot(&self) -> Vec<HandlerPermitsSnapshot> {
+        self.semaphores
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(job_type, (sem, total))| HandlerPermitsSnapshot {
+                job_type: job_type.clone(),
+                available: sem.available_permits(),
+                total: *total,
+            })
+            .collect()
+    }
+}
@@ -1,7 +1,15 @@
+use serde::Serialize;
 use serde_json::Value;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DenialCount {
+    pub queue: String,
+    pub reason_code: String,
+    pub count: i64,
+}
+
 #[derive(Clone)]
 pub struct IngestDecisionsRepo {
     pool: PgPool,
@@ -103,4 +111,46 @@ impl IngestDecisionsRepo {
 
         Ok(rows)
     }
+
+    /// Enqueue denials grouped by queue and reason code.
+    pub async fn denial_counts(&self) -> anyhow::Result<Vec<DenialCount>> {
+        let rows = sqlx::query_as::<_, DenialCount>(
+            r#"
+            SELECT queue, reason_code, COUNT(*)::bigint AS count
+            FROM ingest_decisions
+            WHERE decision = 'DENIED'
+            GROUP BY queue, reason_code
+            ORDER BY queue, count DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Like `denial_counts`, limited to the last `window_secs` and optionally one queue.
+    pub async fn denial_counts_since(
+        &self,
+        window_secs: i64,
+        queue: Option<&str>,
+    ) -> anyhow::Result<Vec<DenialCount>> {
+        let rows = sqlx::query_as::<_, DenialCount>(
+            r#"
+            SELECT queue, reason_code, COUNT(*)::bigint AS count
+            FROM ingest_decisions
+            WHERE decision = 'DENIED'
+              AND created_at >= now() - make_interval(secs => $1)
+              AND ($2::text IS NULL OR queue = $2)
+            GROUP BY queue, reason_code
+            ORDER BY queue, count DESC
+            "#,
+        )
+        .bind(window_secs as f64)
+        .bind(queue)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
 }
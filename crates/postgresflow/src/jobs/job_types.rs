@@ -0,0 +1,47 @@
+use sqlx::PgPool;
+
+/// Registry of known job types (`job_types` table).
+#[derive(Clone)]
+pub struct JobTypesRepo {
+    pool: PgPool,
+}
+
+impl JobTypesRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Add job types to the registry; already registered ones are left as is.
+    pub async fn register(&self, job_types: &[String]) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_types (job_type)
+            SELECT UNNEST($1::text[])
+            ON CONFLICT (job_type) DO NOTHING
+            "#,
+        )
+        .bind(job_types)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn is_registered(&self, job_type: &str) -> anyhow::Result<bool> {
+        let found: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM job_types WHERE job_type = $1)")
+                .bind(job_type)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(found)
+    }
+
+    pub async fn list(&self) -> anyhow::Result<Vec<String>> {
+        let rows = sqlx::query_scalar("SELECT job_type FROM job_types ORDER BY job_type")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows)
+    }
+}
@@ -1,6 +1,21 @@
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, Utc};
 use sqlx::PgPool;
 
+use crate::jobs::JobsRepo;
+
+/// Default for `ARCHIVE_SUCCEEDED_AFTER_DAYS`.
+pub const DEFAULT_ARCHIVE_AFTER_DAYS: i64 = 7;
+
+/// Default for `PRUNE_HISTORY_AFTER_DAYS`.
+pub const DEFAULT_PRUNE_HISTORY_AFTER_DAYS: i64 = 7;
+
+/// Default for `MAINTENANCE_INTERVAL_SECS`.
+pub const DEFAULT_MAINTENANCE_INTERVAL_SECS: u64 = 60;
+
+/// `enqueue_rate_counters` rows older than this are dead: the rate check only
+/// reads the current minute and (sliding window) the previous one.
+pub const RATE_COUNTER_RETENTION_MINUTES: i64 = 2;
+
 #[derive(Clone)]
 pub struct MaintenanceRepo {
     pool: PgPool,
@@ -19,22 +34,7 @@ impl MaintenanceRepo {
         batch: i64,
     ) -> anyhow::Result<u64> {
         let mut tx = self.pool.begin().await?;
-
-        // Best-effort monthly partition bootstrap (no-op on older schemas).
-        sqlx::query(
-            r#"
-            DO $$
-            BEGIN
-              IF to_regprocedure('public.ensure_jobs_archive_partition(timestamp with time zone)') IS NOT NULL THEN
-                PERFORM public.ensure_jobs_archive_partition(now());
-                PERFORM public.ensure_jobs_archive_partition(now() + interval '1 month');
-              END IF;
-            END
-            $$;
-            "#,
-        )
-        .execute(&mut *tx)
-        .await?;
+        ensure_archive_partitions(&mut tx).await?;
 
         // Insert into archive while avoiding duplicates by id.
         let _inserted = sqlx::query(
@@ -44,7 +44,8 @@ impl MaintenanceRepo {
                   id, replay_of_job_id,
                   queue, job_type, payload_json,
                   run_at, status, priority, max_attempts,
-                  dlq_reason_code, dlq_at,
+                  dlq_reason_code, dlq_error_code, dlq_at,
+                  result_json,
                   created_at, updated_at
                 FROM jobs
                 WHERE status = 'succeeded'
@@ -57,14 +58,16 @@ impl MaintenanceRepo {
               id, replay_of_job_id,
               queue, job_type, payload_json,
               run_at, status, priority, max_attempts,
-              dlq_reason_code, dlq_at,
+              dlq_reason_code, dlq_error_code, dlq_at,
+              result_json,
               created_at, updated_at
             )
             SELECT
               c.id, c.replay_of_job_id,
               c.queue, c.job_type, c.payload_json,
               c.run_at, c.status, c.priority, c.max_attempts,
-              c.dlq_reason_code, c.dlq_at,
+              c.dlq_reason_code, c.dlq_error_code, c.dlq_at,
+              c.result_json,
               c.created_at, c.updated_at
             FROM candidates c
             WHERE NOT EXISTS (
@@ -102,8 +105,153 @@ impl MaintenanceRepo {
         Ok(deleted)
     }
 
-    /// Delete attempts + policy decisions for succeeded jobs older than `cutoff`.
-    /// Returns (attempts_deleted, policy_deleted).
+    /// Move every terminal job of `queue` into jobs_archive regardless of age:
+    /// succeeded and canceled, plus the ones waiting on an operator (dlq and
+    /// failed) when `include_dlq`. Used to retire a drained queue. Returns
+    /// number archived.
+    pub async fn archive_queue(&self, queue: &str, include_dlq: bool) -> anyhow::Result<u64> {
+        let statuses: Vec<&str> = if include_dlq {
+            vec!["succeeded", "canceled", "dlq", "failed"]
+        } else {
+            vec!["succeeded", "canceled"]
+        };
+
+        let mut tx = self.pool.begin().await?;
+        ensure_archive_partitions(&mut tx).await?;
+
+        sqlx::query(
+            r#"
+            WITH candidates AS (
+                SELECT
+                  id, replay_of_job_id,
+                  queue, job_type, payload_json,
+                  run_at, status, priority, max_attempts,
+                  dlq_reason_code, dlq_error_code, dlq_at,
+                  result_json,
+                  created_at, updated_at
+                FROM jobs
+                WHERE queue = $1
+                  AND status = ANY($2)
+                ORDER BY id
+                FOR UPDATE
+            )
+            INSERT INTO jobs_archive (
+              id, replay_of_job_id,
+              queue, job_type, payload_json,
+              run_at, status, priority, max_attempts,
+              dlq_reason_code, dlq_error_code, dlq_at,
+              result_json,
+              created_at, updated_at
+            )
+            SELECT
+              c.id, c.replay_of_job_id,
+              c.queue, c.job_type, c.payload_json,
+              c.run_at, c.status, c.priority, c.max_attempts,
+              c.dlq_reason_code, c.dlq_error_code, c.dlq_at,
+              c.result_json,
+              c.created_at, c.updated_at
+            FROM candidates c
+            WHERE NOT EXISTS (
+              SELECT 1
+              FROM jobs_archive a
+              WHERE a.id = c.id
+            )
+            "#,
+        )
+        .bind(queue)
+        .bind(&statuses)
+        .execute(&mut *tx)
+        .await?;
+
+        let deleted = sqlx::query(
+            r#"
+            DELETE FROM jobs j
+            USING jobs_archive a
+            WHERE j.id = a.id
+              AND j.queue = $1
+              AND j.status = ANY($2)
+            "#,
+        )
+        .bind(queue)
+        .bind(&statuses)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Jobs of `queue` that are still queued or running; a queue is drained at 0.
+    pub async fn queue_pending(&self, queue: &str) -> anyhow::Result<i64> {
+        let n: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)::bigint
+            FROM jobs
+            WHERE queue = $1
+              AND status IN ('queued', 'running')
+            "#,
+        )
+        .bind(queue)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(n)
+    }
+
+    /// Delete enqueue rate counter buckets that started before `cutoff`.
+    /// Returns number deleted.
+    pub async fn prune_rate_counters(&self, cutoff: DateTime<Utc>) -> anyhow::Result<u64> {
+        let deleted = sqlx::query(
+            r#"
+            DELETE FROM enqueue_rate_counters
+            WHERE window_start < $1
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(deleted)
+    }
+
+    /// Delete `prepare_enqueue` jobs never committed within their TTL.
+    pub async fn expire_prepared_jobs(&self) -> anyhow::Result<u64> {
+        let res = sqlx::query(
+            r#"
+            DELETE FROM jobs
+            WHERE status = 'preparing'
+              AND prepared_until <= now()
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(res.rows_affected())
+    }
+
+    /// Succeeded jobs older than `cutoff` still waiting to be archived.
+    /// If this keeps growing, archiving (batch/interval) can't keep up.
+    pub async fn archive_backlog(&self, cutoff: DateTime<Utc>) -> anyhow::Result<i64> {
+        let n: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)::bigint
+            FROM jobs
+            WHERE status = 'succeeded'
+              AND updated_at < $1
+            "#,
+        )
+        .bind(cutoff)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(n)
+    }
+
+    /// Delete attempts (with their log lines) + policy decisions for succeeded
+    /// jobs older than `cutoff`. Returns (attempts_deleted, policy_deleted).
+    ///
+    /// Locks the jobs first (skipping ones another transaction holds), then
+    /// their `job_attempts` and `policy_decisions` rows by id: the
+    /// `jobs` -> `job_attempts` -> `policy_decisions` order every
+    /// multi-table transaction follows (see ARCHITECTURE.md).
     pub async fn delete_history_for_succeeded_older_than(
         &self,
         cutoff: DateTime<Utc>,
@@ -111,7 +259,8 @@ impl MaintenanceRepo {
     ) -> anyhow::Result<(u64, u64)> {
         let mut tx = self.pool.begin().await?;
 
-        // pick job ids in small batches
+        // pick job ids in small batches; jobs some other transaction holds
+        // are left for the next pass
         let job_ids: Vec<uuid::Uuid> = sqlx::query_scalar(
             r#"
             SELECT id
@@ -120,6 +269,7 @@ impl MaintenanceRepo {
               AND updated_at < $1
             ORDER BY updated_at ASC
             LIMIT $2
+            FOR UPDATE SKIP LOCKED
             "#,
         )
         .bind(cutoff)
@@ -132,24 +282,41 @@ impl MaintenanceRepo {
             return Ok((0, 0));
         }
 
-        let attempts_deleted = sqlx::query!(
+        let attempts_deleted = sqlx::query(
             r#"
             DELETE FROM job_attempts
-            WHERE job_id = ANY($1)
+            WHERE id IN (
+              SELECT id
+              FROM job_attempts
+              WHERE job_id = ANY($1)
+              ORDER BY id
+              FOR UPDATE
+            )
             "#,
-            &job_ids
         )
+        .bind(&job_ids)
         .execute(&mut *tx)
         .await?
         .rows_affected();
 
-        let policy_deleted = sqlx::query!(
+        sqlx::query("DELETE FROM attempt_logs WHERE job_id = ANY($1)")
+            .bind(&job_ids)
+            .execute(&mut *tx)
+            .await?;
+
+        let policy_deleted = sqlx::query(
             r#"
             DELETE FROM policy_decisions
-            WHERE job_id = ANY($1)
+            WHERE id IN (
+              SELECT id
+              FROM policy_decisions
+              WHERE job_id = ANY($1)
+              ORDER BY id
+              FOR UPDATE
+            )
             "#,
-            &job_ids
         )
+        .bind(&job_ids)
         .execute(&mut *tx)
         .await?
         .rows_affected();
@@ -157,6 +324,151 @@ impl MaintenanceRepo {
         tx.commit().await?;
         Ok((attempts_deleted, policy_deleted))
     }
+
+    /// Delete up to `batch` `job_state_transitions` rows older than `cutoff`
+    /// whose job is finished or gone (archived, pruned); transitions of jobs
+    /// still preparing, queued or running are kept. Returns rows deleted.
+    pub async fn prune_state_transitions(
+        &self,
+        cutoff: DateTime<Utc>,
+        batch: i64,
+    ) -> anyhow::Result<u64> {
+        let deleted = sqlx::query(
+            r#"
+            DELETE FROM job_state_transitions
+            WHERE id IN (
+              SELECT t.id
+              FROM job_state_transitions t
+              WHERE t.at < $1
+                AND NOT EXISTS (
+                  SELECT 1
+                  FROM jobs j
+                  WHERE j.dataset_id = t.dataset_id
+                    AND j.id = t.job_id
+                    AND j.status IN ('preparing', 'queued', 'running')
+                )
+              ORDER BY t.at ASC
+              LIMIT $2
+            )
+            "#,
+        )
+        .bind(cutoff)
+        .bind(batch)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(deleted)
+    }
+}
+
+/// Best-effort monthly partition bootstrap (no-op on older schemas).
+async fn ensure_archive_partitions(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DO $$
+        BEGIN
+          IF to_regprocedure('public.ensure_jobs_archive_partition(timestamp with time zone)') IS NOT NULL THEN
+            PERFORM public.ensure_jobs_archive_partition(now());
+            PERFORM public.ensure_jobs_archive_partition(now() + interval '1 month');
+          END IF;
+        END
+        $$;
+        "#,
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// Daily window (`"02:00-04:00"`, local time at a fixed UTC offset) during
+/// which heavy maintenance may run. A start after the end wraps past midnight.
+/// Fixed offsets don't follow DST changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+    offset: FixedOffset,
+}
+
+impl MaintenanceWindow {
+    pub fn parse(spec: &str, offset: FixedOffset) -> anyhow::Result<Self> {
+        let (start, end) = spec.split_once('-').ok_or_else(|| {
+            anyhow::anyhow!("maintenance window must be HH:MM-HH:MM, got {spec:?}")
+        })?;
+        let time = |s: &str| {
+            NaiveTime::parse_from_str(s.trim(), "%H:%M")
+                .map_err(|e| anyhow::anyhow!("invalid time {s:?} in maintenance window: {e}"))
+        };
+        let (start, end) = (time(start)?, time(end)?);
+        if start == end {
+            anyhow::bail!("maintenance window {spec:?} is empty");
+        }
+        Ok(Self { start, end, offset })
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let t = at.with_timezone(&self.offset).time();
+        if self.start < self.end {
+            t >= self.start && t < self.end
+        } else {
+            t >= self.start || t < self.end
+        }
+    }
+}
+
+/// What one heavy maintenance pass did.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenancePass {
+    pub archived: u64,
+    pub attempts_deleted: u64,
+    pub policy_decisions_deleted: u64,
+    pub transitions_deleted: u64,
+}
+
+/// Archive + prune pass (500 rows each; history and state transitions by
+/// `prune_history_after_days`). Returns `None` without touching the
+/// DB when `now` is outside `window`; reaping is not affected by the window.
+pub async fn run_heavy_maintenance(
+    repo: &MaintenanceRepo,
+    window: Option<&MaintenanceWindow>,
+    now: DateTime<Utc>,
+    archive_after_days: i64,
+    prune_history_after_days: i64,
+) -> anyhow::Result<Option<MaintenancePass>> {
+    if window.is_some_and(|w| !w.contains(now)) {
+        return Ok(None);
+    }
+
+    let archived = repo
+        .archive_succeeded_older_than(cutoff_days(archive_after_days), 500)
+        .await?;
+    let (attempts_deleted, policy_decisions_deleted) = repo
+        .delete_history_for_succeeded_older_than(cutoff_days(prune_history_after_days), 500)
+        .await?;
+    let transitions_deleted = repo
+        .prune_state_transitions(cutoff_days(prune_history_after_days), 500)
+        .await?;
+
+    Ok(Some(MaintenancePass {
+        archived,
+        attempts_deleted,
+        policy_decisions_deleted,
+        transitions_deleted,
+    }))
+}
+
+/// Fast reap: reclaim the jobs of every worker whose heartbeat is older than
+/// `stale_after_secs` and drop its registry row, atomically
+/// (`JobsRepo::reap_dead_workers`).
+/// Returns (worker_id, jobs_requeued) per reaped worker.
+pub async fn reap_stale_workers(
+    jobs: &JobsRepo,
+    stale_after_secs: i64,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    jobs.reap_dead_workers(stale_after_secs).await
 }
 
 /// Convenience: compute cutoff like "now - N days"
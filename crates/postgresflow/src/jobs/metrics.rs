@@ -1,7 +1,16 @@
 use chrono::{DateTime, Utc};
+use futures::{StreamExt, TryStreamExt};
 use serde::Serialize;
 use sqlx::PgPool;
 
+use crate::jobs::maintenance::{cutoff_days, MaintenanceRepo, DEFAULT_ARCHIVE_AFTER_DAYS};
+
+/// Default window, in seconds, of the rate and latency fields of `Metrics`.
+pub const DEFAULT_METRICS_WINDOW_SECS: i64 = 60;
+
+/// Default number of queue snapshots `snapshot_all` runs at once.
+pub const DEFAULT_SNAPSHOT_CONCURRENCY: usize = 4;
+
 #[derive(Debug, Serialize)]
 pub struct Metrics {
     pub at: DateTime<Utc>,
@@ -9,24 +18,144 @@ pub struct Metrics {
     pub queue: String,
     pub runnable_queue_depth: i64,
 
-    // last 60s window
+    // running jobs vs the queue policy cap (None when no policy is set)
+    pub in_flight: i64,
+    pub max_in_flight: Option<i32>,
+
+    // over the last `window_secs` (attempts started in the window)
+    pub window_secs: i64,
     pub jobs_per_sec: f64,
+    // attempts started (first tries and retries): the load storm control sees
+    pub attempts_started_per_sec: f64,
     pub success_rate: f64,
     pub retry_rate: f64,
     pub mean_latency_ms: f64,
+    // enqueue -> first attempt start, for first attempts started in the window
+    pub mean_wait_ms: f64,
+
+    // failed attempts in the window, grouped by error_code
+    pub failures_by_error_code: Vec<ErrorCodeCount>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ErrorCodeCount {
+    pub error_code: String,
+    pub count: i64,
+}
+
+/// Upper bounds (`le`) of the attempts-to-success histogram buckets.
+pub const ATTEMPTS_TO_SUCCESS_BUCKETS: [i32; 6] = [1, 2, 3, 5, 10, 25];
+
+#[derive(Debug, Serialize)]
+pub struct HistogramBucket {
+    pub le: i32,
+    // cumulative: jobs that succeeded on attempt_no <= le
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttemptsToSuccess {
+    pub buckets: Vec<HistogramBucket>,
+    pub count: i64,
+    pub sum: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DlqReasonCount {
+    pub queue: String,
+    pub reason_code: Option<String>,
+    // underlying error code (`dlq_error_code`), e.g. TIMEOUT for MAX_ATTEMPTS_EXCEEDED
+    pub error_code: Option<String>,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct QueueCounter {
+    pub queue: String,
+    pub total: i64,
 }
 
 #[derive(Clone)]
 pub struct MetricsRepo {
     pool: PgPool,
+    archive_after_days: i64,
+    snapshot_concurrency: usize,
 }
 
 impl MetricsRepo {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            archive_after_days: DEFAULT_ARCHIVE_AFTER_DAYS,
+            snapshot_concurrency: DEFAULT_SNAPSHOT_CONCURRENCY,
+        }
+    }
+
+    /// Queue snapshots `snapshot_all` runs concurrently; each holds one pool
+    /// connection at a time, so keep this well below the pool size.
+    pub fn with_snapshot_concurrency(mut self, n: usize) -> Self {
+        self.snapshot_concurrency = n.max(1);
+        self
+    }
+
+    /// Cutoff used by `archive_backlog`; should match the maintenance loop's
+    /// `ARCHIVE_SUCCEEDED_AFTER_DAYS`.
+    pub fn with_archive_after_days(mut self, days: i64) -> Self {
+        self.archive_after_days = days;
+        self
+    }
+
+    /// Succeeded jobs past the archive cutoff that maintenance hasn't archived yet.
+    pub async fn archive_backlog(&self) -> anyhow::Result<i64> {
+        MaintenanceRepo::new(self.pool.clone())
+            .archive_backlog(cutoff_days(self.archive_after_days))
+            .await
+    }
+
+    /// Expired leases reaped so far, per queue (monotonic across workers).
+    pub async fn locks_reaped_totals(&self) -> anyhow::Result<Vec<QueueCounter>> {
+        let rows = sqlx::query_as::<_, QueueCounter>(
+            r#"
+            SELECT queue, reaped_total AS total
+            FROM lock_reap_counters
+            ORDER BY queue
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Enqueues skipped as duplicates of a scheduled dedupe_key, per queue.
+    pub async fn enqueue_deduped_totals(&self) -> anyhow::Result<Vec<QueueCounter>> {
+        let rows = sqlx::query_as::<_, QueueCounter>(
+            r#"
+            SELECT queue, deduped_total AS total
+            FROM enqueue_dedupe_counters
+            ORDER BY queue
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Enqueues let through over the soft payload-size threshold, per queue.
+    pub async fn payload_warning_totals(&self) -> anyhow::Result<Vec<QueueCounter>> {
+        let rows = sqlx::query_as::<_, QueueCounter>(
+            r#"
+            SELECT queue, warned_total AS total
+            FROM payload_warning_counters
+            ORDER BY queue
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
     }
 
-    pub async fn snapshot_all(&self) -> anyhow::Result<Vec<Metrics>> {
+    /// `snapshot_for_queue` for every queue that has jobs.
+    pub async fn snapshot_all(&self, window_secs: i64) -> anyhow::Result<Vec<Metrics>> {
         let queues: Vec<String> = sqlx::query_scalar(
             r#"
             SELECT DISTINCT queue
@@ -37,15 +166,23 @@ impl MetricsRepo {
         .fetch_all(&self.pool)
         .await?;
 
-        let mut out = Vec::with_capacity(queues.len());
-        for queue in queues {
-            out.push(self.snapshot_for_queue(&queue).await?);
-        }
-
-        Ok(out)
+        // bounded fan-out; `buffered` keeps the output in queue order
+        futures::stream::iter(queues)
+            .map(|queue| async move { self.snapshot_for_queue(&queue, window_secs).await })
+            .buffered(self.snapshot_concurrency)
+            .try_collect()
+            .await
     }
 
-    pub async fn snapshot_for_queue(&self, queue: &str) -> anyhow::Result<Metrics> {
+    /// Depth and concurrency now, plus throughput, success/retry rates and
+    /// latencies over attempts started in the last `window_secs` (at least 1).
+    pub async fn snapshot_for_queue(
+        &self,
+        queue: &str,
+        window_secs: i64,
+    ) -> anyhow::Result<Metrics> {
+        let window_secs = window_secs.max(1);
+
         // Depth (runnable queued)
         let depth: i64 = sqlx::query_scalar(
             r#"
@@ -60,11 +197,24 @@ impl MetricsRepo {
         .fetch_one(&self.pool)
         .await?;
 
-        // Attempts window stats (last 60 seconds)
+        // Concurrency: same running count storm control checks against max_in_flight
+        let (in_flight, max_in_flight) = sqlx::query_as::<_, (i64, Option<i32>)>(
+            r#"
+            SELECT
+              (SELECT COUNT(*) FROM jobs WHERE queue = $1 AND status = 'running')::bigint,
+              (SELECT max_in_flight FROM queue_policies WHERE queue = $1)
+            "#,
+        )
+        .bind(queue)
+        .fetch_one(&self.pool)
+        .await?;
+
+        // Attempts window stats (last window_secs seconds)
         // - throughput ~ attempts finished per sec
         // - success_rate = succeeded / finished
         // - retry_rate = attempts with attempt_no >=2 / total attempts started
         // - mean latency = avg(latency_ms) for finished attempts
+        // - mean wait = avg(started_at - created_at) for first attempts (time in queue)
         let row = sqlx::query_as::<
             _,
             (
@@ -73,15 +223,16 @@ impl MetricsRepo {
                 Option<f64>,
                 Option<f64>,
                 Option<f64>,
+                Option<f64>,
             ),
         >(
             r#"
             WITH a AS (
-              SELECT a.*
+              SELECT a.*, j.created_at AS job_created_at
               FROM job_attempts a
               JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
               WHERE j.queue = $1
-                AND a.started_at >= now() - interval '60 seconds'
+                AND a.started_at >= now() - ($2::bigint * interval '1 second')
             ),
             finished AS (
               SELECT *
@@ -93,20 +244,30 @@ impl MetricsRepo {
               (SELECT COUNT(*) FROM finished WHERE status = 'succeeded')::float8 AS succeeded_count,
               (SELECT COUNT(*) FROM a WHERE attempt_no >= 2)::float8 AS retry_count,
               (SELECT COUNT(*) FROM a)::float8 AS started_count,
-              COALESCE((SELECT AVG(latency_ms)::float8 FROM finished), 0.0) AS mean_latency_ms
+              COALESCE((SELECT AVG(latency_ms)::float8 FROM finished), 0.0) AS mean_latency_ms,
+              COALESCE((
+                SELECT AVG(EXTRACT(EPOCH FROM (started_at - job_created_at)) * 1000)::float8
+                FROM a
+                WHERE attempt_no = 1
+              ), 0.0) AS mean_wait_ms
             "#,
         )
         .bind(queue)
+        .bind(window_secs)
         .fetch_one(&self.pool)
         .await?;
 
+        let failures_by_error_code = self.failures_by_error_code(queue, window_secs).await?;
+
         let finished_count = row.0.unwrap_or(0.0);
         let succeeded_count = row.1.unwrap_or(0.0);
         let retry_count = row.2.unwrap_or(0.0);
         let started_count = row.3.unwrap_or(0.0);
         let mean_latency_ms = row.4.unwrap_or(0.0);
+        let mean_wait_ms = row.5.unwrap_or(0.0);
 
-        let jobs_per_sec = finished_count / 60.0;
+        let jobs_per_sec = finished_count / window_secs as f64;
+        let attempts_started_per_sec = started_count / window_secs as f64;
 
         let success_rate = if finished_count > 0.0 {
             succeeded_count / finished_count
@@ -124,10 +285,97 @@ impl MetricsRepo {
             at: Utc::now(),
             queue: queue.to_string(),
             runnable_queue_depth: depth,
+            in_flight,
+            max_in_flight,
+            window_secs,
             jobs_per_sec,
+            attempts_started_per_sec,
             success_rate,
             retry_rate,
             mean_latency_ms,
+            mean_wait_ms,
+            failures_by_error_code,
+        })
+    }
+
+    /// Jobs currently in the DLQ, grouped by queue and `dlq_reason_code`.
+    pub async fn dlq_report(&self) -> anyhow::Result<Vec<DlqReasonCount>> {
+        let rows = sqlx::query_as::<_, DlqReasonCount>(
+            r#"
+            SELECT
+              queue,
+              dlq_reason_code AS reason_code,
+              dlq_error_code AS error_code,
+              COUNT(*)::bigint AS count
+            FROM jobs
+            WHERE status = 'dlq'
+            GROUP BY queue, dlq_reason_code, dlq_error_code
+            ORDER BY queue, count DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Failed attempts started in the last `window_secs` seconds, grouped by
+    /// error_code. Attempts without a code are reported as UNKNOWN.
+    pub async fn failures_by_error_code(
+        &self,
+        queue: &str,
+        window_secs: i64,
+    ) -> anyhow::Result<Vec<ErrorCodeCount>> {
+        let rows = sqlx::query_as::<_, ErrorCodeCount>(
+            r#"
+            SELECT
+              COALESCE(a.error_code, 'UNKNOWN') AS error_code,
+              COUNT(*)::bigint AS count
+            FROM job_attempts a
+            JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
+            WHERE j.queue = $1
+              AND a.status = 'failed'
+              AND a.started_at >= now() - ($2::bigint * interval '1 second')
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(queue)
+        .bind(window_secs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Distribution of the succeeding attempt's `attempt_no` for jobs that
+    /// succeeded in the last `window_secs` seconds.
+    pub async fn attempts_to_success(&self, window_secs: i64) -> anyhow::Result<AttemptsToSuccess> {
+        let rows = sqlx::query_as::<_, (i32, i64)>(
+            r#"
+            SELECT attempt_no, COUNT(*)::bigint
+            FROM job_attempts
+            WHERE status = 'succeeded'
+              AND finished_at >= now() - ($1::bigint * interval '1 second')
+            GROUP BY attempt_no
+            "#,
+        )
+        .bind(window_secs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let buckets = ATTEMPTS_TO_SUCCESS_BUCKETS
+            .iter()
+            .map(|&le| HistogramBucket {
+                le,
+                count: rows.iter().filter(|(n, _)| *n <= le).map(|(_, c)| c).sum(),
+            })
+            .collect();
+
+        Ok(AttemptsToSuccess {
+            buckets,
+            count: rows.iter().map(|(_, c)| c).sum(),
+            sum: rows.iter().map(|(n, c)| i64::from(*n) * c).sum(),
         })
     }
 }
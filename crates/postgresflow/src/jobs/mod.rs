@@ -1,12 +1,23 @@
 pub mod attempts;
+pub mod clock;
 pub mod error_codes;
+pub mod handler_check;
+pub mod handler_permits;
+pub mod job_types;
 pub mod model;
+pub mod payload_template;
 pub mod policies;
 pub mod repo;
 pub mod retry;
 pub mod runner;
+pub mod schedules;
+pub mod sla;
+pub mod system_flags;
+pub mod throttle;
 pub mod timeline;
-pub use policies::{PoliciesRepo, QueuePolicy};
+pub mod wakeup;
+pub mod workers;
+pub use policies::{PoliciesRepo, QueuePolicy, StormControlLock};
 
 pub mod maintenance;
 pub use maintenance::{cutoff_days, MaintenanceRepo};
@@ -19,6 +30,18 @@ pub use metrics::{Metrics, MetricsRepo};
 pub mod policy_decisions;
 pub use policy_decisions::{PolicyDecisionRow, PolicyDecisionsRepo};
 
-pub use attempts::AttemptsRepo;
-pub use model::{Job, JobStatus, NewJob};
+pub use attempts::{AttemptLogLine, AttemptsRepo, LogLevel, StartedAttempts};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use handler_permits::HandlerPermits;
+pub use job_types::JobTypesRepo;
+pub use model::{
+    Enqueued, GroupStatus, Job, JobHeader, JobRecovery, JobStateTransition, JobStatus, LeaseResult,
+    NewJob, PayloadEdit, QueuePressure, SucceededJob,
+};
 pub use repo::JobsRepo;
+pub use schedules::{NewSchedule, Schedule, SchedulesRepo};
+pub use sla::SlaRepo;
+pub use system_flags::SystemFlagsRepo;
+pub use throttle::JobRateLimiter;
+pub use wakeup::WakeupCoalescer;
+pub use workers::WorkersRepo;
@@ -4,11 +4,15 @@ use serde_json::Value;
 
 use uuid::Uuid;
 
+use crate::jobs::policies::QueuePolicy;
+use crate::jobs::retry::RetryOverride;
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct Job {
     pub dataset_id: String,
 
     pub replay_of_job_id: Option<Uuid>,
+    pub replay_include_history: bool,
 
     pub id: Uuid,
     pub queue: String,
@@ -24,12 +28,61 @@ pub struct Job {
     pub lock_expires_at: Option<DateTime<Utc>>,
 
     pub dlq_reason_code: Option<String>,
+    // last error code when the job was DLQ'd (the cause behind dlq_reason_code)
+    pub dlq_error_code: Option<String>,
     pub dlq_at: Option<DateTime<Utc>>,
 
+    pub target_worker_id: Option<String>,
+    pub group_id: Option<Uuid>,
+
+    // value the handler returned on success, if any
+    pub result_json: Option<Value>,
+
+    // per-job backoff from enqueue (`NewJob::retry`)
+    pub retry_base_seconds: Option<i64>,
+    pub retry_max_seconds: Option<i64>,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Job {
+    /// The backoff stored on the row at enqueue; all `None` when unset.
+    pub fn retry_override(&self) -> RetryOverride {
+        RetryOverride {
+            base_seconds: self.retry_base_seconds,
+            max_seconds: self.retry_max_seconds,
+        }
+    }
+}
+
+/// One row of a job's status audit log (`job_state_transitions`, written by
+/// a trigger on every status change). `from_status` is `None` for the enqueue.
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+pub struct JobStateTransition {
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub worker_id: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+/// Job row without `payload_json`, for views that never show the payload
+/// (timeline, explain) so large payloads aren't read for nothing.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct JobHeader {
+    pub id: Uuid,
+    pub queue: String,
+    pub job_type: String,
+    pub run_at: DateTime<Utc>,
+    pub status: String,
+
+    pub replay_of_job_id: Option<Uuid>,
+    pub replay_include_history: bool,
+
+    pub dlq_reason_code: Option<String>,
+    pub dlq_error_code: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct NewJob {
     pub queue: String,
@@ -38,9 +91,133 @@ pub struct NewJob {
     pub run_at: DateTime<Utc>,
     pub priority: i32,
     pub max_attempts: i32,
+    /// Only this worker may lease the job (until the repo's pin timeout elapses).
+    pub target_worker_id: Option<String>,
+    /// Backoff for this job instead of the worker's `RetryConfig`.
+    pub retry: Option<RetryOverride>,
+    /// Stored on the row; `enqueue_batch` keeps only the first job per key.
+    pub dedupe_key: Option<String>,
+    /// While a job enqueued with this key on the same queue is still
+    /// preparing, queued or running (and within the repo's idempotency
+    /// window), enqueueing again returns that job instead of a new one.
+    pub idempotency_key: Option<String>,
+    /// Job group this job belongs to; see `JobsRepo::group_status`.
+    pub group_id: Option<Uuid>,
+}
+
+/// Outcome of `JobsRepo::enqueue_idempotent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Enqueued {
+    pub job_id: Uuid,
+    /// The idempotency key matched a live job; `job_id` is that job.
+    pub deduplicated: bool,
+}
+
+/// A job to mark succeeded, with the handler's feedback to store alongside
+/// (`JobsRepo::mark_succeeded_batch_with_feedback`).
+#[derive(Debug, Clone)]
+pub struct SucceededJob {
+    pub job_id: Uuid,
+    pub result: Option<serde_json::Value>,
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+/// A leased job plus the queue policy in effect when it was leased.
+#[derive(Debug, Clone)]
+pub struct LeaseResult {
+    pub job: Job,
+    pub policy: Option<QueuePolicy>,
+}
+
+/// Current load of a queue against its storm-control policy; `None`
+/// limits are unset.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct QueuePressure {
+    pub in_flight: i64,
+    pub max_in_flight: Option<i32>,
+    pub attempts_last_min: i64,
+    pub max_attempts_per_minute: Option<i32>,
+}
+
+impl QueuePressure {
+    /// Same gates `lease_jobs_batch` applies before throttling.
+    pub fn likely_throttled(&self) -> bool {
+        self.max_in_flight
+            .is_some_and(|max| self.in_flight >= max as i64)
+            || self
+                .max_attempts_per_minute
+                .is_some_and(|max| self.attempts_last_min >= max as i64)
+    }
+
+    /// in_flight / max_in_flight (can exceed 1.0 once over the cap); `None`
+    /// without an in-flight limit.
+    pub fn utilization(&self) -> Option<f64> {
+        let max = self.max_in_flight?;
+        if max <= 0 {
+            return Some(1.0);
+        }
+        Some(self.in_flight as f64 / max as f64)
+    }
+}
+
+/// Outcome of a manual payload edit.
+#[derive(Debug, Clone)]
+pub enum PayloadEdit {
+    Updated(Box<Job>),
+    NotFound,
+    /// Only `queued` and `dlq` jobs can be edited; carries the current status.
+    NotEditable(String),
+}
+
+/// Outcome of moving a `failed` job back to `queued` or on to the DLQ.
+#[derive(Debug, Clone)]
+pub enum JobRecovery {
+    /// The job after the move.
+    Recovered(Box<Job>),
+    NotFound,
+    /// Only `failed` jobs can be recovered; carries the current status.
+    NotFailed(String),
+}
+
+/// Members of a job group (`NewJob::group_id`) by status, and the status of
+/// the group as a whole.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GroupStatus {
+    pub group_id: Uuid,
+    /// `failed` once any member is in the DLQ or `failed`; otherwise
+    /// `in_progress` while any member is still to run, `succeeded` when all
+    /// did, and `canceled` when the rest succeeded but some were canceled.
+    pub status: &'static str,
+    pub total: i64,
+    pub by_status: std::collections::BTreeMap<String, i64>,
+}
+
+impl GroupStatus {
+    pub(crate) fn from_counts(group_id: Uuid, counts: Vec<(String, i64)>) -> Self {
+        let by_status: std::collections::BTreeMap<String, i64> = counts.into_iter().collect();
+        let total = by_status.values().sum();
+        let any = |statuses: &[&str]| statuses.iter().any(|s| by_status.contains_key(*s));
+        let status = if any(&["dlq", "failed"]) {
+            "failed"
+        } else if any(&["preparing", "queued", "running"]) {
+            "in_progress"
+        } else if any(&["canceled"]) {
+            "canceled"
+        } else {
+            "succeeded"
+        };
+        Self {
+            group_id,
+            status,
+            total,
+            by_status,
+        }
+    }
 }
 
 pub enum JobStatus {
+    /// Inserted by `prepare_enqueue`, not leasable until committed.
+    Preparing,
     Queued,
     Running,
     Succeeded,
@@ -52,6 +229,7 @@ pub enum JobStatus {
 impl JobStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
+            JobStatus::Preparing => "preparing",
             JobStatus::Queued => "queued",
             JobStatus::Running => "running",
             JobStatus::Succeeded => "succeeded",
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Resolve `{{now}}`, `{{date}}` and `{{uuid}}` placeholders in every string
+/// value of `template`.
+///
+/// - `{{now}}`  -> RFC3339 timestamp of `now`
+/// - `{{date}}` -> `YYYY-MM-DD` of `now` (UTC)
+/// - `{{uuid}}` -> a fresh v4 UUID per occurrence
+///
+/// Plain substitution only: object keys and unknown placeholders are left as-is.
+pub fn render(template: &Value, now: DateTime<Utc>) -> Value {
+    match template {
+        Value::String(s) => Value::String(render_str(s, now)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render(v, now)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render(v, now)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn render_str(s: &str, now: DateTime<Utc>) -> String {
+    if !s.contains("{{") {
+        return s.to_string();
+    }
+
+    let mut out = s
+        .replace("{{now}}", &now.to_rfc3339())
+        .replace("{{date}}", &now.format("%Y-%m-%d").to_string());
+
+    while let Some(pos) = out.find("{{uuid}}") {
+        out.replace_range(pos..pos + "{{uuid}}".len(), &Uuid::new_v4().to_string());
+    }
+
+    out
+}
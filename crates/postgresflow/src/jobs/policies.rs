@@ -1,11 +1,59 @@
+use crate::jobs::retry::RetryConfig;
 use sqlx::PgPool;
 
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct QueuePolicy {
     pub queue: String,
-    pub max_attempts_per_minute: i32,
-    pub max_in_flight: i32,
+    /// `None` = no limit (rows created by the other setters, not `set_policy`).
+    pub max_attempts_per_minute: Option<i32>,
+    pub max_in_flight: Option<i32>,
     pub throttle_delay_ms: i32,
+    /// Lease equal-priority jobs by `created_at` only, ignoring `run_at` once runnable.
+    pub fifo_within_priority: bool,
+    /// Idle workers may take over slow leases (`set_lease_stealing`).
+    pub steal_enabled: bool,
+}
+
+impl QueuePolicy {
+    /// Whether storm control has anything to check for this queue.
+    pub fn has_limits(&self) -> bool {
+        self.max_attempts_per_minute.is_some() || self.max_in_flight.is_some()
+    }
+}
+
+/// Advisory lock namespace of storm-control checks; per-queue keys append `:<queue>`.
+pub const STORM_CONTROL_LOCK_NAMESPACE: &str = "pgflow_storm_control";
+
+/// How the lease path serializes storm-control checks (in-flight and
+/// attempts-per-minute counts) of queues that have a policy, so concurrent
+/// leasers can't both pass a limit only one of them fits under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StormControlLock {
+    /// No lock: checks run concurrently and may briefly overshoot a limit.
+    Off,
+    /// One advisory lock for every queue: exact, but busy queues wait on each other.
+    Shared,
+    /// One advisory lock per queue: exact per queue, queues never wait on each other.
+    PerQueue,
+}
+
+impl StormControlLock {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "shared" => StormControlLock::Shared,
+            "queue" => StormControlLock::PerQueue,
+            _ => StormControlLock::Off,
+        }
+    }
+
+    /// Name hashed into the advisory lock key guarding `queue`'s checks.
+    pub fn lock_name(self, queue: &str) -> Option<String> {
+        match self {
+            StormControlLock::Off => None,
+            StormControlLock::Shared => Some(STORM_CONTROL_LOCK_NAMESPACE.to_string()),
+            StormControlLock::PerQueue => Some(format!("{STORM_CONTROL_LOCK_NAMESPACE}:{queue}")),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -21,7 +69,8 @@ impl PoliciesRepo {
     pub async fn get_policy(&self, queue: &str) -> anyhow::Result<Option<QueuePolicy>> {
         let rec = sqlx::query_as::<_, QueuePolicy>(
             r#"
-            SELECT queue, max_attempts_per_minute, max_in_flight, throttle_delay_ms
+            SELECT queue, max_attempts_per_minute, max_in_flight, throttle_delay_ms,
+                   fifo_within_priority, steal_enabled
             FROM queue_policies
             WHERE queue = $1
             "#,
@@ -59,4 +108,146 @@ impl PoliciesRepo {
 
         Ok(())
     }
+
+    /// `queue`'s retry backoff over `RetryConfig::default()`; see
+    /// `retry_config_over`.
+    pub async fn get_retry_config(&self, queue: &str) -> anyhow::Result<RetryConfig> {
+        self.retry_config_over(queue, &RetryConfig::default()).await
+    }
+
+    /// `fallback` with the retry columns `queue`'s policy sets replacing the
+    /// matching fields; `fallback` as is when there is no policy row.
+    pub async fn retry_config_over(
+        &self,
+        queue: &str,
+        fallback: &RetryConfig,
+    ) -> anyhow::Result<RetryConfig> {
+        let row: Option<(Option<i32>, Option<i32>, Option<f64>)> = sqlx::query_as(
+            r#"
+            SELECT retry_base_seconds, retry_max_seconds, retry_jitter_pct
+            FROM queue_policies
+            WHERE queue = $1
+            "#,
+        )
+        .bind(queue)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some((base_seconds, max_seconds, jitter_pct)) = row else {
+            return Ok(fallback.clone());
+        };
+        Ok(RetryConfig {
+            base_seconds: base_seconds.map_or(fallback.base_seconds, i64::from),
+            max_seconds: max_seconds.map_or(fallback.max_seconds, i64::from),
+            jitter_pct: jitter_pct.unwrap_or(fallback.jitter_pct),
+            ..fallback.clone()
+        })
+    }
+
+    /// Set `queue`'s retry backoff; `None` fields fall back to the worker's
+    /// `RetryConfig`. Creates the policy row if missing, without storm-control
+    /// limits.
+    pub async fn set_retry_config(
+        &self,
+        queue: &str,
+        base_seconds: Option<i32>,
+        max_seconds: Option<i32>,
+        jitter_pct: Option<f64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO queue_policies(queue, retry_base_seconds, retry_max_seconds, retry_jitter_pct)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(queue) DO UPDATE
+            SET retry_base_seconds = EXCLUDED.retry_base_seconds,
+                retry_max_seconds = EXCLUDED.retry_max_seconds,
+                retry_jitter_pct = EXCLUDED.retry_jitter_pct
+            "#,
+        )
+        .bind(queue)
+        .bind(base_seconds)
+        .bind(max_seconds)
+        .bind(jitter_pct)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Normalize priorities written for `queue`'s jobs from now on (enqueue,
+    /// `set_priority`, replays, DLQ requeues) into `bounds` = (min, max),
+    /// clamping values outside it; `None` allows the full i32 range again.
+    /// Jobs already enqueued keep their priority. Creates the policy row
+    /// if missing, without storm-control limits.
+    pub async fn set_priority_bounds(
+        &self,
+        queue: &str,
+        bounds: Option<(i32, i32)>,
+    ) -> anyhow::Result<()> {
+        if let Some((min, max)) = bounds {
+            anyhow::ensure!(min <= max, "priority bounds {min}..{max} are empty");
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO queue_policies(queue, priority_min, priority_max)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(queue) DO UPDATE
+            SET priority_min = EXCLUDED.priority_min,
+                priority_max = EXCLUDED.priority_max
+            "#,
+        )
+        .bind(queue)
+        .bind(bounds.map(|(min, _)| min))
+        .bind(bounds.map(|(_, max)| max))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Switch `queue` between the default dispatch order (priority, run_at,
+    /// created_at) and FIFO within priority (priority, created_at).
+    /// Creates the policy row if missing, without storm-control limits.
+    pub async fn set_fifo_within_priority(&self, queue: &str, enabled: bool) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO queue_policies(queue, fifo_within_priority)
+            VALUES ($1, $2)
+            ON CONFLICT(queue) DO UPDATE
+            SET fifo_within_priority = EXCLUDED.fifo_within_priority
+            "#,
+        )
+        .bind(queue)
+        .bind(enabled)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Enable lease stealing for `queue` with the given latency multiple, or
+    /// disable it with `None`. Creates the policy row if missing, without
+    /// storm-control limits.
+    pub async fn set_lease_stealing(
+        &self,
+        queue: &str,
+        latency_multiple: Option<f64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO queue_policies(queue, steal_enabled, steal_latency_multiple)
+            VALUES ($1, $2, COALESCE($3, 10))
+            ON CONFLICT(queue) DO UPDATE
+            SET steal_enabled = EXCLUDED.steal_enabled,
+                steal_latency_multiple = COALESCE($3, queue_policies.steal_latency_multiple)
+            "#,
+        )
+        .bind(queue)
+        .bind(latency_multiple.is_some())
+        .bind(latency_multiple)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
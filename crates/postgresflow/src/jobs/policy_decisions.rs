@@ -11,6 +11,9 @@ pub struct PolicyDecisionRow {
     pub reason_code: String, // IN_FLIGHT_EXCEEDED / RETRY_RATE_EXCEEDED ...
     pub details_json: Value,
     pub created_at: DateTime<Utc>,
+    // identical decisions coalesced into this row, latest at last_seen_at
+    pub count: i32,
+    pub last_seen_at: DateTime<Utc>,
 }
 
 #[derive(Clone)]
@@ -58,7 +61,7 @@ impl PolicyDecisionsRepo {
     pub async fn list_for_job(&self, job_id: Uuid) -> anyhow::Result<Vec<PolicyDecisionRow>> {
         let rows = sqlx::query_as::<_, PolicyDecisionRow>(
             r#"
-            SELECT id, job_id, decision, reason_code, details_json, created_at
+            SELECT id, job_id, decision, reason_code, details_json, created_at, count, last_seen_at
             FROM policy_decisions
             WHERE job_id = $1
             ORDER BY created_at ASC
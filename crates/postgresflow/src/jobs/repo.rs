@@ -1,20 +1,260 @@
 // crates/postgresflow/src/jobs/repo.rs
 
 use crate::api::models::JobListItem;
-use crate::jobs::model::{Job, JobStatus, NewJob};
+use crate::db::{self, TxIsolation};
+use crate::jobs::clock::{Clock, SystemClock};
+use crate::jobs::enqueue_guard::{self, EnqueueGuard};
+use crate::jobs::ingest_decisions::IngestDecisionsRepo;
+use crate::jobs::model::{
+    Enqueued, GroupStatus, Job, JobHeader, JobRecovery, JobStateTransition, JobStatus, LeaseResult,
+    NewJob, PayloadEdit, QueuePressure, SucceededJob,
+};
+use crate::jobs::policies::{QueuePolicy, StormControlLock};
+use crate::jobs::retry::{FailurePolicy, RetryConfig, RetryOverride};
 use chrono::{DateTime, Utc};
 use serde_json::json;
-use sqlx::PgPool;
+use sqlx::postgres::{PgArguments, PgListener};
+use sqlx::query::QueryAs;
+use sqlx::{PgPool, Postgres};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use uuid::Uuid;
 
+/// Default time after `run_at` when a job pinned to a worker becomes leasable by anyone.
+pub const DEFAULT_PIN_TIMEOUT_SECS: i64 = 300;
+
+/// Default window in which repeated identical THROTTLED decisions share one row.
+pub const DEFAULT_DECISION_COALESCE_SECS: i64 = 60;
+
+/// Default time an idempotency key dedupes against the job that claimed it.
+pub const DEFAULT_IDEMPOTENCY_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Two schedule-once enqueues with the same dedupe_key count as the same
+/// schedule when their `run_at`s are at most this far apart.
+pub const SCHEDULE_ONCE_TOLERANCE_SECS: i64 = 60;
+
 #[derive(Clone)]
 pub struct JobsRepo {
     pool: PgPool,
+    pin_timeout_secs: i64,
+    success_overrides_cancel: bool,
+    lease_isolation: TxIsolation,
+    serialization_retries: u32,
+    decision_coalesce_secs: i64,
+    dataset_round_robin: bool,
+    single_dataset_batches: bool,
+    max_batch_datasets: Option<usize>,
+    record_dedupe_decisions: bool,
+    batch_chunk_size: usize,
+    reap_requeue_delay_ms: i64,
+    idempotency_window_secs: i64,
+    cancel_group_on_dlq: bool,
+    notify_on_enqueue: bool,
+    storm_control_lock: StormControlLock,
+    enqueue_guard: Option<EnqueueGuard>,
+    clock: Arc<dyn Clock>,
+    // (queue, worker_id) -> dataset of that worker's last non-empty lease
+    last_leased_dataset: Arc<Mutex<HashMap<(String, String), String>>>,
 }
 
+/// CTE adding a statement's `reaped` rows (with a `queue` column) to the
+/// per-queue `lock_reap_counters` behind `pgflow_locks_reaped_total`.
+const LOCK_REAP_COUNTED_CTE: &str = r#"
+            counted AS (
+                INSERT INTO lock_reap_counters (queue, reaped_total)
+                SELECT queue, COUNT(*)
+                FROM reaped
+                GROUP BY queue
+                ON CONFLICT (queue)
+                DO UPDATE SET reaped_total = lock_reap_counters.reaped_total + EXCLUDED.reaped_total,
+                              updated_at = now()
+            )"#;
+
 impl JobsRepo {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            pin_timeout_secs: DEFAULT_PIN_TIMEOUT_SECS,
+            success_overrides_cancel: false,
+            lease_isolation: TxIsolation::Default,
+            serialization_retries: db::DEFAULT_SERIALIZATION_RETRIES,
+            decision_coalesce_secs: DEFAULT_DECISION_COALESCE_SECS,
+            dataset_round_robin: false,
+            single_dataset_batches: true,
+            max_batch_datasets: None,
+            record_dedupe_decisions: false,
+            batch_chunk_size: db::DEFAULT_BATCH_CHUNK_SIZE,
+            reap_requeue_delay_ms: 0,
+            idempotency_window_secs: DEFAULT_IDEMPOTENCY_WINDOW_SECS,
+            cancel_group_on_dlq: false,
+            notify_on_enqueue: true,
+            storm_control_lock: StormControlLock::Off,
+            enqueue_guard: None,
+            clock: Arc::new(SystemClock),
+            last_leased_dataset: Arc::default(),
+        }
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Clock for `run_at`s computed here (`enqueue_now`, `enqueue_in`, replay
+    /// without an explicit `run_at`); tests pass a `MockClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// How long a job pinned via `target_worker_id` waits for its worker
+    /// (measured from `run_at`) before any worker may lease it.
+    pub fn with_pin_timeout_secs(mut self, secs: i64) -> Self {
+        self.pin_timeout_secs = secs.max(0);
+        self
+    }
+
+    /// When a job is canceled while its handler runs and the handler still
+    /// returns Ok, the attempt is always recorded as succeeded. By default the
+    /// job stays `canceled`; set this to let the success win instead.
+    pub fn with_success_overrides_cancel(mut self, enabled: bool) -> Self {
+        self.success_overrides_cancel = enabled;
+        self
+    }
+
+    /// Isolation level for the lease transaction.
+    pub fn with_lease_isolation(mut self, isolation: TxIsolation) -> Self {
+        self.lease_isolation = isolation;
+        self
+    }
+
+    /// How many times a lease aborted with a serialization failure (40001) is retried.
+    pub fn with_serialization_retries(mut self, retries: u32) -> Self {
+        self.serialization_retries = retries;
+        self
+    }
+
+    /// Repeated THROTTLED decisions for a job with the same reason, each within
+    /// `secs` of the previous one, bump `count` on one row instead of inserting.
+    /// `0` disables coalescing.
+    pub fn with_decision_coalesce_secs(mut self, secs: i64) -> Self {
+        self.decision_coalesce_secs = secs.max(0);
+        self
+    }
+
+    /// Rotate successive leases by the same worker across runnable datasets
+    /// (next dataset_id after the last one served, wrapping), so one large
+    /// dataset can't monopolize the worker. When off (default), the dataset
+    /// holding the earliest runnable job is always served first.
+    pub fn with_dataset_round_robin(mut self, enabled: bool) -> Self {
+        self.dataset_round_robin = enabled;
+        self
+    }
+
+    /// When on (default), every leased batch holds jobs of one dataset_id:
+    /// the dataset is picked first (see `with_dataset_round_robin`) and only
+    /// its jobs are leased, which the worker relies on to record successes
+    /// per dataset. When off, a batch takes the best runnable jobs of the
+    /// queue across datasets, for deployments that don't use datasets;
+    /// round-robin then has no effect.
+    pub fn with_single_dataset_batches(mut self, enabled: bool) -> Self {
+        self.single_dataset_batches = enabled;
+        self
+    }
+
+    /// With single-dataset batches off, lease from at most `n` datasets per
+    /// batch: the `n` datasets holding the best runnable jobs are picked
+    /// first, the rest wait for a later lease. Bounds how many datasets a
+    /// worker runs at once.
+    pub fn with_max_batch_datasets(mut self, n: usize) -> Self {
+        self.max_batch_datasets = Some(n.max(1));
+        self
+    }
+
+    /// Also write an ingest decision (`DEDUPED` / `DUPLICATE_DEDUPE_KEY`, or
+    /// `COOLDOWN_ACTIVE` inside a handler's cooldown) for every enqueue
+    /// skipped by `enqueue_scheduled_once`. The per-queue
+    /// counter behind `pgflow_enqueue_deduped_total` is kept either way.
+    pub fn with_dedupe_decisions(mut self, enabled: bool) -> Self {
+        self.record_dedupe_decisions = enabled;
+        self
+    }
+
+    /// Max job ids per statement in `mark_succeeded_batch_for_dataset`.
+    pub fn with_batch_chunk_size(mut self, size: usize) -> Self {
+        self.batch_chunk_size = size.max(1);
+        self
+    }
+
+    /// Serialize storm-control checks under an advisory lock: `Shared` across
+    /// all queues, `PerQueue` keyed by queue name so throttling on one queue
+    /// never waits on another. Default `Off`.
+    pub fn with_storm_control_lock(mut self, lock: StormControlLock) -> Self {
+        self.storm_control_lock = lock;
+        self
+    }
+
+    /// A job requeued by a reap (expired lease or dead worker) gets
+    /// `run_at = now() + ms`, so the worker that lost it and a healthy one
+    /// don't fight over it right away. `0` (default) leaves `run_at` as is.
+    pub fn with_reap_requeue_delay_ms(mut self, ms: u64) -> Self {
+        self.reap_requeue_delay_ms = i64::try_from(ms).unwrap_or(i64::MAX);
+        self
+    }
+
+    /// How long after a job claimed an idempotency key that key keeps
+    /// deduplicating enqueues (while the job isn't finished).
+    pub fn with_idempotency_window_secs(mut self, secs: i64) -> Self {
+        self.idempotency_window_secs = secs.max(0);
+        self
+    }
+
+    /// When a job in a group goes to the DLQ via `mark_dlq`, cancel the
+    /// group's members that are still queued in the same transaction (each
+    /// gets a `GROUP_CANCEL` policy decision). Running members finish as
+    /// usual. `AttemptsRepo::with_cancel_group_on_dlq` does the same for
+    /// jobs the attempt overflow guard dead-letters.
+    pub fn with_cancel_group_on_dlq(mut self, enabled: bool) -> Self {
+        self.cancel_group_on_dlq = enabled;
+        self
+    }
+
+    /// `pg_notify` the queue's channel for every job made runnable by an
+    /// enqueue (default). Off, idle workers only find new jobs on their next
+    /// poll (`PGFLOW_IDLE_POLL_MS`), but enqueues skip the NOTIFY, which
+    /// serializes committing transactions on a global lock.
+    pub fn with_notify_on_enqueue(mut self, enabled: bool) -> Self {
+        self.notify_on_enqueue = enabled;
+        self
+    }
+
+    /// Run `guard`'s checks (kill switch, payload size, job type, rate) on
+    /// every job `enqueue`, `enqueue_batch`, `prepare_enqueue` and
+    /// `enqueue_scheduled_once` insert.
+    /// Producers embedding the repo set this instead of calling the guard
+    /// themselves. Without it only the kill switch is enforced; that one
+    /// also covers `commit_enqueue` and replays.
+    pub fn with_enqueue_guard(mut self, guard: EnqueueGuard) -> Self {
+        self.enqueue_guard = Some(guard);
+        self
+    }
+
+    async fn check_enqueue(&self, job: &NewJob) -> anyhow::Result<()> {
+        match &self.enqueue_guard {
+            Some(guard) => guard.check_new_job(job, None).await,
+            None => self.check_enqueue_enabled(&job.queue).await,
+        }
+    }
+
+    async fn check_enqueue_enabled(&self, queue: &str) -> anyhow::Result<()> {
+        match &self.enqueue_guard {
+            Some(guard) => guard.check_enabled(queue).await,
+            None => {
+                let decisions = IngestDecisionsRepo::new(self.pool.clone());
+                enqueue_guard::check_enabled(&self.pool, &decisions, queue).await
+            }
+        }
     }
 
     fn sanitize_dataset_queue(queue: &str) -> String {
@@ -58,35 +298,448 @@ impl JobsRepo {
         }
     }
 
+    /// NOTIFY channel announcing jobs enqueued on `queue`: `pgflow_<queue>`,
+    /// cut to Postgres' 63-byte identifier limit.
+    pub fn notify_channel(queue: &str) -> String {
+        let mut channel = format!("pgflow_{queue}");
+        let mut end = channel.len().min(63);
+        while !channel.is_char_boundary(end) {
+            end -= 1;
+        }
+        channel.truncate(end);
+        channel
+    }
+
+    /// `pg_notify` each job's queue channel with its id, unless disabled
+    /// (`with_notify_on_enqueue`); inside a transaction, listeners only hear
+    /// it once that commits.
+    async fn notify_enqueued<'e>(
+        &self,
+        executor: impl sqlx::PgExecutor<'e>,
+        jobs: &[(&str, Uuid)],
+    ) -> anyhow::Result<()> {
+        if !self.notify_on_enqueue {
+            return Ok(());
+        }
+        let (channels, ids): (Vec<String>, Vec<Uuid>) = jobs
+            .iter()
+            .map(|(queue, id)| (Self::notify_channel(queue), *id))
+            .unzip();
+        sqlx::query(
+            "SELECT pg_notify(n.channel, n.id::text) FROM unnest($1::text[], $2::uuid[]) AS n(channel, id)",
+        )
+        .bind(channels)
+        .bind(ids)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// A listener subscribed to `queue`'s enqueue notifications. It holds
+    /// one pool connection until dropped and reconnects on its own.
+    pub async fn listen_for_jobs(&self, queue: &str) -> anyhow::Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.pool).await?;
+        listener.listen(&Self::notify_channel(queue)).await?;
+        Ok(listener)
+    }
+
+    /// Wait up to `timeout` for a job to be enqueued on `queue`. Returns
+    /// `true` if notified, `false` on timeout. Jobs enqueued before the
+    /// listener is subscribed are not seen, so callers still poll as a fallback.
+    pub async fn wait_for_job_notification(
+        &self,
+        queue: &str,
+        timeout: Duration,
+    ) -> anyhow::Result<bool> {
+        let mut listener = self.listen_for_jobs(queue).await?;
+        match tokio::time::timeout(timeout, listener.recv()).await {
+            Ok(notification) => {
+                notification?;
+                Ok(true)
+            }
+            Err(_) => Ok(false),
+        }
+    }
+
     // ----------------------------
     // Enqueue helpers
     // ----------------------------
 
+    /// Enqueue `job`. With an `idempotency_key` that a live job on the
+    /// queue already holds, returns that job's id instead of inserting.
     pub async fn enqueue(&self, job: NewJob) -> anyhow::Result<Uuid> {
-        let dataset_id = Self::dataset_id_for(&job.queue, job.run_at);
-        self.ensure_dataset_partition(&dataset_id).await?;
+        Ok(self.enqueue_idempotent(job).await?.job_id)
+    }
+
+    /// `enqueue`, also telling whether the idempotency key deduplicated it.
+    pub async fn enqueue_idempotent(&self, job: NewJob) -> anyhow::Result<Enqueued> {
+        self.insert_job(job, JobStatus::Queued, None).await
+    }
+
+    /// First phase of a cancelable enqueue: insert the job as `preparing`,
+    /// invisible to leasing, and return its id as the token for
+    /// `commit_enqueue` / `abort_enqueue`. Not committed within `ttl`, it can
+    /// no longer be committed and the maintenance pass deletes it.
+    pub async fn prepare_enqueue(&self, job: NewJob, ttl: Duration) -> anyhow::Result<Uuid> {
+        Ok(self
+            .insert_job(job, JobStatus::Preparing, Some(ttl))
+            .await?
+            .job_id)
+    }
+
+    /// Make a prepared job `queued`. False if the token is unknown, already
+    /// committed or aborted, or past its TTL.
+    pub async fn commit_enqueue(&self, token: Uuid) -> anyhow::Result<bool> {
+        let queue: Option<String> =
+            sqlx::query_scalar("SELECT queue FROM jobs WHERE id = $1 AND status = 'preparing'")
+                .bind(token)
+                .fetch_optional(&self.pool)
+                .await?;
+        let Some(queue) = queue else {
+            return Ok(false);
+        };
+        self.check_enqueue_enabled(&queue).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let committed: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE jobs
+            SET status = 'queued',
+                prepared_until = NULL,
+                updated_at = now()
+            WHERE id = $1
+              AND status = 'preparing'
+              AND prepared_until > now()
+            RETURNING queue
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(queue) = committed else {
+            return Ok(false);
+        };
+        self.notify_enqueued(&mut *tx, &[(&queue, token)]).await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Drop a prepared job as if it was never enqueued. False if the token
+    /// is unknown or no longer `preparing` (committed jobs are not touched).
+    pub async fn abort_enqueue(&self, token: Uuid) -> anyhow::Result<bool> {
+        let aborted = sqlx::query("DELETE FROM jobs WHERE id = $1 AND status = 'preparing'")
+            .bind(token)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(aborted > 0)
+    }
+
+    /// Enqueue `jobs` in one transaction, returning one id per input in
+    /// order. Jobs repeating an earlier job's `dedupe_key` on the same queue
+    /// are dropped rather than inserted, and their slot gets the id of that
+    /// first job. Keys are only compared within the batch.
+    pub async fn enqueue_batch(&self, jobs: Vec<NewJob>) -> anyhow::Result<Vec<Uuid>> {
+        // (queue, dedupe_key) -> index into `ids` of the first job with it
+        let mut first_with_key: HashMap<(String, String), usize> = HashMap::new();
+        let mut ids: Vec<Option<Uuid>> = vec![None; jobs.len()];
+        let mut kept = Vec::with_capacity(jobs.len());
+        let mut duplicates = Vec::new();
+        for (i, job) in jobs.into_iter().enumerate() {
+            if let Some(key) = &job.dedupe_key {
+                match first_with_key.entry((job.queue.clone(), key.clone())) {
+                    Entry::Occupied(first) => {
+                        duplicates.push((i, *first.get()));
+                        continue;
+                    }
+                    Entry::Vacant(slot) => {
+                        slot.insert(i);
+                    }
+                }
+            }
+            kept.push((i, job));
+        }
+
+        let mut datasets: Vec<String> = kept
+            .iter()
+            .map(|(_, job)| Self::dataset_id_for(&job.queue, job.run_at))
+            .collect();
+        datasets.sort();
+        datasets.dedup();
+        for dataset_id in &datasets {
+            self.ensure_dataset_partition(dataset_id).await?;
+        }
+        if self.enqueue_guard.is_some() {
+            for (_, job) in &kept {
+                self.check_enqueue(job).await?;
+            }
+        } else {
+            let mut queues: Vec<&str> = kept.iter().map(|(_, job)| job.queue.as_str()).collect();
+            queues.sort();
+            queues.dedup();
+            for queue in queues {
+                self.check_enqueue_enabled(queue).await?;
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for (i, job) in kept {
+            let enqueued = self
+                .insert_job_in(&mut tx, job, JobStatus::Queued, None)
+                .await?;
+            ids[i] = Some(enqueued.job_id);
+        }
+        tx.commit().await?;
+
+        for (i, first) in duplicates {
+            ids[i] = ids[first];
+        }
+        Ok(ids.into_iter().flatten().collect())
+    }
+
+    /// Enqueue `job` as `queued` inside a caller's transaction, so it
+    /// commits or rolls back with the caller's other writes.
+    pub(crate) async fn enqueue_in_tx(
+        &self,
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        job: NewJob,
+    ) -> anyhow::Result<Uuid> {
+        self.ensure_dataset_partition(&Self::dataset_id_for(&job.queue, job.run_at))
+            .await?;
+        Ok(self
+            .insert_job_in(tx, job, JobStatus::Queued, None)
+            .await?
+            .job_id)
+    }
+
+    async fn insert_job(
+        &self,
+        job: NewJob,
+        status: JobStatus,
+        prepare_ttl: Option<Duration>,
+    ) -> anyhow::Result<Enqueued> {
+        self.check_enqueue(&job).await?;
+        self.ensure_dataset_partition(&Self::dataset_id_for(&job.queue, job.run_at))
+            .await?;
+        let mut tx = self.pool.begin().await?;
+        let enqueued = self
+            .insert_job_in(&mut tx, job, status, prepare_ttl)
+            .await?;
+        tx.commit().await?;
+        Ok(enqueued)
+    }
+
+    /// Insert one job inside `tx`, unless its idempotency key is held by a
+    /// live job; then that job is returned as `deduplicated`. The caller
+    /// ensures the dataset partition exists. `prepare_ttl` sets
+    /// `prepared_until` that long after the DB's `now()`, the clock
+    /// `commit_enqueue` compares it with.
+    async fn insert_job_in(
+        &self,
+        tx: &mut sqlx::Transaction<'_, Postgres>,
+        job: NewJob,
+        status: JobStatus,
+        prepare_ttl: Option<Duration>,
+    ) -> anyhow::Result<Enqueued> {
+        if let Some(key) = &job.idempotency_key {
+            // serialize enqueues racing on the same key
+            sqlx::query(
+                "SELECT pg_advisory_xact_lock(hashtextextended('pgflow_idempotency:' || $1 || ':' || $2, 0))",
+            )
+            .bind(&job.queue)
+            .bind(key)
+            .execute(&mut **tx)
+            .await?;
+
+            let existing: Option<Uuid> = sqlx::query_scalar(
+                r#"
+                SELECT k.job_id
+                FROM job_idempotency_keys k
+                JOIN jobs j ON j.dataset_id = k.dataset_id AND j.id = k.job_id
+                WHERE k.queue = $1
+                  AND k.idempotency_key = $2
+                  AND k.created_at > now() - make_interval(secs => $3)
+                  AND j.status IN ('preparing', 'queued', 'running')
+                "#,
+            )
+            .bind(&job.queue)
+            .bind(key)
+            .bind(self.idempotency_window_secs as f64)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+            if let Some(job_id) = existing {
+                return Ok(Enqueued {
+                    job_id,
+                    deduplicated: true,
+                });
+            }
+        }
 
+        let dataset_id = Self::dataset_id_for(&job.queue, job.run_at);
         let id = sqlx::query_scalar::<_, Uuid>(
             r#"
             INSERT INTO jobs (
-                dataset_id, queue, job_type, payload_json, run_at, status, priority, max_attempts
+                dataset_id, queue, job_type, payload_json, run_at, status, priority, max_attempts,
+                target_worker_id, retry_base_seconds, retry_max_seconds, prepared_until,
+                dedupe_key, group_id
+            )
+            VALUES (
+                $1, $2, $3, $4, $5, $6, public.normalize_job_priority($2, $7), $8,
+                $9, $10, $11, now() + make_interval(secs => $12),
+                $13, $14
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             RETURNING id
             "#,
         )
-        .bind(dataset_id)
-        .bind(job.queue)
+        .bind(&dataset_id)
+        .bind(&job.queue)
         .bind(job.job_type)
         .bind(job.payload_json)
         .bind(job.run_at)
-        .bind(JobStatus::Queued.as_str())
+        .bind(status.as_str())
         .bind(job.priority)
         .bind(job.max_attempts)
-        .fetch_one(&self.pool)
+        .bind(job.target_worker_id)
+        .bind(job.retry.and_then(|r| r.base_seconds))
+        .bind(job.retry.and_then(|r| r.max_seconds))
+        .bind(prepare_ttl.map(|ttl| ttl.as_secs_f64()))
+        .bind(job.dedupe_key)
+        .bind(job.group_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if let Some(key) = job.idempotency_key {
+            // the key's previous job (if any) is finished or out of the window
+            sqlx::query(
+                r#"
+                INSERT INTO job_idempotency_keys (queue, idempotency_key, dataset_id, job_id)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (queue, idempotency_key)
+                DO UPDATE SET dataset_id = EXCLUDED.dataset_id,
+                              job_id = EXCLUDED.job_id,
+                              created_at = now()
+                "#,
+            )
+            .bind(&job.queue)
+            .bind(key)
+            .bind(&dataset_id)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        if matches!(status, JobStatus::Queued) {
+            self.notify_enqueued(&mut **tx, &[(&job.queue, id)]).await?;
+        }
+
+        Ok(Enqueued {
+            job_id: id,
+            deduplicated: false,
+        })
+    }
+
+    /// Enqueue a job for `run_at` unless one with the same `dedupe_key` on
+    /// `queue` is already scheduled within `SCHEDULE_ONCE_TOLERANCE_SECS` of it
+    /// (any status but `canceled`), or a succeeded one asked for a cooldown
+    /// (`set_cooldown_until`) lasting past `run_at`. Returns `None` when the
+    /// call was a no-op, so schedulers can re-issue "send reminder at T" safely
+    /// after a restart. No-ops are counted per queue in `enqueue_dedupe_counters`.
+    ///
+    /// `job.dedupe_key` is required; everything else on `job` (priority,
+    /// max_attempts, retry, ...) is inserted as `enqueue` would, after the
+    /// same enqueue guard checks.
+    pub async fn enqueue_scheduled_once(&self, job: NewJob) -> anyhow::Result<Option<Uuid>> {
+        let Some(dedupe_key) = job.dedupe_key.clone() else {
+            anyhow::bail!("enqueue_scheduled_once needs a dedupe_key");
+        };
+        let (queue, job_type, run_at) = (job.queue.clone(), job.job_type.clone(), job.run_at);
+        self.check_enqueue(&job).await?;
+        let dataset_id = Self::dataset_id_for(&queue, run_at);
+        self.ensure_dataset_partition(&dataset_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // serialize concurrent schedulers using the same key
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1 || ':' || $2, 0))")
+            .bind(&queue)
+            .bind(&dedupe_key)
+            .execute(&mut *tx)
+            .await?;
+
+        let existing: Option<(Uuid, Option<DateTime<Utc>>)> = sqlx::query_as(
+            r#"
+            SELECT id, cooldown_until
+            FROM jobs
+            WHERE queue = $1
+              AND dedupe_key = $2
+              AND status <> 'canceled'
+              AND (
+                run_at BETWEEN $3 - ($4::bigint * interval '1 second')
+                           AND $3 + ($4::bigint * interval '1 second')
+                OR (status = 'succeeded' AND cooldown_until > $3)
+              )
+            ORDER BY cooldown_until DESC NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(&queue)
+        .bind(&dedupe_key)
+        .bind(run_at)
+        .bind(SCHEDULE_ONCE_TOLERANCE_SECS)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        Ok(id)
+        if let Some((existing_job_id, cooldown_until)) = existing {
+            sqlx::query(
+                r#"
+                INSERT INTO enqueue_dedupe_counters (queue, deduped_total)
+                VALUES ($1, 1)
+                ON CONFLICT (queue)
+                DO UPDATE SET deduped_total = enqueue_dedupe_counters.deduped_total + 1,
+                              updated_at = now()
+                "#,
+            )
+            .bind(&queue)
+            .execute(&mut *tx)
+            .await?;
+
+            if self.record_dedupe_decisions {
+                let reason_code = match cooldown_until {
+                    Some(until) if until > run_at => "COOLDOWN_ACTIVE",
+                    _ => "DUPLICATE_DEDUPE_KEY",
+                };
+                sqlx::query(
+                    r#"
+                    INSERT INTO ingest_decisions (id, queue, decision, reason_code, details_json)
+                    VALUES ($1, $2, 'DEDUPED', $3, $4)
+                    "#,
+                )
+                .bind(Uuid::new_v4())
+                .bind(&queue)
+                .bind(reason_code)
+                .bind(json!({
+                    "dedupe_key": dedupe_key,
+                    "job_type": job_type,
+                    "run_at": run_at,
+                    "existing_job_id": existing_job_id,
+                    "cooldown_until": cooldown_until,
+                }))
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+            return Ok(None);
+        }
+
+        let id = self
+            .insert_job_in(&mut tx, job, JobStatus::Queued, None)
+            .await?
+            .job_id;
+
+        tx.commit().await?;
+        Ok(Some(id))
     }
 
     pub async fn enqueue_now(
@@ -99,9 +752,14 @@ impl JobsRepo {
             queue: queue.to_string(),
             job_type: job_type.to_string(),
             payload_json,
-            run_at: Utc::now(),
+            run_at: self.clock.now(),
             priority: 0,
             max_attempts: 25,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
         })
         .await
     }
@@ -117,9 +775,14 @@ impl JobsRepo {
             queue: queue.to_string(),
             job_type: job_type.to_string(),
             payload_json,
-            run_at: Utc::now() + chrono::Duration::seconds(delay_secs),
+            run_at: self.clock.now() + chrono::Duration::seconds(delay_secs),
             priority: 0,
             max_attempts: 25,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
         })
         .await
     }
@@ -138,6 +801,11 @@ impl JobsRepo {
             run_at,
             priority: 0,
             max_attempts: 25,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
         })
         .await
     }
@@ -154,6 +822,96 @@ impl JobsRepo {
         Ok(job)
     }
 
+    /// Like `get_job` but without the payload; see `JobHeader`.
+    pub async fn get_job_header(&self, job_id: Uuid) -> anyhow::Result<Option<JobHeader>> {
+        let job = sqlx::query_as::<_, JobHeader>(
+            r#"
+            SELECT id, queue, job_type, run_at, status,
+                   replay_of_job_id, replay_include_history,
+                   dlq_reason_code, dlq_error_code
+            FROM jobs
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(job)
+    }
+
+    /// Every status change of a job, oldest first; kept after the job is
+    /// archived until `MaintenanceRepo::prune_state_transitions` ages it out.
+    pub async fn state_transitions(&self, job_id: Uuid) -> anyhow::Result<Vec<JobStateTransition>> {
+        let rows = sqlx::query_as::<_, JobStateTransition>(
+            r#"
+            SELECT from_status, to_status, worker_id, at
+            FROM job_state_transitions
+            WHERE job_id = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(job_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows)
+    }
+
+    /// Fetch many jobs in one round trip, in the order of `ids`.
+    /// Unknown ids are simply absent from the result.
+    pub async fn get_jobs(&self, ids: &[Uuid]) -> anyhow::Result<Vec<Job>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let jobs = sqlx::query_as::<_, Job>(
+            r#"
+            SELECT *
+            FROM jobs
+            WHERE id = ANY($1)
+            ORDER BY array_position($1, id)
+            "#,
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(jobs)
+    }
+
+    /// Jobs whose top-level payload field `field` equals `value` as text
+    /// (`payload_json->>field = value`, so `12345` matches both `12345` and
+    /// `"12345"`), optionally scoped to a queue and job_type, newest first.
+    /// `limit` is clamped to [1, 500].
+    ///
+    /// Without an expression index on the field this scans the queue's jobs.
+    pub async fn search_jobs_by_payload_field(
+        &self,
+        field: &str,
+        value: &str,
+        queue: Option<&str>,
+        job_type: Option<&str>,
+        limit: i64,
+    ) -> anyhow::Result<Vec<Job>> {
+        let jobs = sqlx::query_as::<_, Job>(
+            r#"
+            SELECT *
+            FROM jobs
+            WHERE payload_json ->> $1 = $2
+              AND ($3::text IS NULL OR queue = $3)
+              AND ($4::text IS NULL OR job_type = $4)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $5
+            "#,
+        )
+        .bind(field)
+        .bind(value)
+        .bind(queue)
+        .bind(job_type)
+        .bind(limit.clamp(1, 500))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(jobs)
+    }
+
     // ----------------------------
     // List / DLQ views (Admin API support)
     // ----------------------------
@@ -181,7 +939,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE queue = $1 AND status = $2
@@ -205,7 +963,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE queue = $1 AND status = $2
@@ -226,7 +984,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE queue = $1
@@ -249,7 +1007,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE queue = $1
@@ -269,7 +1027,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE status = $1
@@ -292,7 +1050,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE status = $1
@@ -312,7 +1070,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     WHERE (created_at, id) < ($1, $2)
@@ -333,7 +1091,7 @@ impl JobsRepo {
                         id, queue, job_type, status,
                         run_at, priority, max_attempts,
                         last_error_code, last_error_message,
-                        dlq_reason_code,
+                        dlq_reason_code, dlq_error_code,
                         created_at, updated_at
                     FROM jobs
                     ORDER BY created_at DESC, id DESC
@@ -353,30 +1111,82 @@ impl JobsRepo {
     // Metrics snapshot (for /metrics)
     // ----------------------------
 
-    /// Returns: (queued, running, succeeded_last_60s, failed_or_dlq_last_60s)
-    pub async fn metrics_snapshot(&self) -> anyhow::Result<(i64, i64, i64, i64)> {
-        let queued: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE status = 'queued'")
-            .fetch_one(&self.pool)
-            .await?;
-
-        let running: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE status = 'running'")
-            .fetch_one(&self.pool)
-            .await?;
-
-        let succeeded_last_60s: i64 = sqlx::query_scalar(
+    /// Job counts per status, grouped by queue (queues sorted by name).
+    ///
+    /// Shared by `/metrics/prom` and the admin dashboard metrics.
+    pub async fn status_counts_by_queue(
+        &self,
+    ) -> anyhow::Result<Vec<(String, HashMap<String, i64>)>> {
+        let rows = sqlx::query_as::<_, (String, String, i64)>(
             r#"
-            SELECT COUNT(*) FROM jobs
-            WHERE status = 'succeeded'
-              AND updated_at >= now() - interval '60 seconds'
+            SELECT queue, status, COUNT(*)::bigint
+            FROM jobs
+            GROUP BY queue, status
+            ORDER BY queue, status
             "#,
         )
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        let failed_last_60s: i64 = sqlx::query_scalar(
-            r#"
-            SELECT COUNT(*) FROM jobs
-            WHERE status IN ('failed', 'dlq')
+        let mut out: Vec<(String, HashMap<String, i64>)> = Vec::new();
+        for (queue, status, count) in rows {
+            match out.last_mut() {
+                Some((q, counts)) if *q == queue => {
+                    counts.insert(status, count);
+                }
+                _ => out.push((queue, HashMap::from([(status, count)]))),
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Every job_type present in `jobs` with its (queued, running, dlq) counts.
+    pub async fn job_type_summary(&self) -> anyhow::Result<Vec<(String, i64, i64, i64)>> {
+        let rows = sqlx::query_as::<_, (String, i64, i64, i64)>(
+            r#"
+            SELECT
+              job_type,
+              COUNT(*) FILTER (WHERE status = 'queued')::bigint,
+              COUNT(*) FILTER (WHERE status = 'running')::bigint,
+              COUNT(*) FILTER (WHERE status = 'dlq')::bigint
+            FROM jobs
+            GROUP BY job_type
+            ORDER BY job_type
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Returns: (queued, running, succeeded_last_60s, failed_or_dlq_last_60s)
+    pub async fn metrics_snapshot(&self) -> anyhow::Result<(i64, i64, i64, i64)> {
+        let by_queue = self.status_counts_by_queue().await?;
+        let total = |status: &str| -> i64 {
+            by_queue
+                .iter()
+                .map(|(_, counts)| counts.get(status).copied().unwrap_or(0))
+                .sum()
+        };
+        let queued = total(JobStatus::Queued.as_str());
+        let running = total(JobStatus::Running.as_str());
+
+        let succeeded_last_60s: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM jobs
+            WHERE status = 'succeeded'
+              AND updated_at >= now() - interval '60 seconds'
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let failed_last_60s: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM jobs
+            WHERE status IN ('failed', 'dlq')
               AND updated_at >= now() - interval '60 seconds'
             "#,
         )
@@ -386,6 +1196,75 @@ impl JobsRepo {
         Ok((queued, running, succeeded_last_60s, failed_last_60s))
     }
 
+    /// Time until the earliest queued job on `queue` that isn't runnable yet
+    /// becomes due, measured on the repo's clock. `None` when nothing is
+    /// scheduled.
+    pub async fn next_run_delay(&self, queue: &str) -> anyhow::Result<Option<Duration>> {
+        let now = self.clock.now();
+        let next: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            SELECT MIN(run_at)
+            FROM jobs
+            WHERE queue = $1
+              AND status = 'queued'
+              AND run_at > $2
+            "#,
+        )
+        .bind(queue)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(next.map(|at| (at - now).to_std().unwrap_or(Duration::ZERO)))
+    }
+
+    /// Distinct job types with queued (runnable or scheduled) jobs in `queue`.
+    pub async fn queued_job_types(&self, queue: &str) -> anyhow::Result<Vec<String>> {
+        let rows = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT DISTINCT job_type
+            FROM jobs
+            WHERE queue = $1
+              AND status = 'queued'
+            ORDER BY job_type
+            "#,
+        )
+        .bind(queue)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Queue load vs its policy, from the same counts storm control uses.
+    /// `None` when the queue has no policy or it sets no limits (never
+    /// throttled).
+    pub async fn queue_pressure(&self, queue: &str) -> anyhow::Result<Option<QueuePressure>> {
+        let rec = sqlx::query_as::<_, QueuePressure>(
+            r#"
+            SELECT
+              (SELECT COUNT(*) FROM jobs WHERE queue = $1 AND status = 'running')::bigint AS in_flight,
+              p.max_in_flight,
+              (
+                SELECT COUNT(*)
+                FROM job_attempts a
+                JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
+                WHERE j.queue = $1
+                  AND a.started_at >= now() - interval '60 seconds'
+              )::bigint AS attempts_last_min,
+              p.max_attempts_per_minute
+            FROM queue_policies p
+            WHERE p.queue = $1
+              AND (p.max_in_flight IS NOT NULL OR p.max_attempts_per_minute IS NOT NULL)
+            "#,
+        )
+        .bind(queue)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(rec)
+    }
+
     // ----------------------------
     // Leasing + Storm Control + Policy Decisions Log (Milestone 11)
     // ----------------------------
@@ -394,14 +1273,24 @@ impl JobsRepo {
     ///
     /// Correctness: SELECT ... FOR UPDATE SKIP LOCKED
     ///
+    /// Jobs pinned to another worker (`target_worker_id`) are skipped until
+    /// `run_at + pin_timeout_secs`, after which anyone may lease them.
+    ///
     /// Storm-control gates (per queue):
     /// - max_in_flight (jobs.status='running')
     /// - max_attempts_per_minute (attempts started in last 60s)
     ///
+    /// checked under the advisory lock picked by `with_storm_control_lock`.
+    ///
     /// If exceeded:
     /// - write a row into policy_decisions
     /// - reschedule one candidate slightly (throttle_delay_ms)
     /// - return an empty batch
+    ///
+    /// Batches are single-dataset unless `with_single_dataset_batches(false)`.
+    ///
+    /// Serialization failures (e.g. under SERIALIZABLE) are retried up to
+    /// `serialization_retries` times.
     pub async fn lease_jobs_batch(
         &self,
         queue: &str,
@@ -409,14 +1298,49 @@ impl JobsRepo {
         lease_seconds: i64,
         batch_size: i64,
     ) -> anyhow::Result<Vec<Job>> {
+        let (jobs, _policy) = self
+            .lease_jobs_batch_with_policy(queue, worker_id, lease_seconds, batch_size)
+            .await?;
+        Ok(jobs)
+    }
+
+    /// Same as `lease_jobs_batch`, also returning the queue policy that was in
+    /// effect for the lease decision (`None` when the queue has no policy).
+    pub async fn lease_jobs_batch_with_policy(
+        &self,
+        queue: &str,
+        worker_id: &str,
+        lease_seconds: i64,
+        batch_size: i64,
+    ) -> anyhow::Result<(Vec<Job>, Option<QueuePolicy>)> {
+        db::run_serializable_with_retry(self.serialization_retries, || {
+            self.lease_jobs_batch_once(queue, worker_id, lease_seconds, batch_size)
+        })
+        .await
+    }
+
+    async fn lease_jobs_batch_once(
+        &self,
+        queue: &str,
+        worker_id: &str,
+        lease_seconds: i64,
+        batch_size: i64,
+    ) -> anyhow::Result<(Vec<Job>, Option<QueuePolicy>)> {
         let batch_size = batch_size.clamp(1, 4096);
         let mut tx = self.pool.begin().await?;
 
+        if self.lease_isolation == TxIsolation::Serializable {
+            sqlx::query("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+                .execute(&mut *tx)
+                .await?;
+        }
+
         // 0) Load queue policy (defaults: basically unlimited)
-        // schema assumed: queue_policies(queue PK, max_attempts_per_minute, max_in_flight, throttle_delay_ms)
-        let policy = sqlx::query_as::<_, (i32, i32, i32)>(
+        // schema assumed: queue_policies(queue PK, max_attempts_per_minute NULL, max_in_flight NULL, throttle_delay_ms)
+        let policy = sqlx::query_as::<_, QueuePolicy>(
             r#"
-            SELECT max_attempts_per_minute, max_in_flight, throttle_delay_ms
+            SELECT queue, max_attempts_per_minute, max_in_flight, throttle_delay_ms,
+                   fifo_within_priority, steal_enabled
             FROM queue_policies
             WHERE queue = $1
             "#,
@@ -431,89 +1355,175 @@ impl JobsRepo {
         let mut in_flight = 0_i64;
         let mut attempts_last_min = 0_i64;
 
-        let dataset_id = sqlx::query_scalar::<_, String>(
-            r#"
+        // FIFO within priority: the CASE keys are NULL (no-op) unless enabled.
+        let fifo = policy.as_ref().is_some_and(|p| p.fifo_within_priority);
+
+        // Round-robin: datasets after the last one served come first (NULL = no-op).
+        let rr_key = (queue.to_string(), worker_id.to_string());
+        let last_dataset = if self.dataset_round_robin && self.single_dataset_batches {
+            self.last_leased_dataset
+                .lock()
+                .expect("last_leased_dataset poisoned")
+                .get(&rr_key)
+                .cloned()
+        } else {
+            None
+        };
+
+        let datasets = if self.single_dataset_batches {
+            let picked = sqlx::query_scalar::<_, String>(
+                r#"
             SELECT dataset_id
             FROM jobs
             WHERE queue = $1
               AND status = 'queued'
               AND run_at <= now()
-            ORDER BY run_at ASC, created_at ASC
+              AND (
+                target_worker_id IS NULL
+                OR target_worker_id = $2
+                OR run_at <= now() - ($3::bigint * interval '1 second')
+              )
+            ORDER BY
+              (dataset_id <= $5) ASC,
+              CASE WHEN $5::text IS NOT NULL THEN dataset_id END ASC,
+              CASE WHEN $4 THEN created_at END ASC,
+              run_at ASC,
+              created_at ASC
             LIMIT 1
             "#,
-        )
-        .bind(queue)
-        .fetch_optional(&mut *tx)
-        .await?;
+            )
+            .bind(queue)
+            .bind(worker_id)
+            .bind(self.pin_timeout_secs)
+            .bind(fifo)
+            .bind(last_dataset)
+            .fetch_optional(&mut *tx)
+            .await?;
 
-        let Some(dataset_id) = dataset_id else {
-            tx.commit().await?;
-            return Ok(Vec::new());
-        };
+            let Some(picked) = picked else {
+                tx.commit().await?;
+                return Ok((Vec::new(), policy));
+            };
+            LeaseDatasets::One(picked)
+        } else if let Some(max_datasets) = self.max_batch_datasets {
+            let picked = sqlx::query_scalar::<_, String>(
+                r#"
+            SELECT dataset_id
+            FROM jobs
+            WHERE queue = $1
+              AND status = 'queued'
+              AND run_at <= now()
+              AND (
+                target_worker_id IS NULL
+                OR target_worker_id = $2
+                OR run_at <= now() - ($3::bigint * interval '1 second')
+              )
+            GROUP BY dataset_id
+            ORDER BY
+              MAX(priority) DESC,
+              CASE WHEN $4 THEN MIN(created_at) END ASC,
+              MIN(run_at) ASC,
+              MIN(created_at) ASC
+            LIMIT $5
+            "#,
+            )
+            .bind(queue)
+            .bind(worker_id)
+            .bind(self.pin_timeout_secs)
+            .bind(fifo)
+            .bind(max_datasets as i64)
+            .fetch_all(&mut *tx)
+            .await?;
 
-        let throttle_reason =
-            if let Some((p_max_attempts, p_max_in_flight, p_throttle_delay_ms)) = policy {
-                max_attempts_per_minute = p_max_attempts;
-                max_in_flight = p_max_in_flight;
-                throttle_delay_ms = p_throttle_delay_ms;
+            if picked.is_empty() {
+                tx.commit().await?;
+                return Ok((Vec::new(), policy));
+            }
+            LeaseDatasets::Any(picked)
+        } else {
+            LeaseDatasets::All
+        };
+        let dataset_pred = datasets.pred();
+
+        // unset limits stay at the unlimited defaults above
+        let throttle_reason = if let Some(p) = policy.as_ref().filter(|p| p.has_limits()) {
+            max_attempts_per_minute = p.max_attempts_per_minute.unwrap_or(max_attempts_per_minute);
+            max_in_flight = p.max_in_flight.unwrap_or(max_in_flight);
+            throttle_delay_ms = p.throttle_delay_ms;
+
+            // held until the lease commits, so the next leaser counts our jobs
+            if let Some(lock_name) = self.storm_control_lock.lock_name(queue) {
+                sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+                    .bind(lock_name)
+                    .execute(&mut *tx)
+                    .await?;
+            }
 
-                // In-flight count for this queue
-                in_flight = sqlx::query_scalar(
-                    r#"
+            // In-flight count for this queue
+            in_flight = sqlx::query_scalar(
+                r#"
                 SELECT COUNT(*)
                 FROM jobs
                 WHERE queue = $1 AND status = 'running'
                 "#,
-                )
-                .bind(queue)
-                .fetch_one(&mut *tx)
-                .await?;
+            )
+            .bind(queue)
+            .fetch_one(&mut *tx)
+            .await?;
 
-                // Attempts started in last 60 seconds for this queue
-                attempts_last_min = sqlx::query_scalar(
-                    r#"
+            // Attempts started in last 60 seconds for this queue
+            attempts_last_min = sqlx::query_scalar(
+                r#"
                 SELECT COUNT(*)
                 FROM job_attempts a
                 JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
                 WHERE j.queue = $1
                   AND a.started_at >= now() - interval '60 seconds'
                 "#,
-                )
-                .bind(queue)
-                .fetch_one(&mut *tx)
-                .await?;
+            )
+            .bind(queue)
+            .fetch_one(&mut *tx)
+            .await?;
 
-                if in_flight >= max_in_flight as i64 {
-                    Some("IN_FLIGHT_EXCEEDED")
-                } else if attempts_last_min >= max_attempts_per_minute as i64 {
-                    Some("RETRY_RATE_EXCEEDED")
-                } else {
-                    None
-                }
+            if in_flight >= max_in_flight as i64 {
+                Some("IN_FLIGHT_EXCEEDED")
+            } else if attempts_last_min >= max_attempts_per_minute as i64 {
+                Some("RETRY_RATE_EXCEEDED")
             } else {
                 None
-            };
+            }
+        } else {
+            None
+        };
 
         if let Some(reason_code) = throttle_reason {
-            let candidate_id = sqlx::query_scalar::<_, Uuid>(
+            let candidate = datasets
+                .bind(sqlx::query_as::<_, (Uuid, String)>(&format!(
                 r#"
-                SELECT id
+                SELECT id, dataset_id
                 FROM jobs
-                WHERE dataset_id = $1
+                WHERE {dataset_pred}
                   AND queue = $2
                   AND status = 'queued'
                   AND run_at <= now()
-                ORDER BY priority DESC, run_at ASC, created_at ASC
+                  AND (
+                    target_worker_id IS NULL
+                    OR target_worker_id = $3
+                    OR run_at <= now() - ($4::bigint * interval '1 second')
+                  )
+                ORDER BY priority DESC, CASE WHEN $5 THEN created_at END ASC, run_at ASC, created_at ASC
                 FOR UPDATE SKIP LOCKED
                 LIMIT 1
-                "#,
-            )
-            .bind(&dataset_id)
+                "#
+            )))
             .bind(queue)
+            .bind(worker_id)
+            .bind(self.pin_timeout_secs)
+            .bind(fifo)
             .fetch_optional(&mut *tx)
             .await?;
 
-            if let Some(job_id) = candidate_id {
+            if let Some((job_id, dataset_id)) = candidate {
                 let details = match reason_code {
                     "IN_FLIGHT_EXCEEDED" => json!({
                         "dataset_id": dataset_id,
@@ -531,21 +1541,54 @@ impl JobsRepo {
                     }),
                 };
 
-                sqlx::query(
+                // Coalesce with the job's latest decision if it is the same
+                // THROTTLED/reason and was last seen within the window.
+                let coalesced = sqlx::query(
                     r#"
-                    INSERT INTO policy_decisions (
-                      id, dataset_id, job_id, decision, reason_code, details_json
-                    )
-                    VALUES ($1, $2, $3, 'THROTTLED', $4, $5)
+                    UPDATE policy_decisions p
+                    SET count = p.count + 1,
+                        last_seen_at = now(),
+                        details_json = $4
+                    FROM (
+                      SELECT id
+                      FROM policy_decisions
+                      WHERE dataset_id = $1 AND job_id = $2
+                      ORDER BY created_at DESC
+                      LIMIT 1
+                    ) last
+                    WHERE p.dataset_id = $1
+                      AND p.id = last.id
+                      AND p.decision = 'THROTTLED'
+                      AND p.reason_code = $3
+                      AND p.last_seen_at >= now() - ($5::bigint * interval '1 second')
                     "#,
                 )
-                .bind(Uuid::new_v4())
                 .bind(&dataset_id)
                 .bind(job_id)
                 .bind(reason_code)
-                .bind(details)
+                .bind(&details)
+                .bind(self.decision_coalesce_secs)
                 .execute(&mut *tx)
-                .await?;
+                .await?
+                .rows_affected();
+
+                if coalesced == 0 {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO policy_decisions (
+                          id, dataset_id, job_id, decision, reason_code, details_json
+                        )
+                        VALUES ($1, $2, $3, 'THROTTLED', $4, $5)
+                        "#,
+                    )
+                    .bind(Uuid::new_v4())
+                    .bind(&dataset_id)
+                    .bind(job_id)
+                    .bind(reason_code)
+                    .bind(details)
+                    .execute(&mut *tx)
+                    .await?;
+                }
 
                 sqlx::query(
                     r#"
@@ -562,20 +1605,26 @@ impl JobsRepo {
             }
 
             tx.commit().await?;
-            return Ok(Vec::new());
+            return Ok((Vec::new(), policy));
         }
 
         // 3) Lease a batch in one round-trip.
-        let leased = sqlx::query_as::<_, Job>(
+        let leased = datasets
+            .bind(sqlx::query_as::<_, Job>(&format!(
             r#"
             WITH candidates AS (
                 SELECT id
                 FROM jobs
-                WHERE dataset_id = $1
+                WHERE {dataset_pred}
                   AND queue = $2
                   AND status = 'queued'
                   AND run_at <= now()
-                ORDER BY priority DESC, run_at ASC, created_at ASC
+                  AND (
+                    target_worker_id IS NULL
+                    OR target_worker_id = $4
+                    OR run_at <= now() - ($6::bigint * interval '1 second')
+                  )
+                ORDER BY priority DESC, CASE WHEN $7 THEN created_at END ASC, run_at ASC, created_at ASC
                 FOR UPDATE SKIP LOCKED
                 LIMIT $3
             ),
@@ -592,97 +1641,298 @@ impl JobsRepo {
             )
             SELECT *
             FROM leased
-            ORDER BY priority DESC, run_at ASC, created_at ASC
-            "#,
-        )
-        .bind(&dataset_id)
+            ORDER BY priority DESC, CASE WHEN $7 THEN created_at END ASC, run_at ASC, created_at ASC
+            "#
+        )))
         .bind(queue)
         .bind(batch_size)
         .bind(worker_id)
         .bind(lease_seconds)
+        .bind(self.pin_timeout_secs)
+        .bind(fifo)
         .fetch_all(&mut *tx)
         .await?;
 
         tx.commit().await?;
-        Ok(leased)
+
+        if let LeaseDatasets::One(dataset_id) = datasets {
+            if self.dataset_round_robin && !leased.is_empty() {
+                self.last_leased_dataset
+                    .lock()
+                    .expect("last_leased_dataset poisoned")
+                    .insert(rr_key, dataset_id);
+            }
+        }
+
+        Ok((leased, policy))
     }
 
-    /// Compatibility helper for call sites/tests that still lease one-by-one.
-    pub async fn lease_one_job(
+    /// Re-lease a running job from a slow-but-alive worker (opt-in per queue via
+    /// `queue_policies.steal_enabled`).
+    ///
+    /// A job is stealable once it has run longer than `steal_latency_multiple` x
+    /// the mean latency of recent (1h) successful attempts of its job_type; job
+    /// types without history are never stolen. Writes a STEAL policy decision.
+    /// The original worker's later success, retry or failure no longer matches `locked_by`.
+    pub async fn steal_stale_lease(
         &self,
         queue: &str,
         worker_id: &str,
         lease_seconds: i64,
     ) -> anyhow::Result<Option<Job>> {
-        let mut jobs = self
-            .lease_jobs_batch(queue, worker_id, lease_seconds, 1)
-            .await?;
-        Ok(jobs.pop())
-    }
+        let mut tx = self.pool.begin().await?;
 
-    // ----------------------------
-    // Maintenance
-    // ----------------------------
+        let candidate = sqlx::query_as::<_, (String, Uuid, Option<String>, i64, f64, f64)>(
+            r#"
+            WITH policy AS (
+              SELECT steal_latency_multiple
+              FROM queue_policies
+              WHERE queue = $1 AND steal_enabled
+            ),
+            typical AS (
+              SELECT j.job_type, AVG(a.latency_ms)::float8 AS avg_latency_ms
+              FROM job_attempts a
+              JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
+              WHERE j.queue = $1
+                AND a.status = 'succeeded'
+                AND a.latency_ms IS NOT NULL
+                AND a.finished_at >= now() - interval '1 hour'
+              GROUP BY j.job_type
+            )
+            SELECT
+              j.dataset_id,
+              j.id,
+              j.locked_by,
+              (EXTRACT(EPOCH FROM (now() - j.locked_at)) * 1000)::bigint AS running_for_ms,
+              t.avg_latency_ms,
+              p.steal_latency_multiple
+            FROM jobs j
+            JOIN typical t ON t.job_type = j.job_type
+            CROSS JOIN policy p
+            WHERE j.queue = $1
+              AND j.status = 'running'
+              AND j.locked_by IS DISTINCT FROM $2
+              AND j.lock_expires_at > now()
+              AND EXTRACT(EPOCH FROM (now() - j.locked_at)) * 1000
+                  > p.steal_latency_multiple * t.avg_latency_ms
+            ORDER BY j.locked_at ASC
+            FOR UPDATE OF j SKIP LOCKED
+            LIMIT 1
+            "#,
+        )
+        .bind(queue)
+        .bind(worker_id)
+        .fetch_optional(&mut *tx)
+        .await?;
 
-    pub async fn reap_expired_locks(&self) -> anyhow::Result<u64> {
-        let res = sqlx::query!(
+        let Some((dataset_id, job_id, previous_worker, running_for_ms, typical_ms, multiple)) =
+            candidate
+        else {
+            tx.commit().await?;
+            return Ok(None);
+        };
+
+        let job = sqlx::query_as::<_, Job>(
             r#"
             UPDATE jobs
-            SET status = 'queued',
-                locked_at = NULL,
-                locked_by = NULL,
-                lock_expires_at = NULL,
+            SET locked_by = $3,
+                locked_at = now(),
+                lock_expires_at = now() + ($4::bigint * interval '1 second'),
                 updated_at = now()
-            WHERE status = 'running'
-              AND lock_expires_at IS NOT NULL
-              AND lock_expires_at < now()
-            "#
+            WHERE dataset_id = $1 AND id = $2
+            RETURNING *
+            "#,
         )
-        .execute(&self.pool)
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(lease_seconds)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(res.rows_affected())
+        sqlx::query(
+            r#"
+            INSERT INTO policy_decisions (
+              id, dataset_id, job_id, decision, reason_code, details_json
+            )
+            VALUES ($1, $2, $3, 'STEAL', 'LEASE_STOLEN', $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(json!({
+            "queue": queue,
+            "previous_worker_id": previous_worker,
+            "worker_id": worker_id,
+            "running_for_ms": running_for_ms,
+            "typical_latency_ms": typical_ms,
+            "steal_latency_multiple": multiple
+        }))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(job))
     }
 
-    // ----------------------------
-    // State transitions
-    // ----------------------------
+    /// Compatibility helper for call sites/tests that still lease one-by-one.
+    pub async fn lease_one_job(
+        &self,
+        queue: &str,
+        worker_id: &str,
+        lease_seconds: i64,
+    ) -> anyhow::Result<Option<Job>> {
+        let mut jobs = self
+            .lease_jobs_batch(queue, worker_id, lease_seconds, 1)
+            .await?;
+        Ok(jobs.pop())
+    }
 
-    /// Fast-path for successful batch execution: transitions many jobs in one statement.
-    pub async fn mark_succeeded_batch(
+    /// `lease_one_job` plus the queue policy that was in effect for the lease.
+    pub async fn lease_one_job_with_policy(
         &self,
-        job_ids: &[Uuid],
+        queue: &str,
         worker_id: &str,
-    ) -> anyhow::Result<u64> {
-        if job_ids.is_empty() {
-            return Ok(0);
-        }
+        lease_seconds: i64,
+    ) -> anyhow::Result<Option<LeaseResult>> {
+        let (mut jobs, policy) = self
+            .lease_jobs_batch_with_policy(queue, worker_id, lease_seconds, 1)
+            .await?;
+        Ok(jobs.pop().map(|job| LeaseResult { job, policy }))
+    }
 
+    /// Push a running job's lease to `now() + extend_by_secs` if `worker_id`
+    /// still holds it. Returns false when the lease was lost (reaped, stolen
+    /// or the job finished), in which case the caller should stop working on it.
+    pub async fn extend_lease(
+        &self,
+        job_id: Uuid,
+        worker_id: &str,
+        extend_by_secs: i64,
+    ) -> anyhow::Result<bool> {
         let res = sqlx::query(
             r#"
             UPDATE jobs
-            SET status = 'succeeded',
-                locked_at = NULL,
-                locked_by = NULL,
-                lock_expires_at = NULL,
+            SET lock_expires_at = now() + ($3::bigint * interval '1 second'),
                 updated_at = now()
-            WHERE id = ANY($1)
+            WHERE id = $1
+              AND status = 'running'
               AND locked_by = $2
             "#,
         )
-        .bind(job_ids)
+        .bind(job_id)
         .bind(worker_id)
+        .bind(extend_by_secs)
         .execute(&self.pool)
         .await?;
 
-        Ok(res.rows_affected())
+        Ok(res.rows_affected() == 1)
     }
 
-    /// Dataset-aware fast-path for partition-pruned successful batch updates.
-    pub async fn mark_succeeded_batch_for_dataset(
-        &self,
-        dataset_id: &str,
-        job_ids: &[Uuid],
+    // ----------------------------
+    // Maintenance
+    // ----------------------------
+
+    /// Requeue running jobs whose lease expired and add them to the per-queue
+    /// `lock_reap_counters` (exported as `pgflow_locks_reaped_total`).
+    pub async fn reap_expired_locks(&self) -> anyhow::Result<u64> {
+        let sql = format!(
+            r#"
+            WITH reaped AS (
+                UPDATE jobs
+                SET status = 'queued',
+                    run_at = CASE WHEN $1 > 0
+                                  THEN now() + make_interval(secs => $1 / 1000.0)
+                                  ELSE run_at END,
+                    locked_at = NULL,
+                    locked_by = NULL,
+                    lock_expires_at = NULL,
+                    updated_at = now()
+                WHERE status = 'running'
+                  AND lock_expires_at IS NOT NULL
+                  AND lock_expires_at < now()
+                RETURNING queue
+            ),
+            {LOCK_REAP_COUNTED_CTE}
+            SELECT COUNT(*)::bigint FROM reaped
+            "#,
+        );
+        let reaped: i64 = sqlx::query_scalar(&sql)
+            .bind(self.reap_requeue_delay_ms)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(reaped as u64)
+    }
+
+    /// Requeue every running job leased by a worker whose heartbeat is older
+    /// than `stale_after_secs`, regardless of lease expiry, and drop those
+    /// workers from the registry. Counted in `lock_reap_counters` like
+    /// `reap_expired_locks`. One statement holding the workers' rows, so
+    /// a worker that heartbeats meanwhile is either reaped before its
+    /// heartbeat lands or not at all. Returns (worker_id, jobs_requeued).
+    pub async fn reap_dead_workers(
+        &self,
+        stale_after_secs: i64,
+    ) -> anyhow::Result<Vec<(String, u64)>> {
+        let sql = format!(
+            r#"
+            WITH stale AS (
+                SELECT worker_id
+                FROM workers
+                WHERE last_heartbeat_at < now() - ($1::bigint * interval '1 second')
+                ORDER BY worker_id
+                FOR UPDATE
+            ),
+            reaped AS (
+                UPDATE jobs j
+                SET status = 'queued',
+                    run_at = CASE WHEN $2 > 0
+                                  THEN now() + make_interval(secs => $2 / 1000.0)
+                                  ELSE j.run_at END,
+                    locked_at = NULL,
+                    locked_by = NULL,
+                    lock_expires_at = NULL,
+                    updated_at = now()
+                FROM stale s
+                WHERE j.status = 'running'
+                  AND j.locked_by = s.worker_id
+                RETURNING s.worker_id, j.queue
+            ),
+            {LOCK_REAP_COUNTED_CTE},
+            removed AS (
+                DELETE FROM workers w
+                USING stale s
+                WHERE w.worker_id = s.worker_id
+            )
+            SELECT s.worker_id, COUNT(r.worker_id)::bigint
+            FROM stale s
+            LEFT JOIN reaped r ON r.worker_id = s.worker_id
+            GROUP BY s.worker_id
+            ORDER BY s.worker_id
+            "#,
+        );
+        let rows: Vec<(String, i64)> = sqlx::query_as(&sql)
+            .bind(stale_after_secs)
+            .bind(self.reap_requeue_delay_ms)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(worker_id, n)| (worker_id, n as u64))
+            .collect())
+    }
+
+    // ----------------------------
+    // State transitions
+    // ----------------------------
+
+    /// Fast-path for successful batch execution: transitions many jobs in one statement.
+    pub async fn mark_succeeded_batch(
+        &self,
+        job_ids: &[Uuid],
         worker_id: &str,
     ) -> anyhow::Result<u64> {
         if job_ids.is_empty() {
@@ -697,22 +1947,138 @@ impl JobsRepo {
                 locked_by = NULL,
                 lock_expires_at = NULL,
                 updated_at = now()
-            WHERE dataset_id = $1
-              AND id = ANY($2)
-              AND locked_by = $3
+            WHERE id = ANY($1)
+              AND locked_by = $2
+              AND (status <> 'canceled' OR $3)
             "#,
         )
-        .bind(dataset_id)
         .bind(job_ids)
         .bind(worker_id)
+        .bind(self.success_overrides_cancel)
         .execute(&self.pool)
         .await?;
 
         Ok(res.rows_affected())
     }
 
+    /// Dataset-aware fast-path for partition-pruned successful batch updates.
+    pub async fn mark_succeeded_batch_for_dataset(
+        &self,
+        dataset_id: &str,
+        job_ids: &[Uuid],
+        worker_id: &str,
+    ) -> anyhow::Result<u64> {
+        let jobs: Vec<(Uuid, Option<serde_json::Value>)> =
+            job_ids.iter().map(|id| (*id, None)).collect();
+        self.mark_succeeded_batch_with_results(dataset_id, &jobs, worker_id)
+            .await
+    }
+
+    /// Like `mark_succeeded_batch_for_dataset`, also storing each job's
+    /// `result_json` (if `Some`) in the same statement as its status change.
+    pub async fn mark_succeeded_batch_with_results(
+        &self,
+        dataset_id: &str,
+        jobs: &[(Uuid, Option<serde_json::Value>)],
+        worker_id: &str,
+    ) -> anyhow::Result<u64> {
+        let jobs: Vec<SucceededJob> = jobs
+            .iter()
+            .map(|(job_id, result)| SucceededJob {
+                job_id: *job_id,
+                result: result.clone(),
+                cooldown_until: None,
+            })
+            .collect();
+        self.mark_succeeded_batch_with_feedback(dataset_id, &jobs, worker_id)
+            .await
+    }
+
+    /// Like `mark_succeeded_batch_with_results`, also setting each job's
+    /// `cooldown_until` (if `Some`) in the same statement, so a concurrent
+    /// `enqueue_scheduled_once` never sees the job succeeded without it.
+    pub async fn mark_succeeded_batch_with_feedback(
+        &self,
+        dataset_id: &str,
+        jobs: &[SucceededJob],
+        worker_id: &str,
+    ) -> anyhow::Result<u64> {
+        if jobs.is_empty() {
+            return Ok(0);
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for chunk in jobs.chunks(self.batch_chunk_size) {
+            let ids: Vec<Uuid> = chunk.iter().map(|j| j.job_id).collect();
+            let results: Vec<Option<serde_json::Value>> =
+                chunk.iter().map(|j| j.result.clone()).collect();
+            let cooldowns: Vec<Option<DateTime<Utc>>> =
+                chunk.iter().map(|j| j.cooldown_until).collect();
+            let res = sqlx::query(
+                r#"
+                UPDATE jobs j
+                SET status = 'succeeded',
+                    locked_at = NULL,
+                    locked_by = NULL,
+                    lock_expires_at = NULL,
+                    result_json = COALESCE(r.result_json, j.result_json),
+                    cooldown_until = COALESCE(r.cooldown_until, j.cooldown_until),
+                    updated_at = now()
+                FROM UNNEST($2::uuid[], $3::jsonb[], $6::timestamptz[])
+                  AS r(id, result_json, cooldown_until)
+                WHERE j.dataset_id = $1
+                  AND j.id = r.id
+                  AND j.locked_by = $4
+                  AND (j.status <> 'canceled' OR $5)
+                "#,
+            )
+            .bind(dataset_id)
+            .bind(ids)
+            .bind(results)
+            .bind(worker_id)
+            .bind(self.success_overrides_cancel)
+            .bind(cooldowns)
+            .execute(&mut *tx)
+            .await?;
+            updated += res.rows_affected();
+        }
+        tx.commit().await?;
+
+        Ok(updated)
+    }
+
+    /// Handler feedback on a succeeded job: `enqueue_scheduled_once` won't
+    /// schedule another run with its `dedupe_key` before `until`. False if the
+    /// job isn't `succeeded`. For a cooldown known when the job finishes, pass
+    /// it to `mark_succeeded_batch_with_feedback` instead, which sets both at
+    /// once.
+    pub async fn set_cooldown_until(
+        &self,
+        job_id: Uuid,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<bool> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET cooldown_until = $2,
+                updated_at = now()
+            WHERE id = $1
+              AND status = 'succeeded'
+            "#,
+        )
+        .bind(job_id)
+        .bind(until)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(updated > 0)
+    }
+
+    /// Mark a leased job succeeded. A job canceled mid-run stays `canceled`
+    /// unless `with_success_overrides_cancel(true)` is set.
     pub async fn mark_succeeded(&self, job_id: Uuid, worker_id: &str) -> anyhow::Result<()> {
-        sqlx::query!(
+        sqlx::query(
             r#"
             UPDATE jobs
             SET status = 'succeeded',
@@ -722,47 +2088,58 @@ impl JobsRepo {
                 updated_at = now()
             WHERE id = $1
               AND locked_by = $2
+              AND (status <> 'canceled' OR $3)
             "#,
-            job_id,
-            worker_id
         )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(self.success_overrides_cancel)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Put this worker's leased job back in the queue for `next_run_at`. A
+    /// no-op once the lease was stolen, so a slow worker's failure can't
+    /// requeue a job its new owner is still running.
     pub async fn reschedule_for_retry(
         &self,
         job_id: Uuid,
+        worker_id: &str,
         next_run_at: DateTime<Utc>,
         last_error_code: Option<&str>,
         last_error_message: Option<&str>,
     ) -> anyhow::Result<()> {
-        sqlx::query!(
+        sqlx::query(
             r#"
             UPDATE jobs
             SET status = 'queued',
-                run_at = $2,
+                run_at = $3,
                 locked_at = NULL,
                 locked_by = NULL,
                 lock_expires_at = NULL,
                 updated_at = now(),
-                last_error_code = $3,
-                last_error_message = $4
+                last_error_code = $4,
+                last_error_message = $5
             WHERE id = $1
+              AND locked_by = $2
             "#,
-            job_id,
-            next_run_at,
-            last_error_code,
-            last_error_message
         )
+        .bind(job_id)
+        .bind(worker_id)
+        .bind(next_run_at)
+        .bind(last_error_code)
+        .bind(last_error_message)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Park this worker's leased job as `failed`: off the retry path and out
+    /// of the DLQ until an operator acts on it (`recover_failed_job` back to
+    /// `queued`, `dead_letter_failed_job` on to the DLQ, or a replay).
     pub async fn mark_failed(
         &self,
         job_id: Uuid,
@@ -794,6 +2171,9 @@ impl JobsRepo {
         Ok(())
     }
 
+    /// Move this worker's leased job to the DLQ through `dead_letter_job`,
+    /// the path every DLQ move shares (see `with_cancel_group_on_dlq`).
+    /// `None` error fields keep the job's last error.
     pub async fn mark_dlq(
         &self,
         job_id: Uuid,
@@ -802,45 +2182,546 @@ impl JobsRepo {
         last_error_code: Option<&str>,
         last_error_message: Option<&str>,
     ) -> anyhow::Result<()> {
-        sqlx::query!(
+        sqlx::query("SELECT dead_letter_job(NULL, $1, $2, $3, $4, $5, $6)")
+            .bind(job_id)
+            .bind(worker_id)
+            .bind(reason_code)
+            .bind(last_error_code)
+            .bind(last_error_message)
+            .bind(self.cancel_group_on_dlq)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Status of the jobs enqueued with `group_id`; `None` if there are none
+    /// (archived members are not counted).
+    pub async fn group_status(&self, group_id: Uuid) -> anyhow::Result<Option<GroupStatus>> {
+        let counts: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT status, COUNT(*)::bigint
+            FROM jobs
+            WHERE group_id = $1
+            GROUP BY status
+            "#,
+        )
+        .bind(group_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if counts.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(GroupStatus::from_counts(group_id, counts)))
+    }
+
+    // ----------------------------
+    // Manual edits
+    // ----------------------------
+
+    /// Replace the payload of a `queued` or `dlq` job and record a
+    /// `MANUAL_EDIT` policy decision holding the previous payload.
+    pub async fn update_payload(
+        &self,
+        job_id: Uuid,
+        payload_json: serde_json::Value,
+    ) -> anyhow::Result<PayloadEdit> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query_as::<_, (String, String, serde_json::Value)>(
+            r#"
+            SELECT dataset_id, status, payload_json
+            FROM jobs
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((dataset_id, status, previous)) = current else {
+            tx.commit().await?;
+            return Ok(PayloadEdit::NotFound);
+        };
+        if status != JobStatus::Queued.as_str() && status != JobStatus::Dlq.as_str() {
+            tx.commit().await?;
+            return Ok(PayloadEdit::NotEditable(status));
+        }
+
+        let job = sqlx::query_as::<_, Job>(
             r#"
             UPDATE jobs
-            SET status = 'dlq',
-                dlq_reason_code = $3,
-                dlq_at = now(),
+            SET payload_json = $3,
+                updated_at = now()
+            WHERE dataset_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(&payload_json)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO policy_decisions (
+              id, dataset_id, job_id, decision, reason_code, details_json
+            )
+            VALUES ($1, $2, $3, 'MANUAL_EDIT', 'PAYLOAD_EDITED', $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(json!({
+            "status": status,
+            "previous_payload": previous,
+            "payload": payload_json
+        }))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(PayloadEdit::Updated(Box::new(job)))
+    }
+
+    /// Move a `failed` job back to `queued`, runnable now.
+    ///
+    /// Jobs only become `failed` through `mark_failed`; the retry path never
+    /// leases them again, so this (or `dead_letter_failed_job`) is the
+    /// operator's way out. The last error is kept for triage and attempt
+    /// numbering continues after the attempts already made. Records a
+    /// `MANUAL_RECOVER` policy decision.
+    pub async fn recover_failed_job(&self, job_id: Uuid) -> anyhow::Result<JobRecovery> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query_as::<_, (String, String, Option<String>, Option<String>)>(
+            r#"
+            SELECT dataset_id, status, last_error_code, last_error_message
+            FROM jobs
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((dataset_id, status, last_error_code, last_error_message)) = current else {
+            tx.commit().await?;
+            return Ok(JobRecovery::NotFound);
+        };
+        if status != JobStatus::Failed.as_str() {
+            tx.commit().await?;
+            return Ok(JobRecovery::NotFailed(status));
+        }
+
+        let job = sqlx::query_as::<_, Job>(
+            r#"
+            UPDATE jobs
+            SET status = 'queued',
+                run_at = now(),
                 locked_at = NULL,
                 locked_by = NULL,
                 lock_expires_at = NULL,
-                updated_at = now(),
-                last_error_code = $4,
-                last_error_message = $5
+                updated_at = now()
+            WHERE dataset_id = $1 AND id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(&dataset_id)
+        .bind(job_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO policy_decisions (
+              id, dataset_id, job_id, decision, reason_code, details_json
+            )
+            VALUES ($1, $2, $3, 'MANUAL_RECOVER', 'RECOVERED_FROM_FAILED', $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(json!({
+            "last_error_code": last_error_code,
+            "last_error_message": last_error_message
+        }))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(JobRecovery::Recovered(Box::new(job)))
+    }
+
+    /// Move a `failed` job on to the DLQ (`MOVED_FROM_FAILED`) through
+    /// `dead_letter_job`, so it gets the same columns and group cancel as a
+    /// worker's DLQ move, and from there the usual DLQ replay and requeue.
+    /// Records a `MANUAL_DLQ` policy decision.
+    pub async fn dead_letter_failed_job(&self, job_id: Uuid) -> anyhow::Result<JobRecovery> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query_as::<_, (String, String, Option<String>, Option<String>)>(
+            r#"
+            SELECT dataset_id, status, last_error_code, last_error_message
+            FROM jobs
             WHERE id = $1
-              AND locked_by = $2
+            FOR UPDATE
             "#,
-            job_id,
-            worker_id,
-            reason_code,
-            last_error_code,
-            last_error_message
         )
+        .bind(job_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((dataset_id, status, last_error_code, last_error_message)) = current else {
+            tx.commit().await?;
+            return Ok(JobRecovery::NotFound);
+        };
+        if status != JobStatus::Failed.as_str() {
+            tx.commit().await?;
+            return Ok(JobRecovery::NotFailed(status));
+        }
+
+        sqlx::query("SELECT dead_letter_job($1, $2, NULL, 'MOVED_FROM_FAILED', NULL, NULL, $3)")
+            .bind(&dataset_id)
+            .bind(job_id)
+            .bind(self.cancel_group_on_dlq)
+            .execute(&mut *tx)
+            .await?;
+
+        let job = sqlx::query_as::<_, Job>("SELECT * FROM jobs WHERE dataset_id = $1 AND id = $2")
+            .bind(&dataset_id)
+            .bind(job_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO policy_decisions (
+              id, dataset_id, job_id, decision, reason_code, details_json
+            )
+            VALUES ($1, $2, $3, 'MANUAL_DLQ', 'MOVED_FROM_FAILED', $4)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&dataset_id)
+        .bind(job_id)
+        .bind(json!({
+            "last_error_code": last_error_code,
+            "last_error_message": last_error_message
+        }))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(JobRecovery::Recovered(Box::new(job)))
+    }
+
+    /// Cancel a job that hasn't started: `queued` -> `canceled`. False if the
+    /// job doesn't exist or isn't queued; running jobs are refused so a cancel
+    /// never races the worker holding the lease.
+    pub async fn cancel_job(&self, job_id: Uuid) -> anyhow::Result<bool> {
+        let canceled = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'canceled',
+                updated_at = now()
+            WHERE id = $1
+              AND status = 'queued'
+            "#,
+        )
+        .bind(job_id)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(canceled > 0)
+    }
+
+    /// Change the priority of a job that hasn't started, so it moves up (or
+    /// down) the lease order without being replayed. False if the job doesn't
+    /// exist or isn't queued.
+    pub async fn set_priority(&self, job_id: Uuid, priority: i32) -> anyhow::Result<bool> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE jobs
+            SET priority = public.normalize_job_priority(queue, $2),
+                updated_at = now()
+            WHERE id = $1
+              AND status = 'queued'
+            "#,
+        )
+        .bind(job_id)
+        .bind(priority)
         .execute(&self.pool)
+        .await?
+        .rows_affected();
+        Ok(updated > 0)
+    }
+
+    /// Make the next failure of a queued, running or failed job go straight
+    /// to the DLQ (`FORCED_NON_RETRYABLE`), whatever the error code. Records a
+    /// `MANUAL_FLAG` policy decision. Returns false if no such job is in one
+    /// of those states.
+    pub async fn set_non_retryable(&self, job_id: Uuid) -> anyhow::Result<bool> {
+        let flagged: i64 = sqlx::query_scalar(
+            r#"
+            WITH flagged AS (
+                UPDATE jobs
+                SET force_dlq_on_failure = true,
+                    updated_at = now()
+                WHERE id = $1
+                  AND status IN ('queued', 'running', 'failed')
+                RETURNING dataset_id, id, status
+            ),
+            audited AS (
+                INSERT INTO policy_decisions (
+                  id, dataset_id, job_id, decision, reason_code, details_json
+                )
+                SELECT gen_random_uuid(), dataset_id, id, 'MANUAL_FLAG', 'MARKED_NON_RETRYABLE',
+                       jsonb_build_object('status', status)
+                FROM flagged
+            )
+            SELECT COUNT(*)::bigint FROM flagged
+            "#,
+        )
+        .bind(job_id)
+        .fetch_one(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(flagged == 1)
+    }
+
+    /// What a failed attempt's retry decision needs, in one round trip: the
+    /// job's `force_dlq_on_failure` flag and its backoff, with the job's own
+    /// retry columns over its queue policy's over `fallback`. `None` for an
+    /// unknown job.
+    pub async fn failure_policy(
+        &self,
+        job_id: Uuid,
+        fallback: &RetryConfig,
+    ) -> anyhow::Result<Option<FailurePolicy>> {
+        #[allow(clippy::type_complexity)]
+        let row: Option<(
+            bool,
+            Option<i64>,
+            Option<i64>,
+            Option<i32>,
+            Option<i32>,
+            Option<f64>,
+        )> = sqlx::query_as(
+            r#"
+            SELECT j.force_dlq_on_failure,
+                   j.retry_base_seconds, j.retry_max_seconds,
+                   p.retry_base_seconds, p.retry_max_seconds, p.retry_jitter_pct
+            FROM jobs j
+            LEFT JOIN queue_policies p ON p.queue = j.queue
+            WHERE j.id = $1
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(
+            |(forced, job_base, job_max, queue_base, queue_max, queue_jitter)| {
+                let queue_cfg = RetryConfig {
+                    base_seconds: queue_base.map_or(fallback.base_seconds, i64::from),
+                    max_seconds: queue_max.map_or(fallback.max_seconds, i64::from),
+                    jitter_pct: queue_jitter.unwrap_or(fallback.jitter_pct),
+                    ..fallback.clone()
+                };
+                let job_override = RetryOverride {
+                    base_seconds: job_base,
+                    max_seconds: job_max,
+                };
+                FailurePolicy {
+                    retry: job_override.apply(&queue_cfg),
+                    forced_non_retryable: forced,
+                }
+            },
+        ))
+    }
+
+    /// Whether `set_non_retryable` was called for this job.
+    pub async fn is_forced_non_retryable(&self, job_id: Uuid) -> anyhow::Result<bool> {
+        let forced: Option<bool> =
+            sqlx::query_scalar("SELECT force_dlq_on_failure FROM jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(forced.unwrap_or(false))
+    }
+
+    /// Put up to `limit` DLQ jobs (optionally only `queue`'s) back to
+    /// `queued`, oldest dead-lettered first, optionally resetting their
+    /// priority.
+    ///
+    /// `run_at`s are spread evenly over `[now, now + spread)` in that order so
+    /// a recovered dependency isn't hit by the whole backlog at once; a zero
+    /// `spread` makes them all runnable now. Attempt numbering continues and
+    /// the last error is kept; each job may make `extra_attempts` (at least 1)
+    /// more attempts, `max_attempts` being raised to fit them, and a
+    /// `set_non_retryable` flag is cleared. Jobs locked by a concurrent
+    /// requeue are skipped. Each job gets a `MANUAL_REQUEUE` policy decision.
+    /// Returns the number requeued.
+    pub async fn requeue_dlq(
+        &self,
+        queue: Option<&str>,
+        priority: Option<i32>,
+        extra_attempts: i32,
+        spread: Duration,
+        limit: i64,
+    ) -> anyhow::Result<u64> {
+        let requeued: i64 = sqlx::query_scalar(
+            r#"
+            WITH locked AS (
+              SELECT dataset_id, id, dlq_reason_code, dlq_at
+              FROM jobs
+              WHERE status = 'dlq'
+                AND ($1::text IS NULL OR queue = $1)
+              ORDER BY dlq_at ASC NULLS FIRST, id ASC
+              LIMIT $4
+              FOR UPDATE SKIP LOCKED
+            ),
+            picked AS (
+              SELECT l.*,
+                     row_number() OVER (ORDER BY dlq_at ASC NULLS FIRST, id ASC) - 1 AS slot,
+                     COUNT(*) OVER () AS total,
+                     COALESCE((
+                       SELECT MAX(a.attempt_no)
+                       FROM job_attempts a
+                       WHERE a.dataset_id = l.dataset_id AND a.job_id = l.id
+                     ), 0) AS attempts_made
+              FROM locked l
+            ),
+            requeued AS (
+              UPDATE jobs j
+              SET status = 'queued',
+                  priority = public.normalize_job_priority(j.queue, COALESCE($2, j.priority)),
+                  run_at = now()
+                    + ($3::bigint * interval '1 millisecond') * (p.slot::float8 / p.total),
+                  max_attempts = GREATEST(j.max_attempts, p.attempts_made + $5),
+                  force_dlq_on_failure = false,
+                  dlq_reason_code = NULL,
+                  dlq_error_code = NULL,
+                  dlq_at = NULL,
+                  updated_at = now()
+              FROM picked p
+              WHERE j.dataset_id = p.dataset_id
+                AND j.id = p.id
+              RETURNING j.dataset_id, j.id, j.run_at, j.max_attempts, p.dlq_reason_code
+            ),
+            audited AS (
+              INSERT INTO policy_decisions (
+                id, dataset_id, job_id, decision, reason_code, details_json
+              )
+              SELECT
+                gen_random_uuid(), r.dataset_id, r.id, 'MANUAL_REQUEUE', 'REQUEUED_FROM_DLQ',
+                jsonb_build_object(
+                  'dlq_reason_code', r.dlq_reason_code,
+                  'run_at', r.run_at,
+                  'max_attempts', r.max_attempts,
+                  'spread_ms', $3::bigint
+                )
+              FROM requeued r
+              RETURNING 1
+            )
+            SELECT COUNT(*)::bigint FROM audited
+            "#,
+        )
+        .bind(queue)
+        .bind(priority)
+        .bind(spread.as_millis() as i64)
+        .bind(limit)
+        .bind(extra_attempts.max(1))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(requeued as u64)
+    }
+
+    /// Move up to `limit` `queued` jobs (optionally only `job_type`) from
+    /// `from_queue` to `to_queue`, in the order they would have been leased.
+    ///
+    /// Running jobs are never moved, and jobs a worker is leasing right now are
+    /// skipped. `dataset_id` is left as is; it only places the row in a partition.
+    /// Priorities are clamped into `to_queue`'s bounds, if it has them.
+    /// Each moved job gets a `MANUAL_MOVE` policy decision. Returns the number moved.
+    pub async fn move_jobs(
+        &self,
+        from_queue: &str,
+        to_queue: &str,
+        job_type: Option<&str>,
+        limit: i64,
+    ) -> anyhow::Result<u64> {
+        let moved: i64 = sqlx::query_scalar(
+            r#"
+            WITH picked AS (
+              SELECT dataset_id, id
+              FROM jobs
+              WHERE queue = $1
+                AND status = 'queued'
+                AND ($3::text IS NULL OR job_type = $3)
+              ORDER BY priority DESC, run_at ASC, created_at ASC
+              LIMIT $4
+              FOR UPDATE SKIP LOCKED
+            ),
+            moved AS (
+              UPDATE jobs j
+              SET queue = $2,
+                  priority = public.normalize_job_priority($2, j.priority),
+                  updated_at = now()
+              FROM picked p
+              WHERE j.dataset_id = p.dataset_id
+                AND j.id = p.id
+                AND j.status = 'queued'
+              RETURNING j.dataset_id, j.id
+            ),
+            audited AS (
+              INSERT INTO policy_decisions (
+                id, dataset_id, job_id, decision, reason_code, details_json
+              )
+              SELECT
+                gen_random_uuid(), m.dataset_id, m.id, 'MANUAL_MOVE', 'QUEUE_MOVED',
+                jsonb_build_object('from_queue', $1::text, 'to_queue', $2::text)
+              FROM moved m
+              RETURNING 1
+            )
+            SELECT COUNT(*)::bigint FROM audited
+            "#,
+        )
+        .bind(from_queue)
+        .bind(to_queue)
+        .bind(job_type)
+        .bind(limit)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(moved as u64)
     }
 
     // ----------------------------
     // Replay
     // ----------------------------
 
+    /// Create a fresh queued job from `job_id`, linked via `replay_of_job_id`.
+    ///
+    /// Queue, run_at and priority default to the source job's (run_at: now).
+    /// With `include_history`, the new job's timeline also surfaces the source
+    /// job's attempts (read-only); attempt numbering still restarts at 1.
     pub async fn replay_job(
         &self,
         job_id: Uuid,
         override_queue: Option<&str>,
         override_run_at: Option<DateTime<Utc>>,
+        override_priority: Option<i32>,
+        include_history: bool,
     ) -> anyhow::Result<Uuid> {
-        let mut tx = self.pool.begin().await?;
-
         let src = sqlx::query_as::<_, Job>(
             r#"
             SELECT *
@@ -849,14 +2730,19 @@ impl JobsRepo {
             "#,
         )
         .bind(job_id)
-        .fetch_one(&mut *tx)
+        .fetch_one(&self.pool)
         .await?;
 
+        let retry = src.retry_override();
         let new_queue = override_queue.unwrap_or(src.queue.as_str()).to_string();
-        let new_run_at = override_run_at.unwrap_or_else(|| Utc::now());
+        self.check_enqueue_enabled(&new_queue).await?;
+        let new_run_at = override_run_at.unwrap_or_else(|| self.clock.now());
         let new_dataset_id = Self::dataset_id_for(&new_queue, new_run_at);
+        // attaching a partition locks `jobs`; do it before our tx holds any lock on it
         self.ensure_dataset_partition(&new_dataset_id).await?;
 
+        let mut tx = self.pool.begin().await?;
+
         let new_id = sqlx::query_scalar::<_, Uuid>(
             r#"
             INSERT INTO jobs (
@@ -864,30 +2750,173 @@ impl JobsRepo {
                 queue, job_type, payload_json, run_at, status, priority, max_attempts,
                 locked_at, locked_by, lock_expires_at,
                 dlq_reason_code, dlq_at,
-                replay_of_job_id
+                replay_of_job_id, replay_include_history,
+                retry_base_seconds, retry_max_seconds
             )
             VALUES (
                 $1,
-                $2, $3, $4, $5, 'queued', $6, $7,
+                $2, $3, $4, $5, 'queued', public.normalize_job_priority($2, $6), $7,
                 NULL, NULL, NULL,
                 NULL, NULL,
-                $8
+                $8, $9,
+                $10, $11
             )
             RETURNING id
             "#,
         )
         .bind(new_dataset_id)
-        .bind(new_queue)
+        .bind(&new_queue)
         .bind(src.job_type)
         .bind(src.payload_json)
         .bind(new_run_at)
-        .bind(src.priority)
+        .bind(override_priority.unwrap_or(src.priority))
         .bind(src.max_attempts)
         .bind(src.id)
+        .bind(include_history)
+        .bind(retry.base_seconds)
+        .bind(retry.max_seconds)
         .fetch_one(&mut *tx)
         .await?;
+        self.notify_enqueued(&mut *tx, &[(&new_queue, new_id)])
+            .await?;
 
         tx.commit().await?;
         Ok(new_id)
     }
+
+    /// Replay up to `limit` DLQ jobs (optionally only `queue`'s) at once,
+    /// oldest dead-lettered first, each as in `replay_job` with no overrides
+    /// besides `override_run_at` (default: now).
+    ///
+    /// The source jobs stay in the DLQ. Jobs locked by a concurrent batch
+    /// replay are skipped, and so are jobs that already have a replay, so
+    /// running it twice doesn't double-replay. Returns the new job ids.
+    pub async fn replay_dlq_batch(
+        &self,
+        queue: Option<&str>,
+        limit: i64,
+        override_run_at: Option<DateTime<Utc>>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let run_at = override_run_at.unwrap_or_else(|| self.clock.now());
+
+        // attaching a partition locks `jobs`; do it before our tx holds any lock on it
+        let queues: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT queue
+            FROM jobs
+            WHERE status = 'dlq'
+              AND ($1::text IS NULL OR queue = $1)
+            "#,
+        )
+        .bind(queue)
+        .fetch_all(&self.pool)
+        .await?;
+        for q in &queues {
+            self.check_enqueue_enabled(q).await?;
+            self.ensure_dataset_partition(&Self::dataset_id_for(q, run_at))
+                .await?;
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let locked: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, queue
+            FROM jobs j
+            WHERE status = 'dlq'
+              AND ($1::text IS NULL OR queue = $1)
+              AND NOT EXISTS (SELECT 1 FROM jobs r WHERE r.replay_of_job_id = j.id)
+            ORDER BY dlq_at ASC NULLS FIRST, id ASC
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(queue)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
+        if locked.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (src_ids, datasets): (Vec<Uuid>, Vec<String>) = locked
+            .into_iter()
+            .map(|(id, q)| (id, Self::dataset_id_for(&q, run_at)))
+            .unzip();
+
+        // a fresh snapshot: a batch that held these locks before us has committed
+        // its replays by now, and they are visible here
+        let replayed: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            WITH picked AS (
+              SELECT src_id, dataset_id, ord
+              FROM unnest($1::uuid[], $2::text[]) WITH ORDINALITY AS p(src_id, dataset_id, ord)
+            ),
+            inserted AS (
+              INSERT INTO jobs (
+                  dataset_id,
+                  queue, job_type, payload_json, run_at, status, priority, max_attempts,
+                  replay_of_job_id, replay_include_history,
+                  retry_base_seconds, retry_max_seconds
+              )
+              SELECT
+                  p.dataset_id,
+                  s.queue, s.job_type, s.payload_json, $3, 'queued',
+                  public.normalize_job_priority(s.queue, s.priority), s.max_attempts,
+                  s.id, false,
+                  s.retry_base_seconds, s.retry_max_seconds
+              FROM picked p
+              JOIN jobs s ON s.id = p.src_id
+              WHERE NOT EXISTS (SELECT 1 FROM jobs r WHERE r.replay_of_job_id = s.id)
+              ORDER BY p.ord
+              RETURNING id, queue, replay_of_job_id
+            )
+            SELECT i.id, i.queue
+            FROM inserted i
+            JOIN picked p ON p.src_id = i.replay_of_job_id
+            ORDER BY p.ord
+            "#,
+        )
+        .bind(&src_ids)
+        .bind(&datasets)
+        .bind(run_at)
+        .fetch_all(&mut *tx)
+        .await?;
+        let notify: Vec<(&str, Uuid)> = replayed
+            .iter()
+            .map(|(id, queue)| (queue.as_str(), *id))
+            .collect();
+        self.notify_enqueued(&mut *tx, &notify).await?;
+
+        tx.commit().await?;
+        Ok(replayed.into_iter().map(|(id, _)| id).collect())
+    }
+}
+
+/// Datasets one lease may take jobs from, bound as `$1` of the lease queries.
+enum LeaseDatasets {
+    One(String),
+    Any(Vec<String>),
+    All,
+}
+
+impl LeaseDatasets {
+    // separate statement texts keep the single-dataset plans pruned to its partition
+    fn pred(&self) -> &'static str {
+        match self {
+            Self::One(_) => "dataset_id = $1",
+            Self::Any(_) => "dataset_id = ANY($1)",
+            Self::All => "$1::text IS NULL",
+        }
+    }
+
+    fn bind<'q, O>(
+        &'q self,
+        query: QueryAs<'q, Postgres, O, PgArguments>,
+    ) -> QueryAs<'q, Postgres, O, PgArguments> {
+        match self {
+            Self::One(id) => query.bind(id),
+            Self::Any(ids) => query.bind(ids),
+            Self::All => query.bind(None::<String>),
+        }
+    }
 }
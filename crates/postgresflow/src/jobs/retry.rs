@@ -5,6 +5,8 @@ pub struct RetryConfig {
     pub base_seconds: i64,
     pub max_seconds: i64,
     pub jitter_pct: f64,
+    // floor applied after jitter so a retry never lands (almost) immediately
+    pub min_delay_seconds: i64,
 }
 
 impl Default for RetryConfig {
@@ -13,10 +15,38 @@ impl Default for RetryConfig {
             base_seconds: 2,
             max_seconds: 15 * 60,
             jitter_pct: 0.20,
+            min_delay_seconds: 0,
         }
     }
 }
 
+/// Per-job backoff stored on the row at enqueue; unset fields fall back to
+/// the worker's `RetryConfig`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryOverride {
+    pub base_seconds: Option<i64>,
+    pub max_seconds: Option<i64>,
+}
+
+impl RetryOverride {
+    pub fn apply(&self, cfg: &RetryConfig) -> RetryConfig {
+        RetryConfig {
+            base_seconds: self.base_seconds.unwrap_or(cfg.base_seconds),
+            max_seconds: self.max_seconds.unwrap_or(cfg.max_seconds),
+            ..cfg.clone()
+        }
+    }
+}
+
+/// A failed attempt's retry inputs, read by `JobsRepo::failure_policy`.
+#[derive(Debug, Clone)]
+pub struct FailurePolicy {
+    /// Backoff for the next attempt.
+    pub retry: RetryConfig,
+    /// `JobsRepo::set_non_retryable` was called for the job.
+    pub forced_non_retryable: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorClass {
     Retryable,
@@ -55,5 +85,6 @@ pub fn next_delay_seconds(attempt_no: i32, cfg: &RetryConfig, rng: &mut impl Rng
     let jitter = rng.gen_range(-jitter_range..=jitter_range);
 
     let jittered = (delay as f64 + jitter).round() as i64;
-    jittered.clamp(0, cfg.max_seconds)
+    let floor = cfg.min_delay_seconds.clamp(0, cfg.max_seconds.max(0));
+    jittered.clamp(floor, cfg.max_seconds.max(floor))
 }
@@ -1,17 +1,23 @@
 use crate::jobs::{
     attempts::AttemptsRepo,
+    clock::{Clock, SystemClock},
+    model::SucceededJob,
     repo::JobsRepo,
     retry::{classify_error, next_delay_seconds, ErrorClass, RetryConfig},
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rand::{rngs::StdRng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Clone)]
 pub struct JobRunner {
     jobs: JobsRepo,
     attempts: AttemptsRepo,
+    // fallback for queues whose policy sets no retry columns
     retry_cfg: RetryConfig,
+    clock: Arc<dyn Clock>,
 }
 
 impl JobRunner {
@@ -20,9 +26,16 @@ impl JobRunner {
             jobs,
             attempts,
             retry_cfg,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Clock the retry `run_at` is computed from; tests pass a `MockClock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     pub async fn on_success(
         &self,
         job_id: Uuid,
@@ -43,6 +56,39 @@ impl JobRunner {
         dataset_id: &str,
         updates: &[(Uuid, Uuid, i32)],
         worker_id: &str,
+    ) -> anyhow::Result<()> {
+        self.on_success_batch_with_results(dataset_id, updates, &HashMap::new(), worker_id)
+            .await
+    }
+
+    /// `on_success_batch`, storing handler results (keyed by job id) as `result_json`.
+    pub async fn on_success_batch_with_results(
+        &self,
+        dataset_id: &str,
+        updates: &[(Uuid, Uuid, i32)],
+        results: &HashMap<Uuid, serde_json::Value>,
+        worker_id: &str,
+    ) -> anyhow::Result<()> {
+        self.on_success_batch_with_feedback(
+            dataset_id,
+            updates,
+            results,
+            &HashMap::new(),
+            worker_id,
+        )
+        .await
+    }
+
+    /// `on_success_batch_with_results`, also storing handler cooldowns (keyed
+    /// by job id) as `cooldown_until` in the same statement as the status
+    /// change, so no enqueue sees the job succeeded without its cooldown.
+    pub async fn on_success_batch_with_feedback(
+        &self,
+        dataset_id: &str,
+        updates: &[(Uuid, Uuid, i32)],
+        results: &HashMap<Uuid, serde_json::Value>,
+        cooldowns: &HashMap<Uuid, DateTime<Utc>>,
+        worker_id: &str,
     ) -> anyhow::Result<()> {
         if updates.is_empty() {
             return Ok(());
@@ -52,17 +98,25 @@ impl JobRunner {
             .iter()
             .map(|(_, attempt_id, latency_ms)| (*attempt_id, *latency_ms))
             .collect();
-        let job_ids: Vec<Uuid> = updates.iter().map(|(job_id, _, _)| *job_id).collect();
+        let jobs: Vec<SucceededJob> = updates
+            .iter()
+            .map(|(job_id, _, _)| SucceededJob {
+                job_id: *job_id,
+                result: results.get(job_id).cloned(),
+                cooldown_until: cooldowns.get(job_id).copied(),
+            })
+            .collect();
 
         self.attempts
             .finish_succeeded_batch(&attempt_updates)
             .await?;
         self.jobs
-            .mark_succeeded_batch_for_dataset(dataset_id, &job_ids, worker_id)
+            .mark_succeeded_batch_with_feedback(dataset_id, &jobs, worker_id)
             .await?;
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn on_failure(
         &self,
         job_id: Uuid,
@@ -73,28 +127,80 @@ impl JobRunner {
         error_message: &str,
         attempt_no: i32,
         max_attempts: i32,
+    ) -> anyhow::Result<()> {
+        self.on_failure_with_details(
+            job_id,
+            attempt_id,
+            worker_id,
+            latency_ms,
+            error_code,
+            error_message,
+            None,
+            attempt_no,
+            max_attempts,
+        )
+        .await
+    }
+
+    /// `on_failure`, also storing the handler's structured error context on
+    /// the attempt.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn on_failure_with_details(
+        &self,
+        job_id: Uuid,
+        attempt_id: Uuid,
+        worker_id: &str,
+        latency_ms: i32,
+        error_code: &str,
+        error_message: &str,
+        error_details: Option<&serde_json::Value>,
+        attempt_no: i32,
+        max_attempts: i32,
     ) -> anyhow::Result<()> {
         // 1) Close out the attempt row (audit)
         self.attempts
-            .finish_failed(attempt_id, latency_ms, error_code, error_message)
+            .finish_failed_with_details(
+                attempt_id,
+                latency_ms,
+                error_code,
+                error_message,
+                error_details,
+            )
             .await?;
 
         // 2) Decide retry vs DLQ
         let class = classify_error(error_code);
         let can_retry = class == ErrorClass::Retryable && attempt_no < max_attempts;
+        // one read for the operator's no-more-retries flag
+        // (JobsRepo::set_non_retryable) and the backoff: per-job from enqueue
+        // over the queue's policy over the worker's RetryConfig
+        let policy = if can_retry {
+            self.jobs.failure_policy(job_id, &self.retry_cfg).await?
+        } else {
+            None
+        };
+        let forced = policy.as_ref().is_some_and(|p| p.forced_non_retryable);
 
-        if can_retry {
+        if can_retry && !forced {
             // retry: exponential backoff + jitter + cap
             let mut rng = StdRng::from_entropy();
-            let delay_secs = next_delay_seconds(attempt_no, &self.retry_cfg, &mut rng);
-            let next_run_at = Utc::now() + chrono::Duration::seconds(delay_secs);
+            let retry_cfg = policy.map_or_else(|| self.retry_cfg.clone(), |p| p.retry);
+            let delay_secs = next_delay_seconds(attempt_no, &retry_cfg, &mut rng);
+            let next_run_at = self.clock.now() + chrono::Duration::seconds(delay_secs);
 
             self.jobs
-                .reschedule_for_retry(job_id, next_run_at, Some(error_code), Some(error_message))
+                .reschedule_for_retry(
+                    job_id,
+                    worker_id,
+                    next_run_at,
+                    Some(error_code),
+                    Some(error_message),
+                )
                 .await?;
         } else {
             // DLQ: retries exhausted OR non-retryable
             let reason_code = match class {
+                _ if forced => "FORCED_NON_RETRYABLE",
                 ErrorClass::NonRetryable => "NON_RETRYABLE",
                 ErrorClass::Retryable => "MAX_ATTEMPTS_EXCEEDED", // retryable but ran out
             };
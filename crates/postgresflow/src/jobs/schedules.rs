@@ -0,0 +1,305 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::enqueue_guard::{self, EnqueueGuard};
+use super::{JobsRepo, NewJob};
+
+/// Default attempts given to jobs materialized from a schedule (same as
+/// `enqueue_now`).
+pub const DEFAULT_SCHEDULED_JOB_MAX_ATTEMPTS: i32 = 25;
+
+/// Column list every `Schedule` query returns.
+const SCHEDULE_COLUMNS: &str = "name, queue, job_type, payload_json, cron_expr, priority, \
+     max_attempts, next_run_at, last_run_at, last_job_id";
+
+/// A recurring job: `job_type` with `payload_json` is enqueued on `queue`
+/// every time `cron_expr` fires.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Schedule {
+    pub name: String,
+    pub queue: String,
+    pub job_type: String,
+    pub payload_json: serde_json::Value,
+    pub cron_expr: String,
+    /// Priority of the jobs it enqueues.
+    pub priority: i32,
+    /// `max_attempts` of the jobs it enqueues.
+    pub max_attempts: i32,
+    /// Next fire time; `None` once the expression has no fire times left.
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_job_id: Option<Uuid>,
+}
+
+/// Definition passed to `SchedulesRepo::upsert_schedule`.
+#[derive(Debug, Clone)]
+pub struct NewSchedule {
+    pub name: String,
+    pub queue: String,
+    pub job_type: String,
+    pub payload_json: serde_json::Value,
+    pub cron_expr: String,
+    pub priority: i32,
+    pub max_attempts: i32,
+}
+
+impl NewSchedule {
+    /// Jobs get priority `0` and `DEFAULT_SCHEDULED_JOB_MAX_ATTEMPTS`.
+    pub fn new(
+        name: &str,
+        queue: &str,
+        job_type: &str,
+        payload_json: serde_json::Value,
+        cron_expr: &str,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            queue: queue.to_string(),
+            job_type: job_type.to_string(),
+            payload_json,
+            cron_expr: cron_expr.to_string(),
+            priority: 0,
+            max_attempts: DEFAULT_SCHEDULED_JOB_MAX_ATTEMPTS,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: i32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+}
+
+/// Parse a cron expression in the standard 5-field form (`*/5 * * * *`,
+/// minute precision) or the 6/7-field form with leading seconds and an
+/// optional trailing year. Times are UTC.
+pub fn parse_cron(expr: &str) -> anyhow::Result<cron::Schedule> {
+    let expr = expr.trim();
+    let normalized = if expr.split_whitespace().count() == 5 {
+        format!("0 {expr}")
+    } else {
+        expr.to_string()
+    };
+    cron::Schedule::from_str(&normalized)
+        .map_err(|e| anyhow::anyhow!("invalid cron expression {expr:?}: {e}"))
+}
+
+/// First fire time of `expr` strictly after `after`, or `None` if it never
+/// fires again.
+pub fn next_fire_after(expr: &str, after: DateTime<Utc>) -> anyhow::Result<Option<DateTime<Utc>>> {
+    Ok(parse_cron(expr)?.after(&after).next())
+}
+
+/// Recurring job definitions backed by the `schedules` table.
+#[derive(Clone)]
+pub struct SchedulesRepo {
+    pool: PgPool,
+    enqueue_guard: Option<EnqueueGuard>,
+}
+
+impl SchedulesRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            enqueue_guard: None,
+        }
+    }
+
+    /// Run `guard`'s checks (kill switch, retired queue, payload size, job
+    /// type, rate) on every job a schedule is about to enqueue. A denied
+    /// window is skipped (recorded in `ingest_decisions` like any denied
+    /// enqueue) and the schedule moves on to its next fire time.
+    pub fn with_enqueue_guard(mut self, guard: EnqueueGuard) -> Self {
+        self.enqueue_guard = Some(guard);
+        self
+    }
+
+    /// Create or replace the schedule `schedule.name`. Its next fire time is
+    /// the first one after now; replacing a schedule without changing
+    /// `cron_expr` keeps its pending fire time.
+    pub async fn upsert_schedule(&self, schedule: NewSchedule) -> anyhow::Result<Schedule> {
+        if schedule.max_attempts <= 0 {
+            anyhow::bail!("max_attempts must be > 0");
+        }
+        let next_run_at = next_fire_after(&schedule.cron_expr, Utc::now())?;
+
+        let schedule = sqlx::query_as(&format!(
+            r#"
+            INSERT INTO schedules (
+                name, queue, job_type, payload_json, cron_expr, priority, max_attempts, next_run_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (name) DO UPDATE
+            SET queue = EXCLUDED.queue,
+                job_type = EXCLUDED.job_type,
+                payload_json = EXCLUDED.payload_json,
+                priority = EXCLUDED.priority,
+                max_attempts = EXCLUDED.max_attempts,
+                next_run_at = CASE
+                    WHEN schedules.cron_expr = EXCLUDED.cron_expr THEN schedules.next_run_at
+                    ELSE EXCLUDED.next_run_at
+                END,
+                cron_expr = EXCLUDED.cron_expr,
+                updated_at = now()
+            RETURNING {SCHEDULE_COLUMNS}
+            "#
+        ))
+        .bind(schedule.name)
+        .bind(schedule.queue)
+        .bind(schedule.job_type)
+        .bind(schedule.payload_json)
+        .bind(schedule.cron_expr)
+        .bind(schedule.priority)
+        .bind(schedule.max_attempts)
+        .bind(next_run_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn get_schedule(&self, name: &str) -> anyhow::Result<Option<Schedule>> {
+        let schedule = sqlx::query_as(&format!(
+            "SELECT {SCHEDULE_COLUMNS} FROM schedules WHERE name = $1"
+        ))
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    /// Delete the schedule `name`; jobs it already enqueued are kept.
+    pub async fn delete_schedule(&self, name: &str) -> anyhow::Result<bool> {
+        let res = sqlx::query("DELETE FROM schedules WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    /// Schedules whose next fire time is at or before `now`, earliest first.
+    pub async fn due_schedules(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<Schedule>> {
+        let due = sqlx::query_as(&format!(
+            r#"
+            SELECT {SCHEDULE_COLUMNS}
+            FROM schedules
+            WHERE next_run_at <= $1
+            ORDER BY next_run_at ASC
+            "#
+        ))
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(due)
+    }
+
+    /// Enqueue one `queued` job (with `run_at` = its fire time) for every
+    /// schedule due at `now`, advancing each to its first fire time after
+    /// `now`: a schedule that missed several windows fires once, not once
+    /// per window. Each schedule is handled in its own transaction holding
+    /// its row `FOR UPDATE SKIP LOCKED`, so workers running this concurrently
+    /// never enqueue the same window twice. Windows the enqueue guard denies
+    /// (`with_enqueue_guard`) are skipped. Returns the new job ids.
+    pub async fn materialize_due(
+        &self,
+        jobs: &JobsRepo,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let mut enqueued = Vec::new();
+        for schedule in self.due_schedules(now).await? {
+            if let Some(job_id) = self.fire(jobs, &schedule.name, now).await? {
+                enqueued.push(job_id);
+            }
+        }
+        Ok(enqueued)
+    }
+
+    async fn fire(
+        &self,
+        jobs: &JobsRepo,
+        name: &str,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+
+        // re-checked under the lock: another worker may have fired it already
+        let locked: Option<Schedule> = sqlx::query_as(&format!(
+            r#"
+            SELECT {SCHEDULE_COLUMNS}
+            FROM schedules
+            WHERE name = $1
+              AND next_run_at <= $2
+            FOR UPDATE SKIP LOCKED
+            "#
+        ))
+        .bind(name)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(schedule) = locked else {
+            return Ok(None);
+        };
+        let Some(fire_at) = schedule.next_run_at else {
+            return Ok(None);
+        };
+        let next_run_at = next_fire_after(&schedule.cron_expr, now)?;
+
+        let job = NewJob {
+            queue: schedule.queue,
+            job_type: schedule.job_type,
+            payload_json: schedule.payload_json,
+            run_at: fire_at,
+            priority: schedule.priority,
+            max_attempts: schedule.max_attempts,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
+        };
+        let denied = match &self.enqueue_guard {
+            Some(guard) => match guard.check_new_job(&job, None).await {
+                Ok(()) => false,
+                Err(e) if enqueue_guard::is_denial(&e) => true,
+                Err(e) => return Err(e),
+            },
+            None => false,
+        };
+        let job_id = if denied {
+            None
+        } else {
+            Some(jobs.enqueue_in_tx(&mut tx, job).await?)
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE schedules
+            SET next_run_at = $2,
+                last_run_at = COALESCE($3, last_run_at),
+                last_job_id = COALESCE($4, last_job_id),
+                updated_at = now()
+            WHERE name = $1
+            "#,
+        )
+        .bind(name)
+        .bind(next_run_at)
+        .bind(job_id.map(|_| fire_at))
+        .bind(job_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(job_id)
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct JobTypeSla {
+    pub job_type: String,
+    pub target_latency_ms: i32,
+    // fraction of successful attempts that must finish within target_latency_ms
+    pub latency_percentile: f64,
+    pub target_success_rate: f64,
+}
+
+/// SLA outcome for one job_type over the report window.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct SlaStatus {
+    pub job_type: String,
+    pub target_latency_ms: i32,
+    pub latency_percentile: f64,
+    pub target_success_rate: f64,
+
+    // finished attempts (succeeded + failed) in the window
+    pub attempts: i64,
+    // latency at `latency_percentile` over succeeded attempts
+    pub observed_latency_ms: Option<f64>,
+    pub observed_success_rate: Option<f64>,
+
+    // None when there were no finished attempts in the window
+    pub meets_latency: Option<bool>,
+    pub meets_success_rate: Option<bool>,
+    pub met: Option<bool>,
+}
+
+#[derive(Clone)]
+pub struct SlaRepo {
+    pool: PgPool,
+}
+
+impl SlaRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn upsert(&self, sla: &JobTypeSla) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_type_slas (job_type, target_latency_ms, latency_percentile, target_success_rate)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (job_type) DO UPDATE
+            SET target_latency_ms = EXCLUDED.target_latency_ms,
+                latency_percentile = EXCLUDED.latency_percentile,
+                target_success_rate = EXCLUDED.target_success_rate,
+                updated_at = now()
+            "#,
+        )
+        .bind(&sla.job_type)
+        .bind(sla.target_latency_ms)
+        .bind(sla.latency_percentile)
+        .bind(sla.target_success_rate)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Evaluate every configured SLA against attempts finished in the last `window_secs`.
+    pub async fn report(&self, window_secs: i64) -> anyhow::Result<Vec<SlaStatus>> {
+        let rows = sqlx::query_as::<_, SlaStatus>(
+            r#"
+            WITH finished AS (
+              SELECT j.job_type, a.status, a.latency_ms
+              FROM job_attempts a
+              JOIN jobs j ON j.id = a.job_id AND j.dataset_id = a.dataset_id
+              JOIN job_type_slas s ON s.job_type = j.job_type
+              WHERE a.status IN ('succeeded', 'failed')
+                AND a.finished_at >= now() - ($1::bigint * interval '1 second')
+            ),
+            observed AS (
+              SELECT
+                s.job_type,
+                COUNT(f.status)::bigint AS attempts,
+                percentile_cont(s.latency_percentile) WITHIN GROUP (ORDER BY f.latency_ms)
+                  FILTER (WHERE f.status = 'succeeded' AND f.latency_ms IS NOT NULL)
+                  AS observed_latency_ms,
+                (COUNT(*) FILTER (WHERE f.status = 'succeeded'))::float8
+                  / NULLIF(COUNT(f.status), 0) AS observed_success_rate
+              FROM job_type_slas s
+              LEFT JOIN finished f ON f.job_type = s.job_type
+              GROUP BY s.job_type, s.latency_percentile
+            )
+            SELECT
+              s.job_type,
+              s.target_latency_ms,
+              s.latency_percentile,
+              s.target_success_rate,
+              o.attempts,
+              o.observed_latency_ms,
+              o.observed_success_rate,
+              CASE WHEN o.attempts = 0 THEN NULL
+                   ELSE COALESCE(o.observed_latency_ms <= s.target_latency_ms, true)
+              END AS meets_latency,
+              CASE WHEN o.attempts = 0 THEN NULL
+                   ELSE o.observed_success_rate >= s.target_success_rate
+              END AS meets_success_rate,
+              CASE WHEN o.attempts = 0 THEN NULL
+                   ELSE COALESCE(o.observed_latency_ms <= s.target_latency_ms, true)
+                        AND o.observed_success_rate >= s.target_success_rate
+              END AS met
+            FROM job_type_slas s
+            JOIN observed o ON o.job_type = s.job_type
+            ORDER BY s.job_type
+            "#,
+        )
+        .bind(window_secs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,53 @@
+use sqlx::PgPool;
+
+/// Flag checked by `EnqueueGuard`; when off every enqueue is denied.
+pub const ENQUEUE_ENABLED: &str = "enqueue_enabled";
+
+/// Flag read by the `jobs` trigger filling `job_state_transitions`; when off
+/// status changes are not recorded.
+pub const STATE_TRANSITIONS_ENABLED: &str = "state_transitions_enabled";
+
+/// Per-queue counterpart of `ENQUEUE_ENABLED` (`enqueue_enabled:<queue>`);
+/// turned off by `pgflowctl retire-queue` while the queue drains.
+pub fn queue_enqueue_flag(queue: &str) -> String {
+    format!("{ENQUEUE_ENABLED}:{queue}")
+}
+
+/// Global runtime switches (`system_flags` table). Flags without a row are enabled.
+#[derive(Clone)]
+pub struct SystemFlagsRepo {
+    pool: PgPool,
+}
+
+impl SystemFlagsRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn is_enabled(&self, name: &str) -> anyhow::Result<bool> {
+        let enabled: Option<bool> =
+            sqlx::query_scalar("SELECT enabled FROM system_flags WHERE name = $1")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(enabled.unwrap_or(true))
+    }
+
+    pub async fn set(&self, name: &str, enabled: bool) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO system_flags (name, enabled)
+            VALUES ($1, $2)
+            ON CONFLICT (name)
+            DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = now()
+            "#,
+        )
+        .bind(name)
+        .bind(enabled)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Client-side cap on how fast one worker starts handlers (token bucket),
+/// independent of the DB-side queue policies. Holds up to one second's worth
+/// of tokens, so a worker idle for a while may start that many at once.
+#[derive(Debug)]
+pub struct JobRateLimiter {
+    per_sec: f64,
+    burst: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl JobRateLimiter {
+    /// `per_sec` must be positive; values below 1 still allow one job per
+    /// `1 / per_sec` seconds.
+    pub fn new(per_sec: f64) -> Self {
+        let burst = per_sec.max(1.0);
+        Self {
+            per_sec,
+            burst,
+            tokens: burst,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Most jobs that can start without waiting; the worker leases no more
+    /// than this per batch so leased jobs don't wait out their lease here.
+    pub fn burst(&self) -> usize {
+        self.burst as usize
+    }
+
+    /// Wait until the next job may start and take its token.
+    pub async fn acquire(&mut self) {
+        self.refill();
+        if self.tokens < 1.0 {
+            let wait = (1.0 - self.tokens) / self.per_sec;
+            tokio::time::sleep(Duration::from_secs_f64(wait)).await;
+            self.refill();
+        }
+        self.tokens = (self.tokens - 1.0).max(0.0);
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.burst);
+        self.refilled_at = now;
+    }
+}
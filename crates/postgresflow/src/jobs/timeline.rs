@@ -1,3 +1,5 @@
+use crate::jobs::attempts::{AttemptOrder, JobAttempt};
+use crate::jobs::error_codes;
 use crate::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
@@ -18,8 +20,30 @@ pub struct JobTimeline {
     // keep existing attempts list (backwards compatible)
     pub attempts: Vec<TimelineAttempt>,
 
+    // source job's attempts, for replays created with include_history (read-only)
+    pub replayed_from: Option<ReplayedHistory>,
+
     // ✅ new: unified ordered narrative (attempts + policy decisions)
     pub story: Vec<TimelineEvent>,
+    // events in the full story; more than story.len() when story_truncated
+    pub story_total: usize,
+    pub story_truncated: bool,
+}
+
+/// How much of the story `build_timeline` returns. The default is the full
+/// history (audits); `limit`/`since` keep long-lived jobs' timelines small.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimelineOptions {
+    /// Keep only the most recent N story events.
+    pub limit: Option<usize>,
+    /// Drop story events before this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl TimelineOptions {
+    pub fn full() -> Self {
+        Self::default()
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -31,15 +55,23 @@ pub struct TimelineAttempt {
     pub finished_at: Option<DateTime<Utc>>,
     pub error_code: Option<String>,
     pub error_message: Option<String>,
+    pub error_details_json: Option<serde_json::Value>,
     pub latency_ms: Option<i32>,
     pub worker_id: String,
     pub suggested_action: Option<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ReplayedHistory {
+    pub job_id: Uuid,
+    pub attempts: Vec<TimelineAttempt>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct LastError {
     pub error_code: Option<String>,
     pub error_message: Option<String>,
+    pub error_details_json: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,6 +85,7 @@ pub enum TimelineEvent {
         worker_id: String,
         error_code: Option<String>,
         error_message: Option<String>,
+        error_details_json: Option<serde_json::Value>,
         suggested_action: Option<String>,
         latency_ms: Option<i32>,
     },
@@ -62,21 +95,34 @@ pub enum TimelineEvent {
         decision: String,
         reason_code: String,
         details_json: serde_json::Value,
+        count: i32,
+        last_seen_at: DateTime<Utc>,
     },
 }
 
+impl TimelineEvent {
+    fn at(&self) -> DateTime<Utc> {
+        match self {
+            TimelineEvent::Attempt { at, .. } | TimelineEvent::PolicyDecision { at, .. } => *at,
+        }
+    }
+}
+
 pub async fn build_timeline(
     jobs: &JobsRepo,
     attempts: &AttemptsRepo,
     policy_decisions: &PolicyDecisionsRepo,
     job_id: Uuid,
+    opts: TimelineOptions,
 ) -> anyhow::Result<Option<JobTimeline>> {
-    let job = match jobs.get_job(job_id).await? {
+    let job = match jobs.get_job_header(job_id).await? {
         Some(j) => j,
         None => return Ok(None),
     };
 
-    let raw_attempts = attempts.list_attempts_for_job(job_id).await?;
+    let raw_attempts = attempts
+        .list_attempts_for_job(job_id, AttemptOrder::Asc)
+        .await?;
     let policy_rows = policy_decisions.list_for_job(job_id).await?;
 
     let last_worker_id = raw_attempts.last().map(|a| a.worker_id.clone());
@@ -85,6 +131,7 @@ pub async fn build_timeline(
     let last_error = last_failed.map(|a| LastError {
         error_code: a.error_code.clone(),
         error_message: a.error_message.clone(),
+        error_details_json: a.error_details_json.clone(),
     });
 
     let next_run_at = if job.status == "queued" {
@@ -93,33 +140,25 @@ pub async fn build_timeline(
         None
     };
 
-    // suggested action mapping
-    use crate::jobs::error_codes;
-
     let attempts_out: Vec<TimelineAttempt> = raw_attempts
         .iter()
         .cloned()
-        .map(|a| {
-            let suggested = a
-                .error_code
-                .as_deref()
-                .map(|code| error_codes::suggested_action(code).to_string());
-
-            TimelineAttempt {
-                id: a.id,
-                attempt_no: a.attempt_no,
-                status: a.status,
-                started_at: a.started_at,
-                finished_at: a.finished_at,
-                error_code: a.error_code,
-                error_message: a.error_message,
-                latency_ms: a.latency_ms,
-                worker_id: a.worker_id,
-                suggested_action: suggested,
-            }
-        })
+        .map(to_timeline_attempt)
         .collect();
 
+    let replayed_from = match job.replay_of_job_id {
+        Some(src_id) if job.replay_include_history => Some(ReplayedHistory {
+            job_id: src_id,
+            attempts: attempts
+                .list_attempts_for_job(src_id, AttemptOrder::Asc)
+                .await?
+                .into_iter()
+                .map(to_timeline_attempt)
+                .collect(),
+        }),
+        _ => None,
+    };
+
     // ✅ build unified story
     let mut story: Vec<TimelineEvent> = Vec::new();
 
@@ -132,6 +171,7 @@ pub async fn build_timeline(
             worker_id: a.worker_id.clone(),
             error_code: a.error_code.clone(),
             error_message: a.error_message.clone(),
+            error_details_json: a.error_details_json.clone(),
             suggested_action: a.suggested_action.clone(),
             latency_ms: a.latency_ms,
         });
@@ -144,6 +184,8 @@ pub async fn build_timeline(
             decision: p.decision,
             reason_code: p.reason_code,
             details_json: p.details_json,
+            count: p.count,
+            last_seen_at: p.last_seen_at,
         });
     }
 
@@ -160,6 +202,17 @@ pub async fn build_timeline(
         ta.cmp(&tb).then(ka.cmp(&kb))
     });
 
+    // attempts above stay complete; only the story is cut
+    let story_total = story.len();
+    if let Some(since) = opts.since {
+        story.retain(|e| e.at() >= since);
+    }
+    if let Some(limit) = opts.limit {
+        let excess = story.len().saturating_sub(limit);
+        story.drain(..excess);
+    }
+    let story_truncated = story.len() < story_total;
+
     Ok(Some(JobTimeline {
         job_id: job.id,
         status: job.status,
@@ -170,6 +223,30 @@ pub async fn build_timeline(
         last_worker_id,
         last_error,
         attempts: attempts_out,
+        replayed_from,
         story,
+        story_total,
+        story_truncated,
     }))
 }
+
+fn to_timeline_attempt(a: JobAttempt) -> TimelineAttempt {
+    let suggested = a
+        .error_code
+        .as_deref()
+        .map(|code| error_codes::suggested_action(code).to_string());
+
+    TimelineAttempt {
+        id: a.id,
+        attempt_no: a.attempt_no,
+        status: a.status,
+        started_at: a.started_at,
+        finished_at: a.finished_at,
+        error_code: a.error_code,
+        error_message: a.error_message,
+        error_details_json: a.error_details_json,
+        latency_ms: a.latency_ms,
+        worker_id: a.worker_id,
+        suggested_action: suggested,
+    }
+}
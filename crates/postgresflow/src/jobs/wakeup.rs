@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Coalesces worker wakeups so a burst of enqueue notifications triggers a
+/// single lease instead of one lease per notification.
+///
+/// `wake()` is cheap and may be called from anywhere (API handlers, listeners);
+/// repeated calls before the worker waits again collapse into one pending wakeup.
+#[derive(Clone, Debug)]
+pub struct WakeupCoalescer {
+    notify: Arc<Notify>,
+    window: Duration,
+    // a wakeup is pending (or its window still running); `wake()`s meanwhile
+    // are folded into it instead of notifying again
+    pending: Arc<Mutex<bool>>,
+    coalesced: Arc<AtomicU64>,
+}
+
+impl WakeupCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            notify: Arc::new(Notify::new()),
+            window,
+            pending: Arc::new(Mutex::new(false)),
+            coalesced: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn wake(&self) {
+        let mut pending = self.pending.lock().unwrap();
+        if *pending {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        } else {
+            *pending = true;
+            self.notify.notify_one();
+        }
+    }
+
+    /// Wakeups so far that didn't get a lease cycle of their own because an
+    /// earlier one in the same burst already triggered it. Only ever grows.
+    pub fn coalesced_total(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Wait up to `idle` for a wakeup. Returns `true` if woken, `false` on timeout.
+    ///
+    /// After a wakeup, holds for the coalescing window and folds any wakeup
+    /// that arrives meanwhile into this one, so the caller leases once for the
+    /// whole burst.
+    pub async fn wait(&self, idle: Duration) -> bool {
+        if tokio::time::timeout(idle, self.notify.notified())
+            .await
+            .is_err()
+        {
+            return false;
+        }
+
+        if !self.window.is_zero() {
+            tokio::time::sleep(self.window).await;
+        }
+
+        *self.pending.lock().unwrap() = false;
+        true
+    }
+}
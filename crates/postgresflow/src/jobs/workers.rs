@@ -0,0 +1,123 @@
+use sqlx::PgPool;
+
+/// Worker registry backed by the `workers` heartbeat table.
+#[derive(Clone)]
+pub struct WorkersRepo {
+    pool: PgPool,
+}
+
+impl WorkersRepo {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Register the worker at startup, clearing a drain request and
+    /// `draining` status left on its row by a previous run under the same id.
+    pub async fn register(&self, worker_id: &str, queue: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO workers (worker_id, queue)
+            VALUES ($1, $2)
+            ON CONFLICT (worker_id) DO UPDATE
+            SET queue = EXCLUDED.queue,
+                drain_requested = false,
+                status = 'running',
+                last_heartbeat_at = now()
+            "#,
+        )
+        .bind(worker_id)
+        .bind(queue)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Register the worker on first call, then bump `last_heartbeat_at`.
+    /// Returns whether `request_drain` was called for it, so the worker
+    /// learns about a drain without a query of its own.
+    pub async fn heartbeat(&self, worker_id: &str, queue: &str) -> anyhow::Result<bool> {
+        let drain_requested: bool = sqlx::query_scalar(
+            r#"
+            INSERT INTO workers (worker_id, queue)
+            VALUES ($1, $2)
+            ON CONFLICT (worker_id) DO UPDATE
+            SET queue = EXCLUDED.queue,
+                last_heartbeat_at = now()
+            RETURNING drain_requested
+            "#,
+        )
+        .bind(worker_id)
+        .bind(queue)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(drain_requested)
+    }
+
+    /// Workers whose last heartbeat is older than `stale_after_secs`.
+    pub async fn stale_workers(&self, stale_after_secs: i64) -> anyhow::Result<Vec<String>> {
+        let ids = sqlx::query_scalar(
+            r#"
+            SELECT worker_id
+            FROM workers
+            WHERE last_heartbeat_at < now() - ($1::bigint * interval '1 second')
+            ORDER BY last_heartbeat_at ASC
+            "#,
+        )
+        .bind(stale_after_secs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Ask a worker to drain: stop leasing, finish its in-flight batch and
+    /// exit. The worker sees it on its next heartbeat. False if no worker
+    /// with that id is registered.
+    pub async fn request_drain(&self, worker_id: &str) -> anyhow::Result<bool> {
+        let res = sqlx::query("UPDATE workers SET drain_requested = true WHERE worker_id = $1")
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(res.rows_affected() > 0)
+    }
+
+    pub async fn is_registered(&self, worker_id: &str) -> anyhow::Result<bool> {
+        let registered: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM workers WHERE worker_id = $1)")
+                .bind(worker_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(registered)
+    }
+
+    /// Report the worker as `draining` (it has stopped leasing).
+    pub async fn mark_draining(&self, worker_id: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE workers
+            SET status = 'draining',
+                last_heartbeat_at = now()
+            WHERE worker_id = $1
+            "#,
+        )
+        .bind(worker_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Deregister on clean shutdown.
+    pub async fn remove(&self, worker_id: &str) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM workers WHERE worker_id = $1")
+            .bind(worker_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
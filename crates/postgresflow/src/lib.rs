@@ -3,3 +3,6 @@ pub mod api;
 pub mod config; // if you moved it here
 pub mod db; // if you moved it here
 pub mod jobs; // if you keep API here
+pub mod shutdown;
+#[cfg(feature = "testing")]
+pub mod testing;
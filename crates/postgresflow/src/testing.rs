@@ -0,0 +1,62 @@
+//! Helpers for integration tests against a real Postgres, for this crate's
+//! tests and for downstream crates embedding postgresflow.
+
+use sqlx::{postgres::PgPoolOptions, PgPool};
+
+/// Env var naming the throwaway database tests may migrate and wipe.
+pub const TEST_DATABASE_URL_ENV: &str = "TEST_DATABASE_URL";
+
+/// Every table holding postgresflow state (not `_sqlx_migrations`/`_pgflow_meta`).
+pub const CORE_TABLES: &[&str] = &[
+    "policy_decisions",
+    "job_attempts",
+    "attempt_logs",
+    "queue_policies",
+    "jobs_archive",
+    "workers",
+    "job_type_slas",
+    "job_types",
+    "system_flags",
+    "lock_reap_counters",
+    "job_state_transitions",
+    "ingest_decisions",
+    "enqueue_rate_counters",
+    "enqueue_dedupe_counters",
+    "payload_warning_counters",
+    "job_idempotency_keys",
+    "schedules",
+    "jobs",
+];
+
+/// Connect to `TEST_DATABASE_URL`, or `Ok(None)` when it is unset so the
+/// caller decides whether that fails the test or skips it.
+pub async fn connect_from_env() -> anyhow::Result<Option<PgPool>> {
+    let Some(url) = std::env::var(TEST_DATABASE_URL_ENV)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .connect(&url)
+        .await?;
+    Ok(Some(pool))
+}
+
+/// Apply pending migrations, then empty every `CORE_TABLES` table, so each
+/// test starts from a fresh schema without recreating the database.
+///
+/// Truncates: only point this at a test database.
+pub async fn migrate_and_reset(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::migrate!("./migrations").run(pool).await?;
+
+    sqlx::query(&format!(
+        "TRUNCATE TABLE {} RESTART IDENTITY CASCADE",
+        CORE_TABLES.join(", ")
+    ))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
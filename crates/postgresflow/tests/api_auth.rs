@@ -0,0 +1,89 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use common::{api_state, setup_db};
+use postgresflow::api::router;
+use serde_json::Value;
+use serial_test::serial;
+use tower::ServiceExt;
+
+async fn get(app: axum::Router, uri: &str, auth: Option<&str>) -> (StatusCode, Value) {
+    let mut req = Request::builder().uri(uri);
+    if let Some(auth) = auth {
+        req = req.header(header::AUTHORIZATION, auth);
+    }
+    let resp = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+#[serial]
+async fn api_token_is_required_on_admin_routes_but_not_health() {
+    let pool = setup_db().await;
+    let mut state = api_state(&pool);
+    state.api_token = Some("s3cret".to_string());
+    let app = router(state);
+
+    let (status, body) = get(app.clone(), "/metrics", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "missing api token");
+
+    let (status, body) = get(app.clone(), "/metrics", Some("Bearer wrong")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "invalid api token");
+
+    let (status, body) = get(app.clone(), "/metrics", Some("Bearer s3cret")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["queues"].is_array());
+
+    let (status, _) = get(app.clone(), "/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial]
+async fn no_api_token_leaves_admin_routes_open() {
+    let pool = setup_db().await;
+    let app = router(api_state(&pool));
+
+    let (status, _) = get(app, "/metrics", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial]
+async fn read_only_api_serves_reads_and_rejects_writes() {
+    let pool = setup_db().await;
+    let mut state = api_state(&pool);
+    state.read_only = true;
+    let app = router(state);
+
+    let (status, _) = get(app.clone(), "/metrics", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let post = |uri: &str, body: &str| {
+        Request::post(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    let resp = app
+        .clone()
+        .oneshot(post("/jobs", r#"{"job_type":"email_send"}"#))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value =
+        serde_json::from_slice(&to_bytes(resp.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["error"], "READ_ONLY");
+
+    // the batch lookup is a read despite being a POST
+    let resp = app
+        .oneshot(post("/jobs/get", r#"{"ids":[]}"#))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::router;
+use postgresflow::api::versioning::{strip_newer_fields, LATEST_API_VERSION};
+use serial_test::serial;
+use tower::ServiceExt;
+
+async fn get(
+    app: axum::Router,
+    uri: &str,
+    accept_version: Option<&str>,
+) -> (StatusCode, Option<String>, serde_json::Value) {
+    let mut req = Request::builder().uri(uri);
+    if let Some(v) = accept_version {
+        req = req.header("Accept-Version", v);
+    }
+    let resp = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+
+    let status = resp.status();
+    let version = resp
+        .headers()
+        .get("x-pgflow-api-version")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let json = serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null);
+    (status, version, json)
+}
+
+#[tokio::test]
+#[serial]
+async fn v1_clients_do_not_see_fields_added_in_v2() {
+    let pool = setup_db().await;
+    let job_id = insert_job(&pool, "q_versions").await;
+    let app = router(api_state(&pool));
+
+    // no header: latest shape
+    let (status, version, latest) = get(app.clone(), "/jobs?queue=q_versions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(version, Some(LATEST_API_VERSION.to_string()));
+    let item = &latest["items"][0];
+    assert_eq!(item["id"], job_id.to_string());
+    assert!(item.as_object().unwrap().contains_key("dlq_error_code"));
+
+    let (status, version, v1) = get(app.clone(), "/jobs?queue=q_versions", Some("1")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(version.as_deref(), Some("1"));
+    let item = v1["items"][0].as_object().unwrap();
+    assert_eq!(item["id"], job_id.to_string());
+    assert!(!item.contains_key("dlq_error_code"));
+    assert!(item.contains_key("dlq_reason_code"));
+
+    let (_, version, detail) = get(app.clone(), &format!("/jobs/{job_id}"), Some("v1")).await;
+    assert_eq!(version.as_deref(), Some("1"));
+    assert!(!detail.as_object().unwrap().contains_key("result_json"));
+    let (_, _, detail) = get(app.clone(), &format!("/jobs/{job_id}"), Some("2")).await;
+    assert!(detail.as_object().unwrap().contains_key("result_json"));
+    assert!(!detail.as_object().unwrap().contains_key("group_id"));
+    let (_, _, detail) = get(app.clone(), &format!("/jobs/{job_id}"), Some("3")).await;
+    assert!(detail.as_object().unwrap().contains_key("group_id"));
+
+    let (status, _, _) = get(app, "/jobs", Some("99")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn stripping_leaves_nested_payload_alone() {
+    let mut body = serde_json::json!({
+        "result_json": {"url": "x"},
+        "payload_json": {"dlq_error_code": "user data"},
+        "items": [{"id": 1, "dlq_error_code": null}]
+    });
+    strip_newer_fields(&mut body, 1, "/jobs");
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "payload_json": {"dlq_error_code": "user data"},
+            "items": [{"id": 1}]
+        })
+    );
+}
+
+#[test]
+fn route_scoped_fields_are_only_stripped_on_their_route() {
+    let mut metrics = serde_json::json!([{ "queue": "q", "window_secs": 60 }]);
+    strip_newer_fields(&mut metrics, 2, "/metrics");
+    assert_eq!(metrics, serde_json::json!([{ "queue": "q" }]));
+
+    let mut summary = serde_json::json!({ "window_secs": 3600, "total": 0 });
+    strip_newer_fields(&mut summary, 2, "/ingest/summary");
+    assert_eq!(
+        summary,
+        serde_json::json!({ "window_secs": 3600, "total": 0 })
+    );
+}
@@ -0,0 +1,24 @@
+use postgresflow::db::make_pool;
+
+#[tokio::test]
+async fn pool_connections_report_application_name() {
+    let _ = dotenvy::dotenv();
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL missing");
+
+    let pool = make_pool(&url, "pgflow-worker-test-7").await.unwrap();
+
+    let name: String = sqlx::query_scalar("SELECT current_setting('application_name')")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(name, "pgflow-worker-test-7");
+
+    let in_activity: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM pg_stat_activity WHERE pid = pg_backend_pid() AND application_name = $1)",
+    )
+    .bind("pgflow-worker-test-7")
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(in_activity);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::attempts::ATTEMPT_LOG_MAX_MESSAGE_BYTES;
+use postgresflow::jobs::{AttemptsRepo, LogLevel};
+use serial_test::serial;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn get_logs(pool: &sqlx::PgPool, uri: String) -> (StatusCode, serde_json::Value) {
+    let resp = router(api_state(pool))
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn attempt_logs_are_served_per_attempt_and_go_away_with_their_job() {
+    let pool = setup_db().await;
+    let attempts = AttemptsRepo::new(pool.clone());
+    let job_id = insert_job(&pool, "q_logs").await;
+    let dataset_id: String = sqlx::query_scalar("SELECT dataset_id FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+    attempts
+        .append_log(&dataset_id, job_id, 1, LogLevel::Info, "first")
+        .await
+        .unwrap();
+    attempts
+        .append_log(
+            &dataset_id,
+            job_id,
+            1,
+            LogLevel::Error,
+            &"é".repeat(ATTEMPT_LOG_MAX_MESSAGE_BYTES),
+        )
+        .await
+        .unwrap();
+    attempts
+        .append_log(&dataset_id, job_id, 2, LogLevel::Debug, "second attempt")
+        .await
+        .unwrap();
+
+    let (status, body) = get_logs(&pool, format!("/jobs/{job_id}/logs?attempt=1")).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines = body["lines"].as_array().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["level"], "info");
+    assert_eq!(lines[0]["message"], "first");
+    assert_eq!(lines[1]["level"], "error");
+    let long = lines[1]["message"].as_str().unwrap();
+    assert!(long.len() <= ATTEMPT_LOG_MAX_MESSAGE_BYTES);
+    assert!(long.chars().all(|c| c == 'é'));
+
+    let (_, body) = get_logs(&pool, format!("/jobs/{job_id}/logs")).await;
+    let attempt_nos: Vec<i64> = body["lines"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line["attempt_no"].as_i64().unwrap())
+        .collect();
+    assert_eq!(attempt_nos, vec![1, 1, 2]);
+
+    let (status, _) = get_logs(&pool, format!("/jobs/{}/logs", Uuid::new_v4())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // lines can't outlive (or exist without) their job
+    assert!(attempts
+        .append_log(&dataset_id, Uuid::new_v4(), 1, LogLevel::Info, "orphan")
+        .await
+        .is_err());
+    sqlx::query("DELETE FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert!(attempts.list_logs(job_id, None).await.unwrap().is_empty());
+}
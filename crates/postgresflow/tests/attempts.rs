@@ -2,6 +2,7 @@ mod common;
 
 use common::{insert_job, setup_db};
 
+use postgresflow::jobs::attempts::AttemptOrder;
 use postgresflow::jobs::{AttemptsRepo, JobsRepo};
 use serial_test::serial;
 
@@ -41,7 +42,10 @@ async fn leasing_creates_and_finishes_attempt_row() {
         .unwrap();
 
     // Confirm attempt history
-    let attempts = attempts_repo.list_attempts_for_job(job.id).await.unwrap();
+    let attempts = attempts_repo
+        .list_attempts_for_job(job.id, AttemptOrder::Asc)
+        .await
+        .unwrap();
     assert_eq!(attempts.len(), 1);
     assert_eq!(attempts[0].attempt_no, 1);
     assert_eq!(attempts[0].status, "succeeded");
@@ -69,7 +73,10 @@ async fn multiple_attempts_increment_attempt_no() {
     assert_eq!(a1.attempt_no, 1);
     assert_eq!(a2.attempt_no, 2);
 
-    let attempts = attempts_repo.list_attempts_for_job(job_id).await.unwrap();
+    let attempts = attempts_repo
+        .list_attempts_for_job(job_id, AttemptOrder::Asc)
+        .await
+        .unwrap();
     assert_eq!(attempts.len(), 2);
     assert_eq!(attempts[0].attempt_no, 1);
     assert_eq!(attempts[1].attempt_no, 2);
@@ -93,7 +100,10 @@ async fn finish_failed_sets_error_fields() {
         .await
         .unwrap();
 
-    let attempts = attempts_repo.list_attempts_for_job(job_id).await.unwrap();
+    let attempts = attempts_repo
+        .list_attempts_for_job(job_id, AttemptOrder::Asc)
+        .await
+        .unwrap();
     assert_eq!(attempts.len(), 1);
     assert_eq!(attempts[0].status, "failed");
     assert_eq!(attempts[0].latency_ms, Some(77));
@@ -103,3 +113,197 @@ async fn finish_failed_sets_error_fields() {
         Some("request timed out")
     );
 }
+
+#[tokio::test]
+#[serial]
+async fn attempts_past_overflow_margin_force_job_to_dlq() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    // insert_job uses max_attempts = 5, so at most 7 attempts may start
+    let attempts = AttemptsRepo::new(pool.clone()).with_attempt_overflow_margin(2);
+
+    // a poison job that keeps getting reaped never reaches the runner's DLQ path
+    let job_id = insert_job(&pool, "q_overflow").await;
+    jobs.lease_one_job("q_overflow", "worker-1", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    for expected in 1..=7 {
+        let a = attempts.start_attempt(job_id, "worker-1").await.unwrap();
+        assert_eq!(a.attempt_no, expected);
+    }
+
+    let err = attempts
+        .start_attempt(job_id, "worker-1")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("ATTEMPT_OVERFLOW"), "{err}");
+
+    let job = jobs.get_job(job_id).await.unwrap().unwrap();
+    assert_eq!(job.status, "dlq");
+    assert_eq!(job.dlq_reason_code.as_deref(), Some("ATTEMPT_OVERFLOW"));
+    let details: serde_json::Value = sqlx::query_scalar(
+        "SELECT details_json FROM policy_decisions WHERE job_id = $1 AND decision = 'ATTEMPT_GUARD'",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(details["attempt_no"], 8);
+    assert_eq!(details["max_attempts"], 5);
+    assert_eq!(
+        attempts
+            .list_attempts_for_job(job_id, AttemptOrder::Asc)
+            .await
+            .unwrap()
+            .len(),
+        7
+    );
+
+    // batch path: the overflowing job is left out and DLQ'd, the other starts
+    let poison = insert_job(&pool, "q_overflow").await;
+    let healthy = insert_job(&pool, "q_overflow").await;
+    jobs.lease_jobs_batch("q_overflow", "worker-1", 30, 2)
+        .await
+        .unwrap();
+    for _ in 0..7 {
+        attempts.start_attempt(poison, "worker-1").await.unwrap();
+    }
+    let poison_job = jobs.get_job(poison).await.unwrap().unwrap();
+    let healthy_job = jobs.get_job(healthy).await.unwrap().unwrap();
+
+    let started = attempts
+        .start_attempts_batch(
+            &[poison_job.dataset_id, healthy_job.dataset_id],
+            &[poison, healthy],
+            "worker-1",
+        )
+        .await
+        .unwrap();
+    assert_eq!(started.started.len(), 1);
+    assert_eq!(started.started[0].0, healthy);
+    assert_eq!(started.started[0].2, 1);
+    assert_eq!(started.overflowed, vec![poison]);
+
+    let poison_job = jobs.get_job(poison).await.unwrap().unwrap();
+    assert_eq!(poison_job.status, "dlq");
+    assert_eq!(
+        poison_job.dlq_reason_code.as_deref(),
+        Some("ATTEMPT_OVERFLOW")
+    );
+    assert_eq!(
+        attempts
+            .list_attempts_for_job(poison, AttemptOrder::Asc)
+            .await
+            .unwrap()
+            .len(),
+        7
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn batch_start_locks_jobs_before_writing_attempts() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+
+    let job_id = insert_job(&pool, "q_lock_order").await;
+    let job = jobs
+        .lease_one_job("q_lock_order", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease");
+
+    // another transaction is updating the job row (e.g. a reaper); the
+    // attempt insert's foreign key check alone wouldn't wait on this
+    let mut holder = pool.begin().await.unwrap();
+    sqlx::query("SELECT 1 FROM jobs WHERE id = $1 FOR NO KEY UPDATE")
+        .bind(job_id)
+        .execute(&mut *holder)
+        .await
+        .unwrap();
+
+    let start = tokio::spawn(async move {
+        attempts
+            .start_attempts_batch(&[job.dataset_id], &[job_id], "worker-1")
+            .await
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert!(!start.is_finished(), "attempt written without the job lock");
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM job_attempts WHERE job_id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    holder.commit().await.unwrap();
+    let started = start.await.unwrap().unwrap();
+    assert_eq!(started.started.len(), 1);
+    assert_eq!(started.started[0].0, job_id);
+}
+
+#[tokio::test]
+#[serial]
+async fn attempts_can_be_listed_oldest_or_newest_first() {
+    use axum::extract::{Path, Query, State};
+    use axum::http::StatusCode;
+    use axum::Json;
+    use common::api_state;
+    use postgresflow::api::{list_job_attempts, AttemptsQuery};
+
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_order").await;
+    for _ in 0..3 {
+        state
+            .attempts
+            .start_attempt(job_id, "worker-1")
+            .await
+            .unwrap();
+    }
+
+    let nos = |rows: &[postgresflow::jobs::attempts::JobAttempt]| {
+        rows.iter().map(|a| a.attempt_no).collect::<Vec<_>>()
+    };
+
+    let asc = state
+        .attempts
+        .list_attempts_for_job(job_id, AttemptOrder::Asc)
+        .await
+        .unwrap();
+    assert_eq!(nos(&asc), vec![1, 2, 3]);
+    let desc = state
+        .attempts
+        .list_attempts_for_job(job_id, AttemptOrder::Desc)
+        .await
+        .unwrap();
+    assert_eq!(nos(&desc), vec![3, 2, 1]);
+
+    // endpoint defaults to oldest first; ?order=desc flips it
+    let Json(resp) = list_job_attempts(
+        State(state.clone()),
+        Path(job_id),
+        Query(AttemptsQuery { order: None }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(nos(&resp.attempts), vec![1, 2, 3]);
+
+    let q: AttemptsQuery = serde_json::from_value(serde_json::json!({ "order": "desc" })).unwrap();
+    let Json(resp) = list_job_attempts(State(state.clone()), Path(job_id), Query(q))
+        .await
+        .unwrap();
+    assert_eq!(nos(&resp.attempts), vec![3, 2, 1]);
+
+    let err = list_job_attempts(
+        State(state),
+        Path(uuid::Uuid::new_v4()),
+        Query(AttemptsQuery { order: None }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.0, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::setup_db;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo};
+use serial_test::serial;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn large_batch_is_processed_in_chunks() {
+    let pool = setup_db().await;
+
+    // 7 doesn't divide 250, so the last chunk is partial
+    let jobs = JobsRepo::new(pool.clone()).with_batch_chunk_size(7);
+    let attempts = AttemptsRepo::new(pool.clone()).with_batch_chunk_size(7);
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    sqlx::query(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts)
+        SELECT 'q_chunk', 'bulk', jsonb_build_object('i', i), now(), 'queued', 0, 5
+        FROM generate_series(1, 250) AS i
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let batch = jobs
+        .lease_jobs_batch("q_chunk", "worker-1", 30, 250)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 250);
+
+    let dataset_ids: Vec<String> = batch.iter().map(|j| j.dataset_id.clone()).collect();
+    let job_ids: Vec<Uuid> = batch.iter().map(|j| j.id).collect();
+    let started = attempts
+        .start_attempts_batch(&dataset_ids, &job_ids, "worker-1")
+        .await
+        .unwrap()
+        .started;
+    assert_eq!(started.len(), 250);
+    let started_jobs: HashSet<Uuid> = started.iter().map(|(job_id, _, _)| *job_id).collect();
+    assert_eq!(started_jobs, job_ids.iter().copied().collect());
+    assert!(started.iter().all(|(_, _, attempt_no)| *attempt_no == 1));
+
+    let updates: Vec<(Uuid, Uuid, i32)> = started
+        .iter()
+        .map(|(job_id, attempt_id, _)| (*job_id, *attempt_id, 5))
+        .collect();
+    runner
+        .on_success_batch(&batch[0].dataset_id, &updates, "worker-1")
+        .await
+        .unwrap();
+
+    let (succeeded_jobs, succeeded_attempts): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+          (SELECT COUNT(*) FROM jobs WHERE queue = 'q_chunk' AND status = 'succeeded'),
+          (SELECT COUNT(*) FROM job_attempts WHERE status = 'succeeded' AND latency_ms = 5)
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(succeeded_jobs, 250);
+    assert_eq!(succeeded_attempts, 250);
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo};
+use serde_json::json;
+use serial_test::serial;
+use sqlx::PgPool;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Lease a job, start its attempt, then cancel it externally while "running".
+async fn lease_then_cancel(
+    pool: &PgPool,
+    jobs: &JobsRepo,
+    attempts: &AttemptsRepo,
+) -> (Uuid, Uuid) {
+    let job_id = jobs
+        .enqueue_now("default", "slow_job", json!({}))
+        .await
+        .unwrap();
+    let job = jobs
+        .lease_one_job("default", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    assert_eq!(job.id, job_id);
+
+    let attempt = attempts.start_attempt(job_id, "worker-1").await.unwrap();
+
+    sqlx::query("UPDATE jobs SET status = 'canceled' WHERE id = $1")
+        .bind(job_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    (job_id, attempt.id)
+}
+
+async fn job_and_attempt_status(pool: &PgPool, job_id: Uuid, attempt_id: Uuid) -> (String, String) {
+    let job_status: String = sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    let attempt_status: String =
+        sqlx::query_scalar("SELECT status FROM job_attempts WHERE id = $1")
+            .bind(attempt_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    (job_status, attempt_status)
+}
+
+#[tokio::test]
+#[serial]
+async fn success_after_cancel_keeps_job_canceled() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let (job_id, attempt_id) = lease_then_cancel(&pool, &jobs, &attempts).await;
+    runner
+        .on_success(job_id, attempt_id, "worker-1", 10)
+        .await
+        .unwrap();
+
+    let (job_status, attempt_status) = job_and_attempt_status(&pool, job_id, attempt_id).await;
+    assert_eq!(job_status, "canceled");
+    assert_eq!(attempt_status, "succeeded");
+
+    // batch path honors the same guard
+    let (job_id, attempt_id) = lease_then_cancel(&pool, &jobs, &attempts).await;
+    let dataset_id: String = sqlx::query_scalar("SELECT dataset_id FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    runner
+        .on_success_batch(&dataset_id, &[(job_id, attempt_id, 10)], "worker-1")
+        .await
+        .unwrap();
+
+    let (job_status, attempt_status) = job_and_attempt_status(&pool, job_id, attempt_id).await;
+    assert_eq!(job_status, "canceled");
+    assert_eq!(attempt_status, "succeeded");
+}
+
+#[tokio::test]
+#[serial]
+async fn success_can_be_configured_to_override_cancel() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone()).with_success_overrides_cancel(true);
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let (job_id, attempt_id) = lease_then_cancel(&pool, &jobs, &attempts).await;
+    runner
+        .on_success(job_id, attempt_id, "worker-1", 10)
+        .await
+        .unwrap();
+
+    let (job_status, _) = job_and_attempt_status(&pool, job_id, attempt_id).await;
+    assert_eq!(job_status, "succeeded");
+}
+
+async fn job_status(pool: &PgPool, job_id: Uuid) -> String {
+    sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn cancel_job_only_cancels_queued_jobs() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let queued = jobs
+        .enqueue_now("q_cancel", "never_runs", json!({}))
+        .await
+        .unwrap();
+    assert!(jobs.cancel_job(queued).await.unwrap());
+    assert_eq!(job_status(&pool, queued).await, "canceled");
+    assert!(jobs
+        .lease_one_job("q_cancel", "worker-1", 30)
+        .await
+        .unwrap()
+        .is_none());
+    // already canceled
+    assert!(!jobs.cancel_job(queued).await.unwrap());
+
+    let running = jobs
+        .enqueue_now("q_cancel", "slow_job", json!({}))
+        .await
+        .unwrap();
+    jobs.lease_one_job("q_cancel", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    assert!(!jobs.cancel_job(running).await.unwrap());
+    assert_eq!(job_status(&pool, running).await, "running");
+
+    jobs.mark_succeeded(running, "worker-1").await.unwrap();
+    assert!(!jobs.cancel_job(running).await.unwrap());
+    assert_eq!(job_status(&pool, running).await, "succeeded");
+
+    assert!(!jobs.cancel_job(Uuid::new_v4()).await.unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn cancel_endpoint_reports_whether_the_job_was_canceled() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let job_id = jobs
+        .enqueue_now("q_cancel", "never_runs", json!({}))
+        .await
+        .unwrap();
+
+    let cancel = |id: Uuid| {
+        let app = router(api_state(&pool));
+        async move {
+            let resp = app
+                .oneshot(
+                    Request::post(format!("/jobs/{id}/cancel"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    assert_eq!(cancel(job_id).await, json!({ "canceled": true }));
+    assert_eq!(cancel(job_id).await, json!({ "canceled": false }));
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use std::io::Read;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use chrono::Utc;
+use common::{api_state, setup_db};
+use flate2::read::GzDecoder;
+use postgresflow::api::router;
+use postgresflow::jobs::NewJob;
+use serial_test::serial;
+use tower::ServiceExt;
+
+async fn get_jobs(app: axum::Router, accept_encoding: Option<&str>) -> (Option<String>, Vec<u8>) {
+    let mut req = Request::builder().uri("/jobs?limit=200");
+    if let Some(enc) = accept_encoding {
+        req = req.header(header::ACCEPT_ENCODING, enc);
+    }
+
+    let resp = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let encoding = resp
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (encoding, body.to_vec())
+}
+
+#[tokio::test]
+#[serial]
+async fn large_responses_are_gzipped_only_when_requested() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    for i in 0..100 {
+        state
+            .jobs
+            .enqueue(NewJob {
+                queue: "q_gzip".to_string(),
+                job_type: "work".to_string(),
+                payload_json: serde_json::json!({ "i": i, "pad": "x".repeat(64) }),
+                run_at: Utc::now(),
+                priority: 0,
+                max_attempts: 3,
+                target_worker_id: None,
+                retry: None,
+                dedupe_key: None,
+                idempotency_key: None,
+                group_id: None,
+            })
+            .await
+            .unwrap();
+    }
+
+    let app = router(state);
+
+    let (encoding, plain) = get_jobs(app.clone(), None).await;
+    assert_eq!(encoding, None);
+    let plain_json: serde_json::Value = serde_json::from_slice(&plain).unwrap();
+    assert_eq!(plain_json["items"].as_array().unwrap().len(), 100);
+
+    let (encoding, gzipped) = get_jobs(app, Some("gzip")).await;
+    assert_eq!(encoding.as_deref(), Some("gzip"));
+    assert!(gzipped.len() < plain.len());
+
+    let mut decoded = Vec::new();
+    GzDecoder::new(gzipped.as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    let decoded_json: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+    assert_eq!(decoded_json["items"], plain_json["items"]);
+}
@@ -1,12 +1,16 @@
 mod common;
 
-use common::setup_db;
+use axum::extract::State;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::{replay_dlq, ReplayDlqRequest};
 use postgresflow::jobs::retry::RetryConfig;
 use postgresflow::jobs::runner::JobRunner;
 use postgresflow::jobs::{AttemptsRepo, JobsRepo};
 
+use serial_test::serial;
 use sqlx::Row;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 async fn insert_job(pool: &sqlx::PgPool, queue: &str, job_type: &str, max_attempts: i32) -> Uuid {
@@ -94,18 +98,23 @@ async fn exhausted_retries_moves_job_to_dlq_and_preserves_attempts() {
         .unwrap();
 
     // Assert job is DLQ and fields are set
-    let row = sqlx::query("SELECT status, dlq_reason_code, dlq_at FROM jobs WHERE id = $1")
-        .bind(job_id)
-        .fetch_one(&pool)
-        .await
-        .unwrap();
+    let row = sqlx::query(
+        "SELECT status, dlq_reason_code, dlq_error_code, dlq_at FROM jobs WHERE id = $1",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
 
     let status: String = row.get("status");
     let dlq_reason_code: Option<String> = row.get("dlq_reason_code");
+    let dlq_error_code: Option<String> = row.get("dlq_error_code");
     let dlq_at: Option<chrono::DateTime<chrono::Utc>> = row.get("dlq_at");
 
     assert_eq!(status, "dlq");
     assert_eq!(dlq_reason_code.as_deref(), Some("MAX_ATTEMPTS_EXCEEDED"));
+    // the cause behind "ran out of attempts" is kept for grouping
+    assert_eq!(dlq_error_code.as_deref(), Some("TIMEOUT"));
     assert!(dlq_at.is_some(), "dlq_at should be set");
 
     // Attempts preserved (should be 2)
@@ -161,3 +170,267 @@ async fn non_retryable_goes_to_dlq_immediately() {
     assert_eq!(status, "dlq");
     assert_eq!(reason.as_deref(), Some("NON_RETRYABLE"));
 }
+
+#[tokio::test]
+#[serial]
+async fn requeue_dlq_spreads_run_at_over_the_window() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let mut dead = Vec::new();
+    for _ in 0..5 {
+        let id = insert_job(&pool, "q_requeue", "flaky_dep", 3).await;
+        let job = jobs
+            .lease_one_job("q_requeue", "worker-a", 30)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.id, id);
+        jobs.mark_dlq(
+            id,
+            "worker-a",
+            "MAX_ATTEMPTS",
+            Some("DEPENDENCY_DOWN"),
+            None,
+        )
+        .await
+        .unwrap();
+        dead.push(id);
+    }
+    // another queue's DLQ is left alone
+    let other = insert_job(&pool, "q_other", "flaky_dep", 3).await;
+    jobs.lease_one_job("q_other", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    jobs.mark_dlq(other, "worker-a", "MAX_ATTEMPTS", None, None)
+        .await
+        .unwrap();
+
+    let requeued = jobs
+        .requeue_dlq(Some("q_requeue"), Some(7), 1, Duration::from_secs(300), 100)
+        .await
+        .unwrap();
+    assert_eq!(requeued, 5);
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, status, priority, dlq_reason_code,
+               EXTRACT(EPOCH FROM (run_at - now()))::float8 AS offset_secs
+        FROM jobs
+        WHERE queue = 'q_requeue'
+        ORDER BY run_at ASC
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let offsets: Vec<f64> = rows.iter().map(|r| r.get("offset_secs")).collect();
+    for row in &rows {
+        assert_eq!(row.get::<String, _>("status"), "queued");
+        assert_eq!(row.get::<i32, _>("priority"), 7);
+        assert_eq!(row.get::<Option<String>, _>("dlq_reason_code"), None);
+    }
+    // oldest dead letter first, one every 60s across [now, now + 5m)
+    let order: Vec<Uuid> = rows.iter().map(|r| r.get("id")).collect();
+    assert_eq!(order, dead);
+    for (i, offset) in offsets.iter().enumerate() {
+        let expected = 60.0 * i as f64;
+        assert!(
+            (offset - expected).abs() < 5.0,
+            "job {i} runs {offset:.1}s from now, expected ~{expected}s"
+        );
+    }
+
+    let status: String = sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(other)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "dlq");
+
+    let audited: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM policy_decisions WHERE decision = 'MANUAL_REQUEUE'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(audited, 5);
+
+    // nothing left in that queue's DLQ
+    let again = jobs
+        .requeue_dlq(Some("q_requeue"), None, 1, Duration::ZERO, 100)
+        .await
+        .unwrap();
+    assert_eq!(again, 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn requeue_dlq_grants_extra_attempts_and_clears_the_non_retryable_flag() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let job_id = insert_job(&pool, "q_requeue_budget", "flaky_dep", 2).await;
+    let job = jobs
+        .lease_one_job("q_requeue_budget", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let a1 = attempts.start_attempt(job_id, "worker-a").await.unwrap();
+    assert!(jobs.set_non_retryable(job_id).await.unwrap());
+    runner
+        .on_failure(
+            job_id,
+            a1.id,
+            "worker-a",
+            5,
+            "DEPENDENCY_DOWN",
+            "down",
+            a1.attempt_no,
+            job.max_attempts,
+        )
+        .await
+        .unwrap();
+
+    let requeued = jobs
+        .requeue_dlq(Some("q_requeue_budget"), None, 3, Duration::ZERO, 100)
+        .await
+        .unwrap();
+    assert_eq!(requeued, 1);
+    assert!(!jobs.is_forced_non_retryable(job_id).await.unwrap());
+
+    // one attempt made, three more allowed
+    let job = jobs
+        .lease_one_job("q_requeue_budget", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.max_attempts, 4);
+
+    // a retryable failure retries again instead of going back to the DLQ
+    let a2 = attempts.start_attempt(job_id, "worker-a").await.unwrap();
+    assert_eq!(a2.attempt_no, 2);
+    runner
+        .on_failure(
+            job_id,
+            a2.id,
+            "worker-a",
+            5,
+            "DEPENDENCY_DOWN",
+            "still down",
+            a2.attempt_no,
+            job.max_attempts,
+        )
+        .await
+        .unwrap();
+    let status: String = sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(status, "queued");
+}
+
+async fn dead_letter(pool: &sqlx::PgPool, jobs: &JobsRepo, queue: &str) -> Uuid {
+    let id = insert_job(pool, queue, "flaky_dep", 3).await;
+    let job = jobs
+        .lease_one_job(queue, "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.id, id);
+    jobs.mark_dlq(id, "worker-a", "MAX_ATTEMPTS", None, None)
+        .await
+        .unwrap();
+    id
+}
+
+#[tokio::test]
+#[serial]
+async fn replay_dlq_batch_creates_one_replay_per_dead_letter() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let mut dead = Vec::new();
+    for _ in 0..4 {
+        dead.push(dead_letter(&pool, &jobs, "q_replay").await);
+    }
+    let other = dead_letter(&pool, &jobs, "q_replay_other").await;
+
+    let run_at = chrono::Utc::now() + chrono::Duration::minutes(10);
+    let first = jobs
+        .replay_dlq_batch(Some("q_replay"), 3, Some(run_at))
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 3);
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, status, queue, replay_of_job_id, run_at
+        FROM jobs
+        WHERE id = ANY($1)
+        "#,
+    )
+    .bind(&first)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let mut lineage: Vec<Uuid> = rows
+        .iter()
+        .map(|r| {
+            assert_eq!(r.get::<String, _>("status"), "queued");
+            assert_eq!(r.get::<String, _>("queue"), "q_replay");
+            let at: chrono::DateTime<chrono::Utc> = r.get("run_at");
+            assert!((at - run_at).num_milliseconds().abs() < 1);
+            r.get::<Option<Uuid>, _>("replay_of_job_id").unwrap()
+        })
+        .collect();
+    lineage.sort();
+    // oldest dead letters first
+    let mut expected = dead[..3].to_vec();
+    expected.sort();
+    assert_eq!(lineage, expected);
+
+    // the sources stay in the DLQ, but are not replayed a second time
+    let Json(resp) = replay_dlq(
+        State(api_state(&pool)),
+        Json(ReplayDlqRequest {
+            queue: Some("q_replay".to_string()),
+            limit: None,
+            run_at: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.new_job_ids.len(), 1);
+    let last: Option<Uuid> = sqlx::query_scalar("SELECT replay_of_job_id FROM jobs WHERE id = $1")
+        .bind(resp.new_job_ids[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(last, Some(dead[3]));
+
+    let dlq: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE status = 'dlq'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(dlq, 5);
+    assert!(jobs
+        .replay_dlq_batch(Some("q_replay"), 100, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // without a queue filter the rest of the DLQ is replayed
+    let rest = jobs.replay_dlq_batch(None, 100, None).await.unwrap();
+    assert_eq!(rest.len(), 1);
+    let src: Option<Uuid> = sqlx::query_scalar("SELECT replay_of_job_id FROM jobs WHERE id = $1")
+        .bind(rest[0])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(src, Some(other));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::setup_db;
+use serial_test::serial;
+use std::process::Command;
+
+#[tokio::test]
+#[serial]
+async fn doctor_reports_healthy_against_migrated_db() {
+    let pool = setup_db().await;
+
+    let report = postgresflow::db::doctor(&pool).await.unwrap();
+    assert!(report.missing_tables.is_empty());
+    assert!(report.pending_migrations.is_empty(), "{report:?}");
+    assert!(report.applied_migrations > 0);
+    assert!(report.is_healthy());
+
+    let url = std::env::var("TEST_DATABASE_URL").unwrap();
+    let out = Command::new(env!("CARGO_BIN_EXE_pgflowctl"))
+        .arg("doctor")
+        .env("DATABASE_URL", &url)
+        .output()
+        .expect("failed to run pgflowctl");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(out.status.success(), "doctor failed:\n{stdout}");
+    assert!(stdout.contains("ok   table jobs"));
+    assert_eq!(stdout.lines().last(), Some("healthy"), "{stdout}");
+    assert!(!stdout.contains("FAIL"));
+}
+
+#[test]
+fn doctor_exits_non_zero_when_db_unreachable() {
+    let out = Command::new(env!("CARGO_BIN_EXE_pgflowctl"))
+        .arg("doctor")
+        .env("DATABASE_URL", "postgres://nobody@127.0.0.1:1/none")
+        .output()
+        .expect("failed to run pgflowctl");
+
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stdout).contains("FAIL connect"));
+}
+
+#[tokio::test]
+#[serial]
+async fn edited_migration_fails_with_version_and_checksums() {
+    use postgresflow::db::{run_migrations, MigrationChecksumError};
+
+    let pool = setup_db().await;
+    let (version, original): (i64, Vec<u8>) = sqlx::query_as(
+        "SELECT version, checksum FROM _sqlx_migrations WHERE success ORDER BY version LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // simulate the file having been edited after it was applied
+    let set_checksum = |checksum: Vec<u8>| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query("UPDATE _sqlx_migrations SET checksum = $2 WHERE version = $1")
+                .bind(version)
+                .bind(checksum)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    };
+    set_checksum(vec![0xab; 48]).await;
+    let err = run_migrations(&pool).await.unwrap_err();
+    set_checksum(original).await;
+
+    let mismatch = err
+        .downcast_ref::<MigrationChecksumError>()
+        .expect("checksum mismatch should be reported as MigrationChecksumError");
+    assert_eq!(mismatch.mismatches.len(), 1);
+    assert_eq!(mismatch.mismatches[0].version, version);
+    assert_eq!(mismatch.mismatches[0].applied_checksum, "ab".repeat(48));
+
+    let msg = err.to_string();
+    assert!(msg.contains(&version.to_string()), "{msg}");
+    assert!(msg.contains(&"ab".repeat(48)), "{msg}");
+    assert!(msg.contains(&mismatch.mismatches[0].file_checksum), "{msg}");
+    assert!(msg.contains("new migration"), "{msg}");
+
+    // restored: migrations run cleanly again
+    run_migrations(&pool).await.unwrap();
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use chrono::Utc;
+use common::setup_db;
+use postgresflow::jobs::{JobsRepo, NewJob};
+use serial_test::serial;
+
+fn new_job(queue: &str, dedupe_key: Option<&str>, n: i64) -> NewJob {
+    NewJob {
+        queue: queue.to_string(),
+        job_type: "batch_job".to_string(),
+        payload_json: serde_json::json!({ "n": n }),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: dedupe_key.map(str::to_string),
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn batch_duplicates_map_to_the_first_job_with_their_key() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let ids = jobs
+        .enqueue_batch(vec![
+            new_job("q_batch", Some("a"), 0),
+            new_job("q_batch", Some("b"), 1),
+            new_job("q_batch", Some("a"), 2),
+            new_job("q_batch", None, 3),
+            new_job("q_batch", None, 4),
+            // same key on another queue is a different job
+            new_job("q_batch_other", Some("a"), 5),
+            new_job("q_batch", Some("b"), 6),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(ids.len(), 7);
+    assert_eq!(ids[2], ids[0]);
+    assert_eq!(ids[6], ids[1]);
+    assert_ne!(ids[0], ids[1]);
+    assert_ne!(ids[3], ids[4]);
+    assert_ne!(ids[5], ids[0]);
+
+    let rows: Vec<(String, Option<String>, i64)> = sqlx::query_as(
+        r#"
+        SELECT queue, dedupe_key, (payload_json->>'n')::bigint
+        FROM jobs
+        WHERE queue LIKE 'q_batch%'
+        ORDER BY (payload_json->>'n')::bigint
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            ("q_batch".to_string(), Some("a".to_string()), 0),
+            ("q_batch".to_string(), Some("b".to_string()), 1),
+            ("q_batch".to_string(), None, 3),
+            ("q_batch".to_string(), None, 4),
+            ("q_batch_other".to_string(), Some("a".to_string()), 5),
+        ]
+    );
+
+    // the kept job is the first occurrence
+    let first = jobs.get_job(ids[0]).await.unwrap().unwrap();
+    assert_eq!(first.payload_json["n"], 0);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use common::{api_state, insert_job, scheduled_once, setup_db};
+use postgresflow::api::{enqueue_job, put_enqueue_enabled, EnqueueRequest, EnqueueSwitch};
+use postgresflow::jobs::system_flags::{SystemFlagsRepo, ENQUEUE_ENABLED};
+use postgresflow::jobs::JobsRepo;
+use serial_test::serial;
+
+fn enqueue_request() -> EnqueueRequest {
+    EnqueueRequest {
+        queue: Some("q_kill".to_string()),
+        job_type: "send_email".to_string(),
+        payload_json: Some(serde_json::json!({})),
+        payload_template: None,
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn kill_switch_rejects_enqueues_until_turned_back_on() {
+    let pool = setup_db().await;
+    sqlx::query("DELETE FROM ingest_decisions WHERE queue = 'q_kill'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let state = api_state(&pool);
+
+    let Json(queued) = enqueue_job(State(state.clone()), Json(enqueue_request()))
+        .await
+        .unwrap();
+
+    let Json(switch) =
+        put_enqueue_enabled(State(state.clone()), Json(EnqueueSwitch { enabled: false }))
+            .await
+            .unwrap();
+    assert!(!switch.enabled);
+
+    let err = enqueue_job(State(state.clone()), Json(enqueue_request()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(err.1.contains("ENQUEUE_DISABLED"));
+
+    let decisions = state
+        .ingest_decisions
+        .list_recent(Some("q_kill"), 10)
+        .await
+        .unwrap();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].3, "ENQUEUE_DISABLED");
+
+    // the backlog still drains while enqueue is off
+    let leased = state
+        .jobs
+        .lease_one_job("q_kill", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("queued job is still leasable");
+    assert_eq!(leased.id, queued.job_id);
+
+    let Json(switch) =
+        put_enqueue_enabled(State(state.clone()), Json(EnqueueSwitch { enabled: true }))
+            .await
+            .unwrap();
+    assert!(switch.enabled);
+    let Json(ok) = enqueue_job(State(state.clone()), Json(enqueue_request()))
+        .await
+        .unwrap();
+    assert!(state.jobs.get_job(ok.job_id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn kill_switch_covers_repo_inserts_without_a_guard() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let flags = SystemFlagsRepo::new(pool.clone());
+    let dead = insert_job(&pool, "q_kill_repo").await;
+    sqlx::query("UPDATE jobs SET status = 'dlq', dlq_at = now() WHERE id = $1")
+        .bind(dead)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    flags.set(ENQUEUE_ENABLED, false).await.unwrap();
+    let denials = [
+        jobs.enqueue_now("q_kill_repo", "send_email", serde_json::json!({}))
+            .await
+            .map(|_| ()),
+        jobs.enqueue_scheduled_once(scheduled_once(
+            "q_kill_repo",
+            "send_email",
+            serde_json::json!({}),
+            chrono::Utc::now(),
+            "reminder",
+        ))
+        .await
+        .map(|_| ()),
+        jobs.replay_job(dead, None, None, None, false)
+            .await
+            .map(|_| ()),
+        jobs.replay_dlq_batch(Some("q_kill_repo"), 10, None)
+            .await
+            .map(|_| ()),
+    ];
+    flags.set(ENQUEUE_ENABLED, true).await.unwrap();
+
+    for denial in denials {
+        assert_eq!(denial.unwrap_err().to_string(), "ENQUEUE_DISABLED");
+    }
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue = 'q_kill_repo'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    assert_eq!(
+        jobs.replay_dlq_batch(Some("q_kill_repo"), 10, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use chrono::TimeZone;
+use common::setup_db;
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig, RateWindow};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use postgresflow::jobs::MockClock;
+use serial_test::serial;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+async fn guard(pool: &PgPool, queue: &str, window: RateWindow) -> (EnqueueGuard, Arc<MockClock>) {
+    sqlx::query("DELETE FROM enqueue_rate_counters WHERE queue = $1")
+        .bind(queue)
+        .execute(pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM ingest_decisions WHERE queue = $1")
+        .bind(queue)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    // 10s before a minute boundary
+    let clock = Arc::new(MockClock::new(
+        chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 50).unwrap(),
+    ));
+    let guard = EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig {
+            max_enqueues_per_minute_per_queue: 10,
+            rate_window: window,
+            ..EnqueueGuardConfig::default()
+        },
+    )
+    .with_clock(clock.clone());
+    (guard, clock)
+}
+
+/// Fills the limit just before the boundary, then enqueues once more just after it.
+async fn burst_across_boundary(guard: &EnqueueGuard, clock: &MockClock, queue: &str) -> bool {
+    for _ in 0..10 {
+        guard.check_rate(queue, None).await.unwrap();
+    }
+    clock.advance(chrono::Duration::seconds(15)); // 00:01:05
+    guard.check_rate(queue, None).await.is_ok()
+}
+
+#[tokio::test]
+#[serial]
+async fn sliding_window_blocks_a_burst_that_straddles_the_minute_boundary() {
+    let pool = setup_db().await;
+
+    let (fixed, clock) = guard(&pool, "q_rate_fixed", RateWindow::Fixed).await;
+    assert!(
+        burst_across_boundary(&fixed, &clock, "q_rate_fixed").await,
+        "fixed window starts from zero in the new minute"
+    );
+
+    let (sliding, clock) = guard(&pool, "q_rate_sliding", RateWindow::Sliding).await;
+    assert!(
+        !burst_across_boundary(&sliding, &clock, "q_rate_sliding").await,
+        "previous minute still weighs 55/60 of its 10 enqueues"
+    );
+
+    let ingest = IngestDecisionsRepo::new(pool.clone());
+    let denied = ingest
+        .list_recent(Some("q_rate_sliding"), 10)
+        .await
+        .unwrap();
+    assert_eq!(denied.len(), 1);
+    let (_, _, decision, reason_code, details, _) = &denied[0];
+    assert_eq!(decision, "DENIED");
+    assert_eq!(reason_code, "ENQUEUE_RATE_EXCEEDED");
+    assert_eq!(details["count_this_minute"], 1);
+    assert_eq!(details["count_previous_minute"], 10);
+
+    // once the previous minute has mostly slid out, the sliding window admits again
+    clock.advance(chrono::Duration::seconds(50)); // 00:01:55
+    assert!(sliding.check_rate("q_rate_sliding", None).await.is_ok());
+
+    assert_eq!(RateWindow::parse("Sliding"), RateWindow::Sliding);
+    assert_eq!(RateWindow::parse("bogus"), RateWindow::Fixed);
+}
+
+#[tokio::test]
+#[serial]
+async fn tenant_limit_denies_one_tenant_without_starving_the_queue() {
+    let pool = setup_db().await;
+    let queue = "q_rate_tenant";
+
+    let guard = EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig {
+            max_enqueues_per_minute_per_queue: 10,
+            max_enqueues_per_minute_per_tenant: Some(3),
+            tenant_limits: [("big".to_string(), 5)].into_iter().collect(),
+            ..EnqueueGuardConfig::default()
+        },
+    )
+    .with_clock(Arc::new(MockClock::new(
+        chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 10).unwrap(),
+    )));
+
+    for _ in 0..3 {
+        guard.check_rate(queue, Some("A")).await.unwrap();
+    }
+    assert!(guard.check_rate(queue, Some("A")).await.is_err());
+
+    // other tenants and untagged enqueues keep their own headroom
+    for _ in 0..3 {
+        guard.check_rate(queue, Some("B")).await.unwrap();
+    }
+    guard.check_rate(queue, None).await.unwrap();
+
+    // an explicit per-tenant limit overrides the default
+    for _ in 0..3 {
+        guard.check_rate(queue, Some("big")).await.unwrap();
+    }
+    // ...but the queue-wide limit still caps the total: 3 + 3 + 1 + 3 = 10
+    let err = guard.check_rate(queue, Some("big")).await.unwrap_err();
+    assert_eq!(err.to_string(), "ENQUEUE_RATE_EXCEEDED");
+
+    let ingest = IngestDecisionsRepo::new(pool.clone());
+    let mut denied: Vec<Option<String>> = ingest
+        .list_recent(Some(queue), 10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, _, _, _, details, _)| details["tenant"].as_str().map(str::to_string))
+        .collect();
+    denied.sort();
+    // the tenant denial names the tenant; the queue-wide one does not
+    assert_eq!(denied, vec![None, Some("A".to_string())]);
+}
@@ -3,7 +3,9 @@ mod common;
 
 use common::setup_db;
 
-use postgresflow::jobs::timeline::build_timeline;
+use postgresflow::jobs::error_codes::{classify_http_status, ErrorCode};
+use postgresflow::jobs::retry::{classify_error, ErrorClass};
+use postgresflow::jobs::timeline::{build_timeline, TimelineOptions};
 use postgresflow::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
 
 use uuid::Uuid;
@@ -51,7 +53,7 @@ async fn timeline_includes_suggested_action_for_known_error_codes() {
         .unwrap();
 
     // timeline should include suggested action
-    let tl = build_timeline(&jobs, &attempts, &policy, job_id) // ✅ new arg
+    let tl = build_timeline(&jobs, &attempts, &policy, job_id, TimelineOptions::full()) // ✅ new arg
         .await
         .unwrap()
         .expect("timeline exists");
@@ -67,3 +69,38 @@ async fn timeline_includes_suggested_action_for_known_error_codes() {
         "expected a runbook-style suggestion for RATE_LIMIT"
     );
 }
+
+#[test]
+fn http_status_codes_map_to_error_codes() {
+    let cases = [
+        (408, ErrorCode::Timeout),
+        (504, ErrorCode::Timeout),
+        (429, ErrorCode::RateLimit),
+        (500, ErrorCode::DependencyDown),
+        (502, ErrorCode::DependencyDown),
+        (503, ErrorCode::DependencyDown),
+        (400, ErrorCode::BadPayload),
+        (404, ErrorCode::BadPayload),
+        (422, ErrorCode::BadPayload),
+        (200, ErrorCode::Unknown),
+        (302, ErrorCode::Unknown),
+    ];
+
+    for (status, expected) in cases {
+        assert_eq!(classify_http_status(status), expected, "status {status}");
+    }
+
+    // Throttling and outages retry; client errors don't.
+    assert_eq!(
+        classify_error(classify_http_status(429).as_str()),
+        ErrorClass::Retryable
+    );
+    assert_eq!(
+        classify_error(classify_http_status(503).as_str()),
+        ErrorClass::Retryable
+    );
+    assert_eq!(
+        classify_error(classify_http_status(400).as_str()),
+        ErrorClass::NonRetryable
+    );
+}
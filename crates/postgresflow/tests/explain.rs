@@ -0,0 +1,93 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo};
+use serde_json::Value;
+use serial_test::serial;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn fail_once(pool: &sqlx::PgPool, queue: &str, error_code: &str) -> Uuid {
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let job_id = insert_job(pool, queue).await;
+    let job = jobs
+        .lease_one_job(queue, "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+    runner
+        .on_failure(
+            job.id,
+            attempt.id,
+            "worker-a",
+            10,
+            error_code,
+            "it broke",
+            attempt.attempt_no,
+            job.max_attempts,
+        )
+        .await
+        .unwrap();
+    job_id
+}
+
+#[tokio::test]
+#[serial]
+async fn explain_returns_remediation_steps_and_runbook_url() {
+    let pool = setup_db().await;
+    let timed_out = fail_once(&pool, "q_explain_a", "TIMEOUT").await;
+    let rate_limited = fail_once(&pool, "q_explain_b", "RATE_LIMIT").await;
+
+    let mut state = api_state(&pool);
+    state.runbook_urls = Arc::new(HashMap::from([(
+        "TIMEOUT".to_string(),
+        "https://runbooks.example/timeout".to_string(),
+    )]));
+    let app = router(state);
+
+    let explain = |id: Uuid| {
+        let app = app.clone();
+        async move {
+            let resp = app
+                .oneshot(
+                    Request::get(format!("/jobs/{id}/explain"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<Value>(&body).unwrap()
+        }
+    };
+
+    let body = explain(timed_out).await;
+    let steps = body["suggested_steps"].as_array().unwrap();
+    assert!(steps.len() > 1, "{body}");
+    assert!(steps.iter().all(|s| !s.as_str().unwrap().is_empty()));
+    let joined: Vec<&str> = steps.iter().map(|s| s.as_str().unwrap()).collect();
+    assert_eq!(body["suggested_action"], joined.join(" "));
+    assert_eq!(body["runbook_url"], "https://runbooks.example/timeout");
+
+    // no runbook configured for this code
+    let body = explain(rate_limited).await;
+    assert!(body["suggested_steps"].as_array().unwrap().len() > 1);
+    assert!(body["runbook_url"].is_null());
+
+    // nothing to remediate without an error
+    let queued = insert_job(&pool, "q_explain_c").await;
+    let body = explain(queued).await;
+    assert_eq!(body["suggested_steps"], serde_json::json!([]));
+}
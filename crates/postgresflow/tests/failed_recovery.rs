@@ -0,0 +1,139 @@
+mod common;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::{dead_letter_failed_job, list_failed, recover_job, ListJobsQuery};
+use serial_test::serial;
+use uuid::Uuid;
+
+fn failed_query() -> ListJobsQuery {
+    ListJobsQuery {
+        queue: Some("q_failed".to_string()),
+        status: None,
+        limit: None,
+        cursor_created_at: None,
+        cursor_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_job_is_listed_and_can_be_recovered() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_failed").await;
+    state
+        .jobs
+        .lease_one_job("q_failed", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    state
+        .jobs
+        .mark_failed(job_id, "worker-1", Some("BAD_STATE"), Some("boom"))
+        .await
+        .unwrap();
+
+    let Json(failed) = list_failed(State(state.clone()), Query(failed_query()))
+        .await
+        .unwrap();
+    assert_eq!(failed.items.len(), 1);
+    assert_eq!(failed.items[0].id, job_id);
+
+    let Json(recovered) = recover_job(State(state.clone()), Path(job_id))
+        .await
+        .unwrap();
+    assert_eq!(recovered.status, "queued");
+
+    let Json(failed) = list_failed(State(state.clone()), Query(failed_query()))
+        .await
+        .unwrap();
+    assert!(failed.items.is_empty());
+
+    let leased = state
+        .jobs
+        .lease_one_job("q_failed", "worker-2", 30)
+        .await
+        .unwrap()
+        .expect("recovered job is runnable");
+    assert_eq!(leased.id, job_id);
+
+    let rows = state.policy_decisions.list_for_job(job_id).await.unwrap();
+    let audit = rows
+        .iter()
+        .find(|r| r.decision == "MANUAL_RECOVER")
+        .expect("expected audit decision");
+    assert_eq!(audit.reason_code, "RECOVERED_FROM_FAILED");
+    assert_eq!(audit.details_json["last_error_code"], "BAD_STATE");
+}
+
+#[tokio::test]
+#[serial]
+async fn recover_rejects_non_failed_and_unknown_jobs() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_failed").await;
+
+    let err = recover_job(State(state.clone()), Path(job_id))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::CONFLICT);
+
+    let err = recover_job(State(state.clone()), Path(Uuid::new_v4()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+#[serial]
+async fn failed_job_can_be_moved_to_the_dlq() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_failed").await;
+    let err = dead_letter_failed_job(State(state.clone()), Path(job_id))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::CONFLICT);
+
+    state
+        .jobs
+        .lease_one_job("q_failed", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    state
+        .jobs
+        .mark_failed(job_id, "worker-1", Some("BAD_STATE"), Some("boom"))
+        .await
+        .unwrap();
+
+    let Json(dead) = dead_letter_failed_job(State(state.clone()), Path(job_id))
+        .await
+        .unwrap();
+    assert_eq!(dead.status, "dlq");
+
+    let (reason, error_code): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT dlq_reason_code, dlq_error_code FROM jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(reason.as_deref(), Some("MOVED_FROM_FAILED"));
+    assert_eq!(error_code.as_deref(), Some("BAD_STATE"));
+
+    let rows = state.policy_decisions.list_for_job(job_id).await.unwrap();
+    assert!(rows
+        .iter()
+        .any(|r| r.decision == "MANUAL_DLQ" && r.reason_code == "MOVED_FROM_FAILED"));
+
+    let err = dead_letter_failed_job(State(state.clone()), Path(Uuid::new_v4()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::NOT_FOUND);
+}
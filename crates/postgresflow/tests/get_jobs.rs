@@ -0,0 +1,60 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::{get_jobs, GetJobsRequest};
+use postgresflow::jobs::NewJob;
+use serial_test::serial;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn get_jobs_fetches_several_ids_in_one_call() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let mut ids = Vec::new();
+    for i in 0..5 {
+        let id = state
+            .jobs
+            .enqueue(NewJob {
+                queue: "q_get".to_string(),
+                job_type: format!("type_{i}"),
+                payload_json: serde_json::json!({ "i": i }),
+                run_at: Utc::now(),
+                priority: 0,
+                max_attempts: 3,
+                target_worker_id: None,
+                retry: None,
+                dedupe_key: None,
+                idempotency_key: None,
+                group_id: None,
+            })
+            .await
+            .unwrap();
+        ids.push(id);
+    }
+
+    // repo call preserves the requested order
+    let wanted = vec![ids[3], ids[0], ids[4]];
+    let jobs = state.jobs.get_jobs(&wanted).await.unwrap();
+    assert_eq!(jobs.iter().map(|j| j.id).collect::<Vec<_>>(), wanted);
+    assert_eq!(jobs[0].job_type, "type_3");
+
+    let unknown = Uuid::new_v4();
+    let mut requested = ids.clone();
+    requested.push(unknown);
+
+    let Json(resp) = get_jobs(State(state), Json(GetJobsRequest { ids: requested }))
+        .await
+        .unwrap();
+    assert_eq!(resp.jobs.len(), 5);
+    for (job, id) in resp.jobs.iter().zip(&ids) {
+        assert_eq!(job.id, *id);
+        assert_eq!(job.queue, "q_get");
+    }
+    assert_eq!(resp.jobs[2].payload_json["i"], 2);
+    assert_eq!(resp.missing, vec![unknown]);
+}
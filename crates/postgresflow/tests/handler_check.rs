@@ -0,0 +1,42 @@
+mod common;
+
+use common::setup_db;
+use postgresflow::jobs::handler_check::validate_handlers;
+use postgresflow::jobs::JobsRepo;
+use serde_json::json;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn unhandled_job_types_warn_or_fail_startup() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    jobs.enqueue_now("default", "email_send", json!({}))
+        .await
+        .unwrap();
+    jobs.enqueue_now("default", "legacy_export", json!({}))
+        .await
+        .unwrap();
+    jobs.enqueue_now("other", "only_elsewhere", json!({}))
+        .await
+        .unwrap();
+
+    let registered = vec!["email_send".to_string(), "ok".to_string()];
+
+    let missing = validate_handlers(&jobs, "default", &registered, false)
+        .await
+        .expect("non-fatal mode only warns");
+    assert_eq!(missing, vec!["legacy_export".to_string()]);
+
+    let err = validate_handlers(&jobs, "default", &registered, true)
+        .await
+        .expect_err("fatal mode should fail startup");
+    assert!(err.to_string().contains("legacy_export"));
+
+    let all = vec!["email_send".to_string(), "legacy_export".to_string()];
+    let missing = validate_handlers(&jobs, "default", &all, true)
+        .await
+        .unwrap();
+    assert!(missing.is_empty());
+}
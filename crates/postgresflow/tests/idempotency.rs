@@ -0,0 +1,126 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::{enqueue_job, EnqueueRequest};
+use postgresflow::jobs::{JobsRepo, NewJob};
+use serial_test::serial;
+
+fn new_job(key: &str) -> NewJob {
+    NewJob {
+        queue: "q_idem".to_string(),
+        job_type: "charge_card".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: Some(key.to_string()),
+        group_id: None,
+    }
+}
+
+fn enqueue_request(key: &str) -> EnqueueRequest {
+    EnqueueRequest {
+        queue: Some("q_idem".to_string()),
+        job_type: "charge_card".to_string(),
+        payload_json: Some(serde_json::json!({ "amount": 10 })),
+        payload_template: None,
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: Some(key.to_string()),
+        group_id: None,
+    }
+}
+
+async fn job_count(pool: &sqlx::PgPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue = 'q_idem'")
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn repeated_idempotency_key_returns_the_live_job() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let Json(first) = enqueue_job(State(state.clone()), Json(enqueue_request("order-1")))
+        .await
+        .unwrap();
+    assert!(!first.deduplicated);
+
+    let Json(retry) = enqueue_job(State(state.clone()), Json(enqueue_request("order-1")))
+        .await
+        .unwrap();
+    assert!(retry.deduplicated);
+    assert_eq!(retry.job_id, first.job_id);
+
+    // still deduplicated while the job runs
+    let jobs = JobsRepo::new(pool.clone());
+    jobs.lease_one_job("q_idem", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    let again = jobs.enqueue_idempotent(new_job("order-1")).await.unwrap();
+    assert!(again.deduplicated);
+    assert_eq!(again.job_id, first.job_id);
+
+    // other keys and keyless enqueues are unaffected
+    let other = jobs.enqueue_idempotent(new_job("order-2")).await.unwrap();
+    assert!(!other.deduplicated);
+    assert_ne!(other.job_id, first.job_id);
+    assert_eq!(job_count(&pool).await, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn idempotency_key_is_reusable_after_the_job_finished() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let first = jobs.enqueue(new_job("order-1")).await.unwrap();
+    jobs.lease_one_job("q_idem", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    jobs.mark_succeeded(first, "worker-a").await.unwrap();
+
+    let second = jobs.enqueue_idempotent(new_job("order-1")).await.unwrap();
+    assert!(!second.deduplicated);
+    assert_ne!(second.job_id, first);
+
+    // the key now points at the new job
+    let third = jobs.enqueue_idempotent(new_job("order-1")).await.unwrap();
+    assert!(third.deduplicated);
+    assert_eq!(third.job_id, second.job_id);
+    assert_eq!(job_count(&pool).await, 2);
+}
+
+#[tokio::test]
+#[serial]
+async fn idempotency_key_stops_deduplicating_after_the_window() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone()).with_idempotency_window_secs(60);
+
+    let first = jobs.enqueue(new_job("order-1")).await.unwrap();
+    sqlx::query("UPDATE job_idempotency_keys SET created_at = now() - interval '2 minutes'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // the first job is still queued, but its claim on the key has lapsed
+    let second = jobs.enqueue_idempotent(new_job("order-1")).await.unwrap();
+    assert!(!second.deduplicated);
+    assert_ne!(second.job_id, first);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use axum::extract::{Query, State};
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::{ingest_summary, IngestSummaryQuery};
+use serde_json::json;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn ingest_summary_groups_recent_denials_by_reason_and_queue() {
+    let pool = setup_db().await;
+    sqlx::query("DELETE FROM ingest_decisions WHERE queue IN ('q_sum_a', 'q_sum_b')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let state = api_state(&pool);
+
+    let deny = |queue: &'static str, reason: &'static str| {
+        let decisions = state.ingest_decisions.clone();
+        async move {
+            decisions
+                .record(queue, "DENIED", reason, json!({}))
+                .await
+                .unwrap();
+        }
+    };
+    for _ in 0..3 {
+        deny("q_sum_a", "ENQUEUE_RATE_EXCEEDED").await;
+    }
+    deny("q_sum_a", "PAYLOAD_TOO_LARGE").await;
+    deny("q_sum_b", "PAYLOAD_TOO_LARGE").await;
+    deny("q_sum_b", "UNKNOWN_JOB_TYPE").await;
+
+    // outside the default 1h window
+    sqlx::query(
+        r#"
+        INSERT INTO ingest_decisions (id, queue, decision, reason_code, details_json, created_at)
+        VALUES (gen_random_uuid(), 'q_sum_a', 'DENIED', 'PAYLOAD_TOO_LARGE', '{}'::jsonb,
+                now() - interval '2 hours')
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let Json(a) = ingest_summary(
+        State(state.clone()),
+        Query(IngestSummaryQuery {
+            queue: Some("q_sum_a".to_string()),
+            window_secs: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(a.window_secs, 3600);
+    assert_eq!(a.total, 4);
+    assert_eq!(a.by_reason_code.get("ENQUEUE_RATE_EXCEEDED"), Some(&3));
+    assert_eq!(a.by_reason_code.get("PAYLOAD_TOO_LARGE"), Some(&1));
+    assert_eq!(a.by_queue.len(), 1);
+
+    let Json(all) = ingest_summary(
+        State(state.clone()),
+        Query(IngestSummaryQuery {
+            queue: None,
+            window_secs: Some(3 * 3600),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(all.by_queue.get("q_sum_a"), Some(&5));
+    assert_eq!(all.by_queue.get("q_sum_b"), Some(&2));
+    let b_unknown = all
+        .by_queue_and_reason
+        .iter()
+        .find(|r| r.queue == "q_sum_b" && r.reason_code == "UNKNOWN_JOB_TYPE")
+        .expect("per queue and reason row");
+    assert_eq!(b_unknown.count, 1);
+}
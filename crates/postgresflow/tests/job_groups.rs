@@ -0,0 +1,183 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, NewJob};
+use serial_test::serial;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn new_job(group_id: Option<Uuid>, priority: i32) -> NewJob {
+    NewJob {
+        queue: "q_group".to_string(),
+        job_type: "group_step".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id,
+    }
+}
+
+async fn status_of(pool: &sqlx::PgPool, id: Uuid) -> String {
+    sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn dlq_member_fails_the_group_and_cancels_queued_members() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone()).with_cancel_group_on_dlq(true);
+    let group = Uuid::new_v4();
+
+    // the highest priority member is leased first
+    let failing = jobs.enqueue(new_job(Some(group), 10)).await.unwrap();
+    let rest = [
+        jobs.enqueue(new_job(Some(group), 0)).await.unwrap(),
+        jobs.enqueue(new_job(Some(group), 0)).await.unwrap(),
+    ];
+    let outsider = jobs.enqueue(new_job(None, 0)).await.unwrap();
+
+    let status = jobs.group_status(group).await.unwrap().unwrap();
+    assert_eq!(status.status, "in_progress");
+    assert_eq!(status.total, 3);
+
+    let leased = jobs
+        .lease_one_job("q_group", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("should lease");
+    assert_eq!(leased.id, failing);
+    assert_eq!(leased.group_id, Some(group));
+    jobs.mark_dlq(
+        failing,
+        "worker-a",
+        "NON_RETRYABLE",
+        Some("BAD_INPUT"),
+        None,
+    )
+    .await
+    .unwrap();
+
+    for id in rest {
+        assert_eq!(status_of(&pool, id).await, "canceled");
+    }
+    assert_eq!(status_of(&pool, outsider).await, "queued");
+
+    let status = jobs.group_status(group).await.unwrap().unwrap();
+    assert_eq!(status.status, "failed");
+    assert_eq!(status.by_status["dlq"], 1);
+    assert_eq!(status.by_status["canceled"], 2);
+
+    let audited: Vec<serde_json::Value> = sqlx::query_scalar(
+        "SELECT details_json FROM policy_decisions WHERE decision = 'GROUP_CANCEL'",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(audited.len(), 2);
+    assert_eq!(audited[0]["dlq_job_id"], failing.to_string());
+}
+
+#[tokio::test]
+#[serial]
+async fn attempt_overflow_cancels_the_group_like_any_dlq_move() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone())
+        .with_attempt_overflow_margin(0)
+        .with_cancel_group_on_dlq(true);
+    let group = Uuid::new_v4();
+
+    let poison = jobs.enqueue(new_job(Some(group), 10)).await.unwrap();
+    let rest = jobs.enqueue(new_job(Some(group), 0)).await.unwrap();
+
+    // max_attempts = 3: the fourth attempt overflows
+    let leased = jobs
+        .lease_jobs_batch("q_group", "worker-a", 30, 1)
+        .await
+        .unwrap();
+    assert_eq!(leased[0].id, poison);
+    for _ in 0..3 {
+        attempts.start_attempt(poison, "worker-a").await.unwrap();
+    }
+    let started = attempts
+        .start_attempts_batch(&[leased[0].dataset_id.clone()], &[poison], "worker-a")
+        .await
+        .unwrap();
+    assert!(started.started.is_empty());
+    assert_eq!(started.overflowed, vec![poison]);
+
+    assert_eq!(status_of(&pool, poison).await, "dlq");
+    assert_eq!(status_of(&pool, rest).await, "canceled");
+    let reason: String = sqlx::query_scalar(
+        "SELECT reason_code FROM policy_decisions WHERE job_id = $1 AND decision = 'GROUP_CANCEL'",
+    )
+    .bind(rest)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(reason, "GROUP_MEMBER_DLQ");
+}
+
+#[tokio::test]
+#[serial]
+async fn group_endpoint_reports_group_status() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let group = Uuid::new_v4();
+
+    let a = jobs.enqueue(new_job(Some(group), 10)).await.unwrap();
+    let b = jobs.enqueue(new_job(Some(group), 0)).await.unwrap();
+
+    let get = |id: Uuid| {
+        let app = router(api_state(&pool));
+        async move {
+            let resp = app
+                .oneshot(
+                    Request::get(format!("/groups/{id}"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = resp.status();
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).ok(),
+            )
+        }
+    };
+
+    for id in [a, b] {
+        let leased = jobs
+            .lease_one_job("q_group", "worker-a", 30)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(leased.id, id);
+        jobs.mark_succeeded(id, "worker-a").await.unwrap();
+    }
+
+    let (status, body) = get(group).await;
+    assert_eq!(status, StatusCode::OK);
+    let body = body.unwrap();
+    assert_eq!(body["status"], "succeeded");
+    assert_eq!(body["total"], 2);
+    assert_eq!(body["by_status"]["succeeded"], 2);
+
+    let (status, _) = get(Uuid::new_v4()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use chrono::Utc;
+use common::setup_db;
+use postgresflow::jobs::{JobsRepo, NewJob};
+use serial_test::serial;
+use std::time::{Duration, Instant};
+
+fn new_job(queue: &str) -> NewJob {
+    NewJob {
+        queue: queue.to_string(),
+        job_type: "notify_job".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn waiting_listener_wakes_promptly_on_enqueue() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let waiter = jobs.clone();
+    let waiting = tokio::spawn(async move {
+        let woken = waiter
+            .wait_for_job_notification("q_notify", Duration::from_secs(5))
+            .await
+            .unwrap();
+        (woken, Instant::now())
+    });
+    // let the listener subscribe
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let enqueued_at = Instant::now();
+    jobs.enqueue_now("q_notify", "notify_job", serde_json::json!({}))
+        .await
+        .unwrap();
+    let (woken, woken_at) = waiting.await.unwrap();
+
+    assert!(woken);
+    let latency = woken_at - enqueued_at;
+    assert!(
+        latency < Duration::from_millis(500),
+        "listener woke {latency:?} after the enqueue"
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn notifications_are_per_queue_and_sent_once_runnable() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let mut listener = jobs.listen_for_jobs("q_notify").await.unwrap();
+
+    // another queue's enqueue and a prepared (not yet committed) job stay silent
+    jobs.enqueue(new_job("q_notify_other")).await.unwrap();
+    let token = jobs
+        .prepare_enqueue(new_job("q_notify"), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(300), listener.recv())
+            .await
+            .is_err()
+    );
+
+    assert!(jobs.commit_enqueue(token).await.unwrap());
+    let notification = tokio::time::timeout(Duration::from_secs(5), listener.recv())
+        .await
+        .expect("commit should notify")
+        .unwrap();
+    assert_eq!(notification.channel(), "pgflow_q_notify");
+    assert_eq!(notification.payload(), token.to_string());
+
+    assert!(!jobs
+        .wait_for_job_notification("q_notify", Duration::from_millis(100))
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn enqueues_stay_silent_with_notify_off() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone()).with_notify_on_enqueue(false);
+    let mut listener = jobs.listen_for_jobs("q_notify").await.unwrap();
+
+    jobs.enqueue(new_job("q_notify")).await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(300), listener.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[test]
+fn notify_channel_fits_postgres_identifier_limit() {
+    assert_eq!(JobsRepo::notify_channel("emails"), "pgflow_emails");
+
+    let long = "é".repeat(40);
+    let channel = JobsRepo::notify_channel(&long);
+    assert!(channel.len() <= 63);
+    assert!(channel.starts_with("pgflow_é"));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::{put_job_payload, PutPayloadRequest};
+use serial_test::serial;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn put_payload_replaces_payload_and_records_audit_decision() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_edit").await;
+    let before = state.jobs.get_job(job_id).await.unwrap().unwrap();
+
+    let new_payload = serde_json::json!({ "user_id": 42, "fixed": true });
+    let Json(updated) = put_job_payload(
+        State(state.clone()),
+        Path(job_id),
+        Json(PutPayloadRequest {
+            payload_json: new_payload.clone(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(updated.id, job_id);
+    assert_eq!(updated.payload_json, new_payload);
+
+    let stored = state.jobs.get_job(job_id).await.unwrap().unwrap();
+    assert_eq!(stored.payload_json, new_payload);
+    assert_eq!(stored.status, "queued");
+
+    let rows = state.policy_decisions.list_for_job(job_id).await.unwrap();
+    let audit = rows
+        .iter()
+        .find(|r| r.decision == "MANUAL_EDIT")
+        .expect("expected audit decision");
+    assert_eq!(audit.reason_code, "PAYLOAD_EDITED");
+    assert_eq!(audit.details_json["previous_payload"], before.payload_json);
+    assert_eq!(audit.details_json["payload"], new_payload);
+}
+
+#[tokio::test]
+#[serial]
+async fn put_payload_rejects_running_and_unknown_jobs() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id = insert_job(&pool, "q_edit").await;
+    state
+        .jobs
+        .lease_one_job("q_edit", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+
+    let req = || {
+        Json(PutPayloadRequest {
+            payload_json: serde_json::json!({ "x": 1 }),
+        })
+    };
+
+    let err = put_job_payload(State(state.clone()), Path(job_id), req())
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::CONFLICT);
+
+    let err = put_job_payload(State(state.clone()), Path(Uuid::new_v4()), req())
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::NOT_FOUND);
+
+    let err = put_job_payload(
+        State(state.clone()),
+        Path(job_id),
+        Json(PutPayloadRequest {
+            payload_json: serde_json::json!([1, 2]),
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.0, StatusCode::BAD_REQUEST);
+
+    let rows = state.policy_decisions.list_for_job(job_id).await.unwrap();
+    assert!(rows.iter().all(|r| r.decision != "MANUAL_EDIT"));
+}
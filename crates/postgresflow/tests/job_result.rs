@@ -0,0 +1,75 @@
+mod common;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::get_job;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use serde_json::json;
+use serial_test::serial;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn handler_result_is_stored_on_success_and_served_by_get_job() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let runner = JobRunner::new(
+        state.jobs.clone(),
+        state.attempts.clone(),
+        RetryConfig::default(),
+    );
+
+    let report_id = state
+        .jobs
+        .enqueue_now("q_result", "report", json!({ "month": "2026-09" }))
+        .await
+        .unwrap();
+    let plain_id = state
+        .jobs
+        .enqueue_now("q_result", "report", json!({ "month": "2026-08" }))
+        .await
+        .unwrap();
+
+    let batch = state
+        .jobs
+        .lease_jobs_batch("q_result", "worker-1", 30, 10)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 2);
+    let dataset_id = batch[0].dataset_id.clone();
+
+    let mut updates = Vec::new();
+    for job in &batch {
+        let attempt = state
+            .attempts
+            .start_attempt(job.id, "worker-1")
+            .await
+            .unwrap();
+        updates.push((job.id, attempt.id, 5));
+    }
+    let result = json!({ "url": "https://reports.example/2026-09.pdf" });
+    let results = HashMap::from([(report_id, result.clone())]);
+    runner
+        .on_success_batch_with_results(&dataset_id, &updates, &results, "worker-1")
+        .await
+        .unwrap();
+
+    let Json(report) = get_job(State(state.clone()), Path(report_id))
+        .await
+        .unwrap();
+    assert_eq!(report.status, "succeeded");
+    assert_eq!(report.result_json, Some(result));
+
+    let Json(plain) = get_job(State(state.clone()), Path(plain_id)).await.unwrap();
+    assert_eq!(plain.status, "succeeded");
+    assert_eq!(plain.result_json, None);
+
+    let err = get_job(State(state.clone()), Path(Uuid::new_v4()))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::NOT_FOUND);
+}
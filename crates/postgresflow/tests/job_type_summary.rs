@@ -0,0 +1,55 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::job_types;
+use serde_json::json;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn job_type_summary_counts_statuses_per_type() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let seed = [
+        ("q1", "email", "queued"),
+        ("q1", "email", "queued"),
+        ("q2", "email", "running"),
+        ("q1", "email", "dlq"),
+        ("q1", "resize", "running"),
+        ("q1", "resize", "succeeded"),
+        ("q2", "report", "succeeded"),
+    ];
+    for (queue, job_type, status) in seed {
+        let id = state
+            .jobs
+            .enqueue_now(queue, job_type, json!({}))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE jobs SET status = $2 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let summary = state.jobs.job_type_summary().await.unwrap();
+    assert_eq!(
+        summary,
+        vec![
+            ("email".to_string(), 2, 1, 1),
+            ("report".to_string(), 0, 0, 0),
+            ("resize".to_string(), 0, 1, 0),
+        ]
+    );
+
+    let Json(resp) = job_types(State(state)).await.unwrap();
+    assert_eq!(resp.job_types.len(), 3);
+    assert_eq!(resp.job_types[0].job_type, "email");
+    assert_eq!(resp.job_types[0].queued, 2);
+    assert_eq!(resp.job_types[0].running, 1);
+    assert_eq!(resp.job_types[0].dlq, 1);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::{enqueue_job, EnqueueRequest};
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use postgresflow::jobs::JobTypesRepo;
+use serial_test::serial;
+
+fn enqueue_request(job_type: &str) -> EnqueueRequest {
+    EnqueueRequest {
+        queue: Some("q_types".to_string()),
+        job_type: job_type.to_string(),
+        payload_json: Some(serde_json::json!({})),
+        payload_template: None,
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn unknown_job_type_is_rejected_when_flag_is_on() {
+    let pool = setup_db().await;
+    sqlx::query("DELETE FROM ingest_decisions WHERE queue = 'q_types'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    JobTypesRepo::new(pool.clone())
+        .register(&["send_email".to_string()])
+        .await
+        .unwrap();
+
+    // flag off: anything goes
+    let state = api_state(&pool);
+    let Json(typo) = enqueue_job(State(state.clone()), Json(enqueue_request("send_emial")))
+        .await
+        .unwrap();
+    assert!(state.jobs.get_job(typo.job_id).await.unwrap().is_some());
+
+    let mut state = state;
+    state.enqueue_guard = EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig {
+            reject_unknown_job_types: true,
+            ..EnqueueGuardConfig::default()
+        },
+    );
+
+    let Json(ok) = enqueue_job(State(state.clone()), Json(enqueue_request("send_email")))
+        .await
+        .unwrap();
+    assert!(state.jobs.get_job(ok.job_id).await.unwrap().is_some());
+
+    let err = enqueue_job(State(state.clone()), Json(enqueue_request("send_emial")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.0, StatusCode::BAD_REQUEST);
+    assert!(err.1.contains("UNKNOWN_JOB_TYPE"));
+
+    let decisions = state
+        .ingest_decisions
+        .list_recent(Some("q_types"), 10)
+        .await
+        .unwrap();
+    assert_eq!(decisions.len(), 1);
+    let (_, _, decision, reason_code, details, _) = &decisions[0];
+    assert_eq!(decision, "DENIED");
+    assert_eq!(reason_code, "UNKNOWN_JOB_TYPE");
+    assert_eq!(details["job_type"], "send_emial");
+
+    let stored: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue = 'q_types' AND job_type = $1")
+            .bind("send_emial")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        stored, 1,
+        "only the enqueue made with the flag off is stored"
+    );
+}
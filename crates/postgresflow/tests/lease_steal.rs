@@ -0,0 +1,219 @@
+mod common;
+
+use chrono::Utc;
+use common::setup_db;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, NewJob, PoliciesRepo, PolicyDecisionsRepo};
+use serial_test::serial;
+use uuid::Uuid;
+
+async fn enqueue(jobs: &JobsRepo, queue: &str) -> Uuid {
+    jobs.enqueue(NewJob {
+        queue: queue.to_string(),
+        job_type: "slow_job".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id: None,
+    })
+    .await
+    .unwrap()
+}
+
+/// Complete one attempt quickly so the job_type has a typical latency of 10ms.
+async fn seed_latency(jobs: &JobsRepo, attempts: &AttemptsRepo, queue: &str) {
+    let id = enqueue(jobs, queue).await;
+    let job = jobs
+        .lease_one_job(queue, "worker-seed", 30)
+        .await
+        .unwrap()
+        .expect("seed job should lease");
+    assert_eq!(job.id, id);
+
+    let attempt = attempts.start_attempt(id, "worker-seed").await.unwrap();
+    attempts.finish_succeeded(attempt.id, 10).await.unwrap();
+    jobs.mark_succeeded(id, "worker-seed").await.unwrap();
+}
+
+async fn backdate_lock(pool: &sqlx::PgPool, job_id: Uuid, secs: i64) {
+    sqlx::query(
+        "UPDATE jobs SET locked_at = locked_at - ($2::bigint * interval '1 second') WHERE id = $1",
+    )
+    .bind(job_id)
+    .bind(secs)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn stale_but_unexpired_lease_can_be_stolen_when_enabled() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let decisions = PolicyDecisionsRepo::new(pool.clone());
+
+    PoliciesRepo::new(pool.clone())
+        .set_lease_stealing("q_steal", Some(2.0))
+        .await
+        .unwrap();
+
+    seed_latency(&jobs, &attempts, "q_steal").await;
+
+    let slow_id = enqueue(&jobs, "q_steal").await;
+    let leased = jobs
+        .lease_one_job("q_steal", "worker-slow", 300)
+        .await
+        .unwrap()
+        .expect("slow job should lease");
+    assert_eq!(leased.id, slow_id);
+
+    // a worker never steals its own lease
+    assert!(jobs
+        .steal_stale_lease("q_steal", "worker-slow", 30)
+        .await
+        .unwrap()
+        .is_none());
+
+    // 1s running vs 2 x 10ms typical latency; lease still has ~299s left
+    backdate_lock(&pool, slow_id, 1).await;
+
+    let stolen = jobs
+        .steal_stale_lease("q_steal", "worker-fast", 30)
+        .await
+        .unwrap()
+        .expect("stale lease should be stolen");
+    assert_eq!(stolen.id, slow_id);
+    assert_eq!(stolen.status, "running");
+    assert_eq!(stolen.locked_by.as_deref(), Some("worker-fast"));
+
+    let rows = decisions.list_for_job(slow_id).await.unwrap();
+    let steal = rows.last().expect("steal decision should be recorded");
+    assert_eq!(steal.decision, "STEAL");
+    assert_eq!(steal.reason_code, "LEASE_STOLEN");
+    assert_eq!(steal.details_json["previous_worker_id"], "worker-slow");
+
+    // the original worker can no longer complete the job
+    jobs.mark_succeeded(slow_id, "worker-slow").await.unwrap();
+    let job = jobs.get_job(slow_id).await.unwrap().unwrap();
+    assert_eq!(job.status, "running");
+    assert_eq!(job.locked_by.as_deref(), Some("worker-fast"));
+}
+
+#[tokio::test]
+#[serial]
+async fn original_workers_retryable_failure_leaves_the_stolen_job_alone() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    PoliciesRepo::new(pool.clone())
+        .set_lease_stealing("q_steal_fail", Some(2.0))
+        .await
+        .unwrap();
+
+    seed_latency(&jobs, &attempts, "q_steal_fail").await;
+
+    let slow_id = enqueue(&jobs, "q_steal_fail").await;
+    jobs.lease_one_job("q_steal_fail", "worker-slow", 300)
+        .await
+        .unwrap()
+        .expect("slow job should lease");
+    let slow_attempt = attempts
+        .start_attempt(slow_id, "worker-slow")
+        .await
+        .unwrap();
+    backdate_lock(&pool, slow_id, 1).await;
+
+    jobs.steal_stale_lease("q_steal_fail", "worker-fast", 30)
+        .await
+        .unwrap()
+        .expect("stale lease should be stolen");
+
+    // the slow worker's handler times out; attempts remain, so it would retry
+    runner
+        .on_failure(
+            slow_id,
+            slow_attempt.id,
+            "worker-slow",
+            1000,
+            "TIMEOUT",
+            "slow upstream",
+            slow_attempt.attempt_no,
+            3,
+        )
+        .await
+        .unwrap();
+
+    let job = jobs.get_job(slow_id).await.unwrap().unwrap();
+    assert_eq!(job.status, "running");
+    assert_eq!(job.locked_by.as_deref(), Some("worker-fast"));
+    assert!(jobs
+        .lease_one_job("q_steal_fail", "worker-third", 30)
+        .await
+        .unwrap()
+        .is_none());
+
+    jobs.mark_succeeded(slow_id, "worker-fast").await.unwrap();
+    let job = jobs.get_job(slow_id).await.unwrap().unwrap();
+    assert_eq!(job.status, "succeeded");
+}
+
+#[tokio::test]
+#[serial]
+async fn leases_are_not_stolen_unless_queue_opts_in() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policies = PoliciesRepo::new(pool.clone());
+
+    seed_latency(&jobs, &attempts, "q_nosteal").await;
+
+    let slow_id = enqueue(&jobs, "q_nosteal").await;
+    jobs.lease_one_job("q_nosteal", "worker-slow", 300)
+        .await
+        .unwrap()
+        .expect("slow job should lease");
+    backdate_lock(&pool, slow_id, 1).await;
+
+    assert!(jobs
+        .steal_stale_lease("q_nosteal", "worker-fast", 30)
+        .await
+        .unwrap()
+        .is_none());
+
+    // enabling then disabling again keeps the lease safe; the policy the
+    // worker leases with tells it whether to try stealing at all
+    policies
+        .set_lease_stealing("q_nosteal", Some(2.0))
+        .await
+        .unwrap();
+    let (_, policy) = jobs
+        .lease_jobs_batch_with_policy("q_nosteal", "worker-fast", 30, 1)
+        .await
+        .unwrap();
+    assert!(policy.unwrap().steal_enabled);
+    policies
+        .set_lease_stealing("q_nosteal", None)
+        .await
+        .unwrap();
+    let (_, policy) = jobs
+        .lease_jobs_batch_with_policy("q_nosteal", "worker-fast", 30, 1)
+        .await
+        .unwrap();
+    assert!(!policy.unwrap().steal_enabled);
+
+    assert!(jobs
+        .steal_stale_lease("q_nosteal", "worker-fast", 30)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -1,9 +1,9 @@
 // tests/leasing.rs
 mod common;
 
-use common::{insert_job, setup_db};
+use common::{api_state, insert_job, setup_db};
 
-use postgresflow::jobs::JobsRepo;
+use postgresflow::jobs::{JobsRepo, NewJob, PoliciesRepo};
 use sqlx::PgPool;
 use std::collections::HashSet;
 use std::time::Duration;
@@ -133,6 +133,80 @@ async fn lease_expires_then_other_worker_can_claim() {
     assert_eq!(leased_b.locked_by.as_deref(), Some("worker-b"));
 }
 
+async fn prom_locks_reaped(state: &postgresflow::api::ApiState, queue: &str) -> Option<i64> {
+    let resp = postgresflow::api::metrics_prom(axum::extract::State(state.clone())).await;
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let prefix = format!("pgflow_locks_reaped_total{{queue=\"{queue}\"}} ");
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find_map(|l| l.strip_prefix(&prefix).map(|v| v.parse().unwrap()))
+}
+
+#[tokio::test]
+#[serial]
+async fn reaping_an_expired_lease_increments_locks_reaped_counter() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let repo = JobsRepo::new(pool.clone());
+
+    insert_job(&pool, "q_reap").await;
+    insert_job(&pool, "q_healthy").await;
+    repo.lease_one_job("q_reap", "worker-a", 1)
+        .await
+        .unwrap()
+        .expect("worker-a should lease job");
+    repo.lease_one_job("q_healthy", "worker-b", 30)
+        .await
+        .unwrap()
+        .expect("worker-b should lease job");
+    assert_eq!(prom_locks_reaped(&state, "q_reap").await, None);
+
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    assert_eq!(repo.reap_expired_locks().await.unwrap(), 1);
+    assert_eq!(prom_locks_reaped(&state, "q_reap").await, Some(1));
+    assert_eq!(prom_locks_reaped(&state, "q_healthy").await, None);
+
+    // nothing left to reap: the counter doesn't move
+    assert_eq!(repo.reap_expired_locks().await.unwrap(), 0);
+    assert_eq!(prom_locks_reaped(&state, "q_reap").await, Some(1));
+}
+
+#[tokio::test]
+#[serial]
+async fn extended_lease_survives_past_original_expiry() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+
+    let job_id = insert_job(&pool, "default").await;
+    repo.lease_one_job("default", "worker-a", 1)
+        .await
+        .unwrap()
+        .expect("worker-a should lease job");
+
+    // handler checkpoint (JobContext::extend_lease) before the 1s lease runs out
+    assert!(repo.extend_lease(job_id, "worker-a", 30).await.unwrap());
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+
+    assert_eq!(repo.reap_expired_locks().await.unwrap(), 0);
+    assert!(repo
+        .lease_one_job("default", "worker-b", 30)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        get_job_status_and_locked_by(&pool, job_id).await,
+        ("running".to_string(), Some("worker-a".to_string()))
+    );
+
+    // someone else's lease can't be extended, and a lost lease reports false
+    assert!(!repo.extend_lease(job_id, "worker-b", 30).await.unwrap());
+    repo.mark_succeeded(job_id, "worker-a").await.unwrap();
+    assert!(!repo.extend_lease(job_id, "worker-a", 30).await.unwrap());
+}
+
 #[tokio::test]
 #[serial]
 async fn leasing_respects_priority_then_run_at() {
@@ -168,6 +242,202 @@ async fn leasing_respects_priority_then_run_at() {
     assert_eq!(locked_by, None);
 }
 
+#[tokio::test]
+#[serial]
+async fn fifo_within_priority_keeps_retried_job_ahead_of_fresh_job() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+    PoliciesRepo::new(pool.clone())
+        .set_fifo_within_priority("q_fifo", true)
+        .await
+        .unwrap();
+
+    for queue in ["q_default_order", "q_fifo"] {
+        let retried = insert_job_with(&pool, queue, "retried", -10, 0).await;
+        let fresh = insert_job_with(&pool, queue, "fresh", -5, 0).await;
+
+        // a retry pushes run_at past the fresh job's, but it stays runnable
+        sqlx::query("UPDATE jobs SET run_at = now() - interval '1 second' WHERE id = $1")
+            .bind(retried)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let first = repo
+            .lease_one_job(queue, "worker-a", 30)
+            .await
+            .unwrap()
+            .expect("expected a job");
+
+        if queue == "q_fifo" {
+            assert_eq!(first.id, retried, "fifo: enqueue order wins");
+        } else {
+            assert_eq!(first.id, fresh, "default: earlier run_at wins");
+        }
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn successive_batches_rotate_across_datasets() {
+    let pool = setup_db().await;
+
+    // two hourly datasets, each with more jobs than several batches can drain
+    for hours_ago in [2, 1] {
+        let run_at = Utc::now() - ChronoDuration::hours(hours_ago);
+        for _ in 0..40 {
+            JobsRepo::new(pool.clone())
+                .enqueue(NewJob {
+                    queue: "q_rr".to_string(),
+                    job_type: "work".to_string(),
+                    payload_json: serde_json::json!({}),
+                    run_at,
+                    priority: 0,
+                    max_attempts: 3,
+                    target_worker_id: None,
+                    retry: None,
+                    dedupe_key: None,
+                    idempotency_key: None,
+                    group_id: None,
+                })
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn served_datasets(repo: &JobsRepo, worker_id: &str) -> Vec<String> {
+        let mut served = Vec::new();
+        for _ in 0..4 {
+            let batch = repo
+                .lease_jobs_batch("q_rr", worker_id, 30, 5)
+                .await
+                .unwrap();
+            assert_eq!(batch.len(), 5);
+            served.push(batch[0].dataset_id.clone());
+        }
+        served
+    }
+
+    let rr = served_datasets(
+        &JobsRepo::new(pool.clone()).with_dataset_round_robin(true),
+        "worker-rr",
+    )
+    .await;
+    assert_ne!(rr[0], rr[1]);
+    assert_eq!(rr[0], rr[2]);
+    assert_eq!(rr[1], rr[3]);
+
+    // without round-robin (the default) the oldest dataset is drained first
+    let head = served_datasets(&JobsRepo::new(pool.clone()), "worker-head").await;
+    assert!(head.iter().all(|d| *d == head[0]), "got {head:?}");
+}
+
+#[tokio::test]
+#[serial]
+async fn batches_span_datasets_only_when_single_dataset_is_off() {
+    let pool = setup_db().await;
+
+    async fn enqueue_two_datasets(pool: &PgPool, queue: &str) {
+        for hours_ago in [2, 1] {
+            for _ in 0..3 {
+                JobsRepo::new(pool.clone())
+                    .enqueue(NewJob {
+                        queue: queue.to_string(),
+                        job_type: "work".to_string(),
+                        payload_json: serde_json::json!({}),
+                        run_at: Utc::now() - ChronoDuration::hours(hours_ago),
+                        priority: 0,
+                        max_attempts: 3,
+                        target_worker_id: None,
+                        retry: None,
+                        dedupe_key: None,
+                        idempotency_key: None,
+                        group_id: None,
+                    })
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
+    enqueue_two_datasets(&pool, "q_single").await;
+    let batch = JobsRepo::new(pool.clone())
+        .lease_jobs_batch("q_single", "worker-1", 30, 10)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 3, "one dataset per batch");
+    assert!(batch.iter().all(|j| j.dataset_id == batch[0].dataset_id));
+
+    enqueue_two_datasets(&pool, "q_cross").await;
+    let batch = JobsRepo::new(pool.clone())
+        .with_single_dataset_batches(false)
+        .lease_jobs_batch("q_cross", "worker-1", 30, 10)
+        .await
+        .unwrap();
+    assert_eq!(batch.len(), 6, "all runnable jobs in one batch");
+    let datasets: HashSet<_> = batch.iter().map(|j| &j.dataset_id).collect();
+    assert_eq!(datasets.len(), 2);
+    // oldest run_at first across datasets
+    assert!(batch.windows(2).all(|w| w[0].run_at <= w[1].run_at));
+}
+
+#[tokio::test]
+#[serial]
+async fn cross_dataset_batches_stay_within_max_batch_datasets() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone())
+        .with_single_dataset_batches(false)
+        .with_max_batch_datasets(2);
+
+    for hours_ago in [4, 3, 2, 1] {
+        for _ in 0..3 {
+            repo.enqueue(NewJob {
+                queue: "q_capped".to_string(),
+                job_type: "work".to_string(),
+                payload_json: serde_json::json!({}),
+                run_at: Utc::now() - ChronoDuration::hours(hours_ago),
+                priority: 0,
+                max_attempts: 3,
+                target_worker_id: None,
+                retry: None,
+                dedupe_key: None,
+                idempotency_key: None,
+                group_id: None,
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    // the worker runs one batch at a time, so a batch's datasets are all it
+    // has in flight
+    let mut leased = 0;
+    let mut seen = HashSet::new();
+    loop {
+        let batch = repo
+            .lease_jobs_batch("q_capped", "worker-1", 30, 100)
+            .await
+            .unwrap();
+        if batch.is_empty() {
+            break;
+        }
+        let datasets: HashSet<_> = batch.iter().map(|j| j.dataset_id.clone()).collect();
+        assert!(
+            datasets.len() <= 2,
+            "batch spans {} datasets",
+            datasets.len()
+        );
+        assert!(
+            datasets.is_disjoint(&seen),
+            "a dataset was split across batches"
+        );
+        seen.extend(datasets);
+        leased += batch.len();
+    }
+    assert_eq!(leased, 12);
+    assert_eq!(seen.len(), 4);
+}
+
 #[tokio::test]
 #[serial]
 async fn delayed_job_is_not_leased_before_run_at() {
@@ -273,3 +543,65 @@ async fn batch_leasing_claims_expected_count_without_duplicates() {
         "the same job was leased in two batches"
     );
 }
+
+async fn enqueue_pinned(repo: &JobsRepo, target_worker_id: &str) -> Uuid {
+    repo.enqueue(NewJob {
+        queue: "default".to_string(),
+        job_type: "pinned".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 5,
+        target_worker_id: Some(target_worker_id.to_string()),
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id: None,
+    })
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn pinned_job_is_only_leased_by_target_worker() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+
+    let job_id = enqueue_pinned(&repo, "worker-b").await;
+
+    let other = repo.lease_one_job("default", "worker-a", 30).await.unwrap();
+    assert!(
+        other.is_none(),
+        "non-target worker must not lease a pinned job"
+    );
+
+    let (status, locked_by) = get_job_status_and_locked_by(&pool, job_id).await;
+    assert_eq!(status, "queued");
+    assert_eq!(locked_by, None);
+
+    let target = repo
+        .lease_one_job("default", "worker-b", 30)
+        .await
+        .unwrap()
+        .expect("target worker should lease its pinned job");
+    assert_eq!(target.id, job_id);
+    assert_eq!(target.target_worker_id.as_deref(), Some("worker-b"));
+}
+
+#[tokio::test]
+#[serial]
+async fn pinned_job_becomes_leasable_by_anyone_after_pin_timeout() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone()).with_pin_timeout_secs(0);
+
+    let job_id = enqueue_pinned(&repo, "dead-worker").await;
+
+    let leased = repo
+        .lease_one_job("default", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("pin should have timed out");
+    assert_eq!(leased.id, job_id);
+    assert_eq!(leased.locked_by.as_deref(), Some("worker-a"));
+}
@@ -5,10 +5,16 @@ use uuid::Uuid;
 mod common;
 use common::setup_db;
 
+use axum::body::to_bytes;
+use axum::extract::State;
+use common::api_state;
+use postgresflow::api::metrics_prom;
 use postgresflow::jobs::maintenance::MaintenanceRepo;
 use postgresflow::jobs::JobsRepo;
+use serial_test::serial;
 
 #[tokio::test]
+#[serial]
 async fn archives_old_succeeded_jobs_and_prunes_history() {
     let pool = setup_db().await;
 
@@ -156,3 +162,296 @@ async fn archives_old_succeeded_jobs_and_prunes_history() {
         .unwrap();
     assert_eq!(archived_count, 1);
 }
+
+async fn insert_succeeded_at(pool: &sqlx::PgPool, at: chrono::DateTime<Utc>) {
+    sqlx::query(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts, created_at, updated_at)
+        VALUES ('default', 'ok_job', '{}'::jsonb, $1, 'succeeded', 0, 25, $1, $1)
+        "#,
+    )
+    .bind(at)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+async fn prom_archive_backlog(state: &postgresflow::api::ApiState) -> i64 {
+    let resp = metrics_prom(State(state.clone())).await;
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    body.lines()
+        .find_map(|l| l.strip_prefix("pgflow_archive_backlog "))
+        .expect("gauge should be exported")
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn archive_backlog_gauge_tracks_unarchived_old_jobs() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let maint = MaintenanceRepo::new(pool.clone());
+
+    for _ in 0..3 {
+        insert_succeeded_at(&pool, Utc::now() - Duration::days(30)).await;
+    }
+    // recent successes are not backlog yet
+    insert_succeeded_at(&pool, Utc::now()).await;
+
+    assert_eq!(prom_archive_backlog(&state).await, 3);
+
+    // a batch smaller than the backlog leaves the rest behind
+    let cutoff = Utc::now() - Duration::days(7);
+    let archived = maint.archive_succeeded_older_than(cutoff, 2).await.unwrap();
+    assert_eq!(archived, 2);
+    assert_eq!(prom_archive_backlog(&state).await, 1);
+
+    maint
+        .archive_succeeded_older_than(cutoff, 500)
+        .await
+        .unwrap();
+    assert_eq!(prom_archive_backlog(&state).await, 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn heavy_maintenance_only_runs_inside_configured_window() {
+    use chrono::FixedOffset;
+    use postgresflow::jobs::maintenance::{run_heavy_maintenance, MaintenanceWindow};
+
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let maint = MaintenanceRepo::new(pool.clone());
+
+    // trg_jobs_updated_at bumps updated_at on UPDATE, so insert it already old
+    let job_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts,
+                          created_at, updated_at)
+        VALUES ('default', 'ok_job', '{}'::jsonb, now(), 'succeeded', 0, 25,
+                now() - interval '30 days', now() - interval '30 days')
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let now = Utc::now();
+    let utc = FixedOffset::east_opt(0).unwrap();
+    let hhmm = |offset_hours: i64| (now + Duration::hours(offset_hours)).format("%H:%M");
+    // "02:00-04:00"-style windows relative to now (either may wrap midnight)
+    let outside = MaintenanceWindow::parse(&format!("{}-{}", hhmm(1), hhmm(2)), utc).unwrap();
+    let inside = MaintenanceWindow::parse(&format!("{}-{}", hhmm(-1), hhmm(1)), utc).unwrap();
+
+    let skipped = run_heavy_maintenance(&maint, Some(&outside), now, 7, 7)
+        .await
+        .unwrap();
+    assert!(skipped.is_none());
+    assert!(jobs.get_job(job_id).await.unwrap().is_some());
+
+    let pass = run_heavy_maintenance(&maint, Some(&inside), now, 7, 7)
+        .await
+        .unwrap()
+        .expect("pass should run inside the window");
+    assert_eq!(pass.archived, 1);
+    assert!(jobs.get_job(job_id).await.unwrap().is_none());
+
+    // the same wall-clock window at another offset is a different UTC range
+    let plus_three = FixedOffset::east_opt(3 * 3600).unwrap();
+    let shifted =
+        MaintenanceWindow::parse(&format!("{}-{}", hhmm(-1), hhmm(1)), plus_three).unwrap();
+    assert!(!shifted.contains(now));
+    assert!(MaintenanceWindow::parse("02:00", utc).is_err());
+    assert!(MaintenanceWindow::parse("02:00-02:00", utc).is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn archive_queue_moves_all_terminal_jobs_of_a_retired_queue() {
+    use postgresflow::jobs::system_flags::{queue_enqueue_flag, SystemFlagsRepo};
+
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let maint = MaintenanceRepo::new(pool.clone());
+
+    let insert = |queue: &'static str, status: &'static str| {
+        let pool = pool.clone();
+        async move {
+            let id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts,
+                                  dlq_reason_code, dlq_error_code)
+                VALUES ($1, 'ok_job', '{}'::jsonb, now(), $2, 0, 25,
+                        CASE WHEN $2 = 'dlq' THEN 'MAX_ATTEMPTS_EXCEEDED' END,
+                        CASE WHEN $2 = 'dlq' THEN 'TIMEOUT' END)
+                RETURNING id
+                "#,
+            )
+            .bind(queue)
+            .bind(status)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            id
+        }
+    };
+
+    // recent jobs: archive_queue ignores age
+    insert("q_retire", "succeeded").await;
+    insert("q_retire", "canceled").await;
+    let dlq_id = insert("q_retire", "dlq").await;
+    let failed_id = insert("q_retire", "failed").await;
+    let queued_id = insert("q_retire", "queued").await;
+    let other_id = insert("q_keep", "succeeded").await;
+
+    // retire-queue pauses enqueue for just that queue
+    SystemFlagsRepo::new(pool.clone())
+        .set(&queue_enqueue_flag("q_retire"), false)
+        .await
+        .unwrap();
+    assert!(state.enqueue_guard.check_enabled("q_retire").await.is_err());
+    assert!(state.enqueue_guard.check_enabled("q_keep").await.is_ok());
+
+    assert_eq!(maint.queue_pending("q_retire").await.unwrap(), 1);
+    assert_eq!(maint.archive_queue("q_retire", false).await.unwrap(), 2);
+    assert!(state.jobs.get_job(dlq_id).await.unwrap().is_some());
+    assert!(state.jobs.get_job(failed_id).await.unwrap().is_some());
+
+    // drain the last one, then archive the rest including the DLQ
+    sqlx::query("UPDATE jobs SET status = 'succeeded' WHERE id = $1")
+        .bind(queued_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(maint.queue_pending("q_retire").await.unwrap(), 0);
+    assert_eq!(maint.archive_queue("q_retire", true).await.unwrap(), 3);
+
+    let left: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue = 'q_retire'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(left, 0);
+    let archived: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM jobs_archive WHERE queue = 'q_retire'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(archived, 5);
+    let dlq_error_code: Option<String> =
+        sqlx::query_scalar("SELECT dlq_error_code FROM jobs_archive WHERE id = $1")
+            .bind(dlq_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(dlq_error_code.as_deref(), Some("TIMEOUT"));
+
+    // other queues are untouched
+    assert!(state.jobs.get_job(other_id).await.unwrap().is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn prunes_old_enqueue_rate_counters_and_keeps_current_window() {
+    use chrono::DurationRound;
+    use postgresflow::jobs::maintenance::RATE_COUNTER_RETENTION_MINUTES;
+
+    let pool = setup_db().await;
+    let maint = MaintenanceRepo::new(pool.clone());
+    sqlx::query("DELETE FROM enqueue_rate_counters WHERE queue = 'q_counters'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let now = Utc::now();
+    let current = now.duration_trunc(Duration::minutes(1)).unwrap();
+    for minutes_ago in [0, 1, 5, 60, 24 * 60] {
+        sqlx::query(
+            "INSERT INTO enqueue_rate_counters (queue, window_start, count) VALUES ('q_counters', $1, 3)",
+        )
+        .bind(current - Duration::minutes(minutes_ago))
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let deleted = maint
+        .prune_rate_counters(now - Duration::minutes(RATE_COUNTER_RETENTION_MINUTES))
+        .await
+        .unwrap();
+    assert!(
+        deleted >= 3,
+        "expected the 5m/1h/1d buckets pruned, got {deleted}"
+    );
+
+    // current and previous minute survive: the sliding window still needs them
+    let left: Vec<chrono::DateTime<Utc>> = sqlx::query_scalar(
+        "SELECT window_start FROM enqueue_rate_counters WHERE queue = 'q_counters' ORDER BY window_start",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(left, vec![current - Duration::minutes(1), current]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[serial]
+async fn history_pruning_alongside_leasing_does_not_deadlock() {
+    let pool = setup_db().await;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+    // create the queue's dataset partition up front: this test is about
+    // lock ordering, not concurrent partition creation
+    JobsRepo::new(pool.clone())
+        .enqueue_now("q_churn", "ok_job", json!({}))
+        .await
+        .unwrap();
+
+    // workers: enqueue, edit (jobs -> policy_decisions), lease, attempt
+    // (jobs -> job_attempts), succeed, reap
+    let mut tasks = tokio::task::JoinSet::new();
+    for w in 0..4 {
+        let pool = pool.clone();
+        tasks.spawn(async move {
+            let jobs = JobsRepo::new(pool.clone());
+            let attempts = postgresflow::jobs::AttemptsRepo::new(pool);
+            let worker_id = format!("worker-{w}");
+            while std::time::Instant::now() < deadline {
+                let id = jobs.enqueue_now("q_churn", "ok_job", json!({})).await?;
+                jobs.update_payload(id, json!({ "by": worker_id })).await?;
+                if let Some(job) = jobs.lease_one_job("q_churn", &worker_id, 30).await? {
+                    let attempt = attempts.start_attempt(job.id, &worker_id).await?;
+                    attempts.finish_succeeded(attempt.id, 1).await?;
+                    jobs.mark_succeeded(job.id, &worker_id).await?;
+                }
+                jobs.reap_expired_locks().await?;
+            }
+            anyhow::Ok(())
+        });
+    }
+
+    // maintenance: everything succeeded so far is past the cutoff
+    {
+        let maint = MaintenanceRepo::new(pool.clone());
+        tasks.spawn(async move {
+            let mut pruned = 0;
+            while std::time::Instant::now() < deadline {
+                let cutoff = Utc::now() + Duration::hours(1);
+                let (attempts, decisions) = maint
+                    .delete_history_for_succeeded_older_than(cutoff, 50)
+                    .await?;
+                pruned += attempts + decisions;
+                maint.archive_succeeded_older_than(cutoff, 50).await?;
+            }
+            anyhow::ensure!(pruned > 0, "maintenance never pruned anything");
+            anyhow::Ok(())
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        if let Err(e) = joined.unwrap() {
+            panic!("concurrent maintenance failed (deadlock?): {e:#}");
+        }
+    }
+}
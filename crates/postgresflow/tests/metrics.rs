@@ -0,0 +1,397 @@
+mod common;
+
+use common::setup_db;
+use postgresflow::jobs::metrics::DEFAULT_METRICS_WINDOW_SECS;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, MetricsRepo, PoliciesRepo};
+use serde_json::json;
+use serial_test::serial;
+
+async fn fail_one(jobs: &JobsRepo, attempts: &AttemptsRepo, queue: &str, code: &str) {
+    jobs.enqueue_now(queue, "fail_me", json!({})).await.unwrap();
+
+    let job = jobs
+        .lease_one_job(queue, "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+
+    let attempt = attempts.start_attempt(job.id, "worker-1").await.unwrap();
+    attempts
+        .finish_failed(attempt.id, 5, code, "boom")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn failures_are_grouped_by_error_code_per_queue() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let metrics = MetricsRepo::new(pool.clone());
+
+    fail_one(&jobs, &attempts, "default", "TIMEOUT").await;
+    fail_one(&jobs, &attempts, "default", "TIMEOUT").await;
+    fail_one(&jobs, &attempts, "default", "RATE_LIMIT").await;
+    fail_one(&jobs, &attempts, "other", "BAD_PAYLOAD").await;
+
+    let m = metrics
+        .snapshot_for_queue("default", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    let counts: Vec<(String, i64)> = m
+        .failures_by_error_code
+        .iter()
+        .map(|f| (f.error_code.clone(), f.count))
+        .collect();
+
+    assert_eq!(
+        counts,
+        vec![("RATE_LIMIT".to_string(), 1), ("TIMEOUT".to_string(), 2)]
+    );
+
+    let other = metrics
+        .snapshot_for_queue("other", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(other.failures_by_error_code.len(), 1);
+    assert_eq!(other.failures_by_error_code[0].error_code, "BAD_PAYLOAD");
+    assert_eq!(other.failures_by_error_code[0].count, 1);
+
+    let resp =
+        postgresflow::api::metrics_prom(axum::extract::State(common::api_state(&pool))).await;
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text
+        .contains("pgflow_attempt_failures_total{queue=\"default\",error_code=\"TIMEOUT\"} 2\n"));
+}
+
+#[tokio::test]
+#[serial]
+async fn status_counts_are_grouped_by_queue() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let seed = [
+        ("alpha", "queued"),
+        ("alpha", "queued"),
+        ("alpha", "running"),
+        ("alpha", "dlq"),
+        ("beta", "succeeded"),
+        ("beta", "succeeded"),
+        ("beta", "succeeded"),
+        ("beta", "failed"),
+    ];
+    for (queue, status) in seed {
+        let id = jobs
+            .enqueue_now(queue, "count_me", json!({}))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE jobs SET status = $2 WHERE id = $1")
+            .bind(id)
+            .bind(status)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let counts = jobs.status_counts_by_queue().await.unwrap();
+    let queues: Vec<&str> = counts.iter().map(|(q, _)| q.as_str()).collect();
+    assert_eq!(queues, vec!["alpha", "beta"]);
+
+    let alpha = &counts[0].1;
+    assert_eq!(alpha.get("queued"), Some(&2));
+    assert_eq!(alpha.get("running"), Some(&1));
+    assert_eq!(alpha.get("dlq"), Some(&1));
+    assert_eq!(alpha.get("succeeded"), None);
+
+    let beta = &counts[1].1;
+    assert_eq!(beta.get("succeeded"), Some(&3));
+    assert_eq!(beta.get("failed"), Some(&1));
+    assert_eq!(beta.len(), 2);
+
+    let (queued, running, _, _) = jobs.metrics_snapshot().await.unwrap();
+    assert_eq!((queued, running), (2, 1));
+}
+
+async fn succeed_on_attempt(jobs: &JobsRepo, attempts: &AttemptsRepo, attempt_no: i32) {
+    let job_id = jobs
+        .enqueue_now("default", "flaky", json!({}))
+        .await
+        .unwrap();
+
+    for _ in 1..attempt_no {
+        let a = attempts.start_attempt(job_id, "worker-1").await.unwrap();
+        attempts
+            .finish_failed(a.id, 5, "TIMEOUT", "retry me")
+            .await
+            .unwrap();
+    }
+
+    let last = attempts.start_attempt(job_id, "worker-1").await.unwrap();
+    assert_eq!(last.attempt_no, attempt_no);
+    attempts.finish_succeeded(last.id, 5).await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn attempts_to_success_histogram_places_jobs_in_buckets() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let metrics = MetricsRepo::new(pool.clone());
+
+    for n in [1, 1, 2, 4, 7, 30] {
+        succeed_on_attempt(&jobs, &attempts, n).await;
+    }
+
+    let h = metrics.attempts_to_success(60).await.unwrap();
+    let buckets: Vec<(i32, i64)> = h.buckets.iter().map(|b| (b.le, b.count)).collect();
+
+    assert_eq!(
+        buckets,
+        vec![(1, 2), (2, 3), (3, 3), (5, 4), (10, 5), (25, 5)]
+    );
+    assert_eq!(h.count, 6);
+    assert_eq!(h.sum, 1 + 1 + 2 + 4 + 7 + 30);
+}
+
+#[tokio::test]
+#[serial]
+async fn snapshot_reports_in_flight_against_policy_cap() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let metrics = MetricsRepo::new(pool.clone());
+    PoliciesRepo::new(pool.clone())
+        .upsert_policy("capped", 1000, 5, 100)
+        .await
+        .unwrap();
+
+    for _ in 0..4 {
+        jobs.enqueue_now("capped", "work", json!({})).await.unwrap();
+    }
+    let leased = jobs
+        .lease_jobs_batch("capped", "worker-1", 30, 3)
+        .await
+        .unwrap();
+    assert_eq!(leased.len(), 3);
+
+    let m = metrics
+        .snapshot_for_queue("capped", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(m.in_flight, 3);
+    assert_eq!(m.max_in_flight, Some(5));
+
+    jobs.enqueue_now("uncapped", "work", json!({}))
+        .await
+        .unwrap();
+    let m = metrics
+        .snapshot_for_queue("uncapped", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(m.in_flight, 0);
+    assert_eq!(m.max_in_flight, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn mean_wait_measures_time_from_enqueue_to_first_attempt() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let metrics = MetricsRepo::new(pool.clone());
+
+    let id = jobs
+        .enqueue_now("q_wait", "wait_me", json!({}))
+        .await
+        .unwrap();
+    // pretend the job sat in the queue for 5s before a worker picked it up
+    sqlx::query("UPDATE jobs SET created_at = now() - interval '5 seconds' WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let job = jobs
+        .lease_one_job("q_wait", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    let attempt = attempts.start_attempt(job.id, "worker-1").await.unwrap();
+    attempts.finish_succeeded(attempt.id, 10).await.unwrap();
+
+    let m = metrics
+        .snapshot_for_queue("q_wait", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert!(
+        (5000.0..6000.0).contains(&m.mean_wait_ms),
+        "mean_wait_ms = {}",
+        m.mean_wait_ms
+    );
+    // handler time is tracked separately
+    assert_eq!(m.mean_latency_ms, 10.0);
+
+    let empty = metrics
+        .snapshot_for_queue("q_idle", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(empty.mean_wait_ms, 0.0);
+}
+
+#[tokio::test]
+#[serial]
+async fn snapshot_all_covers_every_queue_in_order() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    // more queues than the concurrency limit, each with a distinct depth
+    let queues: Vec<String> = (0..20).map(|i| format!("q_snap_{i:02}")).collect();
+    for (i, queue) in queues.iter().enumerate() {
+        for _ in 0..=i {
+            jobs.enqueue_now(queue, "work", json!({})).await.unwrap();
+        }
+    }
+
+    let metrics = MetricsRepo::new(pool.clone()).with_snapshot_concurrency(3);
+    let all = metrics
+        .snapshot_all(DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = all.iter().map(|m| m.queue.as_str()).collect();
+    assert_eq!(names, queues.iter().map(String::as_str).collect::<Vec<_>>());
+    for (i, m) in all.iter().enumerate() {
+        assert_eq!(m.runnable_queue_depth, i as i64 + 1, "queue {}", m.queue);
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn prom_reports_saturated_handler_permits() {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+    use tower::ServiceExt;
+
+    let pool = setup_db().await;
+    let state = common::api_state(&pool);
+    let sem = Arc::new(Semaphore::new(2));
+    state.handler_permits.track("email_send", sem.clone(), 2);
+    let _held = sem.clone().acquire_many_owned(2).await.unwrap();
+
+    let resp = postgresflow::api::router(state)
+        .oneshot(Request::get("/metrics/prom").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+
+    assert!(text.contains("pgflow_handler_permits_available{job_type=\"email_send\"} 0\n"));
+    assert!(text.contains("pgflow_handler_permits_total{job_type=\"email_send\"} 2\n"));
+}
+
+#[tokio::test]
+#[serial]
+async fn attempts_started_per_sec_counts_retries_in_the_window() {
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+
+    // 4 failed first attempts, then 2 retries started (still running)
+    for _ in 0..4 {
+        fail_one(&jobs, &attempts, "q_load", "TIMEOUT").await;
+    }
+    sqlx::query("UPDATE jobs SET status = 'queued', run_at = now() WHERE queue = 'q_load'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        let job = jobs
+            .lease_one_job("q_load", "worker-1", 30)
+            .await
+            .unwrap()
+            .unwrap();
+        attempts.start_attempt(job.id, "worker-1").await.unwrap();
+    }
+    // an attempt from before the window doesn't count
+    sqlx::query(
+        r#"
+        UPDATE job_attempts SET started_at = now() - interval '5 minutes'
+        WHERE id = (SELECT id FROM job_attempts WHERE attempt_no = 1 LIMIT 1)
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let m = MetricsRepo::new(pool.clone())
+        .snapshot_for_queue("q_load", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(m.attempts_started_per_sec, 5.0 / 60.0);
+
+    let resp = postgresflow::api::router(common::api_state(&pool))
+        .oneshot(Request::get("/metrics/prom").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains(&format!(
+        "pgflow_attempts_started_per_sec{{queue=\"q_load\"}} {}\n",
+        5.0 / 60.0
+    )));
+}
+
+#[tokio::test]
+#[serial]
+async fn wider_window_includes_older_attempts() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let metrics = MetricsRepo::new(pool.clone());
+
+    // 3 failures about 2 minutes ago, 1 success just now
+    for _ in 0..3 {
+        fail_one(&jobs, &attempts, "q_window", "TIMEOUT").await;
+    }
+    sqlx::query("UPDATE job_attempts SET started_at = now() - interval '2 minutes'")
+        .execute(&pool)
+        .await
+        .unwrap();
+    jobs.enqueue_now("q_window", "ok", json!({})).await.unwrap();
+    let job = jobs
+        .lease_one_job("q_window", "worker-1", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(job.id, "worker-1").await.unwrap();
+    attempts.finish_succeeded(attempt.id, 5).await.unwrap();
+
+    let minute = metrics
+        .snapshot_for_queue("q_window", DEFAULT_METRICS_WINDOW_SECS)
+        .await
+        .unwrap();
+    assert_eq!(minute.window_secs, 60);
+    assert_eq!(minute.success_rate, 1.0);
+    assert_eq!(minute.jobs_per_sec, 1.0 / 60.0);
+    assert!(minute.failures_by_error_code.is_empty());
+
+    let five = metrics.snapshot_for_queue("q_window", 300).await.unwrap();
+    assert_eq!(five.window_secs, 300);
+    assert_eq!(five.success_rate, 0.25);
+    assert_eq!(five.jobs_per_sec, 4.0 / 300.0);
+    assert_eq!(five.failures_by_error_code.len(), 1);
+    assert_eq!(five.failures_by_error_code[0].count, 3);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::metrics_full;
+use postgresflow::jobs::NewJob;
+use serial_test::serial;
+
+fn new_job(queue: &str) -> NewJob {
+    NewJob {
+        queue: queue.to_string(),
+        job_type: "work".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn full_metrics_contains_all_sections() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    state.jobs.enqueue(new_job("q_full")).await.unwrap();
+    state.jobs.enqueue(new_job("q_full")).await.unwrap();
+    let leased = state
+        .jobs
+        .lease_one_job("q_full", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("job should lease");
+    state
+        .jobs
+        .mark_dlq(
+            leased.id,
+            "worker-1",
+            "NON_RETRYABLE",
+            Some("BAD_INPUT"),
+            None,
+        )
+        .await
+        .unwrap();
+
+    state
+        .ingest_decisions
+        .record(
+            "q_full_denied",
+            "DENIED",
+            "ENQUEUE_RATE_EXCEEDED",
+            serde_json::json!({}),
+        )
+        .await
+        .unwrap();
+
+    let Json(resp) = metrics_full(State(state)).await.unwrap();
+
+    let queue = resp
+        .queues
+        .iter()
+        .find(|m| m.queue == "q_full")
+        .expect("per-queue metrics present");
+    assert_eq!(queue.runnable_queue_depth, 1);
+
+    assert_eq!(resp.totals_by_status.get("queued"), Some(&1));
+    assert_eq!(resp.totals_by_status.get("dlq"), Some(&1));
+
+    assert_eq!(resp.dlq.len(), 1);
+    assert_eq!(resp.dlq[0].queue, "q_full");
+    assert_eq!(resp.dlq[0].reason_code.as_deref(), Some("NON_RETRYABLE"));
+    assert_eq!(resp.dlq[0].error_code.as_deref(), Some("BAD_INPUT"));
+    assert_eq!(resp.dlq[0].count, 1);
+
+    let denial = resp
+        .enqueue_denials
+        .iter()
+        .find(|d| d.queue == "q_full_denied")
+        .expect("enqueue denial counts present");
+    assert_eq!(denial.reason_code, "ENQUEUE_RATE_EXCEEDED");
+    assert!(denial.count >= 1);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::{move_queue_jobs, MoveJobsRequest};
+use postgresflow::jobs::{NewJob, PoliciesRepo};
+use serde_json::json;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn move_jobs_rehomes_queued_jobs_of_a_job_type() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let jobs = &state.jobs;
+
+    let running = jobs
+        .enqueue_now("q_hot", "resize", json!({}))
+        .await
+        .unwrap();
+    jobs.lease_one_job("q_hot", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    let resize_a = jobs
+        .enqueue_now("q_hot", "resize", json!({}))
+        .await
+        .unwrap();
+    let resize_b = jobs
+        .enqueue_now("q_hot", "resize", json!({}))
+        .await
+        .unwrap();
+    let email = jobs.enqueue_now("q_hot", "email", json!({})).await.unwrap();
+
+    let Json(resp) = move_queue_jobs(
+        State(state.clone()),
+        Json(MoveJobsRequest {
+            from_queue: "q_hot".to_string(),
+            to_queue: "q_spare".to_string(),
+            job_type: Some("resize".to_string()),
+            limit: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.moved, 2);
+
+    let queue_of = |id| async move { jobs.get_job(id).await.unwrap().unwrap().queue };
+    assert_eq!(queue_of(running).await, "q_hot", "running jobs never move");
+    assert_eq!(queue_of(email).await, "q_hot");
+    assert_eq!(queue_of(resize_a).await, "q_spare");
+    assert_eq!(queue_of(resize_b).await, "q_spare");
+
+    let mut leased = Vec::new();
+    while let Some(job) = jobs.lease_one_job("q_spare", "worker-2", 30).await.unwrap() {
+        leased.push(job.id);
+    }
+    leased.sort();
+    let mut expected = vec![resize_a, resize_b];
+    expected.sort();
+    assert_eq!(leased, expected);
+
+    let rows = state.policy_decisions.list_for_job(resize_a).await.unwrap();
+    let audit = rows
+        .iter()
+        .find(|r| r.decision == "MANUAL_MOVE")
+        .expect("expected audit decision");
+    assert_eq!(audit.details_json["from_queue"], "q_hot");
+    assert_eq!(audit.details_json["to_queue"], "q_spare");
+}
+
+#[tokio::test]
+#[serial]
+async fn move_jobs_respects_limit_and_rejects_same_queue() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    for _ in 0..5 {
+        state
+            .jobs
+            .enqueue_now("q_hot", "resize", json!({}))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        state
+            .jobs
+            .move_jobs("q_hot", "q_spare", None, 3)
+            .await
+            .unwrap(),
+        3
+    );
+
+    let err = move_queue_jobs(
+        State(state.clone()),
+        Json(MoveJobsRequest {
+            from_queue: "q_hot".to_string(),
+            to_queue: "q_hot".to_string(),
+            job_type: None,
+            limit: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.0, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial]
+async fn moved_jobs_take_the_target_queues_priority_bounds() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    PoliciesRepo::new(pool.clone())
+        .set_priority_bounds("q_bounded", Some((-2, 2)))
+        .await
+        .unwrap();
+    let id = state
+        .jobs
+        .enqueue(NewJob {
+            queue: "q_hot".to_string(),
+            job_type: "resize".to_string(),
+            payload_json: json!({}),
+            run_at: Utc::now(),
+            priority: 100,
+            max_attempts: 3,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        state
+            .jobs
+            .move_jobs("q_hot", "q_bounded", None, 10)
+            .await
+            .unwrap(),
+        1
+    );
+    let job = state.jobs.get_job(id).await.unwrap().unwrap();
+    assert_eq!(job.queue, "q_bounded");
+    assert_eq!(job.priority, 2);
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use common::{api_state, setup_db};
+use postgresflow::api::{enqueue_job, EnqueueRequest};
+use serde_json::json;
+use serial_test::serial;
+
+fn templated_request(payload_template: serde_json::Value) -> EnqueueRequest {
+    EnqueueRequest {
+        queue: None,
+        job_type: "daily_report".to_string(),
+        payload_json: None,
+        payload_template: Some(payload_template),
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn enqueue_resolves_now_placeholder_in_stored_payload() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let before = Utc::now();
+    let Json(resp) = enqueue_job(
+        State(state.clone()),
+        Json(templated_request(json!({
+            "generated_at": "{{now}}",
+            "report": { "day": "{{date}}", "run_id": "{{uuid}}" },
+            "literal": "no placeholders",
+        }))),
+    )
+    .await
+    .expect("enqueue should succeed");
+
+    let job = state.jobs.get_job(resp.job_id).await.unwrap().unwrap();
+    let payload = job.payload_json;
+
+    let generated_at = payload["generated_at"].as_str().unwrap();
+    let ts = DateTime::parse_from_rfc3339(generated_at)
+        .expect("{{now}} should resolve to an RFC3339 timestamp")
+        .with_timezone(&Utc);
+    assert!(ts >= before && ts <= Utc::now());
+
+    assert_eq!(
+        payload["report"]["day"].as_str().unwrap(),
+        ts.format("%Y-%m-%d").to_string()
+    );
+    assert!(uuid::Uuid::parse_str(payload["report"]["run_id"].as_str().unwrap()).is_ok());
+    assert_eq!(payload["literal"], "no placeholders");
+}
+
+#[tokio::test]
+#[serial]
+async fn enqueue_rejects_payload_and_template_together() {
+    let pool = setup_db().await;
+
+    let mut req = templated_request(json!({ "at": "{{now}}" }));
+    req.payload_json = Some(json!({ "at": "fixed" }));
+
+    let err = enqueue_job(State(api_state(&pool)), Json(req))
+        .await
+        .expect_err("both payload forms should be rejected");
+    assert_eq!(err.0, axum::http::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial]
+async fn enqueue_rejects_missing_payload_and_template() {
+    let pool = setup_db().await;
+
+    let mut req = templated_request(json!({}));
+    req.payload_template = None;
+
+    let err = enqueue_job(State(api_state(&pool)), Json(req))
+        .await
+        .expect_err("a job needs one payload form");
+    assert_eq!(err.0, axum::http::StatusCode::BAD_REQUEST);
+    assert_eq!(err.1, "payload_json or payload_template is required");
+}
+
+#[tokio::test]
+#[serial]
+async fn explicit_null_payload_is_stored_but_a_missing_one_is_rejected() {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+    let post = |body: serde_json::Value| {
+        let app = postgresflow::api::router(state.clone());
+        async move {
+            let resp = app
+                .oneshot(
+                    Request::post("/jobs")
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = resp.status();
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            (status, String::from_utf8(body.to_vec()).unwrap())
+        }
+    };
+
+    let (status, body) = post(json!({ "job_type": "daily_report", "payload_json": null })).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let job_id: uuid::Uuid = serde_json::from_str::<serde_json::Value>(&body).unwrap()["job_id"]
+        .as_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    let job = state.jobs.get_job(job_id).await.unwrap().unwrap();
+    assert_eq!(job.payload_json, serde_json::Value::Null);
+
+    let (status, body) = post(json!({ "job_type": "daily_report" })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, "payload_json or payload_template is required");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::{enqueue_job, metrics_prom, EnqueueRequest};
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use serial_test::serial;
+
+fn enqueue_request(filler_len: usize) -> EnqueueRequest {
+    EnqueueRequest {
+        queue: Some("q_payload_warn".to_string()),
+        job_type: "big_payload".to_string(),
+        payload_json: Some(serde_json::json!({ "filler": "x".repeat(filler_len) })),
+        payload_template: None,
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn payload_over_warn_threshold_is_recorded_but_enqueued() {
+    let pool = setup_db().await;
+    let mut state = api_state(&pool);
+    state.enqueue_guard = EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig {
+            max_payload_bytes: 2_000,
+            warn_payload_bytes: Some(1_000),
+            ..EnqueueGuardConfig::default()
+        },
+    );
+
+    // under the threshold: nothing recorded
+    let Json(_) = enqueue_job(State(state.clone()), Json(enqueue_request(100)))
+        .await
+        .unwrap();
+    assert!(state
+        .ingest_decisions
+        .list_recent(Some("q_payload_warn"), 10)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let Json(warned) = enqueue_job(State(state.clone()), Json(enqueue_request(1_500)))
+        .await
+        .unwrap();
+    assert!(state.jobs.get_job(warned.job_id).await.unwrap().is_some());
+
+    let decisions = state
+        .ingest_decisions
+        .list_recent(Some("q_payload_warn"), 10)
+        .await
+        .unwrap();
+    assert_eq!(decisions.len(), 1);
+    let (_, _, decision, reason_code, details, _) = &decisions[0];
+    assert_eq!(decision, "WARNED");
+    assert_eq!(reason_code, "PAYLOAD_LARGE_WARN");
+    assert_eq!(details["warn_payload_bytes"], 1_000);
+    assert_eq!(details["max_payload_bytes"], 2_000);
+    assert!(details["payload_bytes"].as_u64().unwrap() > 1_000);
+
+    // a warning is not a denial
+    assert!(state
+        .ingest_decisions
+        .denial_counts()
+        .await
+        .unwrap()
+        .is_empty());
+
+    let resp = metrics_prom(State(state.clone())).await;
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.contains("pgflow_enqueue_payload_warnings_total{queue=\"q_payload_warn\"} 1\n"));
+
+    // over the hard limit is still denied, without a warning on top
+    assert!(
+        enqueue_job(State(state.clone()), Json(enqueue_request(2_500)))
+            .await
+            .is_err()
+    );
+    let reasons: Vec<String> = state
+        .ingest_decisions
+        .list_recent(Some("q_payload_warn"), 10)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(_, _, _, reason_code, _, _)| reason_code)
+        .collect();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons.contains(&"PAYLOAD_TOO_LARGE".to_string()));
+}
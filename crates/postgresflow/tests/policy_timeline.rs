@@ -1,4 +1,5 @@
 use serde_json::json;
+use serial_test::serial;
 
 mod common;
 use common::setup_db;
@@ -9,6 +10,7 @@ use postgresflow::jobs::{
 };
 
 #[tokio::test]
+#[serial]
 async fn timeline_includes_policy_decision_event() {
     let pool = setup_db().await;
 
@@ -35,10 +37,16 @@ async fn timeline_includes_policy_decision_event() {
     assert!(leased.is_none(), "expected throttle to return None");
 
     // 4) timeline should include a PolicyDecision event in story
-    let tl = timeline::build_timeline(&jobs, &attempts, &policy_decisions, job_id)
-        .await
-        .unwrap()
-        .expect("job should exist");
+    let tl = timeline::build_timeline(
+        &jobs,
+        &attempts,
+        &policy_decisions,
+        job_id,
+        timeline::TimelineOptions::full(),
+    )
+    .await
+    .unwrap()
+    .expect("job should exist");
 
     let has_policy = tl.story.iter().any(|e| {
         matches!(
@@ -62,3 +70,76 @@ async fn timeline_includes_policy_decision_event() {
     assert_eq!(rows[0].decision, "THROTTLED");
     assert_eq!(rows[0].reason_code, "IN_FLIGHT_EXCEEDED");
 }
+
+#[tokio::test]
+#[serial]
+async fn repeated_throttles_coalesce_into_one_decision_with_count() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policies = PoliciesRepo::new(pool.clone());
+    let policy_decisions = PolicyDecisionsRepo::new(pool.clone());
+
+    // always throttle, and keep the job runnable (0ms delay) so every lease hits it
+    policies
+        .upsert_policy("default", 10_000, 0, 0)
+        .await
+        .unwrap();
+
+    let job_id = jobs
+        .enqueue_now("default", "ok_job", json!({}))
+        .await
+        .unwrap();
+
+    for _ in 0..5 {
+        let leased = jobs.lease_one_job("default", "worker-1", 10).await.unwrap();
+        assert!(leased.is_none());
+    }
+
+    let rows = policy_decisions.list_for_job(job_id).await.unwrap();
+    assert_eq!(rows.len(), 1, "throttles should coalesce into one row");
+    assert_eq!(rows[0].count, 5);
+    assert!(rows[0].last_seen_at >= rows[0].created_at);
+
+    let tl = timeline::build_timeline(
+        &jobs,
+        &attempts,
+        &policy_decisions,
+        job_id,
+        timeline::TimelineOptions::full(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let counts: Vec<i32> = tl
+        .story
+        .iter()
+        .filter_map(|e| match e {
+            TimelineEvent::PolicyDecision { count, .. } => Some(*count),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(counts, vec![5]);
+
+    // with coalescing disabled every throttle gets its own row
+    let uncoalesced = JobsRepo::new(pool.clone()).with_decision_coalesce_secs(0);
+    let other_id = uncoalesced
+        .enqueue_now("default", "ok_job", json!({}))
+        .await
+        .unwrap();
+    sqlx::query("UPDATE jobs SET run_at = now() + interval '1 hour' WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    for _ in 0..3 {
+        uncoalesced
+            .lease_one_job("default", "worker-1", 10)
+            .await
+            .unwrap();
+    }
+    let rows = policy_decisions.list_for_job(other_id).await.unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(rows.iter().all(|r| r.count == 1));
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use chrono::Utc;
+use common::setup_db;
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use postgresflow::jobs::maintenance::MaintenanceRepo;
+use postgresflow::jobs::system_flags::ENQUEUE_ENABLED;
+use postgresflow::jobs::{JobsRepo, NewJob, SystemFlagsRepo};
+use serial_test::serial;
+use std::time::Duration;
+use uuid::Uuid;
+
+fn new_job(queue: &str) -> NewJob {
+    NewJob {
+        queue: queue.to_string(),
+        job_type: "two_phase_job".to_string(),
+        payload_json: serde_json::json!({}),
+        run_at: Utc::now(),
+        priority: 0,
+        max_attempts: 3,
+        target_worker_id: None,
+        retry: None,
+        dedupe_key: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+async fn status_of(pool: &sqlx::PgPool, id: Uuid) -> Option<String> {
+    sqlx::query_scalar("SELECT status FROM jobs WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn prepared_job_is_only_leasable_after_commit() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let token = jobs
+        .prepare_enqueue(new_job("q_two_phase"), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert_eq!(status_of(&pool, token).await.as_deref(), Some("preparing"));
+    assert!(jobs
+        .lease_one_job("q_two_phase", "worker-a", 30)
+        .await
+        .unwrap()
+        .is_none());
+
+    assert!(jobs.commit_enqueue(token).await.unwrap());
+    // committing twice is a no-op, and a committed job can't be aborted
+    assert!(!jobs.commit_enqueue(token).await.unwrap());
+    assert!(!jobs.abort_enqueue(token).await.unwrap());
+
+    let leased = jobs
+        .lease_one_job("q_two_phase", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("committed job should be leasable");
+    assert_eq!(leased.id, token);
+}
+
+#[tokio::test]
+#[serial]
+async fn aborted_prepared_job_is_gone() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let token = jobs
+        .prepare_enqueue(new_job("q_two_phase"), Duration::from_secs(60))
+        .await
+        .unwrap();
+    assert!(jobs.abort_enqueue(token).await.unwrap());
+    assert_eq!(status_of(&pool, token).await, None);
+
+    assert!(!jobs.abort_enqueue(token).await.unwrap());
+    assert!(!jobs.commit_enqueue(token).await.unwrap());
+    assert!(!jobs.commit_enqueue(Uuid::new_v4()).await.unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn prepared_job_expires_after_ttl() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let maint = MaintenanceRepo::new(pool.clone());
+
+    let expired = jobs
+        .prepare_enqueue(new_job("q_two_phase"), Duration::from_millis(50))
+        .await
+        .unwrap();
+    let live = jobs
+        .prepare_enqueue(new_job("q_two_phase"), Duration::from_secs(60))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(!jobs.commit_enqueue(expired).await.unwrap());
+    assert_eq!(maint.expire_prepared_jobs().await.unwrap(), 1);
+    assert_eq!(status_of(&pool, expired).await, None);
+
+    // the sweep leaves prepared jobs still inside their TTL alone
+    assert_eq!(status_of(&pool, live).await.as_deref(), Some("preparing"));
+    assert!(jobs.commit_enqueue(live).await.unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn guarded_repo_checks_prepared_jobs_and_their_commit() {
+    let pool = setup_db().await;
+    let guard = EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig {
+            max_payload_bytes: 64,
+            ..EnqueueGuardConfig::default()
+        },
+    );
+    let jobs = JobsRepo::new(pool.clone()).with_enqueue_guard(guard);
+    let flags = SystemFlagsRepo::new(pool.clone());
+
+    let mut big = new_job("q_two_phase");
+    big.payload_json = serde_json::json!({ "blob": "x".repeat(100) });
+    let err = jobs
+        .prepare_enqueue(big, Duration::from_secs(60))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "PAYLOAD_TOO_LARGE");
+
+    let token = jobs
+        .prepare_enqueue(new_job("q_two_phase"), Duration::from_secs(60))
+        .await
+        .unwrap();
+
+    // the kill switch flipped between prepare and commit keeps it out
+    flags.set(ENQUEUE_ENABLED, false).await.unwrap();
+    let err = jobs.commit_enqueue(token).await.unwrap_err();
+    assert_eq!(err.to_string(), "ENQUEUE_DISABLED");
+    assert_eq!(status_of(&pool, token).await.as_deref(), Some("preparing"));
+
+    flags.set(ENQUEUE_ENABLED, true).await.unwrap();
+    assert!(jobs.commit_enqueue(token).await.unwrap());
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, Request, StatusCode};
+use chrono::Utc;
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::{JobsRepo, NewJob, PoliciesRepo};
+use serde_json::json;
+use serial_test::serial;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+#[tokio::test]
+#[serial]
+async fn boosted_job_leases_first() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    let first = insert_job(&pool, "q_priority").await;
+    let second = insert_job(&pool, "q_priority").await;
+
+    assert!(jobs.set_priority(second, 10).await.unwrap());
+
+    let leased = jobs
+        .lease_one_job("q_priority", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("should lease");
+    assert_eq!(leased.id, second);
+    assert_eq!(leased.priority, 10);
+
+    // running jobs keep their priority
+    assert!(!jobs.set_priority(second, 0).await.unwrap());
+    assert!(!jobs.set_priority(Uuid::new_v4(), 5).await.unwrap());
+
+    let leased = jobs
+        .lease_one_job("q_priority", "worker-a", 30)
+        .await
+        .unwrap()
+        .expect("should lease");
+    assert_eq!(leased.id, first);
+}
+
+#[tokio::test]
+#[serial]
+async fn priority_endpoint_reports_whether_the_job_was_updated() {
+    let pool = setup_db().await;
+    let job_id = insert_job(&pool, "q_priority").await;
+
+    let patch = |id: Uuid, body: serde_json::Value| {
+        let app = router(api_state(&pool));
+        async move {
+            let resp = app
+                .oneshot(
+                    Request::patch(format!("/jobs/{id}/priority"))
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = resp.status();
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&body).ok(),
+            )
+        }
+    };
+
+    let (status, body) = patch(job_id, json!({ "priority": 7 })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, Some(json!({ "updated": true })));
+
+    let priority: i32 = sqlx::query_scalar("SELECT priority FROM jobs WHERE id = $1")
+        .bind(job_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(priority, 7);
+
+    let (_, body) = patch(Uuid::new_v4(), json!({ "priority": 7 })).await;
+    assert_eq!(body, Some(json!({ "updated": false })));
+
+    let (status, _) = patch(job_id, json!({})).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+#[serial]
+async fn out_of_range_priorities_are_normalized_into_queue_bounds() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let policies = PoliciesRepo::new(pool.clone());
+
+    assert!(policies
+        .set_priority_bounds("q_bounded", Some((3, -3)))
+        .await
+        .is_err());
+    policies
+        .set_priority_bounds("q_bounded", Some((-2, 2)))
+        .await
+        .unwrap();
+
+    let enqueue = |queue: &'static str, priority: i32| {
+        let jobs = jobs.clone();
+        async move {
+            let id = jobs
+                .enqueue(NewJob {
+                    queue: queue.to_string(),
+                    job_type: "bounded_job".to_string(),
+                    payload_json: json!({}),
+                    run_at: Utc::now(),
+                    priority,
+                    max_attempts: 3,
+                    target_worker_id: None,
+                    retry: None,
+                    dedupe_key: None,
+                    idempotency_key: None,
+                    group_id: None,
+                })
+                .await
+                .unwrap();
+            jobs.get_job(id).await.unwrap().unwrap()
+        }
+    };
+
+    assert_eq!(enqueue("q_bounded", 100).await.priority, 2);
+    assert_eq!(enqueue("q_bounded", -50).await.priority, -2);
+    let in_range = enqueue("q_bounded", 1).await;
+    assert_eq!(in_range.priority, 1);
+
+    assert!(jobs.set_priority(in_range.id, 9).await.unwrap());
+    let boosted = jobs.get_job(in_range.id).await.unwrap().unwrap();
+    assert_eq!(boosted.priority, 2);
+
+    // other queues keep the full range
+    assert_eq!(enqueue("q_unbounded", 100).await.priority, 100);
+
+    policies
+        .set_priority_bounds("q_bounded", None)
+        .await
+        .unwrap();
+    assert_eq!(enqueue("q_bounded", 100).await.priority, 100);
+}
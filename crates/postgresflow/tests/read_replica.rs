@@ -0,0 +1,171 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::setup_db;
+use postgresflow::api::{router, ApiState};
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use postgresflow::jobs::{
+    AttemptsRepo, HandlerPermits, JobsRepo, MetricsRepo, PolicyDecisionsRepo, SlaRepo,
+    SystemFlagsRepo, WakeupCoalescer,
+};
+use serde_json::json;
+use serial_test::serial;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::PgPool;
+use std::str::FromStr;
+use tower::ServiceExt;
+
+/// Two pools on the test database standing in for primary and replica; both
+/// lazy, so `size()` shows whether anything ever touched them. The "replica"
+/// refuses writes like a real standby would.
+fn split_pools() -> (PgPool, PgPool) {
+    let url = std::env::var("TEST_DATABASE_URL").unwrap();
+    let write_pool = PgPoolOptions::new().connect_lazy(&url).unwrap();
+    let read_opts = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("default_transaction_read_only", "on")]);
+    let read_pool = PgPoolOptions::new().connect_lazy_with(read_opts);
+    (write_pool, read_pool)
+}
+
+fn split_state(write_pool: &PgPool, read_pool: &PgPool) -> ApiState {
+    let ingest_decisions = IngestDecisionsRepo::new(write_pool.clone());
+    ApiState {
+        jobs: JobsRepo::new(write_pool.clone()),
+        read_jobs: JobsRepo::new(read_pool.clone()),
+        attempts: AttemptsRepo::new(read_pool.clone()),
+        policy_decisions: PolicyDecisionsRepo::new(read_pool.clone()),
+        ingest_decisions: IngestDecisionsRepo::new(read_pool.clone()),
+        metrics: MetricsRepo::new(read_pool.clone()),
+        sla: SlaRepo::new(write_pool.clone()),
+        system_flags: SystemFlagsRepo::new(write_pool.clone()),
+        enqueue_guard: EnqueueGuard::new(
+            write_pool.clone(),
+            ingest_decisions,
+            EnqueueGuardConfig::default(),
+        ),
+        enqueue_pressure_hint: false,
+        api_token: None,
+        wakeups: WakeupCoalescer::new(std::time::Duration::ZERO),
+        handler_permits: HandlerPermits::new(),
+        runbook_urls: Default::default(),
+        read_only: false,
+    }
+}
+
+async fn send(app: axum::Router, req: Request<Body>) -> (StatusCode, serde_json::Value) {
+    let resp = app.oneshot(req).await.unwrap();
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn reads_use_the_read_pool_and_writes_the_primary() {
+    let pool = setup_db().await;
+    let job_id = JobsRepo::new(pool.clone())
+        .enqueue_now("q_replica", "replica_job", json!({}))
+        .await
+        .unwrap();
+
+    let (write_pool, read_pool) = split_pools();
+    let app = router(split_state(&write_pool, &read_pool));
+
+    for uri in [
+        "/jobs?queue=q_replica".to_string(),
+        format!("/jobs/{job_id}"),
+        format!("/jobs/{job_id}/timeline"),
+        "/metrics".to_string(),
+        "/metrics/full".to_string(),
+        "/metrics/prom".to_string(),
+    ] {
+        let (status, _) = send(
+            app.clone(),
+            Request::get(uri.as_str()).body(Body::empty()).unwrap(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "GET {uri}");
+    }
+    assert!(read_pool.size() > 0, "reads never reached the read pool");
+    assert_eq!(write_pool.size(), 0, "a read went to the primary");
+
+    let (status, body) = send(
+        app,
+        Request::post("/jobs")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({
+                    "queue": "q_replica",
+                    "job_type": "replica_job",
+                    "payload_json": {}
+                })
+                .to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(write_pool.size() > 0, "enqueue never reached the primary");
+}
+
+#[tokio::test]
+#[serial]
+async fn job_lookup_falls_back_to_the_primary_when_the_replica_lags() {
+    let pool = setup_db().await;
+    // a "replica" that hasn't replayed any jobs yet: an empty jobs table
+    // shadows the real one on its search_path
+    sqlx::query("DROP SCHEMA IF EXISTS lagging_replica CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("CREATE SCHEMA lagging_replica")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE lagging_replica.jobs (LIKE public.jobs INCLUDING ALL)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let url = std::env::var("TEST_DATABASE_URL").unwrap();
+    let write_pool = PgPoolOptions::new().connect_lazy(&url).unwrap();
+    let read_opts = PgConnectOptions::from_str(&url)
+        .unwrap()
+        .options([("search_path", "lagging_replica,public")]);
+    let read_pool = PgPoolOptions::new().connect_lazy_with(read_opts);
+    let app = router(split_state(&write_pool, &read_pool));
+
+    let job_id = JobsRepo::new(pool.clone())
+        .enqueue_now("q_replica", "replica_job", json!({}))
+        .await
+        .unwrap();
+    let (status, body) = send(
+        app.clone(),
+        Request::get(format!("/jobs/{job_id}"))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["id"], job_id.to_string());
+
+    let (status, _) = send(
+        app,
+        Request::get(format!("/jobs/{}", uuid::Uuid::new_v4()))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    sqlx::query("DROP SCHEMA lagging_replica CASCADE")
+        .execute(&pool)
+        .await
+        .unwrap();
+}
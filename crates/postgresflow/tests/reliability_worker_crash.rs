@@ -60,3 +60,40 @@ async fn worker_crash_mid_job_another_worker_recovers_after_lease_expiry() -> an
 
     Ok(())
 }
+
+#[tokio::test]
+async fn reaped_job_waits_out_the_requeue_delay() -> anyhow::Result<()> {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone()).with_reap_requeue_delay_ms(500);
+
+    let job_id = jobs
+        .enqueue_now("q_reap_delay", "fail_me", json!({}))
+        .await?;
+    jobs.lease_one_job("q_reap_delay", "workerA", 30)
+        .await?
+        .expect("leased");
+
+    // workerA's lease runs out without it finishing
+    sqlx::query("UPDATE jobs SET lock_expires_at = now() - interval '1 second' WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await?;
+    assert_eq!(jobs.reap_expired_locks().await?, 1);
+
+    assert!(
+        jobs.lease_one_job("q_reap_delay", "workerB", 30)
+            .await?
+            .is_none(),
+        "reaped job leasable before the delay passed"
+    );
+
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    let job = jobs
+        .lease_one_job("q_reap_delay", "workerB", 30)
+        .await?
+        .expect("leasable after the delay");
+    assert_eq!(job.id, job_id);
+
+    Ok(())
+}
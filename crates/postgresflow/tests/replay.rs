@@ -2,7 +2,8 @@ mod common;
 
 use chrono::{Duration as ChronoDuration, Utc};
 use common::setup_db;
-use postgresflow::jobs::JobsRepo;
+use postgresflow::jobs::timeline::{build_timeline, TimelineOptions};
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -30,7 +31,10 @@ async fn replay_creates_new_job_with_lineage() {
 
     let old_id = insert_job_full(&pool, "default", "my_job").await;
 
-    let new_id = repo.replay_job(old_id, None, None).await.unwrap();
+    let new_id = repo
+        .replay_job(old_id, None, None, None, false)
+        .await
+        .unwrap();
 
     // new job exists
     let row = sqlx::query!(
@@ -60,7 +64,7 @@ async fn replay_allows_overrides() {
 
     let run_at = Utc::now() + ChronoDuration::seconds(30);
     let new_id = repo
-        .replay_job(old_id, Some("priority-queue"), Some(run_at))
+        .replay_job(old_id, Some("priority-queue"), Some(run_at), None, false)
         .await
         .unwrap();
 
@@ -82,3 +86,108 @@ async fn replay_allows_overrides() {
     // run_at should be close (db now vs rust now differences can exist; compare >=)
     assert!(row.run_at >= run_at - ChronoDuration::seconds(1));
 }
+
+#[tokio::test]
+async fn replay_with_history_surfaces_original_attempts_in_timeline() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policy_decisions = PolicyDecisionsRepo::new(pool.clone());
+
+    let old_id = insert_job_full(&pool, "default", "my_job").await;
+    let first = attempts.start_attempt(old_id, "worker-1").await.unwrap();
+    attempts
+        .finish_failed(first.id, 12, "TIMEOUT", "upstream timeout")
+        .await
+        .unwrap();
+    let second = attempts.start_attempt(old_id, "worker-1").await.unwrap();
+    attempts.finish_succeeded(second.id, 8).await.unwrap();
+
+    let new_id = repo
+        .replay_job(old_id, None, None, None, true)
+        .await
+        .unwrap();
+
+    let timeline = build_timeline(
+        &repo,
+        &attempts,
+        &policy_decisions,
+        new_id,
+        TimelineOptions::full(),
+    )
+    .await
+    .unwrap()
+    .expect("replayed job should have a timeline");
+
+    assert!(timeline.attempts.is_empty());
+
+    let history = timeline.replayed_from.expect("history should be included");
+    assert_eq!(history.job_id, old_id);
+    assert_eq!(history.attempts.len(), 2);
+    assert_eq!(history.attempts[0].id, first.id);
+    assert_eq!(history.attempts[0].error_code.as_deref(), Some("TIMEOUT"));
+    assert_eq!(history.attempts[1].id, second.id);
+    assert_eq!(history.attempts[1].status, "succeeded");
+
+    // plain replays keep the timeline scoped to the new job
+    let plain_id = repo
+        .replay_job(old_id, None, None, None, false)
+        .await
+        .unwrap();
+    let plain = build_timeline(
+        &repo,
+        &attempts,
+        &policy_decisions,
+        plain_id,
+        TimelineOptions::full(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert!(plain.replayed_from.is_none());
+}
+
+#[tokio::test]
+async fn replay_with_priority_override_leases_before_normal_jobs() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+
+    let dlq_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts,
+                          dlq_reason_code, dlq_at)
+        VALUES ('q_urgent', 'charge', '{}'::jsonb, now(), 'dlq', 0, 3,
+                'MAX_ATTEMPTS_EXCEEDED', now())
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    for _ in 0..3 {
+        repo.enqueue_now("q_urgent", "charge", serde_json::json!({}))
+            .await
+            .unwrap();
+    }
+
+    let plain_id = repo
+        .replay_job(dlq_id, None, None, None, false)
+        .await
+        .unwrap();
+    let urgent_id = repo
+        .replay_job(dlq_id, None, None, Some(100), false)
+        .await
+        .unwrap();
+
+    let plain = repo.get_job(plain_id).await.unwrap().unwrap();
+    assert_eq!(plain.priority, 0, "priority is inherited by default");
+    let urgent = repo.get_job(urgent_id).await.unwrap().unwrap();
+    assert_eq!(urgent.priority, 100);
+
+    let first = repo
+        .lease_one_job("q_urgent", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    assert_eq!(first.id, urgent_id, "prioritized replay jumps the queue");
+}
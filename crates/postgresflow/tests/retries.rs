@@ -36,6 +36,7 @@ async fn retry_schedules_increasing_run_at() {
         base_seconds: 1,
         max_seconds: 15,
         jitter_pct: 0.0, // deterministic test
+        min_delay_seconds: 0,
     };
     let runner = JobRunner::new(jobs.clone(), attempts.clone(), cfg);
 
@@ -114,6 +115,72 @@ async fn retry_schedules_increasing_run_at() {
     assert!(run_at2 > run_at1, "expected increasing backoff run_at");
 }
 
+#[tokio::test]
+#[serial]
+async fn backoff_is_exact_under_a_mock_clock() {
+    use chrono::TimeZone;
+    use postgresflow::jobs::{Clock, MockClock};
+    use std::sync::Arc;
+
+    let pool = setup_db().await;
+
+    // a fixed instant in the past: every computed run_at is already due for the
+    // DB-side lease query, so no sleeping or run_at rewrites are needed
+    let t0 = chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(t0);
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let cfg = RetryConfig {
+        base_seconds: 1,
+        max_seconds: 15,
+        jitter_pct: 0.0,
+        min_delay_seconds: 0,
+    };
+    let runner =
+        JobRunner::new(jobs.clone(), attempts.clone(), cfg).with_clock(Arc::new(clock.clone()));
+
+    let job_id = insert_fail_job(&pool, 10).await;
+
+    let mut expected = t0;
+    for (attempt_no, delay_secs) in [(1, 1), (2, 2), (3, 4), (4, 8), (5, 15)] {
+        let job = jobs
+            .lease_one_job("default", "worker-a", 30)
+            .await
+            .unwrap()
+            .expect("retry should already be due");
+        let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+        assert_eq!(attempt.attempt_no, attempt_no);
+
+        runner
+            .on_failure(
+                job.id,
+                attempt.id,
+                "worker-a",
+                10,
+                "TIMEOUT",
+                "t",
+                attempt.attempt_no,
+                job.max_attempts,
+            )
+            .await
+            .unwrap();
+
+        expected = clock.now() + chrono::Duration::seconds(delay_secs);
+        let run_at: chrono::DateTime<chrono::Utc> =
+            sqlx::query_scalar("SELECT run_at FROM jobs WHERE id = $1")
+                .bind(job_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(run_at, expected, "attempt {attempt_no}");
+
+        // the next failure happens once the retry is due
+        clock.set(expected);
+    }
+    assert_eq!(expected, t0 + chrono::Duration::seconds(1 + 2 + 4 + 8 + 15));
+}
+
 #[tokio::test]
 #[serial]
 
@@ -163,3 +230,273 @@ async fn non_retryable_goes_to_dlq() {
     assert!(updated.dlq_at.is_some());
     assert_eq!(updated.dlq_reason_code.as_deref(), Some("NON_RETRYABLE"));
 }
+
+#[test]
+fn retry_delay_never_drops_below_min_delay() {
+    use postgresflow::jobs::retry::next_delay_seconds;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // jitter of 100% can pull a 2s first-attempt delay all the way down to 0
+    let cfg = RetryConfig {
+        base_seconds: 2,
+        max_seconds: 60,
+        jitter_pct: 1.0,
+        min_delay_seconds: 0,
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+    let unfloored = (0..500)
+        .map(|_| next_delay_seconds(1, &cfg, &mut rng))
+        .min()
+        .unwrap();
+    assert_eq!(unfloored, 0);
+
+    let cfg = RetryConfig {
+        min_delay_seconds: 2,
+        ..cfg
+    };
+    for attempt_no in 1..=10 {
+        for _ in 0..200 {
+            let d = next_delay_seconds(attempt_no, &cfg, &mut rng);
+            assert!((2..=60).contains(&d), "attempt {attempt_no}: delay {d}");
+        }
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn non_retryable_job_goes_to_dlq_on_next_retryable_failure() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let job_id = insert_fail_job(&pool, 10).await;
+    assert!(!jobs.is_forced_non_retryable(job_id).await.unwrap());
+    assert!(jobs.set_non_retryable(job_id).await.unwrap());
+    assert!(jobs.is_forced_non_retryable(job_id).await.unwrap());
+    assert!(!jobs.set_non_retryable(Uuid::new_v4()).await.unwrap());
+
+    let job = jobs
+        .lease_one_job("default", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+
+    // TIMEOUT on attempt 1 of 10 would normally be retried
+    runner
+        .on_failure(
+            job.id,
+            attempt.id,
+            "worker-a",
+            10,
+            "TIMEOUT",
+            "upstream timed out",
+            attempt.attempt_no,
+            job.max_attempts,
+        )
+        .await
+        .unwrap();
+
+    let stored = jobs.get_job(job_id).await.unwrap().unwrap();
+    assert_eq!(stored.status, "dlq");
+    assert_eq!(
+        stored.dlq_reason_code.as_deref(),
+        Some("FORCED_NON_RETRYABLE")
+    );
+    assert_eq!(stored.dlq_error_code.as_deref(), Some("TIMEOUT"));
+
+    let audit: (String, String) = sqlx::query_as(
+        "SELECT decision, reason_code FROM policy_decisions WHERE job_id = $1 AND decision = 'MANUAL_FLAG'",
+    )
+    .bind(job_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(audit.1, "MARKED_NON_RETRYABLE");
+
+    // terminal jobs can't be flagged
+    assert!(!jobs.set_non_retryable(job_id).await.unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn per_job_retry_override_replaces_worker_backoff() {
+    use chrono::TimeZone;
+    use postgresflow::jobs::retry::RetryOverride;
+    use postgresflow::jobs::{Clock, MockClock, NewJob};
+    use std::sync::Arc;
+
+    let pool = setup_db().await;
+    let t0 = chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(t0);
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let cfg = RetryConfig {
+        base_seconds: 1,
+        max_seconds: 15,
+        jitter_pct: 0.0,
+        min_delay_seconds: 0,
+    };
+    let runner =
+        JobRunner::new(jobs.clone(), attempts.clone(), cfg).with_clock(Arc::new(clock.clone()));
+
+    let custom = RetryOverride {
+        base_seconds: Some(60),
+        max_seconds: Some(90),
+    };
+    let mut custom_id = None;
+    for (queue, retry) in [("q_retry_default", None), ("q_retry_custom", Some(custom))] {
+        let id = jobs
+            .enqueue(NewJob {
+                queue: queue.to_string(),
+                job_type: "fail_me".to_string(),
+                payload_json: serde_json::json!({}),
+                run_at: t0,
+                priority: 0,
+                max_attempts: 5,
+                target_worker_id: None,
+                retry,
+                dedupe_key: None,
+                idempotency_key: None,
+                group_id: None,
+            })
+            .await
+            .unwrap();
+        if retry.is_some() {
+            custom_id = Some(id);
+        }
+    }
+
+    // fail the job on `queue` once; returns how far out its retry was scheduled
+    let fail_once = |queue: &'static str| {
+        let (jobs, attempts, runner, clock) = (
+            jobs.clone(),
+            attempts.clone(),
+            runner.clone(),
+            clock.clone(),
+        );
+        async move {
+            let job = jobs
+                .lease_one_job(queue, "worker-a", 30)
+                .await
+                .unwrap()
+                .expect("retry should already be due");
+            let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+            runner
+                .on_failure(
+                    job.id,
+                    attempt.id,
+                    "worker-a",
+                    10,
+                    "DEPENDENCY_DOWN",
+                    "still recovering",
+                    attempt.attempt_no,
+                    job.max_attempts,
+                )
+                .await
+                .unwrap();
+            let run_at = jobs.get_job(job.id).await.unwrap().unwrap().run_at;
+            (run_at - clock.now()).num_seconds()
+        }
+    };
+
+    assert_eq!(fail_once("q_retry_default").await, 1);
+    assert_eq!(fail_once("q_retry_custom").await, 60);
+    // doubled, then capped by the job's own max_seconds rather than the worker's 15
+    assert_eq!(fail_once("q_retry_custom").await, 90);
+
+    // the override is on the Job row, and replays carry it over
+    let custom_id = custom_id.unwrap();
+    let job = jobs.get_job(custom_id).await.unwrap().unwrap();
+    assert_eq!(job.retry_override(), custom);
+    let replay_id = jobs
+        .replay_job(custom_id, None, None, None, false)
+        .await
+        .unwrap();
+    let replay = jobs.get_job(replay_id).await.unwrap().unwrap();
+    assert_eq!(replay.retry_override(), custom);
+}
+
+#[tokio::test]
+#[serial]
+async fn queue_policy_retry_config_replaces_worker_backoff() {
+    use chrono::TimeZone;
+    use postgresflow::jobs::{Clock, MockClock, PoliciesRepo};
+    use std::sync::Arc;
+
+    let pool = setup_db().await;
+    let t0 = chrono::Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+    let clock = MockClock::new(t0);
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policies = PoliciesRepo::new(pool.clone());
+    let cfg = RetryConfig {
+        base_seconds: 10,
+        max_seconds: 600,
+        jitter_pct: 0.0,
+        min_delay_seconds: 0,
+    };
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), cfg.clone())
+        .with_clock(Arc::new(clock.clone()));
+
+    policies
+        .set_retry_config("q_retry_fast", Some(1), Some(4), Some(0.0))
+        .await
+        .unwrap();
+    policies
+        .set_retry_config("q_retry_slow", Some(120), None, Some(0.0))
+        .await
+        .unwrap();
+
+    let fast = policies.get_retry_config("q_retry_fast").await.unwrap();
+    assert_eq!((fast.base_seconds, fast.max_seconds), (1, 4));
+    // unset columns and queues without a policy keep the default
+    let slow = policies.get_retry_config("q_retry_slow").await.unwrap();
+    assert_eq!(slow.max_seconds, RetryConfig::default().max_seconds);
+    let none = policies.get_retry_config("q_retry_none").await.unwrap();
+    assert_eq!(none.base_seconds, RetryConfig::default().base_seconds);
+
+    // fail a fresh job on `queue` once; returns how far out its retry was scheduled
+    let fail_once = |queue: &'static str| {
+        let (jobs, attempts, runner, clock) = (
+            jobs.clone(),
+            attempts.clone(),
+            runner.clone(),
+            clock.clone(),
+        );
+        async move {
+            jobs.enqueue_now(queue, "fail_me", serde_json::json!({}))
+                .await
+                .unwrap();
+            let job = jobs
+                .lease_one_job(queue, "worker-a", 30)
+                .await
+                .unwrap()
+                .expect("job should be leasable");
+            let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+            runner
+                .on_failure(
+                    job.id,
+                    attempt.id,
+                    "worker-a",
+                    10,
+                    "DEPENDENCY_DOWN",
+                    "down",
+                    attempt.attempt_no,
+                    job.max_attempts,
+                )
+                .await
+                .unwrap();
+            let run_at = jobs.get_job(job.id).await.unwrap().unwrap().run_at;
+            (run_at - clock.now()).num_seconds()
+        }
+    };
+
+    assert_eq!(fail_once("q_retry_fast").await, 1);
+    assert_eq!(fail_once("q_retry_slow").await, 120);
+    assert_eq!(fail_once("q_retry_none").await, 10);
+}
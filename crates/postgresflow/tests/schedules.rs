@@ -0,0 +1,166 @@
+mod common;
+
+use chrono::Duration;
+use common::setup_db;
+use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
+use postgresflow::jobs::system_flags::queue_enqueue_flag;
+use postgresflow::jobs::{JobsRepo, NewSchedule, SchedulesRepo, SystemFlagsRepo};
+use serial_test::serial;
+
+async fn scheduled_jobs(pool: &sqlx::PgPool) -> Vec<(chrono::DateTime<chrono::Utc>, String)> {
+    sqlx::query_as("SELECT run_at, status FROM jobs WHERE queue = 'q_cron' ORDER BY run_at")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn every_minute_schedule_enqueues_one_job_per_window() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let schedules = SchedulesRepo::new(pool.clone());
+
+    assert!(schedules
+        .upsert_schedule(NewSchedule::new(
+            "bad",
+            "q_cron",
+            "tick",
+            serde_json::json!({}),
+            "every minute"
+        ))
+        .await
+        .is_err());
+
+    let schedule = schedules
+        .upsert_schedule(
+            NewSchedule::new(
+                "every-minute",
+                "q_cron",
+                "tick",
+                serde_json::json!({ "source": "cron" }),
+                "*/1 * * * *",
+            )
+            .with_priority(5)
+            .with_max_attempts(3),
+        )
+        .await
+        .unwrap();
+    let first = schedule.next_run_at.expect("should have a fire time");
+    assert!(schedules
+        .due_schedules(first - Duration::seconds(1))
+        .await
+        .unwrap()
+        .is_empty());
+
+    // two workers ticking in the same window enqueue one job between them
+    let now = first + Duration::seconds(1);
+    let (a, b) = tokio::join!(
+        schedules.materialize_due(&jobs, now),
+        schedules.materialize_due(&jobs, now),
+    );
+    assert_eq!(a.unwrap().len() + b.unwrap().len(), 1);
+    assert!(schedules
+        .materialize_due(&jobs, now + Duration::seconds(30))
+        .await
+        .unwrap()
+        .is_empty());
+
+    let next = first + Duration::minutes(1);
+    let schedule = schedules
+        .get_schedule("every-minute")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(schedule.next_run_at, Some(next));
+    assert_eq!(schedule.last_run_at, Some(first));
+
+    let ids = schedules
+        .materialize_due(&jobs, next + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), 1);
+    let job = jobs.get_job(ids[0]).await.unwrap().unwrap();
+    assert_eq!(job.job_type, "tick");
+    assert_eq!(job.payload_json["source"], "cron");
+    assert_eq!(job.priority, 5);
+    assert_eq!(job.max_attempts, 3);
+
+    assert_eq!(
+        scheduled_jobs(&pool).await,
+        vec![(first, "queued".to_string()), (next, "queued".to_string()),]
+    );
+
+    // missed windows collapse into one job
+    let late = next + Duration::minutes(5) + Duration::seconds(1);
+    assert_eq!(
+        schedules.materialize_due(&jobs, late).await.unwrap().len(),
+        1
+    );
+    let schedule = schedules
+        .get_schedule("every-minute")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(schedule.next_run_at, Some(next + Duration::minutes(6)));
+    assert_eq!(scheduled_jobs(&pool).await.len(), 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn windows_of_a_retired_queue_are_skipped() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let schedules = SchedulesRepo::new(pool.clone()).with_enqueue_guard(EnqueueGuard::new(
+        pool.clone(),
+        IngestDecisionsRepo::new(pool.clone()),
+        EnqueueGuardConfig::default(),
+    ));
+    let flags = SystemFlagsRepo::new(pool.clone());
+
+    let first = schedules
+        .upsert_schedule(NewSchedule::new(
+            "retired",
+            "q_cron",
+            "tick",
+            serde_json::json!({}),
+            "*/1 * * * *",
+        ))
+        .await
+        .unwrap()
+        .next_run_at
+        .unwrap();
+
+    flags
+        .set(&queue_enqueue_flag("q_cron"), false)
+        .await
+        .unwrap();
+    assert!(schedules
+        .materialize_due(&jobs, first + Duration::seconds(1))
+        .await
+        .unwrap()
+        .is_empty());
+    let schedule = schedules.get_schedule("retired").await.unwrap().unwrap();
+    assert_eq!(schedule.next_run_at, Some(first + Duration::minutes(1)));
+    assert_eq!(schedule.last_job_id, None);
+    assert!(scheduled_jobs(&pool).await.is_empty());
+
+    let denied: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM ingest_decisions WHERE queue = 'q_cron' AND reason_code = 'ENQUEUE_DISABLED'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(denied, 1);
+
+    flags
+        .set(&queue_enqueue_flag("q_cron"), true)
+        .await
+        .unwrap();
+    let ids = schedules
+        .materialize_due(&jobs, first + Duration::minutes(1) + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(ids.len(), 1);
+}
@@ -1,11 +1,15 @@
 mod common;
 
-use common::setup_db;
-use postgresflow::jobs::JobsRepo;
+use chrono::{SubsecRound, Utc};
+use common::{scheduled_once, setup_db};
+use postgresflow::jobs::{JobsRepo, MockClock, SucceededJob};
 use serde_json::json;
+use serial_test::serial;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[tokio::test]
+#[serial]
 async fn scheduled_job_is_not_leased_early_and_is_leased_after_run_at() {
     let pool = setup_db().await;
     let repo = JobsRepo::new(pool.clone());
@@ -27,3 +31,363 @@ async fn scheduled_job_is_not_leased_early_and_is_leased_after_run_at() {
 
     assert!(leased.is_some(), "should lease after run_at");
 }
+
+#[tokio::test]
+#[serial]
+async fn next_run_delay_is_the_time_until_the_earliest_scheduled_job() {
+    let pool = setup_db().await;
+    // whole seconds, so run_at survives Postgres' microsecond precision exactly
+    let clock = MockClock::new(Utc::now().trunc_subsecs(0));
+    let repo = JobsRepo::new(pool.clone()).with_clock(Arc::new(clock.clone()));
+
+    assert!(repo.next_run_delay("q_soon").await.unwrap().is_none());
+
+    repo.enqueue_in("q_soon", "scheduled", json!({}), 90)
+        .await
+        .unwrap();
+    repo.enqueue_in("q_soon", "scheduled", json!({}), 300)
+        .await
+        .unwrap();
+    repo.enqueue_in("q_other", "scheduled", json!({}), 10)
+        .await
+        .unwrap();
+    assert_eq!(
+        repo.next_run_delay("q_soon").await.unwrap(),
+        Some(Duration::from_secs(90))
+    );
+
+    clock.advance(chrono::Duration::seconds(60));
+    assert_eq!(
+        repo.next_run_delay("q_soon").await.unwrap(),
+        Some(Duration::from_secs(30))
+    );
+
+    // once the first is due, the next scheduled one counts
+    clock.advance(chrono::Duration::seconds(40));
+    assert_eq!(
+        repo.next_run_delay("q_soon").await.unwrap(),
+        Some(Duration::from_secs(200))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn scheduling_the_same_dedupe_key_twice_is_ignored() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+
+    let at = chrono::Utc::now() + chrono::Duration::hours(1);
+    let first = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_once",
+            "reminder",
+            json!({"user": 1}),
+            at,
+            "reminder:1",
+        ))
+        .await
+        .unwrap();
+    assert!(first.is_some());
+
+    // e.g. the scheduler restarted and recomputed the same reminder
+    let again = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_once",
+            "reminder",
+            json!({"user": 1}),
+            at,
+            "reminder:1",
+        ))
+        .await
+        .unwrap();
+    assert!(again.is_none());
+    let close = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_once",
+            "reminder",
+            json!({"user": 1}),
+            at + chrono::Duration::seconds(30),
+            "reminder:1",
+        ))
+        .await
+        .unwrap();
+    assert!(close.is_none());
+
+    // a different time or key is a different schedule
+    let later = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_once",
+            "reminder",
+            json!({"user": 1}),
+            at + chrono::Duration::hours(24),
+            "reminder:1",
+        ))
+        .await
+        .unwrap();
+    assert!(later.is_some());
+    let other = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_once",
+            "reminder",
+            json!({"user": 2}),
+            at,
+            "reminder:2",
+        ))
+        .await
+        .unwrap();
+    assert!(other.is_some());
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE queue = 'q_once'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+}
+
+async fn prom_deduped(state: &postgresflow::api::ApiState, queue: &str) -> Option<i64> {
+    let resp = postgresflow::api::metrics_prom(axum::extract::State(state.clone())).await;
+    let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let prefix = format!("pgflow_enqueue_deduped_total{{queue=\"{queue}\"}} ");
+    String::from_utf8(body.to_vec())
+        .unwrap()
+        .lines()
+        .find_map(|l| l.strip_prefix(&prefix).map(|v| v.parse().unwrap()))
+}
+
+#[tokio::test]
+#[serial]
+async fn deduped_enqueues_are_counted_and_optionally_recorded() {
+    let pool = setup_db().await;
+    let state = common::api_state(&pool);
+    let repo = JobsRepo::new(pool.clone()).with_dedupe_decisions(true);
+
+    let at = chrono::Utc::now() + chrono::Duration::hours(1);
+    let first = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_dedupe",
+            "reminder",
+            json!({}),
+            at,
+            "reminder:1",
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(prom_deduped(&state, "q_dedupe").await, None);
+
+    for _ in 0..2 {
+        let dup = repo
+            .enqueue_scheduled_once(scheduled_once(
+                "q_dedupe",
+                "reminder",
+                json!({}),
+                at,
+                "reminder:1",
+            ))
+            .await
+            .unwrap();
+        assert!(dup.is_none());
+    }
+    assert_eq!(prom_deduped(&state, "q_dedupe").await, Some(2));
+
+    let decisions = state
+        .ingest_decisions
+        .list_recent(Some("q_dedupe"), 10)
+        .await
+        .unwrap();
+    assert_eq!(decisions.len(), 2);
+    let (_, _, decision, reason_code, details, _) = &decisions[0];
+    assert_eq!(decision, "DEDUPED");
+    assert_eq!(reason_code, "DUPLICATE_DEDUPE_KEY");
+    assert_eq!(details["existing_job_id"], first.to_string());
+
+    // counted without the decision rows when not opted in
+    JobsRepo::new(pool.clone())
+        .enqueue_scheduled_once(scheduled_once(
+            "q_dedupe",
+            "reminder",
+            json!({}),
+            at,
+            "reminder:1",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(prom_deduped(&state, "q_dedupe").await, Some(3));
+    assert_eq!(
+        state
+            .ingest_decisions
+            .list_recent(Some("q_dedupe"), 10)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn handler_cooldown_holds_back_the_next_recurring_run() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone()).with_dedupe_decisions(true);
+    let state = common::api_state(&pool);
+
+    // a recurring poller: one run per minute under the same dedupe_key
+    let now = chrono::Utc::now();
+    let tick = |minutes: i64| now + chrono::Duration::minutes(minutes);
+    let first = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_poll",
+            "poll_feed",
+            json!({}),
+            now,
+            "poll:feed",
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let job = repo
+        .lease_one_job("q_poll", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(job.id, first);
+    repo.mark_succeeded(first, "worker-a").await.unwrap();
+    // the handler was rate limited upstream: no new run for 10 minutes
+    assert!(repo.set_cooldown_until(first, tick(10)).await.unwrap());
+
+    for minutes in [1, 5, 9] {
+        assert_eq!(
+            repo.enqueue_scheduled_once(scheduled_once(
+                "q_poll",
+                "poll_feed",
+                json!({}),
+                tick(minutes),
+                "poll:feed"
+            ))
+            .await
+            .unwrap(),
+            None,
+            "run at +{minutes}m scheduled inside the cooldown"
+        );
+    }
+    let decisions = state
+        .ingest_decisions
+        .list_recent(Some("q_poll"), 10)
+        .await
+        .unwrap();
+    assert_eq!(decisions.len(), 3);
+    assert_eq!(decisions[0].3, "COOLDOWN_ACTIVE");
+
+    let next = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_poll",
+            "poll_feed",
+            json!({}),
+            tick(11),
+            "poll:feed",
+        ))
+        .await
+        .unwrap();
+    assert!(next.is_some(), "run after the cooldown was not scheduled");
+
+    // only succeeded jobs take a cooldown
+    assert!(!repo
+        .set_cooldown_until(next.unwrap(), tick(30))
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+#[serial]
+async fn cooldown_is_stored_with_the_success_itself() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+
+    let now = chrono::Utc::now();
+    let until = now + chrono::Duration::minutes(10);
+    let first = repo
+        .enqueue_scheduled_once(scheduled_once(
+            "q_poll",
+            "poll_feed",
+            json!({}),
+            now,
+            "poll:feed",
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let job = repo
+        .lease_one_job("q_poll", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let succeeded = repo
+        .mark_succeeded_batch_with_feedback(
+            &job.dataset_id,
+            &[SucceededJob {
+                job_id: first,
+                result: None,
+                cooldown_until: Some(until),
+            }],
+            "worker-a",
+        )
+        .await
+        .unwrap();
+    assert_eq!(succeeded, 1);
+
+    let (status, cooldown_until): (String, Option<chrono::DateTime<Utc>>) =
+        sqlx::query_as("SELECT status, cooldown_until FROM jobs WHERE id = $1")
+            .bind(first)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "succeeded");
+    assert_eq!(
+        cooldown_until.map(|t| t.timestamp_micros()),
+        Some(until.timestamp_micros())
+    );
+    assert_eq!(
+        repo.enqueue_scheduled_once(scheduled_once(
+            "q_poll",
+            "poll_feed",
+            json!({}),
+            now + chrono::Duration::minutes(5),
+            "poll:feed"
+        ))
+        .await
+        .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn schedule_once_keeps_the_jobs_own_settings() {
+    let pool = setup_db().await;
+    let repo = JobsRepo::new(pool.clone());
+    let at = Utc::now().trunc_subsecs(0);
+
+    let mut job = scheduled_once("q_once_opts", "reminder", json!({}), at, "reminder:opts");
+    job.priority = 7;
+    job.max_attempts = 3;
+    let id = repo.enqueue_scheduled_once(job).await.unwrap().unwrap();
+
+    let stored = repo.get_job(id).await.unwrap().unwrap();
+    assert_eq!(stored.priority, 7);
+    assert_eq!(stored.max_attempts, 3);
+    let dedupe_key: Option<String> =
+        sqlx::query_scalar("SELECT dedupe_key FROM jobs WHERE id = $1")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(dedupe_key.as_deref(), Some("reminder:opts"));
+
+    let mut keyless = scheduled_once("q_once_opts", "reminder", json!({}), at, "unused");
+    keyless.dedupe_key = None;
+    assert!(repo.enqueue_scheduled_once(keyless).await.is_err());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, setup_db};
+use postgresflow::api::router;
+use serde_json::json;
+use serial_test::serial;
+use tower::ServiceExt;
+
+async fn search(app: axum::Router, query: &str) -> (StatusCode, serde_json::Value) {
+    let resp = app
+        .oneshot(
+            Request::get(format!("/jobs/search?{query}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (
+        status,
+        serde_json::from_slice(&body).unwrap_or(serde_json::Value::Null),
+    )
+}
+
+#[tokio::test]
+#[serial]
+async fn search_finds_jobs_by_payload_field() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let wanted = state
+        .jobs
+        .enqueue_now("q_orders", "order_ship", json!({ "order_id": 12345 }))
+        .await
+        .unwrap();
+    state
+        .jobs
+        .enqueue_now("q_orders", "order_ship", json!({ "order_id": 12346 }))
+        .await
+        .unwrap();
+    state
+        .jobs
+        .enqueue_now("q_orders", "order_ship", json!({ "customer_id": 12345 }))
+        .await
+        .unwrap();
+    let refund = state
+        .jobs
+        .enqueue_now("q_refunds", "order_refund", json!({ "order_id": "12345" }))
+        .await
+        .unwrap();
+    let app = router(state);
+
+    let (status, body) = search(app.clone(), "field=order_id&value=12345").await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<&str> = body["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["id"].as_str().unwrap())
+        .collect();
+    // newest first; numbers and strings match alike
+    assert_eq!(ids, vec![refund.to_string(), wanted.to_string()]);
+    assert_eq!(body["jobs"][1]["payload_json"]["order_id"], 12345);
+
+    let (_, body) = search(app.clone(), "field=order_id&value=12345&queue=q_orders").await;
+    assert_eq!(body["jobs"].as_array().unwrap().len(), 1);
+    assert_eq!(body["jobs"][0]["id"], wanted.to_string());
+
+    let (_, body) = search(
+        app.clone(),
+        "field=order_id&value=12345&job_type=order_refund",
+    )
+    .await;
+    assert_eq!(body["jobs"][0]["id"], refund.to_string());
+
+    let (_, body) = search(app.clone(), "field=order_id&value=12345&limit=1").await;
+    assert_eq!(body["jobs"].as_array().unwrap().len(), 1);
+
+    let (_, body) = search(app.clone(), "field=order_id&value=99999").await;
+    assert_eq!(body["jobs"], json!([]));
+
+    let (status, _) = search(app, "field=&value=12345").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
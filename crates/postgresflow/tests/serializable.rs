@@ -0,0 +1,91 @@
+mod common;
+
+use common::setup_db;
+use postgresflow::db::{run_serializable_with_retry, TxIsolation};
+use postgresflow::jobs::JobsRepo;
+use serde_json::json;
+use serial_test::serial;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Raises a real SQLSTATE 40001 from the server.
+async fn raise_serialization_failure(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DO $$
+        BEGIN
+          RAISE EXCEPTION 'injected' USING ERRCODE = 'serialization_failure';
+        END
+        $$
+        "#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn serialization_failure_is_retried_until_success() {
+    let pool = setup_db().await;
+    let calls = AtomicU32::new(0);
+
+    let out = run_serializable_with_retry(3, || async {
+        if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+            raise_serialization_failure(&pool).await?;
+        }
+        let one: i32 = sqlx::query_scalar("SELECT 1").fetch_one(&pool).await?;
+        Ok(one)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(out, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+#[serial]
+async fn serialization_retries_are_bounded_and_other_errors_not_retried() {
+    let pool = setup_db().await;
+
+    let calls = AtomicU32::new(0);
+    let res: anyhow::Result<()> = run_serializable_with_retry(2, || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        raise_serialization_failure(&pool).await
+    })
+    .await;
+    assert!(res.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let calls = AtomicU32::new(0);
+    let res: anyhow::Result<()> = run_serializable_with_retry(2, || async {
+        calls.fetch_add(1, Ordering::SeqCst);
+        sqlx::query("SELECT * FROM no_such_table")
+            .execute(&pool)
+            .await?;
+        Ok(())
+    })
+    .await;
+    assert!(res.is_err());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn serializable_lease_path_leases_jobs() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone()).with_lease_isolation(TxIsolation::Serializable);
+
+    let id = jobs
+        .enqueue_now("default", "work", json!({}))
+        .await
+        .unwrap();
+    let leased = jobs
+        .lease_one_job("default", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease under SERIALIZABLE");
+    assert_eq!(leased.id, id);
+    assert_eq!(leased.status, "running");
+}
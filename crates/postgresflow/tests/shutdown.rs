@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+use axum::routing::get;
+use axum::Router;
+use postgresflow::api::serve;
+use postgresflow::shutdown::Shutdown;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+fn slow_app(delay: Duration) -> Router {
+    Router::new().route(
+        "/slow",
+        get(move || async move {
+            tokio::time::sleep(delay).await;
+            "done"
+        }),
+    )
+}
+
+async fn start(
+    delay: Duration,
+    grace: Duration,
+) -> (
+    Shutdown,
+    std::net::SocketAddr,
+    tokio::task::JoinHandle<anyhow::Result<()>>,
+) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let shutdown = Shutdown::new();
+    let server = tokio::spawn(serve(
+        listener,
+        slow_app(delay),
+        shutdown.subscribe(),
+        grace,
+    ));
+    (shutdown, addr, server)
+}
+
+async fn send_slow_request(addr: std::net::SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    stream
+}
+
+#[tokio::test]
+async fn graceful_shutdown_drains_in_flight_request() {
+    let (shutdown, addr, server) = start(Duration::from_millis(300), Duration::from_secs(5)).await;
+
+    let mut stream = send_slow_request(addr).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    shutdown.trigger();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "got: {response}");
+    assert!(response.ends_with("done"));
+
+    tokio::time::timeout(Duration::from_secs(2), server)
+        .await
+        .expect("server should stop after draining")
+        .unwrap()
+        .unwrap();
+
+    // no new connections after shutdown
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn shutdown_gives_up_on_requests_after_grace_period() {
+    let (shutdown, addr, server) = start(Duration::from_secs(30), Duration::from_millis(200)).await;
+
+    let _stream = send_slow_request(addr).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let started = Instant::now();
+    shutdown.trigger();
+    server.await.unwrap().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::Utc;
+use common::{api_state, setup_db};
+use postgresflow::api::{put_sla, sla_report, PutSlaRequest, SlaQuery};
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, NewJob};
+use serial_test::serial;
+
+async fn run_attempt(jobs: &JobsRepo, attempts: &AttemptsRepo, job_type: &str, latency_ms: i32) {
+    let id = jobs
+        .enqueue(NewJob {
+            queue: "q_sla".to_string(),
+            job_type: job_type.to_string(),
+            payload_json: serde_json::json!({}),
+            run_at: Utc::now(),
+            priority: 0,
+            max_attempts: 3,
+            target_worker_id: None,
+            retry: None,
+            dedupe_key: None,
+            idempotency_key: None,
+            group_id: None,
+        })
+        .await
+        .unwrap();
+    let attempt = attempts.start_attempt(id, "worker-1").await.unwrap();
+    attempts
+        .finish_succeeded(attempt.id, latency_ms)
+        .await
+        .unwrap();
+}
+
+fn target(target_latency_ms: i32) -> PutSlaRequest {
+    PutSlaRequest {
+        target_latency_ms,
+        target_success_rate: 0.9,
+        latency_percentile: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn sla_reports_miss_for_job_type_breaching_latency() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    for job_type in ["email_send", "thumbnail", "idle"] {
+        let Json(saved) = put_sla(
+            State(state.clone()),
+            Path(job_type.to_string()),
+            Json(target(100)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(saved.latency_percentile, 0.99);
+    }
+
+    // email_send: slow tail breaches the 100ms p99 target
+    for latency in [20, 30, 40, 5_000] {
+        run_attempt(&state.jobs, &state.attempts, "email_send", latency).await;
+    }
+    // thumbnail: comfortably within target
+    for latency in [10, 15, 20] {
+        run_attempt(&state.jobs, &state.attempts, "thumbnail", latency).await;
+    }
+
+    let Json(resp) = sla_report(State(state), Query(SlaQuery { window_secs: None }))
+        .await
+        .unwrap();
+    assert_eq!(resp.window_secs, 3600);
+    assert_eq!(resp.job_types.len(), 3);
+
+    let by_type = |t: &str| resp.job_types.iter().find(|s| s.job_type == t).unwrap();
+
+    let email = by_type("email_send");
+    assert_eq!(email.attempts, 4);
+    assert!(email.observed_latency_ms.unwrap() > 100.0);
+    assert_eq!(email.meets_latency, Some(false));
+    assert_eq!(email.meets_success_rate, Some(true));
+    assert_eq!(email.met, Some(false));
+
+    let thumb = by_type("thumbnail");
+    assert_eq!(thumb.met, Some(true));
+    assert_eq!(thumb.observed_success_rate, Some(1.0));
+
+    // no attempts in the window: neither pass nor fail
+    let idle = by_type("idle");
+    assert_eq!(idle.attempts, 0);
+    assert_eq!(idle.met, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn put_sla_rejects_invalid_targets() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let err = put_sla(
+        State(state.clone()),
+        Path("email_send".to_string()),
+        Json(PutSlaRequest {
+            target_latency_ms: 100,
+            target_success_rate: 1.5,
+            latency_percentile: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.0, axum::http::StatusCode::BAD_REQUEST);
+}
@@ -3,8 +3,13 @@ mod common;
 
 use chrono::Utc;
 
-use common::setup_db;
-use postgresflow::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
+use axum::extract::State;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::{enqueue_job, EnqueueRequest};
+use postgresflow::jobs::{
+    AttemptsRepo, JobsRepo, PoliciesRepo, PolicyDecisionsRepo, StormControlLock,
+};
 use serial_test::serial;
 use uuid::Uuid;
 
@@ -131,3 +136,208 @@ async fn throttles_when_retry_rate_exceeded() {
     assert_eq!(last.decision, "THROTTLED");
     assert_eq!(last.reason_code, "RETRY_RATE_EXCEEDED");
 }
+
+fn enqueue_request(queue: &str) -> EnqueueRequest {
+    EnqueueRequest {
+        queue: Some(queue.to_string()),
+        job_type: "work".to_string(),
+        payload_json: Some(serde_json::json!({})),
+        payload_template: None,
+        run_at: None,
+        priority: None,
+        max_attempts: None,
+        target_worker_id: None,
+        retry_base_seconds: None,
+        retry_max_seconds: None,
+        tenant: None,
+        idempotency_key: None,
+        group_id: None,
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn enqueue_to_saturated_queue_reports_likely_throttled() {
+    let pool = setup_db().await;
+    let mut state = api_state(&pool);
+    state.enqueue_pressure_hint = true;
+
+    upsert_queue_policy(&pool, "q_sat", 1000, 2, 100).await;
+
+    let Json(first) = enqueue_job(State(state.clone()), Json(enqueue_request("q_sat")))
+        .await
+        .unwrap();
+    assert!(!first.likely_throttled);
+    assert_eq!(first.utilization, Some(0.0));
+
+    let Json(second) = enqueue_job(State(state.clone()), Json(enqueue_request("q_sat")))
+        .await
+        .unwrap();
+    assert!(!second.likely_throttled);
+
+    let leased = state
+        .jobs
+        .lease_jobs_batch("q_sat", "worker-1", 30, 2)
+        .await
+        .unwrap();
+    assert_eq!(leased.len(), 2);
+
+    let Json(saturated) = enqueue_job(State(state.clone()), Json(enqueue_request("q_sat")))
+        .await
+        .unwrap();
+    assert!(saturated.likely_throttled);
+    assert_eq!(saturated.utilization, Some(1.0));
+
+    // queues without a policy never report throttling
+    let Json(free) = enqueue_job(State(state), Json(enqueue_request("q_free")))
+        .await
+        .unwrap();
+    assert!(!free.likely_throttled);
+    assert_eq!(free.utilization, None);
+}
+
+#[tokio::test]
+#[serial]
+async fn lease_with_policy_returns_configured_policy() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+
+    upsert_queue_policy(&pool, "q_pol", 120, 7, 450).await;
+    let job_id = insert_job_direct(&pool, "q_pol", "work").await;
+
+    let leased = jobs
+        .lease_one_job_with_policy("q_pol", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    assert_eq!(leased.job.id, job_id);
+
+    let policy = leased.policy.expect("policy should be returned");
+    assert_eq!(policy.queue, "q_pol");
+    assert_eq!(policy.max_attempts_per_minute, Some(120));
+    assert_eq!(policy.max_in_flight, Some(7));
+    assert_eq!(policy.throttle_delay_ms, 450);
+
+    insert_job_direct(&pool, "q_nopol", "work").await;
+    let leased = jobs
+        .lease_one_job_with_policy("q_nopol", "worker-1", 30)
+        .await
+        .unwrap()
+        .expect("should lease job");
+    assert!(leased.policy.is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn policy_rows_from_other_setters_have_no_limits() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let policies = PoliciesRepo::new(pool.clone());
+
+    policies
+        .set_retry_config("q_retry_only", Some(5), None, None)
+        .await
+        .unwrap();
+    policies
+        .set_priority_bounds("q_retry_only", Some((-2, 2)))
+        .await
+        .unwrap();
+
+    let policy = policies.get_policy("q_retry_only").await.unwrap().unwrap();
+    assert_eq!(policy.max_in_flight, None);
+    assert_eq!(policy.max_attempts_per_minute, None);
+    assert!(!policy.has_limits());
+    assert!(jobs.queue_pressure("q_retry_only").await.unwrap().is_none());
+
+    // well past the old default of 50 in flight
+    for _ in 0..60 {
+        insert_job_direct(&pool, "q_retry_only", "work").await;
+    }
+    let leased = jobs
+        .lease_jobs_batch("q_retry_only", "worker-1", 30, 60)
+        .await
+        .unwrap();
+    assert_eq!(leased.len(), 60);
+}
+
+/// Hold the advisory lock `lock` uses for `queue` the way a slow lease would,
+/// until the returned transaction ends.
+async fn hold_storm_lock(
+    pool: &sqlx::PgPool,
+    lock: StormControlLock,
+    queue: &str,
+) -> sqlx::Transaction<'static, sqlx::Postgres> {
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+        .bind(lock.lock_name(queue).unwrap())
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    tx
+}
+
+#[tokio::test]
+#[serial]
+async fn per_queue_storm_lock_keeps_queues_from_blocking_each_other() {
+    let pool = setup_db().await;
+    let wait = std::time::Duration::from_millis(500);
+
+    for queue in ["q_storm_a", "q_storm_b"] {
+        upsert_queue_policy(&pool, queue, 1000, 1000, 50).await;
+        insert_job_direct(&pool, queue, "email_send").await;
+    }
+
+    let jobs = JobsRepo::new(pool.clone()).with_storm_control_lock(StormControlLock::PerQueue);
+
+    // a lease on queue A is mid-check
+    let held = hold_storm_lock(&pool, StormControlLock::PerQueue, "q_storm_a").await;
+
+    let leased_b = tokio::time::timeout(wait, jobs.lease_one_job("q_storm_b", "worker-b", 30))
+        .await
+        .expect("lease on queue B waited for queue A's storm-control check")
+        .unwrap();
+    assert!(leased_b.is_some());
+
+    let lease_a = tokio::spawn({
+        let jobs = jobs.clone();
+        async move { jobs.lease_one_job("q_storm_a", "worker-a", 30).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!lease_a.is_finished(), "same-queue checks must serialize");
+
+    held.commit().await.unwrap();
+    let leased_a = tokio::time::timeout(wait, lease_a)
+        .await
+        .expect("lease on queue A never resumed")
+        .unwrap()
+        .unwrap();
+    assert!(leased_a.is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn shared_storm_lock_serializes_checks_across_queues() {
+    let pool = setup_db().await;
+
+    for queue in ["q_storm_a", "q_storm_b"] {
+        upsert_queue_policy(&pool, queue, 1000, 1000, 50).await;
+        insert_job_direct(&pool, queue, "email_send").await;
+    }
+
+    let jobs = JobsRepo::new(pool.clone()).with_storm_control_lock(StormControlLock::Shared);
+    let held = hold_storm_lock(&pool, StormControlLock::Shared, "q_storm_a").await;
+
+    let blocked = tokio::time::timeout(
+        std::time::Duration::from_millis(200),
+        jobs.lease_one_job("q_storm_b", "worker-b", 30),
+    )
+    .await;
+    assert!(blocked.is_err(), "shared lock should make queue B wait");
+
+    held.commit().await.unwrap();
+    assert!(jobs
+        .lease_one_job("q_storm_b", "worker-b", 30)
+        .await
+        .unwrap()
+        .is_some());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use common::{insert_job, setup_db};
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, SystemFlagsRepo};
+use postgresflow::testing::{migrate_and_reset, CORE_TABLES};
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn migrate_and_reset_leaves_core_tables_empty() {
+    let pool = setup_db().await;
+
+    // some state in several tables
+    let jobs = JobsRepo::new(pool.clone());
+    insert_job(&pool, "q_reset").await;
+    let job = jobs
+        .lease_one_job("q_reset", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    AttemptsRepo::new(pool.clone())
+        .start_attempt(job.id, "worker-a")
+        .await
+        .unwrap();
+    SystemFlagsRepo::new(pool.clone())
+        .set("enqueue_enabled", false)
+        .await
+        .unwrap();
+
+    migrate_and_reset(&pool).await.unwrap();
+
+    for table in CORE_TABLES {
+        let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 0, "{table} not empty");
+    }
+
+    // idempotent: running it again against a migrated, empty schema is fine
+    migrate_and_reset(&pool).await.unwrap();
+}
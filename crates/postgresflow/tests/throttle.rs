@@ -0,0 +1,38 @@
+use postgresflow::jobs::JobRateLimiter;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn job_starts_stay_under_the_configured_rate() {
+    let mut limiter = JobRateLimiter::new(20.0);
+    assert_eq!(limiter.burst(), 20);
+
+    // a fresh worker may start one second's worth at once
+    let started = Instant::now();
+    for _ in 0..20 {
+        limiter.acquire().await;
+    }
+    assert!(started.elapsed() < Duration::from_millis(50));
+
+    // after that, starts are paced at the cap
+    let started = Instant::now();
+    for _ in 0..10 {
+        limiter.acquire().await;
+    }
+    let rate = 10.0 / started.elapsed().as_secs_f64();
+    assert!(
+        rate <= 20.0 * 1.02,
+        "started {rate:.1} jobs/sec with a cap of 20"
+    );
+}
+
+#[tokio::test]
+async fn rate_below_one_allows_one_job_per_interval() {
+    let mut limiter = JobRateLimiter::new(0.5);
+    assert_eq!(limiter.burst(), 1);
+    limiter.acquire().await;
+
+    tokio::select! {
+        _ = limiter.acquire() => panic!("second job started within 2s"),
+        _ = tokio::time::sleep(Duration::from_millis(300)) => {}
+    }
+}
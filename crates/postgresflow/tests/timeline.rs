@@ -1,10 +1,18 @@
 // crates/postgresflow/tests/timeline.rs
 mod common;
 
-use common::setup_db;
+use common::{api_state, setup_db};
 
-use postgresflow::jobs::timeline::build_timeline;
+use axum::body::to_bytes;
+use axum::extract::{Path, Query, State};
+use axum::response::IntoResponse;
+use postgresflow::api::{get_timeline, TimelineQuery};
+use postgresflow::jobs::attempts::AttemptOrder;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::timeline::{build_timeline, TimelineOptions};
 use postgresflow::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
+use serial_test::serial;
 
 use uuid::Uuid;
 
@@ -44,6 +52,7 @@ async fn timeline_shows_attempt_story() {
     // mimic retry scheduling
     jobs.reschedule_for_retry(
         job_id,
+        "worker-a",
         chrono::Utc::now(),
         Some("timeout"),
         Some("request timed out"),
@@ -65,7 +74,7 @@ async fn timeline_shows_attempt_story() {
     attempts.finish_succeeded(a2.id, 5).await.unwrap();
     jobs.mark_succeeded(job_id, "worker-b").await.unwrap();
 
-    let tl = build_timeline(&jobs, &attempts, &policy, job_id) // ✅ new arg
+    let tl = build_timeline(&jobs, &attempts, &policy, job_id, TimelineOptions::full()) // ✅ new arg
         .await
         .unwrap()
         .unwrap();
@@ -82,3 +91,180 @@ async fn timeline_shows_attempt_story() {
     assert_eq!(tl.attempts[1].attempt_no, 2);
     assert_eq!(tl.attempts[1].status, "succeeded");
 }
+
+#[tokio::test]
+async fn timeline_reads_job_header_without_payload() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policy = PolicyDecisionsRepo::new(pool.clone());
+
+    // ~4MB payload: the timeline never shows it, so it shouldn't be fetched
+    let big = "x".repeat(4 * 1024 * 1024);
+    let job_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts)
+        VALUES ('default', 'bulk_import', jsonb_build_object('blob', $1::text), now(), 'queued', 0, 5)
+        RETURNING id
+        "#,
+    )
+    .bind(&big)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let header = jobs.get_job_header(job_id).await.unwrap().unwrap();
+    assert_eq!(header.id, job_id);
+    assert_eq!(header.job_type, "bulk_import");
+    assert_eq!(header.status, "queued");
+
+    let tl = build_timeline(&jobs, &attempts, &policy, job_id, TimelineOptions::full())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tl.job_type, "bulk_import");
+    assert_eq!(tl.next_run_at, Some(header.run_at));
+    assert!(!serde_json::to_string(&tl).unwrap().contains(&big[..64]));
+
+    assert!(jobs.get_job_header(Uuid::new_v4()).await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn timeline_story_can_be_limited_to_recent_events() {
+    let pool = setup_db().await;
+    let state = api_state(&pool);
+
+    let job_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO jobs (queue, job_type, payload_json, run_at, status, priority, max_attempts)
+        VALUES ('default', 'chatty', '{}'::jsonb, now(), 'queued', 0, 5)
+        RETURNING id
+        "#,
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // 300 decisions, one minute apart, the newest a minute ago
+    sqlx::query(
+        r#"
+        INSERT INTO policy_decisions (id, job_id, decision, reason_code, details_json, created_at)
+        SELECT gen_random_uuid(), $1, 'THROTTLED', 'IN_FLIGHT_EXCEEDED',
+               jsonb_build_object('n', n), now() - make_interval(mins => 301 - n)
+        FROM generate_series(1, 300) AS n
+        "#,
+    )
+    .bind(job_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let full = build_timeline(
+        &state.jobs,
+        &state.attempts,
+        &state.policy_decisions,
+        job_id,
+        TimelineOptions::full(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(full.story.len(), 300);
+    assert_eq!(full.story_total, 300);
+    assert!(!full.story_truncated);
+
+    let timeline_json = |q: TimelineQuery| {
+        let state = state.clone();
+        async move {
+            let resp = get_timeline(Path(job_id), State(state), Query(q))
+                .await
+                .into_response();
+            let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    let recent = timeline_json(TimelineQuery {
+        limit: Some(10),
+        ..TimelineQuery::default()
+    })
+    .await;
+    let story = recent["story"].as_array().unwrap();
+    assert_eq!(story.len(), 10);
+    assert_eq!(story[0]["details_json"]["n"], 291);
+    assert_eq!(story[9]["details_json"]["n"], 300);
+    assert_eq!(recent["story_total"], 300);
+    assert_eq!(recent["story_truncated"], true);
+
+    let since = timeline_json(TimelineQuery {
+        since: Some(chrono::Utc::now() - chrono::Duration::seconds(5 * 60 + 30)),
+        ..TimelineQuery::default()
+    })
+    .await;
+    assert_eq!(since["story"].as_array().unwrap().len(), 5);
+    assert_eq!(since["story_truncated"], true);
+
+    let audit = timeline_json(TimelineQuery {
+        limit: Some(10),
+        full: Some(true),
+        ..TimelineQuery::default()
+    })
+    .await;
+    assert_eq!(audit["story"].as_array().unwrap().len(), 300);
+    assert_eq!(audit["story_truncated"], false);
+}
+
+#[tokio::test]
+#[serial]
+async fn error_details_from_the_handler_appear_on_the_attempt() {
+    let pool = setup_db().await;
+
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let policy = PolicyDecisionsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let job_id = jobs
+        .enqueue_now("default", "webhook_call", serde_json::json!({}))
+        .await
+        .unwrap();
+    let leased = jobs
+        .lease_one_job("default", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(leased.id, "worker-a").await.unwrap();
+
+    let details = serde_json::json!({ "http_status": 503, "request_id": "req-42" });
+    runner
+        .on_failure_with_details(
+            job_id,
+            attempt.id,
+            "worker-a",
+            12,
+            "DEPENDENCY_DOWN",
+            "HTTP 503: upstream unavailable",
+            Some(&details),
+            attempt.attempt_no,
+            leased.max_attempts,
+        )
+        .await
+        .unwrap();
+
+    let stored = attempts
+        .list_attempts_for_job(job_id, AttemptOrder::Asc)
+        .await
+        .unwrap();
+    assert_eq!(stored[0].error_details_json.as_ref(), Some(&details));
+
+    let tl = build_timeline(&jobs, &attempts, &policy, job_id, TimelineOptions::full())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(tl.attempts[0].error_details_json.as_ref(), Some(&details));
+    let json = serde_json::to_value(&tl).unwrap();
+    assert_eq!(json["last_error"]["error_details_json"], details);
+    assert_eq!(json["story"][0]["error_details_json"], details);
+}
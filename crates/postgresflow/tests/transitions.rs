@@ -0,0 +1,164 @@
+mod common;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use common::{api_state, insert_job, setup_db};
+use postgresflow::api::router;
+use postgresflow::jobs::maintenance::MaintenanceRepo;
+use postgresflow::jobs::retry::RetryConfig;
+use postgresflow::jobs::runner::JobRunner;
+use postgresflow::jobs::system_flags::STATE_TRANSITIONS_ENABLED;
+use postgresflow::jobs::{AttemptsRepo, JobsRepo, SystemFlagsRepo};
+use serde_json::Value;
+use serial_test::serial;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn get_transitions(pool: &sqlx::PgPool, id: Uuid) -> (StatusCode, Value) {
+    let resp = router(api_state(pool))
+        .oneshot(
+            Request::get(format!("/jobs/{id}/transitions"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+#[serial]
+async fn transitions_are_recorded_for_a_job_that_fails_then_succeeds() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let attempts = AttemptsRepo::new(pool.clone());
+    let runner = JobRunner::new(jobs.clone(), attempts.clone(), RetryConfig::default());
+
+    let job_id = insert_job(&pool, "q_audit").await;
+
+    let job = jobs
+        .lease_one_job("q_audit", "worker-a", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(job.id, "worker-a").await.unwrap();
+    runner
+        .on_failure(
+            job.id,
+            attempt.id,
+            "worker-a",
+            10,
+            "TIMEOUT",
+            "slow upstream",
+            attempt.attempt_no,
+            job.max_attempts,
+        )
+        .await
+        .unwrap();
+
+    // skip the backoff; a run_at change alone isn't a transition
+    sqlx::query("UPDATE jobs SET run_at = now() WHERE id = $1")
+        .bind(job_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let job = jobs
+        .lease_one_job("q_audit", "worker-b", 30)
+        .await
+        .unwrap()
+        .unwrap();
+    let attempt = attempts.start_attempt(job.id, "worker-b").await.unwrap();
+    runner
+        .on_success(job.id, attempt.id, "worker-b", 10)
+        .await
+        .unwrap();
+
+    let (status, body) = get_transitions(&pool, job_id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["job_id"], job_id.to_string());
+
+    let steps: Vec<(Value, Value, Value)> = body["transitions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            assert!(t["at"].is_string());
+            (
+                t["from_status"].clone(),
+                t["to_status"].clone(),
+                t["worker_id"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            (Value::Null, "queued".into(), Value::Null),
+            ("queued".into(), "running".into(), "worker-a".into()),
+            ("running".into(), "queued".into(), "worker-a".into()),
+            ("queued".into(), "running".into(), "worker-b".into()),
+            ("running".into(), "succeeded".into(), "worker-b".into()),
+        ]
+    );
+
+    let (status, _) = get_transitions(&pool, Uuid::new_v4()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn transition_count(pool: &sqlx::PgPool, id: Uuid) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM job_state_transitions WHERE job_id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+#[serial]
+async fn transitions_can_be_turned_off() {
+    let pool = setup_db().await;
+    let flags = SystemFlagsRepo::new(pool.clone());
+
+    flags.set(STATE_TRANSITIONS_ENABLED, false).await.unwrap();
+    let quiet = insert_job(&pool, "q_audit").await;
+    assert_eq!(transition_count(&pool, quiet).await, 0);
+
+    flags.set(STATE_TRANSITIONS_ENABLED, true).await.unwrap();
+    let recorded = insert_job(&pool, "q_audit").await;
+    assert_eq!(transition_count(&pool, recorded).await, 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn old_transitions_of_finished_jobs_are_pruned() {
+    let pool = setup_db().await;
+    let maint = MaintenanceRepo::new(pool.clone());
+
+    let live = insert_job(&pool, "q_audit").await;
+    let done = insert_job(&pool, "q_audit").await;
+    sqlx::query("UPDATE jobs SET status = 'succeeded' WHERE id = $1")
+        .bind(done)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let gone = insert_job(&pool, "q_audit").await;
+    sqlx::query("DELETE FROM jobs WHERE id = $1")
+        .bind(gone)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE job_state_transitions SET at = now() - interval '30 days'")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+    assert_eq!(maint.prune_state_transitions(cutoff, 100).await.unwrap(), 3);
+    // a queued job keeps its history however old
+    assert_eq!(transition_count(&pool, live).await, 1);
+    assert_eq!(transition_count(&pool, done).await, 0);
+    assert_eq!(transition_count(&pool, gone).await, 0);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use axum::extract::State;
+use axum::Json;
+use common::{api_state, setup_db};
+use postgresflow::api::version;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn version_reports_the_migration_applied_last() {
+    let pool = setup_db().await;
+
+    let Json(resp) = version(State(api_state(&pool))).await.unwrap();
+    assert_eq!(resp.version, env!("CARGO_PKG_VERSION"));
+    assert!(resp.migration.is_some(), "migrations were applied");
+
+    // an upgrade applies a migration below the highest (mis-dated) version
+    let (newest, installed_on): (i64, chrono::DateTime<chrono::Utc>) = sqlx::query_as(
+        "SELECT version, installed_on FROM _sqlx_migrations
+         WHERE success AND version < (SELECT MAX(version) FROM _sqlx_migrations)
+         ORDER BY version DESC LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let set_installed_on = |at: chrono::DateTime<chrono::Utc>| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query("UPDATE _sqlx_migrations SET installed_on = $2 WHERE version = $1")
+                .bind(newest)
+                .bind(at)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    };
+    set_installed_on(chrono::Utc::now() + chrono::Duration::minutes(1)).await;
+    let Json(resp) = version(State(api_state(&pool))).await.unwrap();
+    set_installed_on(installed_on).await;
+
+    let migration = resp.migration.expect("migrations were applied");
+    assert_eq!(migration.version, newest);
+}
@@ -0,0 +1,56 @@
+use postgresflow::jobs::WakeupCoalescer;
+use std::time::Duration;
+
+#[tokio::test]
+async fn burst_of_wakeups_triggers_bounded_number_of_leases() {
+    let wakeups = WakeupCoalescer::new(Duration::from_millis(50));
+
+    let waker = wakeups.clone();
+    let burst = tokio::spawn(async move {
+        for _ in 0..100 {
+            waker.wake();
+            tokio::task::yield_now().await;
+        }
+    });
+
+    // each return from wait() stands in for one lease call in the worker loop
+    let mut lease_calls = 0;
+    while wakeups.wait(Duration::from_millis(200)).await {
+        lease_calls += 1;
+    }
+    burst.await.unwrap();
+
+    assert!(lease_calls >= 1, "burst should wake the worker");
+    assert!(
+        lease_calls <= 2,
+        "100 notifications should coalesce, got {lease_calls} leases"
+    );
+    assert_eq!(wakeups.coalesced_total(), 100 - lease_calls);
+}
+
+#[tokio::test]
+async fn wait_times_out_without_wakeups() {
+    let wakeups = WakeupCoalescer::new(Duration::from_millis(10));
+    assert!(!wakeups.wait(Duration::from_millis(20)).await);
+    assert_eq!(wakeups.coalesced_total(), 0);
+}
+
+#[tokio::test]
+async fn coalesced_total_never_goes_down() {
+    let wakeups = WakeupCoalescer::new(Duration::ZERO);
+
+    // the first wake is pending, the next two fold into it
+    wakeups.wake();
+    wakeups.wake();
+    wakeups.wake();
+    assert_eq!(wakeups.coalesced_total(), 2);
+
+    assert!(wakeups.wait(Duration::from_millis(20)).await);
+    assert_eq!(wakeups.coalesced_total(), 2);
+    assert!(!wakeups.wait(Duration::from_millis(20)).await);
+
+    // a wake after the wait took the pending one starts a new cycle
+    wakeups.wake();
+    assert!(wakeups.wait(Duration::from_millis(20)).await);
+    assert_eq!(wakeups.coalesced_total(), 2);
+}
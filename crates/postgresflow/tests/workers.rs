@@ -0,0 +1,114 @@
+mod common;
+
+use common::{insert_job, setup_db};
+use postgresflow::jobs::maintenance::reap_stale_workers;
+use postgresflow::jobs::{JobsRepo, WorkersRepo};
+use serial_test::serial;
+
+async fn backdate_heartbeat(pool: &sqlx::PgPool, worker_id: &str, secs: i64) {
+    sqlx::query(
+        "UPDATE workers SET last_heartbeat_at = now() - ($2::bigint * interval '1 second') WHERE worker_id = $1",
+    )
+    .bind(worker_id)
+    .bind(secs)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn stale_worker_jobs_are_reclaimed_before_lease_expiry() {
+    let pool = setup_db().await;
+    let jobs = JobsRepo::new(pool.clone());
+    let workers = WorkersRepo::new(pool.clone());
+
+    let dead_job = insert_job(&pool, "default").await;
+    let live_job = insert_job(&pool, "default").await;
+
+    workers.heartbeat("worker-dead", "default").await.unwrap();
+    workers.heartbeat("worker-live", "default").await.unwrap();
+
+    // long leases: without fast reap these stay running for 10 minutes
+    let leased = jobs
+        .lease_one_job("default", "worker-dead", 600)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(leased.id, dead_job);
+    let leased = jobs
+        .lease_one_job("default", "worker-live", 600)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(leased.id, live_job);
+
+    backdate_heartbeat(&pool, "worker-dead", 120).await;
+
+    // lease-based reaping doesn't touch it yet
+    assert_eq!(jobs.reap_expired_locks().await.unwrap(), 0);
+
+    let reaped = reap_stale_workers(&jobs, 30).await.unwrap();
+    assert_eq!(reaped, vec![("worker-dead".to_string(), 1)]);
+
+    // counted with lease-expiry reaps in pgflow_locks_reaped_total
+    let counted: i64 =
+        sqlx::query_scalar("SELECT reaped_total FROM lock_reap_counters WHERE queue = 'default'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(counted, 1);
+
+    let job = jobs.get_job(dead_job).await.unwrap().unwrap();
+    assert_eq!(job.status, "queued");
+    assert_eq!(job.locked_by, None);
+
+    let job = jobs.get_job(live_job).await.unwrap().unwrap();
+    assert_eq!(job.status, "running");
+    assert_eq!(job.locked_by.as_deref(), Some("worker-live"));
+
+    // the dead worker is dropped from the registry; the live one stays
+    assert!(workers.stale_workers(30).await.unwrap().is_empty());
+    let registered: Vec<String> = sqlx::query_scalar("SELECT worker_id FROM workers")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(registered, vec!["worker-live".to_string()]);
+}
+
+#[tokio::test]
+#[serial]
+async fn heartbeat_reports_a_drain_request_until_the_worker_re_registers() {
+    let pool = setup_db().await;
+    let workers = WorkersRepo::new(pool.clone());
+
+    workers.register("worker-a", "default").await.unwrap();
+    assert!(!workers.heartbeat("worker-a", "default").await.unwrap());
+
+    assert!(workers.request_drain("worker-a").await.unwrap());
+    assert!(!workers.request_drain("worker-unknown").await.unwrap());
+    // every heartbeat reports it until the worker is gone
+    assert!(workers.heartbeat("worker-a", "default").await.unwrap());
+    assert!(workers.heartbeat("worker-a", "default").await.unwrap());
+
+    workers.mark_draining("worker-a").await.unwrap();
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM workers WHERE worker_id = 'worker-a'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "draining");
+
+    // a restart under the same id (crash before deregistering) starts clean
+    workers.register("worker-a", "default").await.unwrap();
+    assert!(!workers.heartbeat("worker-a", "default").await.unwrap());
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM workers WHERE worker_id = 'worker-a'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "running");
+
+    workers.remove("worker-a").await.unwrap();
+    assert!(!workers.is_registered("worker-a").await.unwrap());
+}
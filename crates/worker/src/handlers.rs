@@ -1,8 +1,22 @@
-use postgresflow::jobs::Job;
+use postgresflow::jobs::attempts::DEFAULT_ATTEMPT_LOG_MAX_LINES;
+use postgresflow::jobs::error_codes::classify_http_status;
+use postgresflow::jobs::{AttemptsRepo, HandlerPermits, Job, JobsRepo, LogLevel};
 use serde::Deserialize;
 use sqlx::PgPool;
-use std::{collections::HashMap, pin::Pin, sync::Arc, time::Duration};
-use tokio::{sync::Semaphore, time::timeout};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError},
+    time::timeout,
+};
+use uuid::Uuid;
 
 pub type BoxFuture<'a, T> = Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
 type HandlerFn =
@@ -12,6 +26,9 @@ type HandlerFn =
 pub struct JobError {
     pub code: &'static str,
     pub message: String,
+    /// Machine-readable context (HTTP status, upstream request id, ...),
+    /// stored on the attempt as `error_details_json`.
+    pub details: Option<serde_json::Value>,
 }
 
 impl JobError {
@@ -19,15 +36,152 @@ impl JobError {
         Self {
             code,
             message: message.into(),
+            details: None,
         }
     }
+
+    /// Attach structured context to the error. Objects are merged into
+    /// details already present (e.g. `from_http`'s `http_status`).
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        match (&mut self.details, details) {
+            (Some(serde_json::Value::Object(existing)), serde_json::Value::Object(extra)) => {
+                existing.extend(extra)
+            }
+            (slot, details) => *slot = Some(details),
+        }
+        self
+    }
+
+    /// Build an error from an upstream HTTP response (429 -> RATE_LIMIT, 5xx -> DEPENDENCY_DOWN, ...).
+    /// The status is kept in the details as `http_status`.
+    pub fn from_http(status: u16, body: impl Into<String>) -> Self {
+        Self::new(
+            classify_http_status(status).as_str(),
+            format!("HTTP {status}: {}", body.into()),
+        )
+        .with_details(serde_json::json!({ "http_status": status }))
+    }
 }
 
 #[derive(Clone)]
-#[allow(dead_code)]
 pub struct JobContext {
     pub db: PgPool,
     pub worker_id: String,
+    /// Job being run; set per run via `for_attempt`.
+    pub job_id: Uuid,
+    /// Dataset of the job being run; set per run via `for_attempt`.
+    pub dataset_id: String,
+    /// 1-based attempt being run; set per run via `for_attempt`.
+    pub attempt_no: i32,
+    pub max_attempts: i32,
+    /// Cap on lines `log` stores per attempt.
+    pub log_max_lines: i64,
+    // lines `log` has stored (or dropped) this attempt
+    log_lines: Arc<AtomicI64>,
+    // set by the handler, stored as the job's result_json on success
+    result: Arc<Mutex<Option<serde_json::Value>>>,
+    // set by the handler, stored as the job's cooldown_until on success
+    cooldown: Arc<Mutex<Option<Duration>>>,
+}
+
+impl JobContext {
+    pub fn new(db: PgPool, worker_id: String) -> Self {
+        Self {
+            db,
+            worker_id,
+            job_id: Uuid::nil(),
+            dataset_id: String::new(),
+            attempt_no: 0,
+            max_attempts: 0,
+            log_max_lines: DEFAULT_ATTEMPT_LOG_MAX_LINES,
+            log_lines: Arc::default(),
+            result: Arc::default(),
+            cooldown: Arc::default(),
+        }
+    }
+
+    /// Store at most `max_lines` `log` lines per attempt; `0` stores none.
+    pub fn with_log_max_lines(mut self, max_lines: i64) -> Self {
+        self.log_max_lines = max_lines;
+        self
+    }
+
+    /// Copy of this context for one handler run of `job`, with its own
+    /// result slot and log line count.
+    pub fn for_attempt(&self, job: &Job, attempt_no: i32) -> Self {
+        Self {
+            job_id: job.id,
+            dataset_id: job.dataset_id.clone(),
+            attempt_no,
+            max_attempts: job.max_attempts,
+            log_lines: Arc::default(),
+            result: Arc::default(),
+            cooldown: Arc::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Record a value for other systems to read from the job (`result_json`,
+    /// e.g. a generated report URL). Only kept if the handler returns `Ok`.
+    pub fn set_result(&self, value: serde_json::Value) {
+        *self.result.lock().unwrap() = Some(value);
+    }
+
+    pub fn take_result(&self) -> Option<serde_json::Value> {
+        self.result.lock().unwrap().take()
+    }
+
+    /// Success with a follow-up delay: a recurring schedule using
+    /// `enqueue_scheduled_once` won't run this job's `dedupe_key` again until
+    /// `duration` after it finished (e.g. rate-limited polling). Only kept if
+    /// the handler returns `Ok`.
+    pub fn set_cooldown(&self, duration: Duration) {
+        *self.cooldown.lock().unwrap() = Some(duration);
+    }
+
+    pub fn take_cooldown(&self) -> Option<Duration> {
+        self.cooldown.lock().unwrap().take()
+    }
+
+    /// Checkpoint for long handlers: push this job's lease out to `by` from
+    /// now. Returns false if the lease was lost (reaped, stolen, canceled);
+    /// the job may already be running elsewhere, so stop and return.
+    pub async fn extend_lease(&self, by: Duration) -> bool {
+        // a DB error means we can't vouch for the lease either
+        JobsRepo::new(self.db.clone())
+            .extend_lease(self.job_id, &self.worker_id, by.as_secs().max(1) as i64)
+            .await
+            .unwrap_or(false)
+    }
+
+    /// Append a line to this attempt's log, readable through
+    /// `GET /jobs/:id/logs`. Lines past `log_max_lines` are dropped, and a
+    /// failure to store the line is printed rather than failing the handler.
+    pub async fn log(&self, level: LogLevel, message: impl AsRef<str>) {
+        if self.log_lines.fetch_add(1, Ordering::Relaxed) >= self.log_max_lines {
+            return;
+        }
+        let stored = AttemptsRepo::new(self.db.clone())
+            .append_log(
+                &self.dataset_id,
+                self.job_id,
+                self.attempt_no,
+                level,
+                message.as_ref(),
+            )
+            .await;
+        if let Err(e) = stored {
+            eprintln!(
+                "[{}] job id={} attempt_no={} log error: {e}",
+                self.worker_id, self.job_id, self.attempt_no
+            );
+        }
+    }
+
+    /// True when a failure of this run will not be retried.
+    pub fn is_last_attempt(&self) -> bool {
+        self.attempt_no >= self.max_attempts
+    }
 }
 
 #[derive(Clone)]
@@ -35,21 +189,37 @@ pub struct HandlerEntry {
     pub handler: Arc<HandlerFn>,
     pub semaphore: Option<Arc<Semaphore>>,
     pub timeout: Option<Duration>,
+    /// Waiting longer than this for a concurrency permit is logged.
+    pub permit_wait_warn: Duration,
+    /// Top-level payload keys checked before the handler runs.
+    pub required_fields: Arc<[String]>,
 }
 
+/// Default for `HandlerOptions::permit_wait_warn`.
+pub const DEFAULT_PERMIT_WAIT_WARN: Duration = Duration::from_secs(1);
+
 #[derive(Clone)]
 pub struct HandlerRegistry {
     handlers: HashMap<String, HandlerEntry>,
+    permits: HandlerPermits,
+    default_timeout: Option<Duration>,
 }
 
 impl HandlerRegistry {
     pub fn new() -> Self {
         Self {
             handlers: HashMap::new(),
+            permits: HandlerPermits::new(),
+            default_timeout: None,
         }
     }
 
-    #[allow(dead_code)]
+    /// Backstop timeout for handlers registered without one, so a hung
+    /// handler can't hold its lease forever. `None` leaves them unbounded.
+    pub fn set_default_timeout(&mut self, dur: Option<Duration>) {
+        self.default_timeout = dur;
+    }
+
     pub fn register<F>(&mut self, job_type: &str, handler: F)
     where
         F: for<'a> Fn(&'a Job, &'a JobContext) -> BoxFuture<'a, Result<(), JobError>>
@@ -60,7 +230,6 @@ impl HandlerRegistry {
         self.register_with_options(job_type, handler, HandlerOptions::new());
     }
 
-    #[allow(dead_code)]
     pub fn register_with_limit<F>(&mut self, job_type: &str, handler: F, max_concurrency: usize)
     where
         F: for<'a> Fn(&'a Job, &'a JobContext) -> BoxFuture<'a, Result<(), JobError>>
@@ -96,21 +265,45 @@ impl HandlerRegistry {
             + Sync
             + 'static,
     {
-        let semaphore = opts
-            .max_concurrency
-            .map(|n| Arc::new(Semaphore::new(n.max(1))));
+        let semaphore = opts.max_concurrency.map(|n| {
+            let sem = Arc::new(Semaphore::new(n.max(1)));
+            self.permits.track(job_type, sem.clone(), n.max(1));
+            sem
+        });
         self.handlers.insert(
             job_type.to_string(),
             HandlerEntry {
                 handler: Arc::new(handler),
                 semaphore,
                 timeout: opts.timeout,
+                permit_wait_warn: opts.permit_wait_warn,
+                required_fields: opts.required_fields.into(),
             },
         );
     }
 
     pub fn handler_for(&self, job_type: &str) -> Option<HandlerEntry> {
-        self.handlers.get(job_type).cloned()
+        self.handlers.get(job_type).map(|entry| HandlerEntry {
+            timeout: entry.timeout.or(self.default_timeout),
+            ..entry.clone()
+        })
+    }
+
+    pub fn job_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.handlers.keys().cloned().collect();
+        types.sort();
+        types
+    }
+
+    /// Live view of the limited handlers' permits, for `/metrics/prom`.
+    pub fn permits(&self) -> HandlerPermits {
+        self.permits.clone()
+    }
+}
+
+impl Default for HandlerRegistry {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -118,6 +311,8 @@ impl HandlerRegistry {
 pub struct HandlerOptions {
     max_concurrency: Option<usize>,
     timeout: Option<Duration>,
+    permit_wait_warn: Duration,
+    required_fields: Vec<String>,
 }
 
 impl HandlerOptions {
@@ -125,6 +320,8 @@ impl HandlerOptions {
         Self {
             max_concurrency: None,
             timeout: None,
+            permit_wait_warn: DEFAULT_PERMIT_WAIT_WARN,
+            required_fields: Vec::new(),
         }
     }
 
@@ -137,17 +334,33 @@ impl HandlerOptions {
         self.timeout = Some(dur);
         self
     }
+
+    pub fn permit_wait_warn(mut self, dur: Duration) -> Self {
+        self.permit_wait_warn = dur;
+        self
+    }
+
+    /// Fail jobs missing any of these top-level payload keys with
+    /// `BAD_PAYLOAD` before the handler body runs.
+    pub fn required_fields(mut self, fields: &[&str]) -> Self {
+        self.required_fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+}
+
+impl Default for HandlerOptions {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl HandlerEntry {
     pub async fn run(&self, job: &Job, ctx: &JobContext) -> Result<(), JobError> {
+        // checked before taking a permit: a bad payload shouldn't queue behind good ones
+        self.check_required_fields(job)?;
+
         let _permit = if let Some(sem) = &self.semaphore {
-            Some(
-                sem.clone()
-                    .acquire_owned()
-                    .await
-                    .map_err(|_| JobError::new("WORKER_SHUTDOWN", "handler semaphore closed"))?,
-            )
+            Some(self.acquire_permit(sem, &job.job_type).await?)
         } else {
             None
         };
@@ -168,6 +381,48 @@ impl HandlerEntry {
         drop(_permit);
         res
     }
+
+    fn check_required_fields(&self, job: &Job) -> Result<(), JobError> {
+        let missing: Vec<&str> = self
+            .required_fields
+            .iter()
+            .filter(|f| job.payload_json.get(f.as_str()).is_none())
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(JobError::new(
+            "BAD_PAYLOAD",
+            format!("missing required payload fields: {}", missing.join(", ")),
+        ))
+    }
+
+    async fn acquire_permit(
+        &self,
+        sem: &Arc<Semaphore>,
+        job_type: &str,
+    ) -> Result<OwnedSemaphorePermit, JobError> {
+        let closed = || JobError::new("WORKER_SHUTDOWN", "handler semaphore closed");
+        match sem.clone().try_acquire_owned() {
+            Ok(permit) => return Ok(permit),
+            Err(TryAcquireError::Closed) => return Err(closed()),
+            Err(TryAcquireError::NoPermits) => {}
+        }
+
+        // saturated: the job is leased but can't start until a run finishes
+        let started = Instant::now();
+        let permit = sem.clone().acquire_owned().await.map_err(|_| closed())?;
+        let waited = started.elapsed();
+        if waited > self.permit_wait_warn {
+            eprintln!(
+                "[handler] job_type={} waited {}ms for a concurrency permit (max_concurrency saturated)",
+                job_type,
+                waited.as_millis()
+            );
+        }
+        Ok(permit)
+    }
 }
 
 #[derive(Deserialize)]
@@ -185,8 +440,9 @@ fn boxed<'a, T>(fut: impl std::future::Future<Output = T> + Send + 'a) -> BoxFut
     Box::pin(fut)
 }
 
-pub fn build_registry() -> Arc<HandlerRegistry> {
+pub fn build_registry(default_timeout: Option<Duration>) -> Arc<HandlerRegistry> {
     let mut registry = HandlerRegistry::new();
+    registry.set_default_timeout(default_timeout);
 
     // Demo handlers. Replace these with your real handlers.
     registry.register_with_timeout(
@@ -228,8 +484,392 @@ pub fn build_registry() -> Arc<HandlerRegistry> {
         },
         HandlerOptions::new()
             .max_concurrency(50)
-            .timeout(Duration::from_secs(10)),
+            .timeout(Duration::from_secs(10))
+            .required_fields(&["user_id"]),
     );
 
     Arc::new(registry)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use postgresflow::jobs::retry::RetryConfig;
+    use postgresflow::jobs::runner::JobRunner;
+    use serial_test::serial;
+
+    fn job(max_attempts: i32) -> Job {
+        Job {
+            dataset_id: "legacy".to_string(),
+            replay_of_job_id: None,
+            replay_include_history: false,
+            id: Uuid::new_v4(),
+            queue: "default".to_string(),
+            job_type: "flaky".to_string(),
+            payload_json: serde_json::json!({}),
+            run_at: Utc::now(),
+            status: "running".to_string(),
+            priority: 0,
+            max_attempts,
+            locked_at: None,
+            locked_by: None,
+            lock_expires_at: None,
+            dlq_reason_code: None,
+            dlq_error_code: None,
+            dlq_at: None,
+            target_worker_id: None,
+            group_id: None,
+            result_json: None,
+            retry_base_seconds: None,
+            retry_max_seconds: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn handler_sees_attempt_no_across_retries() {
+        let Some(db) = postgresflow::testing::connect_from_env().await.unwrap() else {
+            eprintln!("TEST_DATABASE_URL unset; skipping");
+            return;
+        };
+        postgresflow::testing::migrate_and_reset(&db).await.unwrap();
+
+        let seen: Arc<Mutex<Vec<(i32, bool)>>> = Arc::default();
+        let mut registry = HandlerRegistry::new();
+        let seen_by_handler = seen.clone();
+        registry.register("flaky", move |_job, ctx| {
+            let seen = seen_by_handler.clone();
+            boxed(async move {
+                seen.lock()
+                    .unwrap()
+                    .push((ctx.attempt_no, ctx.is_last_attempt()));
+                if ctx.is_last_attempt() {
+                    Ok(())
+                } else {
+                    Err(JobError::new("TIMEOUT", "try again"))
+                }
+            })
+        });
+
+        let jobs = JobsRepo::new(db.clone());
+        let attempts = AttemptsRepo::new(db.clone());
+        let retry_cfg = RetryConfig {
+            base_seconds: 1,
+            max_seconds: 1,
+            jitter_pct: 0.0,
+            min_delay_seconds: 0,
+        };
+        let runner = JobRunner::new(jobs.clone(), attempts.clone(), retry_cfg);
+        let job_id = jobs
+            .enqueue_now("q_flaky", "flaky", serde_json::json!({}))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE jobs SET max_attempts = 3 WHERE id = $1")
+            .bind(job_id)
+            .execute(&db)
+            .await
+            .unwrap();
+
+        // lease, start the attempt and record its outcome the way the worker does
+        let base = JobContext::new(db.clone(), "worker-1".to_string());
+        let entry = registry.handler_for("flaky").unwrap();
+        loop {
+            let job = jobs
+                .lease_one_job("q_flaky", "worker-1", 30)
+                .await
+                .unwrap()
+                .expect("job is leasable");
+            let attempt = attempts.start_attempt(job.id, "worker-1").await.unwrap();
+            match entry
+                .run(&job, &base.for_attempt(&job, attempt.attempt_no))
+                .await
+            {
+                Ok(()) => {
+                    runner
+                        .on_success(job.id, attempt.id, "worker-1", 1)
+                        .await
+                        .unwrap();
+                    break;
+                }
+                Err(err) => runner
+                    .on_failure(
+                        job.id,
+                        attempt.id,
+                        "worker-1",
+                        1,
+                        err.code,
+                        &err.message,
+                        attempt.attempt_no,
+                        job.max_attempts,
+                    )
+                    .await
+                    .unwrap(),
+            }
+            // skip the backoff
+            sqlx::query("UPDATE jobs SET run_at = now() WHERE id = $1")
+                .bind(job_id)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(1, false), (2, false), (3, true)]
+        );
+        assert_eq!(base.attempt_no, 0, "base context is not mutated");
+        let job = jobs.get_job(job_id).await.unwrap().unwrap();
+        assert_eq!(job.status, "succeeded");
+    }
+
+    #[tokio::test]
+    async fn handler_error_details_reach_the_caller() {
+        let mut registry = HandlerRegistry::new();
+        registry.register("webhook", |_job, _ctx| {
+            boxed(async move {
+                Err(JobError::from_http(503, "upstream unavailable")
+                    .with_details(serde_json::json!({ "request_id": "req-42" })))
+            })
+        });
+
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let ctx = JobContext::new(db, "worker-1".to_string());
+        let err = registry
+            .handler_for("webhook")
+            .unwrap()
+            .run(&job(3), &ctx)
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, "DEPENDENCY_DOWN");
+        assert_eq!(
+            err.details,
+            Some(serde_json::json!({ "http_status": 503, "request_id": "req-42" }))
+        );
+        assert_eq!(
+            JobError::from_http(429, "slow down").details,
+            Some(serde_json::json!({ "http_status": 429 }))
+        );
+    }
+
+    #[tokio::test]
+    async fn default_timeout_stops_handlers_without_their_own() {
+        fn hang<'a>(_job: &'a Job, _ctx: &'a JobContext) -> BoxFuture<'a, Result<(), JobError>> {
+            boxed(async move {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                Ok(())
+            })
+        }
+        let mut registry = HandlerRegistry::new();
+        registry.register("hangs", hang);
+        registry.register_with_timeout("hangs_briefly", hang, Duration::from_millis(150));
+        registry.set_default_timeout(Some(Duration::from_millis(50)));
+
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let ctx = JobContext::new(db, "worker-1".to_string());
+        let job = job(3);
+
+        let started = Instant::now();
+        let err = registry
+            .handler_for("hangs")
+            .unwrap()
+            .run(&job, &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, "TIMEOUT");
+        assert_eq!(err.message, "handler timeout after 50ms");
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // an explicit timeout wins over the default
+        let err = registry
+            .handler_for("hangs_briefly")
+            .unwrap()
+            .run(&job, &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "handler timeout after 150ms");
+    }
+
+    #[tokio::test]
+    async fn handler_result_is_taken_from_its_attempt_context() {
+        let mut registry = HandlerRegistry::new();
+        registry.register("report", |job, ctx| {
+            boxed(async move {
+                ctx.set_result(serde_json::json!({ "url": format!("s3://reports/{}", job.id) }));
+                ctx.set_cooldown(Duration::from_secs(600));
+                Ok(())
+            })
+        });
+
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let base = JobContext::new(db, "worker-1".to_string());
+        let job = job(1);
+        let ctx = base.for_attempt(&job, 1);
+        registry
+            .handler_for("report")
+            .unwrap()
+            .run(&job, &ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ctx.take_result(),
+            Some(serde_json::json!({ "url": format!("s3://reports/{}", job.id) }))
+        );
+        assert_eq!(ctx.take_result(), None);
+        assert_eq!(base.take_result(), None, "runs don't share a result slot");
+        assert_eq!(ctx.take_cooldown(), Some(Duration::from_secs(600)));
+        assert_eq!(base.take_cooldown(), None);
+    }
+
+    #[tokio::test]
+    async fn saturated_handler_reports_zero_available_permits() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let mut registry = HandlerRegistry::new();
+        let gate = release.clone();
+        registry.register_with_limit(
+            "slow",
+            move |_job, _ctx| {
+                let gate = gate.clone();
+                boxed(async move {
+                    gate.notified().await;
+                    Ok(())
+                })
+            },
+            2,
+        );
+        registry.register("unlimited", |_job, _ctx| boxed(async move { Ok(()) }));
+
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let ctx = JobContext::new(db, "worker-1".to_string());
+        let entry = registry.handler_for("slow").unwrap();
+        let runs: Vec<_> = (0..3)
+            .map(|_| {
+                let (entry, ctx, job) = (entry.clone(), ctx.clone(), job(1));
+                tokio::spawn(async move { entry.run(&job, &ctx).await })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+
+        let permits = registry.permits();
+        let snapshot = permits.snapshot();
+        assert_eq!(snapshot.len(), 1, "only limited handlers are reported");
+        assert_eq!(snapshot[0].job_type, "slow");
+        assert_eq!((snapshot[0].available, snapshot[0].total), (0, 2));
+
+        // the third run gets a permit once the first two finish
+        for _ in 0..3 {
+            release.notify_one();
+            tokio::task::yield_now().await;
+        }
+        for run in runs {
+            run.await.unwrap().unwrap();
+        }
+        assert_eq!(permits.snapshot()[0].available, 2);
+    }
+
+    #[tokio::test]
+    async fn missing_required_fields_fail_before_the_handler_runs() {
+        let ran = Arc::new(Mutex::new(false));
+        let mut registry = HandlerRegistry::new();
+        let ran_by_handler = ran.clone();
+        registry.register_with_options(
+            "invite",
+            move |_job, _ctx| {
+                *ran_by_handler.lock().unwrap() = true;
+                boxed(async move { Ok(()) })
+            },
+            HandlerOptions::new().required_fields(&["user_id", "team_id", "role"]),
+        );
+
+        let db = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let ctx = JobContext::new(db, "worker-1".to_string());
+        let entry = registry.handler_for("invite").unwrap();
+
+        let mut bad = job(1);
+        bad.payload_json = serde_json::json!({ "team_id": 7 });
+        let err = entry.run(&bad, &ctx).await.unwrap_err();
+        assert_eq!(err.code, "BAD_PAYLOAD");
+        assert_eq!(
+            err.message,
+            "missing required payload fields: user_id, role"
+        );
+        assert!(!*ran.lock().unwrap(), "handler body must not run");
+
+        let mut good = job(1);
+        good.payload_json = serde_json::json!({ "user_id": 1, "team_id": 7, "role": null });
+        entry.run(&good, &ctx).await.unwrap();
+        assert!(*ran.lock().unwrap());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn handler_log_lines_are_stored_for_its_attempt() {
+        let Some(db) = postgresflow::testing::connect_from_env().await.unwrap() else {
+            eprintln!("TEST_DATABASE_URL unset; skipping");
+            return;
+        };
+        postgresflow::testing::migrate_and_reset(&db).await.unwrap();
+
+        let mut registry = HandlerRegistry::new();
+        registry.register("chatty", |job, ctx| {
+            boxed(async move {
+                ctx.log(LogLevel::Info, format!("processing {}", job.id))
+                    .await;
+                if ctx.is_last_attempt() {
+                    Ok(())
+                } else {
+                    ctx.log(LogLevel::Warn, "upstream slow, retrying").await;
+                    Err(JobError::new("TIMEOUT", "try again"))
+                }
+            })
+        });
+
+        let jobs = JobsRepo::new(db.clone());
+        let job_id = jobs
+            .enqueue_now("q_logs", "chatty", serde_json::json!({}))
+            .await
+            .unwrap();
+        let mut job = jobs.get_job(job_id).await.unwrap().unwrap();
+        job.max_attempts = 2;
+
+        // the second attempt's third line is over the cap
+        let base = JobContext::new(db.clone(), "worker-1".to_string()).with_log_max_lines(2);
+        let entry = registry.handler_for("chatty").unwrap();
+        assert!(entry.run(&job, &base.for_attempt(&job, 1)).await.is_err());
+        let last = base.for_attempt(&job, 2);
+        entry.run(&job, &last).await.unwrap();
+        last.log(LogLevel::Info, "done").await;
+        last.log(LogLevel::Info, "dropped").await;
+
+        let attempts = AttemptsRepo::new(db);
+        let first: Vec<(String, String)> = attempts
+            .list_logs(job_id, Some(1))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|line| (line.level, line.message))
+            .collect();
+        assert_eq!(
+            first,
+            vec![
+                ("info".to_string(), format!("processing {job_id}")),
+                ("warn".to_string(), "upstream slow, retrying".to_string()),
+            ]
+        );
+
+        let all = attempts.list_logs(job_id, None).await.unwrap();
+        let second: Vec<&str> = all
+            .iter()
+            .filter(|line| line.attempt_no == 2)
+            .map(|line| line.message.as_str())
+            .collect();
+        assert_eq!(
+            second,
+            vec![format!("processing {job_id}").as_str(), "done"]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+//! Handler API for the worker binary: register job handlers and use
+//! `JobContext` from inside them.
+
+pub mod handlers;
@@ -3,18 +3,27 @@ use postgresflow::config;
 use postgresflow::db;
 
 use postgresflow::jobs::enqueue_guard::{EnqueueGuard, EnqueueGuardConfig};
+use postgresflow::jobs::handler_check;
 use postgresflow::jobs::ingest_decisions::IngestDecisionsRepo;
-use postgresflow::jobs::maintenance::{cutoff_days, MaintenanceRepo};
+use postgresflow::jobs::maintenance::{
+    reap_stale_workers, run_heavy_maintenance, MaintenanceRepo, RATE_COUNTER_RETENTION_MINUTES,
+};
 use postgresflow::jobs::metrics::MetricsRepo;
 use postgresflow::jobs::retry::RetryConfig;
 use postgresflow::jobs::runner::JobRunner;
-use postgresflow::jobs::{AttemptsRepo, JobsRepo, PolicyDecisionsRepo};
+use postgresflow::jobs::{
+    AttemptsRepo, JobRateLimiter, JobTypesRepo, JobsRepo, PolicyDecisionsRepo, SchedulesRepo,
+    SlaRepo, SystemFlagsRepo, WakeupCoalescer, WorkersRepo,
+};
+use postgresflow::shutdown::{self, Shutdown};
 
+use chrono::Utc;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
-mod handlers;
-use handlers::{build_registry, JobContext, JobError};
+use worker::handlers::{build_registry, JobContext, JobError};
 
 enum JobExecutionOutcome {
     Succeeded {
@@ -22,6 +31,8 @@ enum JobExecutionOutcome {
         attempt_id: Uuid,
         attempt_no: i32,
         latency_ms: i32,
+        result: Option<serde_json::Value>,
+        cooldown: Option<Duration>,
     },
     Failed {
         job_id: Uuid,
@@ -31,6 +42,7 @@ enum JobExecutionOutcome {
         latency_ms: i32,
         error_code: String,
         error_message: String,
+        error_details: Option<serde_json::Value>,
     },
 }
 
@@ -44,20 +56,10 @@ async fn main() -> anyhow::Result<()> {
     let reap_interval = Duration::from_millis(cfg.reap_interval_ms);
     let verbose_job_logs = cfg.verbose_job_logs;
     let api_addr = cfg.admin_addr.clone();
-
-    // Maintenance envs
-    let archive_after_days: i64 = std::env::var("ARCHIVE_SUCCEEDED_AFTER_DAYS")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(7);
-    let prune_history_after_days: i64 = std::env::var("PRUNE_HISTORY_AFTER_DAYS")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(7);
-    let maintenance_interval_secs: u64 = std::env::var("MAINTENANCE_INTERVAL_SECS")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(60);
+    let shutdown_grace = Duration::from_millis(cfg.shutdown_grace_ms);
+    let archive_after_days = cfg.archive_after_days;
+    let prune_history_after_days = cfg.prune_history_after_days;
+    let maintenance_interval_secs = cfg.maintenance_interval_secs;
 
     println!(
         "pgflow starting... worker_id={} queue={} lease={}s dequeue_batch_size={} reap_interval_ms={} verbose_job_logs={} api={} auth={} migrate_on_startup={} archive_after_days={} prune_history_after_days={} maintenance_interval_secs={}",
@@ -75,108 +77,339 @@ async fn main() -> anyhow::Result<()> {
         maintenance_interval_secs
     );
 
-    let pool = db::make_pool(&cfg.database_url).await?;
+    let pool = db::make_pool(&cfg.database_url, &cfg.application_name).await?;
+    // set when migrations were skipped over a checksum mismatch: the schema
+    // may not be what this build expects, so only the API runs, read-only
+    let mut read_only = false;
     if cfg.migrate_on_startup {
-        db::run_migrations(&pool).await?;
+        match db::run_migrations(&pool).await {
+            Ok(()) => {}
+            Err(e) if e.is::<db::MigrationChecksumError>() => {
+                eprintln!("[migrate] {e}");
+                if cfg.migration_mismatch == db::MigrationMismatchMode::Fail {
+                    return Err(e);
+                }
+                eprintln!(
+                    "[migrate] PGFLOW_MIGRATION_MISMATCH=skip: serving the API read-only; not leasing jobs, running maintenance or schedules"
+                );
+                read_only = true;
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    let jobs_repo = JobsRepo::new(pool.clone());
-    let attempts_repo = AttemptsRepo::new(pool.clone());
-    let policy_decisions_repo = PolicyDecisionsRepo::new(pool.clone());
+    let jobs_repo = JobsRepo::new(pool.clone())
+        .with_pin_timeout_secs(cfg.pin_timeout_secs)
+        .with_success_overrides_cancel(cfg.success_overrides_cancel)
+        .with_cancel_group_on_dlq(cfg.cancel_group_on_dlq)
+        .with_notify_on_enqueue(cfg.notify_on_enqueue)
+        .with_lease_isolation(cfg.lease_isolation)
+        .with_storm_control_lock(cfg.storm_control_lock)
+        .with_serialization_retries(cfg.serialization_retries)
+        .with_decision_coalesce_secs(cfg.decision_coalesce_secs)
+        .with_dataset_round_robin(cfg.dataset_round_robin)
+        .with_single_dataset_batches(cfg.max_concurrent_datasets == 1)
+        .with_max_batch_datasets(cfg.max_concurrent_datasets)
+        .with_reap_requeue_delay_ms(cfg.reap_requeue_delay_ms)
+        .with_idempotency_window_secs(cfg.idempotency_window_secs)
+        .with_batch_chunk_size(cfg.batch_chunk_size);
+    let attempts_repo = AttemptsRepo::new(pool.clone())
+        .with_attempt_overflow_margin(cfg.attempt_overflow_margin)
+        .with_cancel_group_on_dlq(cfg.cancel_group_on_dlq)
+        .with_batch_chunk_size(cfg.batch_chunk_size);
     let ingest_decisions_repo = IngestDecisionsRepo::new(pool.clone());
     let maintenance_repo = MaintenanceRepo::new(pool.clone());
-    let metrics_repo = MetricsRepo::new(pool.clone());
+    let wakeups = WakeupCoalescer::new(Duration::from_millis(cfg.wakeup_coalesce_ms));
     let enqueue_guard = EnqueueGuard::new(
         pool.clone(),
         ingest_decisions_repo.clone(),
         EnqueueGuardConfig {
             max_payload_bytes: cfg.max_payload_bytes,
+            warn_payload_bytes: cfg.warn_payload_bytes,
             max_enqueues_per_minute_per_queue: cfg.max_enqueues_per_minute_per_queue,
+            max_enqueues_per_minute_per_tenant: cfg.max_enqueues_per_minute_per_tenant,
+            tenant_limits: cfg.tenant_enqueue_limits.clone(),
+            reject_unknown_job_types: cfg.reject_unknown_job_types,
+            rate_window: cfg.enqueue_rate_window,
         },
     );
 
     let runner = JobRunner::new(
         jobs_repo.clone(),
         attempts_repo.clone(),
-        RetryConfig::default(),
+        RetryConfig {
+            min_delay_seconds: cfg.retry_min_delay_seconds,
+            ..RetryConfig::default()
+        },
     );
-    let registry = build_registry();
-    let ctx = JobContext {
-        db: pool.clone(),
-        worker_id: cfg.worker_id.clone(),
-    };
+    let registry = build_registry(cfg.default_job_timeout_ms.map(Duration::from_millis));
+    // make this worker's handlers known to enqueue-time job_type validation
+    JobTypesRepo::new(pool.clone())
+        .register(&registry.job_types())
+        .await?;
+    handler_check::validate_handlers(
+        &jobs_repo,
+        &queue,
+        &registry.job_types(),
+        cfg.strict_handlers,
+    )
+    .await?;
+    let ctx = JobContext::new(pool.clone(), cfg.worker_id.clone())
+        .with_log_max_lines(cfg.attempt_log_max_lines);
 
     // ---- API task ----
+    let read_pool = match &cfg.read_database_url {
+        Some(url) => db::make_pool(url, &cfg.application_name).await?,
+        None => pool.clone(),
+    };
     let api_state = api::ApiState {
         jobs: jobs_repo.clone(),
-        attempts: attempts_repo.clone(),
-        policy_decisions: policy_decisions_repo.clone(),
-        ingest_decisions: ingest_decisions_repo.clone(),
-        metrics: metrics_repo.clone(),
+        read_jobs: JobsRepo::new(read_pool.clone()),
+        attempts: AttemptsRepo::new(read_pool.clone()),
+        policy_decisions: PolicyDecisionsRepo::new(read_pool.clone()),
+        ingest_decisions: IngestDecisionsRepo::new(read_pool.clone()),
+        metrics: MetricsRepo::new(read_pool).with_archive_after_days(archive_after_days),
+        sla: SlaRepo::new(pool.clone()),
+        system_flags: SystemFlagsRepo::new(pool.clone()),
         enqueue_guard: enqueue_guard.clone(),
+        enqueue_pressure_hint: cfg.enqueue_pressure_hint,
         api_token: cfg.api_token.clone(),
+        wakeups: wakeups.clone(),
+        handler_permits: registry.permits(),
+        runbook_urls: Arc::new(cfg.runbook_urls.clone()),
+        read_only,
     };
     let app = api::router(api_state);
 
-    let api_handle = tokio::spawn(async move {
+    // The first task to exit (or a termination signal) shuts the others down.
+    let shutdown = Shutdown::new();
+    let mut tasks = tokio::task::JoinSet::new();
+
+    let mut api_shutdown = shutdown.subscribe();
+    let api_task = async move {
         if let Some(addr) = api_addr {
             let listener = tokio::net::TcpListener::bind(&addr).await?;
             println!("admin api listening on http://{addr}");
-            axum::serve(listener, app).await?;
+            api::serve(listener, app, api_shutdown, shutdown_grace).await?;
         } else {
-            std::future::pending::<()>().await;
+            api_shutdown.wait().await;
         }
         Ok::<(), anyhow::Error>(())
-    });
+    };
+    tasks.spawn(async move { ("api", api_task.await) });
 
     // ---- Maintenance task ----
-    let maintenance_handle = {
+    if !read_only {
         let maintenance = maintenance_repo.clone();
-        tokio::spawn(async move {
-            loop {
-                // 1) archive succeeded jobs older than N days
-                let cutoff_archive = cutoff_days(archive_after_days);
+        let maintenance_window = cfg.maintenance_window;
+        let mut maintenance_shutdown = shutdown.subscribe();
+        tasks.spawn(async move {
+            while !maintenance_shutdown.is_triggered() {
+                // rate counters are tiny and only the last minute or two matter,
+                // so they're pruned every pass regardless of the window
                 match maintenance
-                    .archive_succeeded_older_than(cutoff_archive, 500)
+                    .prune_rate_counters(
+                        Utc::now() - chrono::Duration::minutes(RATE_COUNTER_RETENTION_MINUTES),
+                    )
                     .await
                 {
-                    Ok(n) if n > 0 => println!("[maintenance] archived {} succeeded jobs", n),
+                    Ok(n) if n > 0 => println!("[maintenance] pruned {n} enqueue rate counters"),
                     Ok(_) => {}
-                    Err(e) => eprintln!("[maintenance] archive error: {e}"),
+                    Err(e) => eprintln!("[maintenance] rate counter prune error: {e}"),
                 }
 
-                // 2) prune history for succeeded jobs older than N days
-                let cutoff_prune = cutoff_days(prune_history_after_days);
-                match maintenance
-                    .delete_history_for_succeeded_older_than(cutoff_prune, 500)
-                    .await
+                match maintenance.expire_prepared_jobs().await {
+                    Ok(n) if n > 0 => {
+                        println!("[maintenance] expired {n} uncommitted prepared jobs")
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("[maintenance] prepared job expiry error: {e}"),
+                }
+
+                // archive + prune succeeded jobs older than N days, only inside
+                // PGFLOW_MAINTENANCE_WINDOW when one is configured
+                match run_heavy_maintenance(
+                    &maintenance,
+                    maintenance_window.as_ref(),
+                    Utc::now(),
+                    archive_after_days,
+                    prune_history_after_days,
+                )
+                .await
                 {
-                    Ok((a, p)) if a > 0 || p > 0 => println!(
-                        "[maintenance] deleted attempts={} policy_decisions={}",
-                        a, p
-                    ),
+                    Ok(Some(pass)) => {
+                        if pass.archived > 0 {
+                            println!("[maintenance] archived {} succeeded jobs", pass.archived);
+                        }
+                        if pass.attempts_deleted > 0
+                            || pass.policy_decisions_deleted > 0
+                            || pass.transitions_deleted > 0
+                        {
+                            println!(
+                                "[maintenance] deleted attempts={} policy_decisions={} transitions={}",
+                                pass.attempts_deleted,
+                                pass.policy_decisions_deleted,
+                                pass.transitions_deleted
+                            );
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("[maintenance] error: {e}"),
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(maintenance_interval_secs)) => {}
+                    _ = maintenance_shutdown.wait() => {}
+                }
+            }
+            ("maintenance", Ok(()))
+        });
+    }
+
+    // ---- Heartbeat + dead-worker fast reap ----
+    // set from the heartbeat's row once `pgflowctl drain-worker` asked this worker to drain
+    let drain_requested = Arc::new(AtomicBool::new(false));
+    if !read_only {
+        let workers = WorkersRepo::new(pool.clone());
+        // a drain request left by a previous run under this id doesn't apply to us
+        workers.register(&cfg.worker_id, &queue).await?;
+        let drain = drain_requested.clone();
+        let jobs = jobs_repo.clone();
+        let worker_id = cfg.worker_id.clone();
+        let queue = queue.clone();
+        let interval = Duration::from_millis(cfg.heartbeat_interval_ms);
+        let stale_secs = cfg.worker_stale_secs;
+        let mut heartbeat_shutdown = shutdown.subscribe();
+        tasks.spawn(async move {
+            while !heartbeat_shutdown.is_triggered() {
+                match workers.heartbeat(&worker_id, &queue).await {
+                    Ok(true) => drain.store(true, Ordering::Relaxed),
+                    Ok(false) => {}
+                    Err(e) => eprintln!("[heartbeat] error: {e}"),
+                }
+
+                // reclaim jobs of workers that stopped heartbeating, without waiting for lease expiry
+                if stale_secs > 0 {
+                    match reap_stale_workers(&jobs, stale_secs).await {
+                        Ok(reaped) => {
+                            for (dead, n) in reaped {
+                                println!("[heartbeat] worker {dead} is stale; requeued {n} jobs");
+                            }
+                        }
+                        Err(e) => eprintln!("[heartbeat] fast reap error: {e}"),
+                    }
+                }
+
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = heartbeat_shutdown.wait() => {}
+                }
+            }
+
+            if let Err(e) = workers.remove(&worker_id).await {
+                eprintln!("[heartbeat] deregister error: {e}");
+            }
+            ("heartbeat", Ok(()))
+        });
+    }
+
+    // ---- Recurring schedules ----
+    // every worker runs this; the schedule row lock keeps each window to one job
+    if cfg.scheduler_interval_ms > 0 && !read_only {
+        let schedules = SchedulesRepo::new(pool.clone()).with_enqueue_guard(enqueue_guard.clone());
+        let jobs = jobs_repo.clone();
+        let interval = Duration::from_millis(cfg.scheduler_interval_ms);
+        let mut scheduler_shutdown = shutdown.subscribe();
+        tasks.spawn(async move {
+            while !scheduler_shutdown.is_triggered() {
+                match schedules.materialize_due(&jobs, Utc::now()).await {
+                    Ok(ids) if !ids.is_empty() => {
+                        println!("[scheduler] enqueued {} scheduled jobs", ids.len())
+                    }
                     Ok(_) => {}
-                    Err(e) => eprintln!("[maintenance] prune error: {e}"),
+                    Err(e) => eprintln!("[scheduler] error: {e}"),
                 }
 
-                tokio::time::sleep(Duration::from_secs(maintenance_interval_secs)).await;
+                tokio::select! {
+                    _ = tokio::time::sleep(interval) => {}
+                    _ = scheduler_shutdown.wait() => {}
+                }
             }
-            #[allow(unreachable_code)]
-            Ok::<(), anyhow::Error>(())
-        })
-    };
+            ("scheduler", Ok(()))
+        });
+    }
+
+    // ---- Enqueue notifications ----
+    // enqueues from any process NOTIFY the queue's channel; forward them to the
+    // idle worker loop, which still polls as a fallback if the listener is down
+    if !read_only {
+        let jobs = jobs_repo.clone();
+        let queue = queue.clone();
+        let wakeups = wakeups.clone();
+        let mut listen_shutdown = shutdown.subscribe();
+        tasks.spawn(async move {
+            while !listen_shutdown.is_triggered() {
+                let mut listener = match jobs.listen_for_jobs(&queue).await {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        eprintln!("[listen] subscribe error: {e}");
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                            _ = listen_shutdown.wait() => {}
+                        }
+                        continue;
+                    }
+                };
+                loop {
+                    tokio::select! {
+                        notification = listener.recv() => match notification {
+                            Ok(_) => wakeups.wake(),
+                            Err(e) => {
+                                eprintln!("[listen] error: {e}");
+                                break;
+                            }
+                        },
+                        _ = listen_shutdown.wait() => break,
+                    }
+                }
+            }
+            ("listen", Ok(()))
+        });
+    }
 
     // ---- Worker loop task ----
     let worker_id = cfg.worker_id.clone();
     let worker_queue = queue.clone();
-    let worker_batch_size = dequeue_batch_size;
+    // PGFLOW_MAX_JOBS_PER_SEC: never lease more than the limiter lets start at once
+    let mut job_limiter = cfg.max_jobs_per_sec.map(JobRateLimiter::new);
+    let worker_batch_size = match &job_limiter {
+        Some(l) => dequeue_batch_size.min(l.burst() as i64),
+        None => dequeue_batch_size,
+    };
     let worker_reap_interval = reap_interval;
     let worker_verbose_job_logs = verbose_job_logs;
+    let worker_idle_poll = Duration::from_millis(cfg.idle_poll_ms);
+    let worker_registry = WorkersRepo::new(pool.clone());
+    let mut worker_shutdown = shutdown.subscribe();
 
-    let worker_handle = tokio::spawn(async move {
+    let worker_loop = async move {
         let mut last_reap_at = Instant::now() - worker_reap_interval;
+        // when the earliest scheduled job on the queue comes due; `None` until
+        // looked up. Only shortens the idle sleep, so a stale value costs at
+        // most one idle poll of latency.
+        let mut next_due: Option<Option<Instant>> = None;
+
+        // the in-progress batch always finishes; shutdown is only checked between batches
+        while !worker_shutdown.is_triggered() {
+            // drain (set in the DB, e.g. by a rolling restart, and picked up by
+            // the heartbeat): the previous batch has finished, so stop here;
+            // exiting shuts the other tasks down
+            if drain_requested.load(Ordering::Relaxed) {
+                worker_registry.mark_draining(&worker_id).await?;
+                println!("[{}] drain requested; stopped leasing", worker_id);
+                break;
+            }
 
-        loop {
             // reclaim jobs from dead workers on a fixed interval to avoid hot-loop write load.
             if last_reap_at.elapsed() >= worker_reap_interval {
                 let reaped = jobs_repo.reap_expired_locks().await?;
@@ -186,19 +419,86 @@ async fn main() -> anyhow::Result<()> {
                 }
             }
 
-            let batch = jobs_repo
-                .lease_jobs_batch(&worker_queue, &worker_id, lease_seconds, worker_batch_size)
+            let (batch, policy) = jobs_repo
+                .lease_jobs_batch_with_policy(
+                    &worker_queue,
+                    &worker_id,
+                    lease_seconds,
+                    worker_batch_size,
+                )
                 .await?;
 
+            if worker_verbose_job_logs && !batch.is_empty() {
+                match &policy {
+                    Some(p) => println!(
+                        "[{}] leased {} jobs queue={} policy: max_in_flight={:?} max_attempts_per_minute={:?} throttle_delay_ms={}",
+                        worker_id,
+                        batch.len(),
+                        worker_queue,
+                        p.max_in_flight,
+                        p.max_attempts_per_minute,
+                        p.throttle_delay_ms
+                    ),
+                    None => println!(
+                        "[{}] leased {} jobs queue={} policy: none",
+                        worker_id,
+                        batch.len(),
+                        worker_queue
+                    ),
+                }
+            }
+
+            // nothing runnable: take over a lease from a slow worker if the queue opts in
+            let steal_enabled = policy.as_ref().is_some_and(|p| p.steal_enabled);
+            let batch = if batch.is_empty() && steal_enabled {
+                jobs_repo
+                    .steal_stale_lease(&worker_queue, &worker_id, lease_seconds)
+                    .await?
+                    .map(|job| {
+                        println!("[{}] stole stale lease job id={}", worker_id, job.id);
+                        vec![job]
+                    })
+                    .unwrap_or_default()
+            } else {
+                batch
+            };
+
             if batch.is_empty() {
-                tokio::time::sleep(Duration::from_millis(250)).await;
+                // idle poll, cut short (and debounced) by enqueue notifications;
+                // if a scheduled job comes due sooner, sleep exactly until then.
+                // The due time is looked up again only after a wakeup, a leased
+                // batch, or once it has passed, not on every idle poll.
+                let now = Instant::now();
+                let due = match next_due {
+                    Some(due) if due.is_none_or(|at| at > now) => due,
+                    _ => {
+                        let due = jobs_repo
+                            .next_run_delay(&worker_queue)
+                            .await?
+                            .map(|delay| now + delay);
+                        next_due = Some(due);
+                        due
+                    }
+                };
+                let idle = due.map_or(worker_idle_poll, |at| {
+                    at.saturating_duration_since(now).min(worker_idle_poll)
+                });
+                tokio::select! {
+                    woken = wakeups.wait(idle) => {
+                        if woken {
+                            next_due = None;
+                        }
+                    }
+                    _ = worker_shutdown.wait() => {}
+                }
                 continue;
             }
+            next_due = None;
 
-            let leased_dataset_id = batch[0].dataset_id.clone();
-            if batch.iter().any(|j| j.dataset_id != leased_dataset_id) {
-                anyhow::bail!("mixed dataset batch returned from lease query");
-            }
+            // the batch's datasets are all this worker has in flight until it
+            // finishes; the lease never spans more than PGFLOW_MAX_CONCURRENT_DATASETS
+            let dataset_by_job: HashMap<Uuid, String> =
+                batch.iter().map(|j| (j.id, j.dataset_id.clone())).collect();
 
             let dataset_ids: Vec<String> = batch.iter().map(|j| j.dataset_id.clone()).collect();
             let job_ids: Vec<Uuid> = batch.iter().map(|j| j.id).collect();
@@ -207,28 +507,45 @@ async fn main() -> anyhow::Result<()> {
                 .start_attempts_batch(&dataset_ids, &job_ids, &worker_id)
                 .await?;
 
-            if started_attempts.len() != batch.len() {
+            if started_attempts.started.len() + started_attempts.overflowed.len() != batch.len() {
                 anyhow::bail!(
-                    "attempt insert count mismatch: inserted={} leased={}",
-                    started_attempts.len(),
+                    "attempt insert count mismatch: inserted={} overflowed={} leased={}",
+                    started_attempts.started.len(),
+                    started_attempts.overflowed.len(),
                     batch.len()
                 );
             }
 
+            // jobs refused by the attempt overflow guard were moved to the DLQ
+            for job_id in &started_attempts.overflowed {
+                eprintln!(
+                    "[{}] job id={} moved to DLQ: ATTEMPT_OVERFLOW",
+                    worker_id, job_id
+                );
+            }
+
             let mut attempts_by_job: HashMap<Uuid, (Uuid, i32)> = started_attempts
+                .started
                 .into_iter()
                 .map(|(job_id, attempt_id, attempt_no)| (job_id, (attempt_id, attempt_no)))
                 .collect();
+            let batch: Vec<_> = batch
+                .into_iter()
+                .filter(|job| attempts_by_job.contains_key(&job.id))
+                .collect();
 
             let mut join_set = tokio::task::JoinSet::new();
             for job in batch {
                 let registry = registry.clone();
-                let ctx = ctx.clone();
                 let worker_id_for_task = worker_id.clone();
                 let verbose_job_logs_for_task = worker_verbose_job_logs;
                 let (attempt_id, attempt_no) = attempts_by_job
                     .remove(&job.id)
                     .ok_or_else(|| anyhow::anyhow!("missing started attempt for job {}", job.id))?;
+                let ctx = ctx.for_attempt(&job, attempt_no);
+                if let Some(limiter) = job_limiter.as_mut() {
+                    limiter.acquire().await;
+                }
 
                 join_set.spawn(async move {
                     let start = Instant::now();
@@ -255,6 +572,8 @@ async fn main() -> anyhow::Result<()> {
                             attempt_id,
                             attempt_no,
                             latency_ms,
+                            result: ctx.take_result(),
+                            cooldown: ctx.take_cooldown(),
                         },
                         Err(err) => JobExecutionOutcome::Failed {
                             job_id: job.id,
@@ -264,6 +583,7 @@ async fn main() -> anyhow::Result<()> {
                             latency_ms,
                             error_code: err.code.to_string(),
                             error_message: err.message,
+                            error_details: err.details,
                         },
                     };
 
@@ -271,8 +591,11 @@ async fn main() -> anyhow::Result<()> {
                 });
             }
 
-            let mut succeeded_batch: Vec<(Uuid, Uuid, i32)> = Vec::new();
+            let mut succeeded_by_dataset: HashMap<String, Vec<(Uuid, Uuid, i32)>> = HashMap::new();
+            let mut results: HashMap<Uuid, serde_json::Value> = HashMap::new();
+            let mut cooldowns: HashMap<Uuid, chrono::DateTime<Utc>> = HashMap::new();
             let mut failed_batch: Vec<(Uuid, Uuid, i32, i32, i32, String, String)> = Vec::new();
+            let mut error_details: HashMap<Uuid, serde_json::Value> = HashMap::new();
 
             while let Some(joined) = join_set.join_next().await {
                 match joined?? {
@@ -281,6 +604,8 @@ async fn main() -> anyhow::Result<()> {
                         attempt_id,
                         attempt_no,
                         latency_ms,
+                        result,
+                        cooldown,
                     } => {
                         if worker_verbose_job_logs {
                             println!(
@@ -288,7 +613,20 @@ async fn main() -> anyhow::Result<()> {
                                 worker_id, job_id, attempt_no, latency_ms
                             );
                         }
-                        succeeded_batch.push((job_id, attempt_id, latency_ms));
+                        succeeded_by_dataset
+                            .entry(dataset_by_job[&job_id].clone())
+                            .or_default()
+                            .push((job_id, attempt_id, latency_ms));
+                        if let Some(result) = result {
+                            results.insert(job_id, result);
+                        }
+                        if let Some(cooldown) = cooldown {
+                            let until = chrono::Duration::from_std(cooldown)
+                                .ok()
+                                .and_then(|cooldown| Utc::now().checked_add_signed(cooldown))
+                                .unwrap_or(chrono::DateTime::<Utc>::MAX_UTC);
+                            cooldowns.insert(job_id, until);
+                        }
                     }
                     JobExecutionOutcome::Failed {
                         job_id,
@@ -298,6 +636,7 @@ async fn main() -> anyhow::Result<()> {
                         latency_ms,
                         error_code,
                         error_message,
+                        error_details: details,
                     } => {
                         failed_batch.push((
                             job_id,
@@ -308,13 +647,24 @@ async fn main() -> anyhow::Result<()> {
                             error_code,
                             error_message,
                         ));
+                        if let Some(details) = details {
+                            error_details.insert(job_id, details);
+                        }
                     }
                 }
             }
 
-            runner
-                .on_success_batch(&leased_dataset_id, &succeeded_batch, &worker_id)
-                .await?;
+            for (dataset_id, succeeded_batch) in &succeeded_by_dataset {
+                runner
+                    .on_success_batch_with_feedback(
+                        dataset_id,
+                        succeeded_batch,
+                        &results,
+                        &cooldowns,
+                        &worker_id,
+                    )
+                    .await?;
+            }
 
             for (
                 job_id,
@@ -327,13 +677,14 @@ async fn main() -> anyhow::Result<()> {
             ) in failed_batch
             {
                 runner
-                    .on_failure(
+                    .on_failure_with_details(
                         job_id,
                         attempt_id,
                         &worker_id,
                         latency_ms,
                         &error_code,
                         &error_message,
+                        error_details.get(&job_id),
                         attempt_no,
                         max_attempts,
                     )
@@ -347,15 +698,39 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
-        #[allow(unreachable_code)]
         Ok::<(), anyhow::Error>(())
-    });
+    };
+    if !read_only {
+        tasks.spawn(async move { ("worker", worker_loop.await) });
+    }
+
+    let mut first_err: Option<anyhow::Error> = None;
+    let mut record =
+        |joined: Result<(&'static str, anyhow::Result<()>), tokio::task::JoinError>| {
+            let err = match joined {
+                Ok((name, Ok(()))) => {
+                    println!("[shutdown] {name} task stopped");
+                    return;
+                }
+                Ok((name, Err(e))) => e.context(format!("{name} task failed")),
+                Err(e) => anyhow::Error::new(e).context("task panicked"),
+            };
+            eprintln!("[shutdown] {err:#}");
+            first_err.get_or_insert(err);
+        };
 
     tokio::select! {
-        res = api_handle => res??,
-        res = worker_handle => res??,
-        res = maintenance_handle => res??,
+        Some(joined) = tasks.join_next() => record(joined),
+        _ = shutdown::termination_signal() => println!("[shutdown] signal received"),
     }
 
-    Ok(())
+    shutdown.trigger();
+    while let Some(joined) = tasks.join_next().await {
+        record(joined);
+    }
+
+    match first_err {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }